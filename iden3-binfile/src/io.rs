@@ -0,0 +1,321 @@
+//! The byte I/O files are read and written through.
+//!
+//! With the default `std` feature these are the [`std::io`] traits and types, along with the
+//! byteorder extensions. Without it, they are minimal `alloc`-only replacements with the same
+//! names and signatures, implemented for byte slices and vectors, so that the parsing and
+//! serialization code is the same in both configurations.
+
+#[cfg(feature = "std")]
+pub use byteorder::{ReadBytesExt, WriteBytesExt};
+#[cfg(feature = "std")]
+pub use std::io::{copy, sink, Error, ErrorKind, Read, Result, Sink, Take, Write};
+
+#[cfg(not(feature = "std"))]
+pub use self::alloc_io::*;
+
+#[cfg(not(feature = "std"))]
+mod alloc_io {
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+    use core::fmt;
+
+    use byteorder::ByteOrder;
+
+    pub type Result<T, E = Error> = core::result::Result<T, E>;
+
+    /// The kinds of [`Error`] produced by this crate and the formats built on it.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[non_exhaustive]
+    pub enum ErrorKind {
+        InvalidData,
+        InvalidInput,
+        UnexpectedEof,
+        WriteZero,
+        Other,
+    }
+
+    impl fmt::Display for ErrorKind {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(match self {
+                ErrorKind::InvalidData => "invalid data",
+                ErrorKind::InvalidInput => "invalid input parameter",
+                ErrorKind::UnexpectedEof => "unexpected end of file",
+                ErrorKind::WriteZero => "write zero",
+                ErrorKind::Other => "other error",
+            })
+        }
+    }
+
+    /// An I/O error: its kind and a description.
+    #[derive(Debug)]
+    pub struct Error {
+        kind: ErrorKind,
+        message: Option<String>,
+    }
+
+    impl Error {
+        pub fn new<E: fmt::Display>(kind: ErrorKind, error: E) -> Self {
+            Error {
+                kind,
+                message: Some(error.to_string()),
+            }
+        }
+
+        pub fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+
+    impl From<ErrorKind> for Error {
+        fn from(kind: ErrorKind) -> Self {
+            Error {
+                kind,
+                message: None,
+            }
+        }
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match &self.message {
+                Some(message) => f.write_str(message),
+                None => self.kind.fmt(f),
+            }
+        }
+    }
+
+    /// A source of bytes.
+    pub trait Read {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+        fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.read(buf)? {
+                    0 => {
+                        return Err(Error::new(
+                            ErrorKind::UnexpectedEof,
+                            "failed to fill whole buffer",
+                        ))
+                    }
+                    n => buf = &mut buf[n..],
+                }
+            }
+
+            Ok(())
+        }
+
+        fn read_to_end(&mut self, buf: &mut Vec<u8>) -> Result<usize> {
+            let start = buf.len();
+            let mut chunk = [0u8; 512];
+            loop {
+                match self.read(&mut chunk)? {
+                    0 => return Ok(buf.len() - start),
+                    n => buf.extend_from_slice(&chunk[..n]),
+                }
+            }
+        }
+
+        fn by_ref(&mut self) -> &mut Self
+        where
+            Self: Sized,
+        {
+            self
+        }
+
+        fn take(self, limit: u64) -> Take<Self>
+        where
+            Self: Sized,
+        {
+            Take { inner: self, limit }
+        }
+    }
+
+    impl Read for &[u8] {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let n = buf.len().min(self.len());
+            let (head, tail) = self.split_at(n);
+            buf[..n].copy_from_slice(head);
+            *self = tail;
+
+            Ok(n)
+        }
+    }
+
+    impl<R: Read + ?Sized> Read for &mut R {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            (**self).read(buf)
+        }
+    }
+
+    /// A sink for bytes.
+    pub trait Write {
+        fn write(&mut self, buf: &[u8]) -> Result<usize>;
+
+        fn flush(&mut self) -> Result<()>;
+
+        fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.write(buf)? {
+                    0 => {
+                        return Err(Error::new(
+                            ErrorKind::WriteZero,
+                            "failed to write whole buffer",
+                        ))
+                    }
+                    n => buf = &buf[n..],
+                }
+            }
+
+            Ok(())
+        }
+
+        fn by_ref(&mut self) -> &mut Self
+        where
+            Self: Sized,
+        {
+            self
+        }
+    }
+
+    impl Write for Vec<u8> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.extend_from_slice(buf);
+
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<W: Write + ?Sized> Write for &mut W {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            (**self).write(buf)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            (**self).flush()
+        }
+    }
+
+    /// Reads at most `limit` bytes of the inner reader, see [`Read::take`].
+    #[derive(Debug)]
+    pub struct Take<R> {
+        inner: R,
+        limit: u64,
+    }
+
+    impl<R> Take<R> {
+        /// The number of bytes left to read.
+        pub fn limit(&self) -> u64 {
+            self.limit
+        }
+
+        pub fn set_limit(&mut self, limit: u64) {
+            self.limit = limit;
+        }
+
+        pub fn get_ref(&self) -> &R {
+            &self.inner
+        }
+
+        pub fn get_mut(&mut self) -> &mut R {
+            &mut self.inner
+        }
+
+        pub fn into_inner(self) -> R {
+            self.inner
+        }
+    }
+
+    impl<R: Read> Read for Take<R> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let max = self.limit.min(buf.len() as u64) as usize;
+            let n = self.inner.read(&mut buf[..max])?;
+            self.limit -= n as u64;
+
+            Ok(n)
+        }
+    }
+
+    /// A writer discarding everything, see [`sink`].
+    #[derive(Debug, Default)]
+    pub struct Sink;
+
+    pub fn sink() -> Sink {
+        Sink
+    }
+
+    impl Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Copies everything left in `r` to `w`, returning the number of bytes copied.
+    pub fn copy<R: Read + ?Sized, W: Write + ?Sized>(r: &mut R, w: &mut W) -> Result<u64> {
+        let mut chunk = [0u8; 512];
+        let mut copied = 0;
+        loop {
+            match r.read(&mut chunk)? {
+                0 => return Ok(copied),
+                n => {
+                    w.write_all(&chunk[..n])?;
+                    copied += n as u64;
+                }
+            }
+        }
+    }
+
+    /// The integer reads of byteorder's `ReadBytesExt` used by the formats.
+    pub trait ReadBytesExt: Read {
+        fn read_u8(&mut self) -> Result<u8> {
+            let mut buf = [0u8; 1];
+            self.read_exact(&mut buf)?;
+
+            Ok(buf[0])
+        }
+
+        fn read_u32<B: ByteOrder>(&mut self) -> Result<u32> {
+            let mut buf = [0u8; 4];
+            self.read_exact(&mut buf)?;
+
+            Ok(B::read_u32(&buf))
+        }
+
+        fn read_u64<B: ByteOrder>(&mut self) -> Result<u64> {
+            let mut buf = [0u8; 8];
+            self.read_exact(&mut buf)?;
+
+            Ok(B::read_u64(&buf))
+        }
+    }
+
+    impl<R: Read + ?Sized> ReadBytesExt for R {}
+
+    /// The integer writes of byteorder's `WriteBytesExt` used by the formats.
+    pub trait WriteBytesExt: Write {
+        fn write_u8(&mut self, n: u8) -> Result<()> {
+            self.write_all(&[n])
+        }
+
+        fn write_u32<B: ByteOrder>(&mut self, n: u32) -> Result<()> {
+            let mut buf = [0u8; 4];
+            B::write_u32(&mut buf, n);
+            self.write_all(&buf)
+        }
+
+        fn write_u64<B: ByteOrder>(&mut self, n: u64) -> Result<()> {
+            let mut buf = [0u8; 8];
+            B::write_u64(&mut buf, n);
+            self.write_all(&buf)
+        }
+    }
+
+    impl<W: Write + ?Sized> WriteBytesExt for W {}
+}
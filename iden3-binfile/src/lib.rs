@@ -0,0 +1,638 @@
+//! The sectioned container shared by the iden3 binary formats: `.r1cs`, `.wtns`, `.zkey`.
+//!
+//! A file starts with a 4-byte magic number, a `u32` version and a `u32` number of sections.
+//! Each section is a `u32` type id, a `u64` body size and the body. Integers are little-endian.
+//! The meaning of the section ids and bodies is up to each format.
+//!
+//! The crate is `no_std` with `alloc` when the default `std` feature is disabled. Files are then
+//! read and written through the minimal traits of [`io`], and the parts needing [`std::io::Seek`]
+//! are left out.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod io;
+
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+use core::ops::RangeInclusive;
+#[cfg(feature = "std")]
+use std::io::{Seek, SeekFrom};
+
+use byteorder::LittleEndian;
+
+use crate::io::{Read, ReadBytesExt, Write, WriteBytesExt};
+
+pub type Result<T, E = BinFileError> = core::result::Result<T, E>;
+
+/// Size of the magic number, version and number of sections.
+pub const PREAMBLE_SIZE: u64 = 12;
+/// Size of a section id and body size.
+pub const SECTION_HEADER_SIZE: u64 = 12;
+
+/// The preamble accepted by [`BinFileReader::new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Format {
+    pub magic: [u8; 4],
+    pub versions: RangeInclusive<u32>,
+    /// Largest accepted number of sections, unbounded if `None`.
+    pub max_sections: Option<u32>,
+}
+
+/// Errors produced while reading a file.
+///
+/// Offsets are counted from the start of the file.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BinFileError {
+    InvalidMagic {
+        found: [u8; 4],
+    },
+    UnsupportedVersion(u32),
+    TooManySections(u32),
+    /// A section body running past `u64::MAX`, at the offset of the body.
+    SectionSizeOverflow {
+        offset: u64,
+    },
+    /// The input ended early.
+    UnexpectedEof {
+        offset: u64,
+    },
+    Io(io::Error),
+}
+
+impl BinFileError {
+    /// Turns an unexpected end of input into [`BinFileError::UnexpectedEof`] at `offset`.
+    fn eof_at(self, offset: u64) -> Self {
+        match self {
+            BinFileError::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                BinFileError::UnexpectedEof { offset }
+            }
+            e => e,
+        }
+    }
+}
+
+impl fmt::Display for BinFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinFileError::InvalidMagic { found } => {
+                write!(f, "Invalid magic number {:?} at offset 0", found)
+            }
+            BinFileError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported version {} at offset 4", version)
+            }
+            BinFileError::TooManySections(n) => {
+                write!(f, "Number of sections {} at offset 8 is not supported", n)
+            }
+            BinFileError::SectionSizeOverflow { offset } => write!(
+                f,
+                "Section size overflows the file offset at offset {}",
+                offset
+            ),
+            BinFileError::UnexpectedEof { offset } => {
+                write!(f, "Unexpected end of file at offset {}", offset)
+            }
+            BinFileError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BinFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BinFileError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for BinFileError {
+    fn from(e: io::Error) -> Self {
+        BinFileError::Io(e)
+    }
+}
+
+impl From<BinFileError> for io::Error {
+    fn from(e: BinFileError) -> Self {
+        match e {
+            BinFileError::Io(e) => e,
+            BinFileError::UnexpectedEof { .. } => io::Error::new(io::ErrorKind::UnexpectedEof, e),
+            e => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
+
+/// A section listed by [`BinFileReader::section_table`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectionEntry {
+    pub id: u32,
+    pub size: u64,
+    /// Offset of the body from the start of the file.
+    pub offset: u64,
+}
+
+/// Reads the sections of a file in order.
+///
+/// Reading through the `BinFileReader` itself reads the raw bytes at the current position, such
+/// as trailing data after the last section.
+#[derive(Debug)]
+pub struct BinFileReader<R> {
+    inner: R,
+    position: u64,
+    version: u32,
+    num_sections: u32,
+    sections_read: u32,
+    /// End of the body of the last section returned.
+    section_end: u64,
+}
+
+impl<R: Read> BinFileReader<R> {
+    /// Reads and checks the preamble of a file.
+    pub fn new(r: R, format: &Format) -> Result<Self> {
+        let mut file = BinFileReader {
+            inner: r,
+            position: 0,
+            version: 0,
+            num_sections: 0,
+            sections_read: 0,
+            section_end: PREAMBLE_SIZE,
+        };
+        file.read_preamble(format)
+            .map_err(|e| e.eof_at(file.position))?;
+
+        Ok(file)
+    }
+
+    fn read_preamble(&mut self, format: &Format) -> Result<()> {
+        let mut magic = [0u8; 4];
+        self.read_exact(&mut magic)?;
+        if magic != format.magic {
+            return Err(BinFileError::InvalidMagic { found: magic });
+        }
+
+        self.version = self.read_u32::<LittleEndian>()?;
+        if !format.versions.contains(&self.version) {
+            return Err(BinFileError::UnsupportedVersion(self.version));
+        }
+
+        self.num_sections = self.read_u32::<LittleEndian>()?;
+        match format.max_sections {
+            Some(max) if self.num_sections > max => {
+                Err(BinFileError::TooManySections(self.num_sections))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Reads the header of the next section, returning its id, its body size and a reader over
+    /// its body. `None` once the number of sections declared by the preamble have been read.
+    ///
+    /// The part of the previous section body that wasn't read is skipped.
+    pub fn next_section(&mut self) -> Result<Option<(u32, u64, SectionReader<'_, R>)>> {
+        if self.sections_read >= self.num_sections {
+            return Ok(None);
+        }
+
+        self.skip_section()?;
+        let (id, size) = self
+            .read_section_header()
+            .map_err(|e| e.eof_at(self.position))?;
+        self.sections_read += 1;
+        let section = self.section(size)?;
+
+        Ok(Some((id, size, section)))
+    }
+
+    /// Skips the part of the current section body that wasn't read.
+    pub fn skip_section(&mut self) -> Result<()> {
+        let len = self.section_end.saturating_sub(self.position);
+        let skipped = io::copy(&mut (&mut *self).take(len), &mut io::sink())?;
+        if skipped != len {
+            return Err(BinFileError::UnexpectedEof {
+                offset: self.position,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn read_section_header(&mut self) -> Result<(u32, u64)> {
+        let id = self.read_u32::<LittleEndian>()?;
+        let size = self.read_u64::<LittleEndian>()?;
+
+        Ok((id, size))
+    }
+
+    /// A reader over the `size` bytes from the current position.
+    fn section(&mut self, size: u64) -> Result<SectionReader<'_, R>> {
+        let offset = self.position;
+        self.section_end = offset
+            .checked_add(size)
+            .ok_or(BinFileError::SectionSizeOverflow { offset })?;
+
+        Ok(SectionReader {
+            file: self,
+            remaining: size,
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read + Seek> BinFileReader<R> {
+    /// Walks the section headers not read yet, seeking past the bodies.
+    ///
+    /// Once the table is read, [`BinFileReader::next_section`] returns `None` and the sections
+    /// are read with [`BinFileReader::section_at`], in any order.
+    pub fn section_table(&mut self) -> Result<Vec<SectionEntry>> {
+        let mut sections = Vec::new();
+        self.seek(SeekFrom::Start(self.section_end))?;
+
+        while self.sections_read < self.num_sections {
+            let (id, size) = self
+                .read_section_header()
+                .map_err(|e| e.eof_at(self.position))?;
+            self.sections_read += 1;
+
+            let offset = self.position;
+            self.section_end = offset
+                .checked_add(size)
+                .ok_or(BinFileError::SectionSizeOverflow { offset })?;
+            self.seek(SeekFrom::Start(self.section_end))?;
+            sections.push(SectionEntry { id, size, offset });
+        }
+
+        Ok(sections)
+    }
+
+    /// Seeks to the body of a section listed by [`BinFileReader::section_table`].
+    pub fn section_at(&mut self, entry: &SectionEntry) -> Result<SectionReader<'_, R>> {
+        self.seek(SeekFrom::Start(entry.offset))?;
+        self.section(entry.size)
+    }
+}
+
+impl<R> BinFileReader<R> {
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn num_sections(&self) -> u32 {
+        self.num_sections
+    }
+
+    /// The offset from the start of the file.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for BinFileReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.position += n as u64;
+
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Seek> Seek for BinFileReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = self.inner.seek(pos)?;
+
+        Ok(self.position)
+    }
+}
+
+/// Reads the body of a section, up to its declared size.
+#[derive(Debug)]
+pub struct SectionReader<'a, R> {
+    file: &'a mut BinFileReader<R>,
+    remaining: u64,
+}
+
+impl<R> SectionReader<'_, R> {
+    /// The offset from the start of the file.
+    pub fn position(&self) -> u64 {
+        self.file.position
+    }
+
+    /// The number of body bytes not read yet.
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+}
+
+impl<R: Read> Read for SectionReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let max = self.remaining.min(buf.len() as u64) as usize;
+        let n = self.file.read(&mut buf[..max])?;
+        self.remaining -= n as u64;
+
+        Ok(n)
+    }
+}
+
+/// Writes a file section by section.
+///
+/// The section bodies are written through the `BinFileWriter` itself, between the start and the
+/// end of each section.
+#[derive(Debug)]
+pub struct BinFileWriter<W> {
+    inner: W,
+    position: u64,
+    num_sections: u32,
+    sections_written: u32,
+    /// Offset of the body of the open section, if any.
+    section_start: Option<u64>,
+    /// Body size declared by the open section, `None` if it is written by
+    /// [`BinFileWriter::end_section`].
+    section_size: Option<u64>,
+}
+
+impl<W: Write> BinFileWriter<W> {
+    /// Writes the preamble of a file with `num_sections` sections.
+    pub fn new(w: W, magic: &[u8; 4], version: u32, num_sections: u32) -> io::Result<Self> {
+        let mut file = BinFileWriter {
+            inner: w,
+            position: 0,
+            num_sections,
+            sections_written: 0,
+            section_start: None,
+            section_size: None,
+        };
+        file.write_all(magic)?;
+        file.write_u32::<LittleEndian>(version)?;
+        file.write_u32::<LittleEndian>(num_sections)?;
+
+        Ok(file)
+    }
+
+    /// Writes a section whose body size is known up front, the body being written by `body`.
+    ///
+    /// Fails if `body` writes a different number of bytes.
+    pub fn write_section<E: From<io::Error>>(
+        &mut self,
+        id: u32,
+        size: u64,
+        body: impl FnOnce(&mut Self) -> Result<(), E>,
+    ) -> Result<(), E> {
+        self.begin_sized_section(id, size)?;
+        body(self)?;
+        self.end_sized_section()?;
+
+        Ok(())
+    }
+
+    /// Starts a section whose body size is known up front, the body being then written piece by
+    /// piece through the `BinFileWriter`. See [`BinFileWriter::end_sized_section`].
+    pub fn begin_sized_section(&mut self, id: u32, size: u64) -> io::Result<()> {
+        self.begin(id, size)?;
+        self.section_size = Some(size);
+
+        Ok(())
+    }
+
+    /// Ends the section started by [`BinFileWriter::begin_sized_section`].
+    ///
+    /// Fails if the body written differs from the declared size.
+    pub fn end_sized_section(&mut self) -> io::Result<()> {
+        let (start, size) = match (self.section_start, self.section_size) {
+            (Some(start), Some(size)) => (start, size),
+            _ => return Err(invalid_input("No sized section is open")),
+        };
+        self.section_start = None;
+        self.section_size = None;
+
+        if self.position - start != size {
+            return Err(invalid_input(format!(
+                "Section declares {} bytes but {} were written",
+                size,
+                self.position - start
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Starts a section whose body size isn't known yet, see [`BinFileWriter::end_section`].
+    pub fn begin_section(&mut self, id: u32) -> io::Result<()> {
+        self.begin(id, 0)
+    }
+
+    fn begin(&mut self, id: u32, size: u64) -> io::Result<()> {
+        if self.section_start.is_some() {
+            return Err(invalid_input("A section is already open"));
+        }
+        if self.sections_written == self.num_sections {
+            return Err(invalid_input(format!(
+                "The preamble declares {} sections",
+                self.num_sections
+            )));
+        }
+
+        self.write_u32::<LittleEndian>(id)?;
+        self.write_u64::<LittleEndian>(size)?;
+        self.sections_written += 1;
+        self.section_start = Some(self.position);
+
+        Ok(())
+    }
+
+    /// Checks that every section declared by the preamble was written, and returns the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.section_start.is_some() {
+            return Err(invalid_input("A section is still open"));
+        }
+        if self.sections_written != self.num_sections {
+            return Err(invalid_input(format!(
+                "The preamble declares {} sections but {} were written",
+                self.num_sections, self.sections_written
+            )));
+        }
+
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write + Seek> BinFileWriter<W> {
+    /// Ends the section started by [`BinFileWriter::begin_section`], seeking back to write the
+    /// size of its body.
+    pub fn end_section(&mut self) -> io::Result<()> {
+        let start = match (self.section_start, self.section_size) {
+            (Some(start), None) => start,
+            _ => return Err(invalid_input("No section of unknown size is open")),
+        };
+        self.section_start = None;
+        let end = self.position;
+
+        self.inner.seek(SeekFrom::Start(start - 8))?;
+        self.inner.write_u64::<LittleEndian>(end - start)?;
+        self.inner.seek(SeekFrom::Start(end))?;
+
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for BinFileWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.position += n as u64;
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Seek> Seek for BinFileWriter<W> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = self.inner.seek(pos)?;
+
+        Ok(self.position)
+    }
+}
+
+fn invalid_input(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const FORMAT: Format = Format {
+        magic: *b"test",
+        versions: 1..=2,
+        max_sections: Some(3),
+    };
+
+    fn file() -> Vec<u8> {
+        let mut w = BinFileWriter::new(Vec::new(), b"test", 2, 3).unwrap();
+        w.write_section(1, 4, |w| w.write_all(b"abcd")).unwrap();
+        w.write_section(7, 0, |_| Ok::<_, io::Error>(())).unwrap();
+        w.write_section(2, 3, |w| w.write_all(b"xyz")).unwrap();
+        w.finish().unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let data = file();
+        let mut r = BinFileReader::new(data.as_slice(), &FORMAT).unwrap();
+        assert_eq!((r.version(), r.num_sections()), (2, 3));
+
+        let (id, size, mut section) = r.next_section().unwrap().unwrap();
+        assert_eq!((id, size, section.position()), (1, 4, 24));
+        let mut buf = [0; 2];
+        section.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"ab");
+
+        // The rest of the first section is skipped.
+        let (id, size, _) = r.next_section().unwrap().unwrap();
+        assert_eq!((id, size), (7, 0));
+        let (id, _, mut section) = r.next_section().unwrap().unwrap();
+        let mut body = Vec::new();
+        section.read_to_end(&mut body).unwrap();
+        assert_eq!((id, body.as_slice()), (2, &b"xyz"[..]));
+        assert!(r.next_section().unwrap().is_none());
+        assert_eq!(r.position(), data.len() as u64);
+
+        let mut r = BinFileReader::new(Cursor::new(&data), &FORMAT).unwrap();
+        let table = r.section_table().unwrap();
+        assert_eq!(
+            table,
+            [
+                SectionEntry {
+                    id: 1,
+                    size: 4,
+                    offset: 24
+                },
+                SectionEntry {
+                    id: 7,
+                    size: 0,
+                    offset: 40
+                },
+                SectionEntry {
+                    id: 2,
+                    size: 3,
+                    offset: 52
+                },
+            ]
+        );
+        let mut body = Vec::new();
+        r.section_at(&table[0])
+            .unwrap()
+            .read_to_end(&mut body)
+            .unwrap();
+        assert_eq!(body, b"abcd");
+
+        let mut w = BinFileWriter::new(Cursor::new(Vec::new()), b"test", 2, 3).unwrap();
+        for (id, body) in [(1, &b"abcd"[..]), (7, b""), (2, b"xyz")] {
+            w.begin_section(id).unwrap();
+            w.write_all(body).unwrap();
+            w.end_section().unwrap();
+        }
+        assert_eq!(w.finish().unwrap().into_inner(), data);
+    }
+
+    #[test]
+    fn test_errors() {
+        let data = file();
+
+        let other = Format {
+            magic: *b"wtns",
+            ..FORMAT
+        };
+        assert!(matches!(
+            BinFileReader::new(data.as_slice(), &other),
+            Err(BinFileError::InvalidMagic { found }) if found == *b"test"
+        ));
+        let old = Format {
+            versions: 1..=1,
+            ..FORMAT
+        };
+        assert!(matches!(
+            BinFileReader::new(data.as_slice(), &old),
+            Err(BinFileError::UnsupportedVersion(2))
+        ));
+        let small = Format {
+            max_sections: Some(2),
+            ..FORMAT
+        };
+        assert!(matches!(
+            BinFileReader::new(data.as_slice(), &small),
+            Err(BinFileError::TooManySections(3))
+        ));
+        assert!(matches!(
+            BinFileReader::new(&data[..6], &FORMAT),
+            Err(BinFileError::UnexpectedEof { offset: 6 })
+        ));
+
+        let mut r = BinFileReader::new(&data[..30], &FORMAT).unwrap();
+        r.next_section().unwrap();
+        assert!(matches!(
+            r.next_section(),
+            Err(BinFileError::UnexpectedEof { offset: 30 })
+        ));
+
+        let mut w = BinFileWriter::new(Vec::new(), b"test", 1, 1).unwrap();
+        assert!(w.write_section(1, 4, |w| w.write_all(b"abc")).is_err());
+        let mut w = BinFileWriter::new(Vec::new(), b"test", 1, 2).unwrap();
+        w.write_section(1, 3, |w| w.write_all(b"abc")).unwrap();
+        assert!(w.finish().is_err());
+    }
+}
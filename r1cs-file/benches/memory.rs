@@ -0,0 +1,86 @@
+//! Memory taken by parsed constraints, nested and interned, on a circuit whose coefficients are
+//! mostly 1 and -1 as in circom output. Prints the heap size of both representations.
+
+use std::io::Cursor;
+use std::mem::size_of;
+
+use r1cs_file::*;
+
+const N_CONSTRAINTS: u32 = 200_000;
+
+/// A file with `N_CONSTRAINTS` constraints whose coefficients are 1, -1, or one of 16 constants
+/// for one term in 8.
+fn generate() -> Vec<u8> {
+    let prime = FieldElement::bn254_scalar_prime();
+    let n_wires = N_CONSTRAINTS + 1;
+    let mut bytes = [0; 32];
+    bytes.copy_from_slice(prime.as_bytes());
+    bytes[0] -= 1;
+    let minus_one = FieldElement::from(bytes);
+    let coeff = |i: u32| match i % 8 {
+        0 => FieldElement::from_u64(u64::from(i / 8 % 16) + 2),
+        1 | 3 | 5 => minus_one,
+        _ => FieldElement::from_u64(1),
+    };
+    let term = |i: u32| (coeff(i), i % n_wires);
+    let constraints = (0..N_CONSTRAINTS)
+        .map(|i| {
+            Constraint(
+                vec![term(i), term(i + 1), term(i + 2)].into(),
+                vec![term(i + 3)].into(),
+                vec![term(i + 4), term(i + 5)].into(),
+            )
+        })
+        .collect();
+
+    let file = R1csFile::<32> {
+        header: Header {
+            prime,
+            n_wires,
+            n_pub_out: 1,
+            n_pub_in: 1,
+            n_prvt_in: 1,
+            n_labels: u64::from(n_wires),
+            n_constraints: N_CONSTRAINTS,
+        },
+        constraints: Constraints(constraints),
+        map: WireMap::identity(n_wires),
+        custom_gates_list: None,
+        custom_gates_application: None,
+        metadata: None,
+        custom_sections: Vec::new(),
+        version: 1,
+    };
+
+    let mut data = Vec::new();
+    file.write(&mut data).unwrap();
+    data
+}
+
+fn main() {
+    let data = generate();
+
+    let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+    let nested: usize = file.constraints.0.capacity() * size_of::<Constraint<32>>()
+        + file
+            .constraints
+            .iter()
+            .flat_map(|c| [&c.0, &c.1, &c.2])
+            .map(|lc| lc.0.capacity() * size_of::<(FieldElement<32>, u32)>())
+            .sum::<usize>();
+
+    let interned = InternedConstraints::<32>::read_file(Cursor::new(&data)).unwrap();
+    let flat = interned.heap_size();
+
+    println!(
+        "{} constraints, {} distinct coefficients",
+        interned.len(),
+        interned.pool().len()
+    );
+    println!("nested:   {:>10} bytes", nested);
+    println!(
+        "interned: {:>10} bytes ({:.1}x smaller)",
+        flat,
+        nested as f64 / flat as f64
+    );
+}
@@ -0,0 +1,77 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use r1cs_file::*;
+
+const N_CONSTRAINTS: u32 = 200_000;
+
+/// A file with `N_CONSTRAINTS` constraints of a few terms each.
+fn generate() -> Vec<u8> {
+    let n_wires = N_CONSTRAINTS + 1;
+    let term = |i: u32| {
+        (
+            FieldElement::from_u64(u64::from(i) * 0x9e37_79b9 + 1),
+            i % n_wires,
+        )
+    };
+    let constraints = (0..N_CONSTRAINTS)
+        .map(|i| {
+            Constraint(
+                vec![term(i), term(i + 1), term(i + 2)].into(),
+                vec![term(i + 3)].into(),
+                vec![term(i + 4), term(i + 5)].into(),
+            )
+        })
+        .collect();
+
+    let file = R1csFile::<32> {
+        header: Header {
+            prime: FieldElement::bn254_scalar_prime(),
+            n_wires,
+            n_pub_out: 1,
+            n_pub_in: 1,
+            n_prvt_in: 1,
+            n_labels: u64::from(n_wires),
+            n_constraints: N_CONSTRAINTS,
+        },
+        constraints: Constraints(constraints),
+        map: WireMap((0..u64::from(n_wires)).collect()),
+        custom_gates_list: None,
+        custom_gates_application: None,
+        metadata: None,
+        custom_sections: Vec::new(),
+        version: 1,
+    };
+
+    let mut data = Vec::new();
+    file.write(&mut data).unwrap();
+    data
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let data = generate();
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.sample_size(10);
+
+    group.bench_function("sequential", |b| {
+        b.iter(|| R1csFile::<32>::read(data.as_slice()).unwrap())
+    });
+
+    #[cfg(feature = "rayon")]
+    group.bench_function("parallel", |b| {
+        let opts = ReadOptions::default().parallel(true);
+        b.iter(|| R1csFile::<32>::read_with(data.as_slice(), &opts).unwrap())
+    });
+
+    group.bench_function("flat", |b| {
+        b.iter(|| FlatConstraints::<32>::read_file(std::io::Cursor::new(&data)).unwrap())
+    });
+
+    group.bench_function("borrowed", |b| {
+        b.iter(|| R1csFileRef::<32>::parse(&data).unwrap())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse);
+criterion_main!(benches);
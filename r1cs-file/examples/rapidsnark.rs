@@ -0,0 +1,35 @@
+//! Checks a circuit and a witness, writes the public signals and prints the rapidsnark command
+//! proving them.
+//!
+//! `cargo run --example rapidsnark --features wtns,json -- circuit.r1cs witness.wtns out circuit.zkey`
+
+use std::convert::TryFrom;
+use std::env;
+use std::process::exit;
+
+use r1cs_file::prepare_rapidsnark_inputs;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [r1cs, wtns, out_dir, zkey] = match <[String; 4]>::try_from(args) {
+        Ok(args) => args,
+        Err(_) => {
+            eprintln!("usage: rapidsnark <r1cs> <wtns> <out_dir> <zkey>");
+            exit(2);
+        }
+    };
+
+    match prepare_rapidsnark_inputs::<32>(&r1cs, &wtns, &out_dir) {
+        Ok(inputs) => println!(
+            "prover {} {} {} {}",
+            zkey,
+            inputs.wtns.display(),
+            inputs.proof_json.display(),
+            inputs.public_json.display()
+        ),
+        Err(e) => {
+            eprintln!("{}", e);
+            exit(1);
+        }
+    }
+}
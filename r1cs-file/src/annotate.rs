@@ -0,0 +1,326 @@
+//! Constraints rendered with signal names from a `.sym` file.
+
+use std::fmt;
+
+use crate::display::{fmt_constraint, fmt_wire};
+use crate::{Constraint, R1csFile, ReverseWireMap, SymFile};
+
+/// A circuit paired with its symbols, resolving wires to signal names through the wire map.
+///
+/// Several wires may map to the label of a signal once the circuit is optimized; every one of
+/// them is resolved to the signal name, and a name is resolved to all of them.
+#[derive(Debug, Clone)]
+pub struct AnnotatedR1cs<'a, const FS: usize> {
+    r1cs: &'a R1csFile<FS>,
+    sym: &'a SymFile,
+    reverse: ReverseWireMap,
+    warnings: Vec<SymbolWarning>,
+}
+
+/// A disagreement between a circuit and its `.sym` file, found by [`AnnotatedR1cs::new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SymbolWarning {
+    /// The label of a wire, other than the constant wire 0, has no symbol.
+    UnnamedLabel { wire: u32, label: u64 },
+    /// A symbol is assigned to a wire past the wires of the circuit.
+    WireOutOfRange { label: u64, wire: u32 },
+    /// A symbol is assigned to a wire mapped to another label.
+    LabelMismatch { label: u64, wire: u32, mapped: u64 },
+}
+
+impl fmt::Display for SymbolWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymbolWarning::UnnamedLabel { wire, label } => {
+                write!(f, "Label {} of wire {} has no symbol", label, wire)
+            }
+            SymbolWarning::WireOutOfRange { label, wire } => write!(
+                f,
+                "Symbol of label {} is assigned to wire {} past the last wire",
+                label, wire
+            ),
+            SymbolWarning::LabelMismatch {
+                label,
+                wire,
+                mapped,
+            } => write!(
+                f,
+                "Symbol of label {} is assigned to wire {} mapped to label {}",
+                label, wire, mapped
+            ),
+        }
+    }
+}
+
+impl<'a, const FS: usize> AnnotatedR1cs<'a, FS> {
+    /// Pairs the circuit with its symbols, collecting the inconsistencies between them into
+    /// [`AnnotatedR1cs::warnings`].
+    pub fn new(r1cs: &'a R1csFile<FS>, sym: &'a SymFile) -> Self {
+        let mut warnings = Vec::new();
+        for (wire, &label) in (0u32..).zip(&r1cs.map.0).skip(1) {
+            if sym.by_label(label).is_none() {
+                warnings.push(SymbolWarning::UnnamedLabel { wire, label });
+            }
+        }
+
+        for entry in sym.entries() {
+            let wire = match entry.wire {
+                Some(wire) => wire,
+                None => continue,
+            };
+            match r1cs.map.0.get(wire as usize) {
+                _ if wire >= r1cs.header.n_wires => warnings.push(SymbolWarning::WireOutOfRange {
+                    label: entry.label,
+                    wire,
+                }),
+                Some(&mapped) if mapped != entry.label => {
+                    warnings.push(SymbolWarning::LabelMismatch {
+                        label: entry.label,
+                        wire,
+                        mapped,
+                    })
+                }
+                _ => {}
+            }
+        }
+
+        AnnotatedR1cs {
+            r1cs,
+            sym,
+            reverse: r1cs.map.reverse(),
+            warnings,
+        }
+    }
+
+    pub fn warnings(&self) -> &[SymbolWarning] {
+        &self.warnings
+    }
+
+    /// The name of the signal on `wire`, if its label has a symbol.
+    pub fn wire_name(&self, wire: u32) -> Option<&'a str> {
+        let label = *self.r1cs.map.0.get(wire as usize)?;
+        self.sym.by_label(label).map(|entry| entry.name.as_str())
+    }
+
+    /// The lowest wire of the signal `name`, or `None` if it's unknown or optimized out.
+    pub fn name_to_wire(&self, name: &str) -> Option<u32> {
+        self.name_to_wires(name).first().copied()
+    }
+
+    /// Every wire mapped to the label of the signal `name` in ascending order, empty if it's
+    /// unknown or optimized out.
+    pub fn name_to_wires(&self, name: &str) -> &[u32] {
+        match self.sym.by_name(name) {
+            Some(entry) => self.reverse.all(entry.label),
+            None => &[],
+        }
+    }
+
+    /// The value in `wtns` of every wire of the signal `name`, together with the wire, see
+    /// [`AnnotatedR1cs::name_to_wires`]. Wires past the end of the witness are left out.
+    #[cfg(feature = "wtns")]
+    pub fn values_of<'w>(
+        &self,
+        name: &str,
+        wtns: &'w wtns_file::WtnsFile<FS>,
+    ) -> Vec<(u32, &'w crate::FieldElement<FS>)> {
+        let wires = self.name_to_wires(name).iter();
+        wires
+            .filter_map(|&wire| Some((wire, wtns.witness.0.get(wire as usize)?)))
+            .collect()
+    }
+
+    /// Renders constraint `i` like [`Constraint::display_with_prime`], with wires replaced by
+    /// signal names, e.g. `(3*main.x + main.y) * (main.z) = (main.out)`. Unnamed wires are shown
+    /// as `w_<index>`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is not below the number of constraints.
+    pub fn annotate_constraint(&self, i: usize) -> String {
+        Annotated {
+            view: self,
+            constraint: &self.r1cs.constraints.0[i],
+        }
+        .to_string()
+    }
+}
+
+/// Names the witness values of the circuit in [`wtns_file::WtnsFile::write_csv`], witness index
+/// `i` being wire `i`.
+#[cfg(feature = "csv")]
+impl<const FS: usize> wtns_file::SignalNames for AnnotatedR1cs<'_, FS> {
+    fn signal_name(&self, index: usize) -> Option<&str> {
+        use std::convert::TryFrom;
+
+        u32::try_from(index)
+            .ok()
+            .and_then(|wire| self.wire_name(wire))
+    }
+}
+
+struct Annotated<'v, 'a, const FS: usize> {
+    view: &'v AnnotatedR1cs<'a, FS>,
+    constraint: &'v Constraint<FS>,
+}
+
+impl<const FS: usize> fmt::Display for Annotated<'_, '_, FS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let wire = |wire: u32, f: &mut fmt::Formatter<'_>| match self.view.wire_name(wire) {
+            Some(name) => f.write_str(name),
+            None => fmt_wire(wire, f),
+        };
+        fmt_constraint(
+            self.constraint,
+            Some(&self.view.r1cs.header.prime),
+            &wire,
+            f,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotate() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let data = std::fs::read("tests/simple_circuit.sym").unwrap();
+        let sym = SymFile::read(data.as_slice()).unwrap();
+
+        let annotated = AnnotatedR1cs::new(&file, &sym);
+        assert_eq!(annotated.warnings(), []);
+        assert_eq!(annotated.wire_name(6), Some("main.mul.out"));
+        assert_eq!(annotated.wire_name(0), None);
+        assert_eq!(annotated.wire_name(7), None);
+        assert_eq!(annotated.name_to_wire("main.a"), Some(2));
+        assert_eq!(annotated.name_to_wire("main.tmp"), None);
+        assert_eq!(
+            annotated.annotate_constraint(0),
+            "(3*main.d + 8*main.mul.out) * (2*w_0 + 20*main.a + 12*main.b) = (5*w_0 + 7*main.a)"
+        );
+    }
+
+    #[test]
+    fn test_warnings() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let sym = SymFile::read(&b"3,1,0,main.out\n10,3,0,main.a\n11,9,0,main.b\n"[..]).unwrap();
+
+        let annotated = AnnotatedR1cs::new(&file, &sym);
+        assert_eq!(
+            annotated.warnings(),
+            [
+                SymbolWarning::UnnamedLabel { wire: 4, label: 12 },
+                SymbolWarning::UnnamedLabel { wire: 5, label: 15 },
+                SymbolWarning::UnnamedLabel {
+                    wire: 6,
+                    label: 324
+                },
+                SymbolWarning::LabelMismatch {
+                    label: 10,
+                    wire: 3,
+                    mapped: 11
+                },
+                SymbolWarning::WireOutOfRange { label: 11, wire: 9 },
+            ]
+        );
+        // Resolved through the wire map rather than the wire of the symbol.
+        assert_eq!(annotated.name_to_wire("main.b"), Some(3));
+        assert_eq!(annotated.name_to_wire("main.a"), Some(2));
+    }
+
+    /// `tests/aliased_circuit.r1cs` is `tests/simple_circuit.r1cs` with wire 4 aliasing `main.b`
+    /// and wire 6 aliasing `main.d`.
+    fn read_aliased() -> (R1csFile<32>, SymFile) {
+        let data = std::fs::read("tests/aliased_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let data = std::fs::read("tests/aliased_circuit.sym").unwrap();
+
+        (file, SymFile::read(data.as_slice()).unwrap())
+    }
+
+    #[test]
+    fn test_aliased_labels() {
+        let (file, sym) = read_aliased();
+        let reverse = file.map.reverse();
+        assert_eq!(reverse.first(11), Some(3));
+        assert_eq!(reverse.all(11), [3, 4]);
+        assert_eq!(reverse.all(15), [5, 6]);
+        assert_eq!(reverse.all(12), [] as [u32; 0]);
+        assert_eq!(
+            reverse.iter().collect::<Vec<_>>(),
+            [
+                (0, &[0][..]),
+                (3, &[1][..]),
+                (10, &[2][..]),
+                (11, &[3, 4][..]),
+                (15, &[5, 6][..])
+            ]
+        );
+
+        let annotated = AnnotatedR1cs::new(&file, &sym);
+        assert_eq!(annotated.warnings(), []);
+        assert_eq!(annotated.name_to_wire("main.d"), Some(5));
+        assert_eq!(annotated.name_to_wires("main.d"), [5, 6]);
+        assert_eq!(annotated.name_to_wires("main.b"), [3, 4]);
+        assert_eq!(annotated.name_to_wires("main.tmp"), [] as [u32; 0]);
+        assert_eq!(annotated.name_to_wires("main.c"), [] as [u32; 0]);
+        assert_eq!(annotated.wire_name(4), Some("main.b"));
+        assert_eq!(annotated.wire_name(6), Some("main.d"));
+        assert_eq!(
+            annotated.annotate_constraint(0),
+            "(3*main.d + 8*main.d) * (2*w_0 + 20*main.a + 12*main.b) = (5*w_0 + 7*main.a)"
+        );
+    }
+
+    #[cfg(feature = "wtns")]
+    #[test]
+    fn test_values_of() {
+        use crate::FieldElement;
+
+        let (file, sym) = read_aliased();
+        let annotated = AnnotatedR1cs::new(&file, &sym);
+        let mut witness = file.header.witness_template();
+        for (wire, value) in witness.witness.0.iter_mut().enumerate() {
+            *value = FieldElement::from_u64(wire as u64 * 10);
+        }
+
+        let e = |value| FieldElement::<32>::from_u64(value);
+        assert_eq!(
+            annotated.values_of("main.d", &witness),
+            [(5, &e(50)), (6, &e(60))]
+        );
+        assert_eq!(annotated.values_of("main.a", &witness), [(2, &e(20))]);
+        assert_eq!(annotated.values_of("main.tmp", &witness), []);
+
+        witness.witness.0.truncate(6);
+        assert_eq!(annotated.values_of("main.d", &witness), [(5, &e(50))]);
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_witness_csv() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let data = std::fs::read("tests/simple_circuit.sym").unwrap();
+        let sym = SymFile::read(data.as_slice()).unwrap();
+        let annotated = AnnotatedR1cs::new(&file, &sym);
+
+        let witness = file.header.witness_template();
+        let mut csv = Vec::new();
+        let opts = wtns_file::CsvOptions {
+            range: Some(5..7),
+            names: Some(&annotated),
+            ..Default::default()
+        };
+        witness.write_csv(&mut csv, opts).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "index,value,name\n5,0,main.d\n6,0,main.mul.out\n"
+        );
+    }
+}
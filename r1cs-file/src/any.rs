@@ -0,0 +1,184 @@
+//! Reading files whose field size is only known at runtime.
+
+use std::convert::TryFrom;
+use std::io::{Cursor, Read, Write};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use iden3_binfile::BinFileReader;
+
+use crate::{R1csError, R1csFile, Result, SectionType, FORMAT};
+
+/// An r1cs file of one of the supported field sizes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum R1csFileAny {
+    /// 8-byte fields, such as Goldilocks.
+    Fs8(R1csFile<8>),
+    /// 32-byte fields, such as the BN254 and BLS12-381 scalar fields.
+    Fs32(R1csFile<32>),
+    Fs48(R1csFile<48>),
+    Fs64(R1csFile<64>),
+}
+
+impl R1csFileAny {
+    /// Reads a file, picking the variant from the field size declared in the header.
+    pub fn read<R: Read>(mut r: R) -> Result<Self> {
+        let (field_size, offset, consumed) = peek_field_size(&mut r)?;
+        let r = Cursor::new(consumed).chain(r);
+
+        match field_size {
+            8 => R1csFile::read(r).map(R1csFileAny::Fs8),
+            32 => R1csFile::read(r).map(R1csFileAny::Fs32),
+            48 => R1csFile::read(r).map(R1csFileAny::Fs48),
+            64 => R1csFile::read(r).map(R1csFileAny::Fs64),
+            found => Err(R1csError::UnsupportedFieldSize { found, offset }),
+        }
+    }
+
+    pub fn write<W: Write>(&self, w: W) -> Result<()> {
+        match self {
+            R1csFileAny::Fs8(file) => file.write(w),
+            R1csFileAny::Fs32(file) => file.write(w),
+            R1csFileAny::Fs48(file) => file.write(w),
+            R1csFileAny::Fs64(file) => file.write(w),
+        }
+    }
+
+    /// Field element size in bytes.
+    pub fn field_size(&self) -> usize {
+        match self {
+            R1csFileAny::Fs8(_) => 8,
+            R1csFileAny::Fs32(_) => 32,
+            R1csFileAny::Fs48(_) => 48,
+            R1csFileAny::Fs64(_) => 64,
+        }
+    }
+}
+
+/// Reads up to the field size in the header section, returning it with its offset and every byte
+/// consumed so far, so that parsing can start over.
+pub(crate) fn peek_field_size<R: Read>(r: R) -> Result<(u32, u64, Vec<u8>)> {
+    let recorder = Recorder {
+        inner: r,
+        recorded: Vec::new(),
+    };
+    let mut file = BinFileReader::new(recorder, &FORMAT)?;
+
+    // Sections before the header are rare, they are skipped and kept around for the actual parsing.
+    while let Some((id, _, mut section)) = file.next_section()? {
+        if SectionType::from_id(id) == Some(SectionType::Header) {
+            let offset = section.position();
+            let field_size = section
+                .read_u32::<LittleEndian>()
+                .map_err(|e| R1csError::from(e).eof_in(Some(SectionType::Header), offset))?;
+
+            return Ok((field_size, offset, file.into_inner().recorded));
+        }
+    }
+
+    Err(R1csError::MissingSection(SectionType::Header))
+}
+
+/// Reader wrapper keeping a copy of everything read.
+struct Recorder<R> {
+    inner: R,
+    recorded: Vec<u8>,
+}
+
+impl<R: Read> Read for Recorder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.recorded.extend_from_slice(&buf[..n]);
+
+        Ok(n)
+    }
+}
+
+macro_rules! impl_try_from_any {
+    ($($variant:ident => $fs:literal),*) => {
+        $(
+            impl From<R1csFile<$fs>> for R1csFileAny {
+                fn from(file: R1csFile<$fs>) -> Self {
+                    R1csFileAny::$variant(file)
+                }
+            }
+
+            /// Fails with the original value if the field size doesn't match.
+            impl TryFrom<R1csFileAny> for R1csFile<$fs> {
+                type Error = R1csFileAny;
+
+                fn try_from(file: R1csFileAny) -> std::result::Result<Self, Self::Error> {
+                    match file {
+                        R1csFileAny::$variant(file) => Ok(file),
+                        other => Err(other),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_any!(Fs8 => 8, Fs32 => 32, Fs48 => 48, Fs64 => 64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FieldElement;
+
+    #[test]
+    fn test_read_any() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let expected = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        let any = R1csFileAny::read(data.as_slice()).unwrap();
+        assert_eq!(any.field_size(), 32);
+        assert!(R1csFile::<64>::try_from(any).is_err());
+        let any = R1csFileAny::read(data.as_slice()).unwrap();
+        assert_eq!(R1csFile::<32>::try_from(any).unwrap(), expected);
+
+        // An 8-byte field file with the header after the wire map.
+        let small = R1csFile::<8> {
+            header: crate::Header {
+                prime: crate::primes::GOLDILOCKS,
+                n_wires: 1,
+                n_pub_out: 0,
+                n_pub_in: 0,
+                n_prvt_in: 0,
+                n_labels: 1,
+                n_constraints: 1,
+            },
+            constraints: crate::Constraints(vec![crate::Constraint(
+                vec![(FieldElement::from_u64(2), 0)].into(),
+                Default::default(),
+                Default::default(),
+            )]),
+            map: crate::WireMap(vec![0]),
+            custom_gates_list: None,
+            custom_gates_application: None,
+            metadata: None,
+            custom_sections: vec![],
+            version: 1,
+        };
+        let mut serialized = Vec::new();
+        small.write(&mut serialized).unwrap();
+        let header_len = 12 + 4 + 8 + 4 * 4 + 8 + 4;
+        let header: Vec<u8> = serialized.drain(12..12 + header_len).collect();
+        serialized.extend(header);
+
+        assert_eq!(
+            R1csFileAny::read(serialized.as_slice()).unwrap(),
+            R1csFileAny::Fs8(small)
+        );
+
+        let mut unsupported = data;
+        unsupported[24..28].copy_from_slice(&16u32.to_le_bytes());
+        assert!(matches!(
+            R1csFileAny::read(unsupported.as_slice()),
+            Err(R1csError::UnsupportedFieldSize {
+                found: 16,
+                offset: 24
+            })
+        ));
+    }
+}
@@ -0,0 +1,162 @@
+//! Appending constraints to a file in place.
+
+use std::convert::TryFrom;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use byteorder::{LittleEndian, WriteBytesExt};
+use iden3_binfile::BinFileReader;
+
+use crate::validate::wire_out_of_range;
+use crate::{Constraint, CountingReader, Header, R1csError, R1csFile, Result, SectionType, FORMAT};
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Appends constraints to the file in `file` without rewriting the sections before the
+    /// constraints, adding `extra_wires` wires to the header.
+    ///
+    /// The constraints are appended to the last constraints section. Sections after it are moved
+    /// to make room, which requires holding them in memory. The header constraint and wire counts
+    /// are updated in place; the wire map is left as is.
+    ///
+    /// Every term of `new` must reference a wire below the updated wire count, otherwise
+    /// [`R1csError::Invalid`] is returned before anything is written.
+    pub fn append_constraints<F: Read + Write + Seek>(
+        file: F,
+        new: &[Constraint<FS>],
+        extra_wires: u32,
+    ) -> Result<()> {
+        let mut file = BinFileReader::new(file, &FORMAT)?;
+        let table = file.section_table()?;
+
+        let header_entry = table
+            .iter()
+            .find(|entry| SectionType::from_id(entry.id) == Some(SectionType::Header))
+            .ok_or(R1csError::MissingSection(SectionType::Header))?;
+        let constraints_entry = table
+            .iter()
+            .rev()
+            .find(|entry| SectionType::from_id(entry.id) == Some(SectionType::Constraint))
+            .ok_or(R1csError::MissingSection(SectionType::Constraint))?;
+
+        let mut r = CountingReader::at(file.section_at(header_entry)?, header_entry.offset);
+        let header = Header::<FS>::read(&mut r)
+            .map_err(|e| e.eof_in(Some(SectionType::Header), r.position()))?;
+
+        let n_wires = header
+            .n_wires
+            .checked_add(extra_wires)
+            .ok_or_else(|| invalid_input("wire count overflows"))?;
+        let n_constraints = u32::try_from(new.len())
+            .ok()
+            .and_then(|n| header.n_constraints.checked_add(n))
+            .ok_or_else(|| invalid_input("constraint count overflows"))?;
+        if let Some(e) = wire_out_of_range(new, header.n_constraints as usize, n_wires) {
+            return Err(R1csError::Invalid(e));
+        }
+
+        let mut data = Vec::with_capacity(new.iter().map(Constraint::size).sum());
+        for c in new {
+            c.write(&mut data)?;
+        }
+
+        // Move the following sections, if any, after the new constraints.
+        let end = constraints_entry.offset + constraints_entry.size;
+        let mut tail = Vec::new();
+        file.seek(SeekFrom::Start(end))?;
+        file.read_to_end(&mut tail)?;
+
+        let mut w = file.into_inner();
+        w.seek(SeekFrom::Start(end))?;
+        w.write_all(&data)?;
+        w.write_all(&tail)?;
+
+        // Section size, right before the body.
+        w.seek(SeekFrom::Start(constraints_entry.offset - 8))?;
+        w.write_u64::<LittleEndian>(constraints_entry.size + data.len() as u64)?;
+
+        // Field size and prime come first in the header.
+        w.seek(SeekFrom::Start(header_entry.offset + 4 + FS as u64))?;
+        w.write_u32::<LittleEndian>(n_wires)?;
+        w.seek(SeekFrom::Current(3 * 4 + 8))?;
+        w.write_u32::<LittleEndian>(n_constraints)?;
+        w.flush()?;
+
+        Ok(())
+    }
+}
+
+pub(crate) fn invalid_input(message: &str) -> R1csError {
+    io::Error::new(io::ErrorKind::InvalidInput, message).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FieldElement, LinearCombination, SectionInfo, ValidationError};
+    use std::io::Cursor;
+
+    fn new_constraints(n_wires: u32) -> Vec<Constraint<32>> {
+        vec![
+            Constraint(
+                LinearCombination::term(FieldElement::from_u64(2), n_wires),
+                LinearCombination::term(FieldElement::from_u64(3), 1),
+                LinearCombination::new(),
+            ),
+            Constraint::default(),
+        ]
+    }
+
+    /// The file rewritten from scratch with the constraints appended.
+    fn expected(data: &[u8], new: Vec<Constraint<32>>, extra_wires: u32) -> R1csFile<32> {
+        let mut file = R1csFile::<32>::read(data).unwrap();
+        file.header.n_wires += extra_wires;
+        file.header.n_constraints += new.len() as u32;
+        file.constraints.0.extend(new);
+        file
+    }
+
+    #[test]
+    fn test_append_constraints() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let n_wires = R1csFile::<32>::read(data.as_slice())
+            .unwrap()
+            .header
+            .n_wires;
+
+        // The wire map follows the constraints and has to be moved.
+        let mut appended = Cursor::new(data.clone());
+        R1csFile::append_constraints(&mut appended, &new_constraints(n_wires), 1).unwrap();
+        let file = R1csFile::<32>::read(appended.get_ref().as_slice()).unwrap();
+        assert_eq!(file, expected(&data, new_constraints(n_wires), 1));
+        assert_eq!(appended.into_inner(), file.serialize());
+
+        // Constraints last: only the new constraints are written.
+        let sections = R1csFile::<32>::scan_sections(Cursor::new(&data)).unwrap();
+        let bytes = |s: &SectionInfo| &data[s.offset as usize..(s.offset + 12 + s.size) as usize];
+        let mut reordered = data[..12].to_vec();
+        for i in [0, 2, 1] {
+            reordered.extend_from_slice(bytes(&sections[i]));
+        }
+        let mut appended = Cursor::new(reordered.clone());
+        R1csFile::append_constraints(&mut appended, &new_constraints(n_wires), 1).unwrap();
+        assert_eq!(
+            R1csFile::<32>::read(appended.get_ref().as_slice()).unwrap(),
+            expected(&reordered, new_constraints(n_wires), 1)
+        );
+    }
+
+    #[test]
+    fn test_append_out_of_range_wire() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let header = R1csFile::<32>::read_header(Cursor::new(&data)).unwrap();
+
+        let mut appended = Cursor::new(data.clone());
+        let e = R1csFile::append_constraints(&mut appended, &new_constraints(header.n_wires), 0)
+            .unwrap_err();
+        assert!(matches!(
+            e,
+            R1csError::Invalid(ValidationError::WireOutOfRange { constraint, wire, .. })
+                if constraint == header.n_constraints as usize && wire == header.n_wires
+        ));
+        assert_eq!(appended.into_inner(), data);
+    }
+}
@@ -0,0 +1,219 @@
+//! arbitrary support, behind the `arbitrary` feature.
+//!
+//! A generated [`R1csFile`] is consistent: the header counts match the constraints and the wire
+//! map, the signals fit in the wires, every term refers to an existing wire and every coefficient
+//! is below the prime, so it can be written with [`R1csFile::write`] and read back unchanged. The
+//! other types are generated on their own, without a prime or a number of wires to respect.
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::{
+    Constraint, Constraints, CustomGate, CustomGateApplication, CustomGatesApplication,
+    CustomGatesList, CustomSection, FieldElement, Header, LinearCombination, R1csFile, SectionType,
+    WireMap,
+};
+
+impl<'a, const FS: usize> Arbitrary<'a> for Header<FS> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let n_wires = u.arbitrary::<u32>()?.max(1);
+        let n_constraints = u.arbitrary()?;
+        let prime = FieldElement::arbitrary_modulus(u)?;
+
+        header(u, prime, n_wires, n_constraints)
+    }
+}
+
+impl<'a, const FS: usize> Arbitrary<'a> for LinearCombination<FS> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.arbitrary().map(LinearCombination)
+    }
+}
+
+impl<'a, const FS: usize> Arbitrary<'a> for Constraint<FS> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Constraint(u.arbitrary()?, u.arbitrary()?, u.arbitrary()?))
+    }
+}
+
+impl<'a, const FS: usize> Arbitrary<'a> for Constraints<FS> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.arbitrary().map(Constraints)
+    }
+}
+
+impl<'a> Arbitrary<'a> for WireMap {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.arbitrary().map(WireMap)
+    }
+}
+
+impl<'a, const FS: usize> Arbitrary<'a> for R1csFile<FS> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let prime = FieldElement::arbitrary_modulus(u)?;
+        // The constant wire is always there.
+        let mut map = WireMap::arbitrary(u)?;
+        if map.is_empty() {
+            map.0.push(0);
+        }
+        let n_wires = map.len() as u32;
+
+        let constraints = repeat(u, |u| constraint(u, &prime, n_wires))?;
+        let header = header(u, prime, n_wires, constraints.len() as u32)?;
+
+        let custom_gates_list = if u.arbitrary()? {
+            let gates = repeat(u, |u| custom_gate(u, &prime))?;
+            Some(CustomGatesList(gates))
+        } else {
+            None
+        };
+        let n_gates = custom_gates_list.as_ref().map_or(0, |list| list.0.len());
+        let custom_gates_application = if n_gates > 0 && u.arbitrary()? {
+            let applications = repeat(u, |u| application(u, n_gates as u32, n_wires))?;
+            Some(CustomGatesApplication(applications))
+        } else {
+            None
+        };
+
+        let metadata = u.arbitrary()?;
+        let custom_sections = repeat(u, custom_section)?;
+        // Custom gates are only written in version 2 files.
+        let version = if custom_gates_list.is_some() {
+            2
+        } else {
+            u.int_in_range(1..=2)?
+        };
+
+        Ok(R1csFile {
+            header,
+            constraints: Constraints(constraints),
+            map,
+            custom_gates_list,
+            custom_gates_application,
+            metadata,
+            custom_sections,
+            version,
+        })
+    }
+}
+
+/// A header whose public and private signals fit in `n_wires`, which must not be zero.
+fn header<const FS: usize>(
+    u: &mut Unstructured<'_>,
+    prime: FieldElement<FS>,
+    n_wires: u32,
+    n_constraints: u32,
+) -> Result<Header<FS>> {
+    let signals = n_wires - 1;
+    let n_pub_out = u.int_in_range(0..=signals)?;
+    let n_pub_in = u.int_in_range(0..=signals - n_pub_out)?;
+    let n_prvt_in = u.int_in_range(0..=signals - n_pub_out - n_pub_in)?;
+
+    Ok(Header {
+        prime,
+        n_wires,
+        n_pub_out,
+        n_pub_in,
+        n_prvt_in,
+        n_labels: u.arbitrary()?,
+        n_constraints,
+    })
+}
+
+fn constraint<const FS: usize>(
+    u: &mut Unstructured<'_>,
+    prime: &FieldElement<FS>,
+    n_wires: u32,
+) -> Result<Constraint<FS>> {
+    let combination = |u: &mut Unstructured<'_>| -> Result<LinearCombination<FS>> {
+        let terms = repeat(u, |u| {
+            let coeff = FieldElement::arbitrary_below(u, prime)?;
+            Ok((coeff, u.int_in_range(0..=n_wires - 1)?))
+        })?;
+
+        Ok(LinearCombination(terms))
+    };
+
+    Ok(Constraint(
+        combination(u)?,
+        combination(u)?,
+        combination(u)?,
+    ))
+}
+
+fn custom_gate<const FS: usize>(
+    u: &mut Unstructured<'_>,
+    prime: &FieldElement<FS>,
+) -> Result<CustomGate<FS>> {
+    // Names are written null-terminated.
+    let name = String::arbitrary(u)?.replace('\0', "");
+    let parameters = repeat(u, |u| FieldElement::arbitrary_below(u, prime))?;
+
+    Ok(CustomGate { name, parameters })
+}
+
+fn application(
+    u: &mut Unstructured<'_>,
+    n_gates: u32,
+    n_wires: u32,
+) -> Result<CustomGateApplication> {
+    let gate = u.int_in_range(0..=n_gates - 1)?;
+    let signals = repeat(u, |u| u.int_in_range(0..=u64::from(n_wires) - 1))?;
+
+    Ok(CustomGateApplication { gate, signals })
+}
+
+/// A section of an id no known section uses.
+fn custom_section(u: &mut Unstructured<'_>) -> Result<CustomSection> {
+    let mut id = u.arbitrary()?;
+    if SectionType::from_id(id).is_some() {
+        id += SectionType::CustomGatesApplication as u32;
+    }
+
+    Ok(CustomSection {
+        id,
+        data: u.arbitrary()?,
+    })
+}
+
+/// Generates items with `f` while the data says to continue, like [`Unstructured::arbitrary_iter`]
+/// but letting `f` use `u`.
+fn repeat<T>(
+    u: &mut Unstructured<'_>,
+    mut f: impl FnMut(&mut Unstructured<'_>) -> Result<T>,
+) -> Result<Vec<T>> {
+    let mut items = Vec::new();
+    while !u.is_empty() && u.arbitrary()? {
+        items.push(f(u)?);
+    }
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Writes the file generated from `data` and reads it back.
+    fn round_trip<const FS: usize>(data: &[u8]) -> std::result::Result<(), TestCaseError> {
+        let file = R1csFile::<FS>::arbitrary(&mut Unstructured::new(data)).unwrap();
+        let mut written = Vec::new();
+        file.write(&mut written).unwrap();
+        prop_assert_eq!(written.len(), file.serialized_size());
+        prop_assert_eq!(&R1csFile::read(written.as_slice()).unwrap(), &file);
+        prop_assert_eq!(
+            &R1csFile::read_seek(std::io::Cursor::new(&written)).unwrap(),
+            &file
+        );
+
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn test_write_read_identity(data in prop::collection::vec(any::<u8>(), 0..4096)) {
+            round_trip::<8>(&data)?;
+            round_trip::<32>(&data)?;
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Conversions to and from arkworks prime fields, behind the `ark` feature.
+
+use ark_ff::{BigInteger, PrimeField};
+
+use crate::{Constraint, FieldConversionError, Header, LinearCombination, ValidationError};
+
+/// The `A`, `B` and `C` rows of a constraint as `(coefficient, wire)` pairs.
+pub type FieldConstraint<F> = (Vec<(F, usize)>, Vec<(F, usize)>, Vec<(F, usize)>);
+
+impl<const FS: usize> Header<FS> {
+    /// Checks that the prime is the modulus of `F`.
+    pub fn check_modulus<F: PrimeField>(&self) -> Result<(), ValidationError> {
+        let modulus = F::MODULUS.to_bytes_le();
+        let len = modulus.len().max(FS);
+        let byte = |bytes: &[u8], i: usize| bytes.get(i).copied().unwrap_or(0);
+        if (0..len).all(|i| byte(self.prime.as_bytes(), i) == byte(&modulus, i)) {
+            return Ok(());
+        }
+
+        let hex = |bytes: &[u8]| {
+            let digits: String = bytes.iter().rev().map(|b| format!("{:02x}", b)).collect();
+            format!("0x{}", digits.trim_start_matches('0'))
+        };
+        Err(ValidationError::ModulusMismatch {
+            prime: hex(self.prime.as_bytes()),
+            modulus: hex(&modulus),
+        })
+    }
+}
+
+impl<const FS: usize> Constraint<FS> {
+    /// Converts the coefficients of every row into `F`.
+    pub fn to_field<F: PrimeField>(&self) -> Result<FieldConstraint<F>, FieldConversionError> {
+        Ok((
+            to_field_terms(&self.0)?,
+            to_field_terms(&self.1)?,
+            to_field_terms(&self.2)?,
+        ))
+    }
+}
+
+fn to_field_terms<F: PrimeField, const FS: usize>(
+    lc: &LinearCombination<FS>,
+) -> Result<Vec<(F, usize)>, FieldConversionError> {
+    lc.iter()
+        .map(|(coeff, wire)| Ok((coeff.to_field()?, *wire as usize)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FieldElement, R1csFile};
+    use ark_bn254::Fr;
+
+    #[test]
+    fn test_field_conversions() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        assert_eq!(file.header.check_modulus::<Fr>(), Ok(()));
+
+        let (a, _, _) = file.constraints.0[0].to_field::<Fr>().unwrap();
+        assert_eq!(a[0], (Fr::from(3u64), 5));
+        let (_, b, _) = file.constraints.0[2].to_field::<Fr>().unwrap();
+        assert_eq!(b[0], (Fr::from(6u64), 0));
+
+        assert_eq!(
+            Constraint::new(vec![(file.header.prime, 1)], vec![], vec![]).to_field::<Fr>(),
+            Err(FieldConversionError::NotInField)
+        );
+
+        let header = Header {
+            prime: FieldElement::<32>::from_u64(7),
+            ..file.header
+        };
+        assert_eq!(
+            header.check_modulus::<Fr>(),
+            Err(ValidationError::ModulusMismatch {
+                prime: "0x7".to_owned(),
+                modulus: "0x30644e72e131a029b85045b68181585d2833e84879b97091\
+                          43e1f593f0000001"
+                    .to_owned(),
+            })
+        );
+    }
+}
@@ -0,0 +1,800 @@
+//! Comparison of two directories of circuit artifacts, such as the outputs of two releases.
+
+use std::fmt;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use crate::any::peek_field_size;
+use crate::content_id::Fnv;
+use crate::fingerprint::{write_fingerprint_head, write_fingerprint_tail};
+use crate::{
+    r1cs_diff, CircuitStats, DiffOptions, Header, HeaderDifference, R1csDiff, R1csError, R1csFile,
+    R1csReader, Result, SectionType, ZkeyHeader,
+};
+
+/// Options for [`compare_artifact_dirs`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirCompareOptions {
+    /// Circuits up to this size, in bytes, are read whole for [`r1cs_diff`] and
+    /// [`R1csFile::stats`]. Larger ones are streamed for their fingerprint only.
+    pub max_diff_size: u64,
+    /// Options of the structural diff.
+    pub diff: DiffOptions,
+}
+
+impl Default for DirCompareOptions {
+    fn default() -> Self {
+        DirCompareOptions {
+            max_diff_size: 256 << 20,
+            diff: DiffOptions::default(),
+        }
+    }
+}
+
+/// The comparison of two directories, see [`compare_artifact_dirs`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DirComparisonReport {
+    pub old: PathBuf,
+    pub new: PathBuf,
+    /// One entry per `.r1cs` file of either directory, sorted by name.
+    pub circuits: Vec<CircuitComparison>,
+    /// Directories and entries that couldn't be listed.
+    pub errors: Vec<String>,
+}
+
+/// The two versions of a circuit.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CircuitComparison {
+    /// The file name, the same in both directories.
+    pub name: String,
+    pub status: CircuitStatus,
+    pub old: Artifact,
+    pub new: Artifact,
+    /// Header fields that differ, the field size included, if both files were parsed.
+    pub header: Vec<HeaderDifference>,
+    /// The number of constraints of the new file minus those of the old one, if both files were
+    /// parsed.
+    pub constraint_delta: Option<i64>,
+    /// Statistics that differ, if both files were read whole.
+    pub stats: Vec<StatDifference>,
+    /// The structural diff, if both files were read whole and have the same field size.
+    pub diff: Option<R1csDiff>,
+    /// Problems of the `.zkey` and `.wtns` files of the new directory named after the circuit,
+    /// checked against the new circuit.
+    pub cross_checks: Vec<CrossCheck>,
+}
+
+/// The outcome of comparing the two versions of a circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CircuitStatus {
+    /// Both files have the same fingerprint.
+    Unchanged,
+    Changed,
+    OnlyInOld,
+    OnlyInNew,
+    /// At least one of the files couldn't be parsed.
+    Unparsable,
+}
+
+/// One version of a circuit.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "state", rename_all = "snake_case"))]
+pub enum Artifact {
+    /// The directory has no file of this name.
+    Missing,
+    Unparsable {
+        error: String,
+    },
+    Parsed(ArtifactSummary),
+}
+
+/// The header of a circuit file with its fingerprint, and its statistics for smaller files.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ArtifactSummary {
+    pub file_size: u64,
+    pub field_size: u32,
+    /// The prime, in hexadecimal.
+    pub prime: String,
+    pub n_wires: u32,
+    pub n_pub_out: u32,
+    pub n_pub_in: u32,
+    pub n_prvt_in: u32,
+    pub n_labels: u64,
+    pub n_constraints: u32,
+    /// FNV-1a 128 of the header, of the constraints with their terms sorted and of the wire map,
+    /// in hexadecimal. `None` if the file can't be streamed, its header coming after the
+    /// constraints.
+    pub fingerprint: Option<String>,
+    /// The statistics of a file read whole, see [`DirCompareOptions::max_diff_size`].
+    pub stats: Option<CircuitStats>,
+}
+
+/// A statistic that differs, with both values formatted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StatDifference {
+    pub stat: &'static str,
+    pub old: String,
+    pub new: String,
+}
+
+/// A file of the new directory that doesn't match its circuit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CrossCheck {
+    pub file: String,
+    pub problem: String,
+}
+
+impl DirComparisonReport {
+    /// The number of circuits with this status.
+    pub fn count(&self, status: CircuitStatus) -> usize {
+        self.circuits.iter().filter(|c| c.status == status).count()
+    }
+
+    /// Whether every circuit is unchanged, every new file matches its circuit and both
+    /// directories were listed.
+    pub fn is_clean(&self) -> bool {
+        self.errors.is_empty()
+            && self
+                .circuits
+                .iter()
+                .all(|c| c.status == CircuitStatus::Unchanged && c.cross_checks.is_empty())
+    }
+}
+
+/// Compares the `.r1cs` files of `old` and `new` matched by name.
+///
+/// Each file is summarized from its header, read alone, and its fingerprint. Files up to
+/// [`DirCompareOptions::max_diff_size`] are read whole for their statistics and, if the field
+/// sizes agree, a structural diff. Larger files are streamed, one constraint at a time. The
+/// `.zkey` and `.wtns` files of `new` sharing the name of a circuit are checked against it, see
+/// [`ZkeyHeader::matches`].
+///
+/// Nothing aborts the comparison: missing counterparts, unparsable files and unlisted
+/// directories are recorded in the report.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(name = "r1cs_compare_artifact_dirs", level = "debug", skip_all)
+)]
+pub fn compare_artifact_dirs(
+    old: &Path,
+    new: &Path,
+    opts: &DirCompareOptions,
+) -> DirComparisonReport {
+    let mut errors = Vec::new();
+    let mut names = list_circuits(old, &mut errors);
+    names.extend(list_circuits(new, &mut errors));
+    names.sort();
+    names.dedup();
+
+    let circuits = names
+        .into_iter()
+        .map(|name| compare(old, new, name, opts))
+        .collect();
+
+    DirComparisonReport {
+        old: old.to_owned(),
+        new: new.to_owned(),
+        circuits,
+        errors,
+    }
+}
+
+/// The names of the `.r1cs` files of `dir`.
+fn list_circuits(dir: &Path, errors: &mut Vec<String>) -> Vec<String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            errors.push(format!("{}: {}", dir.display(), e));
+            return Vec::new();
+        }
+    };
+
+    let mut names = Vec::new();
+    for entry in entries {
+        let path = match entry {
+            Ok(entry) => entry.path(),
+            Err(e) => {
+                errors.push(format!("{}: {}", dir.display(), e));
+                continue;
+            }
+        };
+        let is_r1cs = path.extension().is_some_and(|ext| ext == "r1cs");
+        if let (true, Some(name)) = (is_r1cs && path.is_file(), path.file_name()) {
+            names.push(name.to_string_lossy().into_owned());
+        }
+    }
+
+    names
+}
+
+/// A parsed circuit, its header and the whole file if it was read.
+enum Inspected {
+    Fs8(Header<8>, Option<R1csFile<8>>),
+    Fs32(Header<32>, Option<R1csFile<32>>),
+    Fs48(Header<48>, Option<R1csFile<48>>),
+    Fs64(Header<64>, Option<R1csFile<64>>),
+}
+
+fn compare(
+    old_dir: &Path,
+    new_dir: &Path,
+    name: String,
+    opts: &DirCompareOptions,
+) -> CircuitComparison {
+    let (old, old_inspected) = inspect(&old_dir.join(&name), opts);
+    let (new, new_inspected) = inspect(&new_dir.join(&name), opts);
+    let cross_checks = match &new_inspected {
+        Some(inspected) => cross_check(new_dir, &name, inspected),
+        None => Vec::new(),
+    };
+
+    let mut comparison = CircuitComparison {
+        status: CircuitStatus::Unparsable,
+        name,
+        old,
+        new,
+        header: Vec::new(),
+        constraint_delta: None,
+        stats: Vec::new(),
+        diff: None,
+        cross_checks,
+    };
+
+    let (a, b) = match (&comparison.old, &comparison.new) {
+        (Artifact::Parsed(a), Artifact::Parsed(b)) => (a, b),
+        (Artifact::Missing, Artifact::Parsed(_)) => {
+            comparison.status = CircuitStatus::OnlyInNew;
+            return comparison;
+        }
+        (Artifact::Parsed(_), Artifact::Missing) => {
+            comparison.status = CircuitStatus::OnlyInOld;
+            return comparison;
+        }
+        _ => return comparison,
+    };
+
+    comparison.header = header_differences(a, b);
+    comparison.constraint_delta = Some(i64::from(b.n_constraints) - i64::from(a.n_constraints));
+    if let (Some(a), Some(b)) = (&a.stats, &b.stats) {
+        comparison.stats = stat_differences(a, b);
+    }
+    comparison.diff = match (old_inspected, new_inspected) {
+        (Some(Inspected::Fs8(_, Some(a))), Some(Inspected::Fs8(_, Some(b)))) => {
+            Some(r1cs_diff(&a, &b, &opts.diff))
+        }
+        (Some(Inspected::Fs32(_, Some(a))), Some(Inspected::Fs32(_, Some(b)))) => {
+            Some(r1cs_diff(&a, &b, &opts.diff))
+        }
+        (Some(Inspected::Fs48(_, Some(a))), Some(Inspected::Fs48(_, Some(b)))) => {
+            Some(r1cs_diff(&a, &b, &opts.diff))
+        }
+        (Some(Inspected::Fs64(_, Some(a))), Some(Inspected::Fs64(_, Some(b)))) => {
+            Some(r1cs_diff(&a, &b, &opts.diff))
+        }
+        _ => None,
+    };
+
+    // Without fingerprints, only a structural diff can tell that nothing changed.
+    let unchanged = match (&a.fingerprint, &b.fingerprint) {
+        (Some(fa), Some(fb)) => fa == fb,
+        _ => {
+            comparison.header.is_empty() && comparison.diff.as_ref().is_some_and(R1csDiff::is_empty)
+        }
+    };
+    comparison.status = if unchanged {
+        CircuitStatus::Unchanged
+    } else {
+        CircuitStatus::Changed
+    };
+
+    comparison
+}
+
+/// Summarizes the circuit at `path`, `None` unless it was parsed.
+fn inspect(path: &Path, opts: &DirCompareOptions) -> (Artifact, Option<Inspected>) {
+    let file_size = match fs::metadata(path) {
+        Ok(metadata) => metadata.len(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return (Artifact::Missing, None),
+        Err(e) => {
+            let error = e.to_string();
+            return (Artifact::Unparsable { error }, None);
+        }
+    };
+
+    let inspected = (|| -> Result<(ArtifactSummary, Inspected)> {
+        let (field_size, offset, _) = peek_field_size(File::open(path)?)?;
+        Ok(match field_size {
+            8 => inspect_fs(path, file_size, opts, Inspected::Fs8)?,
+            32 => inspect_fs(path, file_size, opts, Inspected::Fs32)?,
+            48 => inspect_fs(path, file_size, opts, Inspected::Fs48)?,
+            64 => inspect_fs(path, file_size, opts, Inspected::Fs64)?,
+            found => return Err(R1csError::UnsupportedFieldSize { found, offset }),
+        })
+    })();
+
+    match inspected {
+        Ok((summary, inspected)) => (Artifact::Parsed(summary), Some(inspected)),
+        Err(e) => {
+            let error = e.to_string();
+            (Artifact::Unparsable { error }, None)
+        }
+    }
+}
+
+fn inspect_fs<const FS: usize>(
+    path: &Path,
+    file_size: u64,
+    opts: &DirCompareOptions,
+    variant: fn(Header<FS>, Option<R1csFile<FS>>) -> Inspected,
+) -> Result<(ArtifactSummary, Inspected)> {
+    let header = R1csFile::<FS>::read_header(BufReader::new(File::open(path)?))?;
+
+    let mut w = Fnv::new();
+    let (fingerprint, file) = if file_size <= opts.max_diff_size {
+        let file = R1csFile::<FS>::read(BufReader::new(File::open(path)?))?;
+        file.write_fingerprinted(&mut w)?;
+        (Some(w.finish()), Some(file))
+    } else {
+        let mut stream = || -> Result<()> {
+            // The constraints keep their encoded size, split sections or not.
+            let constraints_size = R1csFile::<FS>::scan_sections(File::open(path)?)?
+                .iter()
+                .filter(|s| s.known == Some(SectionType::Constraint))
+                .map(|s| s.size)
+                .sum();
+            let mut reader = R1csReader::<_, FS>::new(BufReader::new(File::open(path)?))?;
+            write_fingerprint_head(&mut w, reader.header(), constraints_size)?;
+            for c in reader.constraints() {
+                c?.sorted_terms().write(&mut w)?;
+            }
+            let (map, list, application) = reader.remaining_sections()?;
+            write_fingerprint_tail(&mut w, map, list, application)
+        };
+        match stream() {
+            Ok(()) => (Some(w.finish()), None),
+            Err(R1csError::SectionOutOfOrder { .. }) => (None, None),
+            Err(e) => return Err(e),
+        }
+    };
+
+    let summary = ArtifactSummary {
+        file_size,
+        field_size: FS as u32,
+        prime: format!("{:#x}", header.prime),
+        n_wires: header.n_wires,
+        n_pub_out: header.n_pub_out,
+        n_pub_in: header.n_pub_in,
+        n_prvt_in: header.n_prvt_in,
+        n_labels: header.n_labels,
+        n_constraints: header.n_constraints,
+        fingerprint: fingerprint.map(|f| f.iter().map(|b| format!("{:02x}", b)).collect()),
+        stats: file.as_ref().map(R1csFile::stats),
+    };
+
+    Ok((summary, variant(header, file)))
+}
+
+fn header_differences(a: &ArtifactSummary, b: &ArtifactSummary) -> Vec<HeaderDifference> {
+    let mut differences = Vec::new();
+    let mut header = |field, a: String, b: String| {
+        if a != b {
+            differences.push(HeaderDifference { field, a, b });
+        }
+    };
+    header(
+        "field_size",
+        a.field_size.to_string(),
+        b.field_size.to_string(),
+    );
+    header("prime", a.prime.clone(), b.prime.clone());
+    header("n_wires", a.n_wires.to_string(), b.n_wires.to_string());
+    header(
+        "n_pub_out",
+        a.n_pub_out.to_string(),
+        b.n_pub_out.to_string(),
+    );
+    header("n_pub_in", a.n_pub_in.to_string(), b.n_pub_in.to_string());
+    header(
+        "n_prvt_in",
+        a.n_prvt_in.to_string(),
+        b.n_prvt_in.to_string(),
+    );
+    header("n_labels", a.n_labels.to_string(), b.n_labels.to_string());
+    header(
+        "n_constraints",
+        a.n_constraints.to_string(),
+        b.n_constraints.to_string(),
+    );
+
+    differences
+}
+
+/// The statistics not already in the header that differ.
+fn stat_differences(a: &CircuitStats, b: &CircuitStats) -> Vec<StatDifference> {
+    let mut differences = Vec::new();
+    let mut stat = |stat, old: String, new: String| {
+        if old != new {
+            differences.push(StatDifference { stat, old, new });
+        }
+    };
+    stat("nnz_a", a.nnz[0].to_string(), b.nnz[0].to_string());
+    stat("nnz_b", a.nnz[1].to_string(), b.nnz[1].to_string());
+    stat("nnz_c", a.nnz[2].to_string(), b.nnz[2].to_string());
+    stat(
+        "max_terms_per_lc",
+        a.max_terms_per_lc.to_string(),
+        b.max_terms_per_lc.to_string(),
+    );
+    stat(
+        "avg_terms_per_lc",
+        format!("{:.2}", a.avg_terms_per_lc),
+        format!("{:.2}", b.avg_terms_per_lc),
+    );
+    stat(
+        "unused_wires",
+        a.unused_wires.to_string(),
+        b.unused_wires.to_string(),
+    );
+    stat(
+        "domain_size",
+        a.domain_size.to_string(),
+        b.domain_size.to_string(),
+    );
+
+    differences
+}
+
+/// Checks the `.zkey` and `.wtns` files of `dir` named after the circuit `name` against it.
+fn cross_check(dir: &Path, name: &str, inspected: &Inspected) -> Vec<CrossCheck> {
+    match inspected {
+        Inspected::Fs8(header, _) => cross_check_fs(dir, name, header),
+        Inspected::Fs32(header, _) => cross_check_fs(dir, name, header),
+        Inspected::Fs48(header, _) => cross_check_fs(dir, name, header),
+        Inspected::Fs64(header, _) => cross_check_fs(dir, name, header),
+    }
+}
+
+fn cross_check_fs<const FS: usize>(dir: &Path, name: &str, header: &Header<FS>) -> Vec<CrossCheck> {
+    let mut checks = Vec::new();
+    let circuit = dir.join(name);
+
+    let zkey = circuit.with_extension("zkey");
+    if zkey.is_file() {
+        let read =
+            || -> Result<ZkeyHeader> { ZkeyHeader::read(BufReader::new(File::open(&zkey)?)) };
+        let problem = match read() {
+            Ok(key) => key.matches(header).err().map(|report| report.to_string()),
+            Err(e) => Some(e.to_string()),
+        };
+        checks.extend(problem.map(|problem| CrossCheck {
+            file: file_name(&zkey),
+            problem,
+        }));
+    }
+
+    #[cfg(feature = "wtns")]
+    {
+        let wtns = circuit.with_extension("wtns");
+        if wtns.is_file() {
+            let problem = match File::open(&wtns)
+                .map_err(wtns_file::WtnsError::from)
+                .and_then(|file| wtns_file::WtnsRandomAccess::<_, FS>::open(BufReader::new(file)))
+            {
+                Ok(witness) if witness.header().prime != header.prime => {
+                    Some("The witness prime differs from the circuit prime".to_owned())
+                }
+                Ok(witness) if witness.len() != header.n_wires as usize => Some(format!(
+                    "The witness has {} values but the circuit has {} wires",
+                    witness.len(),
+                    header.n_wires
+                )),
+                Ok(_) => None,
+                Err(e) => Some(e.to_string()),
+            };
+            checks.extend(problem.map(|problem| CrossCheck {
+                file: file_name(&wtns),
+                problem,
+            }));
+        }
+    }
+
+    checks
+}
+
+fn file_name(path: &Path) -> String {
+    let name = path.file_name().unwrap_or_default();
+    name.to_string_lossy().into_owned()
+}
+
+impl fmt::Display for DirComparisonReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} circuits: {} unchanged, {} changed, {} only in old, {} only in new, {} unparsable",
+            self.circuits.len(),
+            self.count(CircuitStatus::Unchanged),
+            self.count(CircuitStatus::Changed),
+            self.count(CircuitStatus::OnlyInOld),
+            self.count(CircuitStatus::OnlyInNew),
+            self.count(CircuitStatus::Unparsable),
+        )?;
+        for error in &self.errors {
+            writeln!(f, "Error: {}", error)?;
+        }
+
+        for c in &self.circuits {
+            write!(f, "{}: {}", c.name, c.status)?;
+            if let Some(delta) = c.constraint_delta.filter(|delta| *delta != 0) {
+                write!(f, ", {:+} constraints", delta)?;
+            }
+            writeln!(f)?;
+
+            for (side, artifact) in [("old", &c.old), ("new", &c.new)] {
+                if let Artifact::Unparsable { error } = artifact {
+                    writeln!(f, "  {}: {}", side, error)?;
+                }
+            }
+            for d in &c.header {
+                writeln!(f, "  Header {}: {} -> {}", d.field, d.a, d.b)?;
+            }
+            for d in &c.stats {
+                writeln!(f, "  Stat {}: {} -> {}", d.stat, d.old, d.new)?;
+            }
+            if let Some(diff) = &c.diff {
+                let constraints = [("old", &diff.only_in_a), ("new", &diff.only_in_b)];
+                for (side, differences) in constraints {
+                    if !differences.is_empty() {
+                        writeln!(f, "  {} constraints only in {}", differences.count, side)?;
+                    }
+                }
+                if !diff.wire_map.is_empty() {
+                    writeln!(f, "  {} wire labels differ", diff.wire_map.count)?;
+                }
+            }
+            for check in &c.cross_checks {
+                writeln!(f, "  {}: {}", check.file, check.problem)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for CircuitStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            CircuitStatus::Unchanged => "unchanged",
+            CircuitStatus::Changed => "changed",
+            CircuitStatus::OnlyInOld => "only in old",
+            CircuitStatus::OnlyInNew => "only in new",
+            CircuitStatus::Unparsable => "unparsable",
+        };
+        f.write_str(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Constraint, CustomGate, CustomGateApplication, CustomGatesApplication, CustomGatesList,
+        FieldElement,
+    };
+
+    fn dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("r1cs-file-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_compare_artifact_dirs() {
+        let (old, new) = (dir("artifacts-old"), dir("artifacts-new"));
+        let simple = R1csFile::<32>::read_file("tests/simple_circuit.r1cs").unwrap();
+        let chain = R1csFile::<32>::read_file("tests/test_circuit.r1cs").unwrap();
+
+        // Unchanged but for the order of the terms of a constraint.
+        simple.write_file(old.join("same.r1cs")).unwrap();
+        let mut reordered = simple.clone();
+        reordered.constraints.0[0].a_mut().0.reverse();
+        reordered.write_file(new.join("same.r1cs")).unwrap();
+        // One more constraint.
+        simple.write_file(old.join("grown.r1cs")).unwrap();
+        let mut grown = simple.clone();
+        let one = FieldElement::from_u64(1);
+        grown.constraints.0.push(Constraint::new(
+            vec![(one, 1)],
+            vec![(one, 0)],
+            vec![(one, 1)],
+        ));
+        grown.header.n_constraints += 1;
+        grown.write_file(new.join("grown.r1cs")).unwrap();
+        // Moved to a wider field.
+        chain.write_file(old.join("widened.r1cs")).unwrap();
+        let widened: R1csFile<64> = chain.map_coefficients_to(|c, _, _| c.widen(), |e| e.widen());
+        widened.write_file(new.join("widened.r1cs")).unwrap();
+        // Removed, added and broken.
+        chain.write_file(old.join("removed.r1cs")).unwrap();
+        simple.write_file(new.join("added.r1cs")).unwrap();
+        simple.write_file(old.join("broken.r1cs")).unwrap();
+        fs::write(new.join("broken.r1cs"), b"r1cs").unwrap();
+        fs::write(new.join("notes.txt"), b"not a circuit").unwrap();
+
+        let report = compare_artifact_dirs(&old, &new, &DirCompareOptions::default());
+        let names: Vec<_> = report.circuits.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "added.r1cs",
+                "broken.r1cs",
+                "grown.r1cs",
+                "removed.r1cs",
+                "same.r1cs",
+                "widened.r1cs"
+            ]
+        );
+        let statuses: Vec<_> = report.circuits.iter().map(|c| c.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                CircuitStatus::OnlyInNew,
+                CircuitStatus::Unparsable,
+                CircuitStatus::Changed,
+                CircuitStatus::OnlyInOld,
+                CircuitStatus::Unchanged,
+                CircuitStatus::Changed,
+            ]
+        );
+        assert!(report.errors.is_empty());
+        assert!(!report.is_clean());
+
+        let broken = &report.circuits[1];
+        assert!(matches!(broken.old, Artifact::Parsed(_)));
+        assert!(matches!(broken.new, Artifact::Unparsable { .. }));
+
+        let grown = &report.circuits[2];
+        assert_eq!(grown.constraint_delta, Some(1));
+        assert_eq!(grown.header[0].field, "n_constraints");
+        let diff = grown.diff.as_ref().unwrap();
+        assert_eq!((diff.only_in_a.count, diff.only_in_b.count), (0, 1));
+        assert_eq!(grown.stats[0].stat, "nnz_a");
+
+        let same = &report.circuits[4];
+        assert!(same.header.is_empty());
+        assert!(same.diff.as_ref().unwrap().is_empty());
+
+        // No structural diff across field sizes.
+        let widened = &report.circuits[5];
+        assert_eq!(widened.header[0].field, "field_size");
+        assert_eq!(widened.constraint_delta, Some(0));
+        assert!(widened.diff.is_none());
+
+        let summary = report.to_string();
+        assert!(summary.starts_with(
+            "6 circuits: 1 unchanged, 2 changed, 1 only in old, 1 only in new, 1 unparsable\n"
+        ));
+        assert!(summary
+            .contains("grown.r1cs: changed, +1 constraints\n  Header n_constraints: 3 -> 4\n"));
+
+        // Huge files are only fingerprinted, with the same fingerprints.
+        let streamed = DirCompareOptions {
+            max_diff_size: 0,
+            ..DirCompareOptions::default()
+        };
+        let streamed = compare_artifact_dirs(&old, &new, &streamed);
+        for (a, b) in streamed.circuits.iter().zip(&report.circuits) {
+            assert_eq!(a.status, b.status);
+            assert!(a.diff.is_none() && a.stats.is_empty());
+            if let (Artifact::Parsed(a), Artifact::Parsed(b)) = (&a.new, &b.new) {
+                assert!(a.stats.is_none());
+                assert_eq!(a.fingerprint, b.fingerprint);
+            }
+        }
+
+        let missing = old.join("missing");
+        let report = compare_artifact_dirs(&missing, &new, &DirCompareOptions::default());
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.count(CircuitStatus::OnlyInNew), 4);
+
+        fs::remove_dir_all(old).unwrap();
+        fs::remove_dir_all(new).unwrap();
+    }
+
+    #[test]
+    fn test_compare_artifact_dirs_custom_gates() {
+        let (old, new) = (dir("gates-old"), dir("gates-new"));
+        let mut file = R1csFile::<32>::read_file("tests/simple_circuit.r1cs").unwrap();
+        file.custom_gates_list = Some(CustomGatesList(vec![CustomGate {
+            name: "G".to_owned(),
+            parameters: vec![FieldElement::from_u64(3)],
+        }]));
+        file.custom_gates_application = Some(CustomGatesApplication(vec![CustomGateApplication {
+            gate: 0,
+            signals: vec![1],
+        }]));
+        file.write_file(old.join("main.r1cs")).unwrap();
+        file.custom_gates_list.as_mut().unwrap().0[0].parameters[0] = FieldElement::from_u64(4);
+        file.write_file(new.join("main.r1cs")).unwrap();
+
+        for max_diff_size in [u64::MAX, 0] {
+            let opts = DirCompareOptions {
+                max_diff_size,
+                ..DirCompareOptions::default()
+            };
+            let report = compare_artifact_dirs(&old, &new, &opts);
+            let circuit = &report.circuits[0];
+            assert_eq!(circuit.status, CircuitStatus::Changed);
+            assert!(circuit.header.is_empty());
+        }
+
+        fs::remove_dir_all(old).unwrap();
+        fs::remove_dir_all(new).unwrap();
+    }
+
+    #[cfg(feature = "wtns")]
+    #[test]
+    fn test_compare_artifact_dirs_cross_checks() {
+        let (old, new) = (dir("cross-old"), dir("cross-new"));
+        let simple = R1csFile::<32>::read_file("tests/simple_circuit.r1cs").unwrap();
+        simple.write_file(old.join("main.r1cs")).unwrap();
+        simple.write_file(new.join("main.r1cs")).unwrap();
+        fs::copy("tests/simple_circuit.wtns", new.join("main.wtns")).unwrap();
+
+        let report = compare_artifact_dirs(&old, &new, &DirCompareOptions::default());
+        assert!(report.is_clean());
+
+        // The new release drops a wire without regenerating the witness.
+        let mut shrunk = simple;
+        shrunk.header.n_wires -= 1;
+        shrunk
+            .write_unchecked(File::create(new.join("main.r1cs")).unwrap())
+            .unwrap();
+        fs::write(new.join("main.zkey"), b"zkey").unwrap();
+        let report = compare_artifact_dirs(&old, &new, &DirCompareOptions::default());
+        let checks = &report.circuits[0].cross_checks;
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0].file, "main.zkey");
+        assert_eq!(
+            checks[1],
+            CrossCheck {
+                file: "main.wtns".to_owned(),
+                problem: "The witness has 7 values but the circuit has 6 wires".to_owned(),
+            }
+        );
+        assert!(report
+            .to_string()
+            .contains("  main.wtns: The witness has 7 values"));
+
+        fs::remove_dir_all(old).unwrap();
+        fs::remove_dir_all(new).unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_report() {
+        let (old, new) = (dir("serde-old"), dir("serde-new"));
+        fs::copy("tests/simple_circuit.r1cs", old.join("main.r1cs")).unwrap();
+        fs::copy("tests/test_circuit.r1cs", new.join("main.r1cs")).unwrap();
+
+        let report = compare_artifact_dirs(&old, &new, &DirCompareOptions::default());
+        let json = serde_json::to_value(&report).unwrap();
+        let circuit = &json["circuits"][0];
+        assert_eq!(circuit["status"], "changed");
+        assert_eq!(circuit["old"]["state"], "parsed");
+        assert_eq!(circuit["old"]["n_constraints"], 3);
+        assert_eq!(circuit["constraint_delta"], 997);
+
+        fs::remove_dir_all(old).unwrap();
+        fs::remove_dir_all(new).unwrap();
+    }
+}
@@ -0,0 +1,175 @@
+//! Reading and writing with tokio's async I/O traits.
+
+use byteorder::{ByteOrder, LittleEndian};
+use iden3_binfile::BinFileReader;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{
+    CountingReader, Limit, ParseMode, ParseWarning, R1csError, R1csFile, ReadOptions, Result,
+    SectionHeader, Sections, ValidatedR1cs, FORMAT,
+};
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Async version of [`R1csFile::read`].
+    pub async fn read_async<R: AsyncRead + Unpin>(r: R) -> Result<Self> {
+        Self::read_async_with(r, &ReadOptions::default())
+            .await
+            .map(|(file, _)| file)
+    }
+
+    /// Async version of [`R1csFile::read_with`].
+    ///
+    /// Each section is read into memory before being decoded by the same code as
+    /// [`R1csFile::read_with`], so one section at a time is held in memory on top of the decoded
+    /// file. Unknown sections dropped with [`ReadOptions::drop_unknown_sections`] are skipped
+    /// without buffering.
+    pub async fn read_async_with<R: AsyncRead + Unpin>(
+        mut r: R,
+        opts: &ReadOptions,
+    ) -> Result<(Self, Vec<ParseWarning>)> {
+        let preamble = read_chunk(&mut r, 12).await?;
+        let file = BinFileReader::new(preamble.as_slice(), &FORMAT)?;
+        let num_sections = file.num_sections();
+        let mut sections = Sections {
+            version: file.version(),
+            ..Sections::default()
+        };
+        let mut position = 12;
+
+        for _ in 0..num_sections {
+            let chunk = read_chunk(&mut r, 12).await?;
+            if chunk.len() < 12 {
+                return Err(R1csError::UnexpectedEof {
+                    section: None,
+                    offset: position + chunk.len() as u64,
+                });
+            }
+            let section_header = SectionHeader {
+                id: LittleEndian::read_u32(&chunk[..4]),
+                size: LittleEndian::read_u64(&chunk[4..]),
+            };
+            position += 12;
+            opts.check_limit(Limit::SectionSize, section_header.size, position - 12)?;
+
+            if opts.drop_unknown_sections && section_header.ty().is_none() {
+                sections.skip_section(&section_header, position, opts)?;
+                let skipped = tokio::io::copy(
+                    &mut (&mut r).take(section_header.size),
+                    &mut tokio::io::sink(),
+                )
+                .await?;
+                if skipped != section_header.size {
+                    return Err(R1csError::UnexpectedEof {
+                        section: None,
+                        offset: position + skipped,
+                    });
+                }
+            } else {
+                let body = read_chunk(&mut r, section_header.size).await?;
+                let mut body = CountingReader {
+                    inner: body.as_slice(),
+                    position,
+                };
+                sections.read_section(&mut body, &section_header, opts)?;
+            }
+            position += section_header.size;
+        }
+
+        if opts.mode != ParseMode::Unchecked {
+            let bytes = tokio::io::copy(&mut r, &mut tokio::io::sink()).await?;
+            if bytes > 0 {
+                let warning = ParseWarning::TrailingData {
+                    offset: position,
+                    bytes,
+                };
+                sections.report(opts.mode, warning)?;
+            }
+        }
+
+        sections.into_file(opts.mode)
+    }
+
+    /// Async version of [`R1csFile::write`].
+    ///
+    /// The file is serialized in memory first, see [`R1csFile::serialize`].
+    pub async fn write_async<W: AsyncWrite + Unpin>(&self, mut w: W) -> Result<()> {
+        self.check_header().map_err(R1csError::Invalid)?;
+        w.write_all(&self.serialize()).await?;
+        w.flush().await?;
+
+        Ok(())
+    }
+}
+
+impl<const FS: usize> ValidatedR1cs<FS> {
+    /// Async version of [`ValidatedR1cs::write_validated`].
+    pub async fn write_async_validated<W: AsyncWrite + Unpin>(&self, mut w: W) -> Result<()> {
+        w.write_all(&self.serialize()).await?;
+        w.flush().await?;
+
+        Ok(())
+    }
+}
+
+/// Reads up to `size` bytes, fewer only at the end of the input. Short chunks are left to the
+/// decoders, which report the end of input with its offset.
+async fn read_chunk<R: AsyncRead + Unpin>(r: &mut R, size: u64) -> Result<Vec<u8>> {
+    let mut chunk = Vec::new();
+    r.take(size).read_to_end(&mut chunk).await?;
+
+    Ok(chunk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn test_async_round_trip() {
+        for path in ["tests/simple_circuit.r1cs", "tests/split_constraints.r1cs"] {
+            let data = std::fs::read(path).unwrap();
+            let expected = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+            let file = block_on(R1csFile::<32>::read_async(data.as_slice())).unwrap();
+            assert_eq!(file, expected);
+
+            let mut written = Vec::new();
+            block_on(file.write_async(&mut written)).unwrap();
+            assert_eq!(written, expected.serialize());
+        }
+    }
+
+    #[test]
+    fn test_async_errors_match_sync() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+
+        for len in [5, 20, 100, data.len() - 1] {
+            let sync = R1csFile::<32>::read(&data[..len]).unwrap_err();
+            let async_ = block_on(R1csFile::<32>::read_async(&data[..len])).unwrap_err();
+            assert_eq!(async_.to_string(), sync.to_string());
+        }
+
+        let mut trailing = data.clone();
+        trailing.extend_from_slice(&[0; 3]);
+        let opts = ReadOptions {
+            mode: ParseMode::Lenient,
+            ..ReadOptions::default()
+        };
+        let (_, warnings) =
+            block_on(R1csFile::<32>::read_async_with(trailing.as_slice(), &opts)).unwrap();
+        assert_eq!(
+            warnings,
+            [ParseWarning::TrailingData {
+                offset: data.len() as u64,
+                bytes: 3
+            }]
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Synthesis of circuits into bellman constraint systems, behind the `bellman` feature.
+
+use std::marker::PhantomData;
+
+use bellman::{Circuit, ConstraintSystem, SynthesisError, Variable};
+use ff::PrimeField;
+
+use crate::{FieldElement, LinearCombination, R1csFile, ValidationError};
+
+/// A circuit, and optionally its witness, as a bellman [`Circuit`] over `S`.
+///
+/// Wire 0 is mapped to [`ConstraintSystem::one`], the public output and input wires to inputs
+/// and the remaining wires to auxiliary variables. Without a witness every variable is assigned
+/// zero, which is enough for parameter generation. Coefficients and witness values not below the
+/// prime fail synthesis with [`SynthesisError::Unsatisfiable`].
+#[derive(Debug, Clone, Copy)]
+pub struct BellmanCircuit<'a, S, const FS: usize> {
+    r1cs: &'a R1csFile<FS>,
+    witness: Option<&'a [FieldElement<FS>]>,
+    scalar: PhantomData<S>,
+}
+
+impl<'a, S: PrimeField, const FS: usize> BellmanCircuit<'a, S, FS> {
+    /// Wraps the circuit, checking that its prime is the modulus of `S`.
+    pub fn new(r1cs: &'a R1csFile<FS>) -> Result<Self, ValidationError> {
+        r1cs.header.check_ff_prime::<S>()?;
+
+        Ok(BellmanCircuit {
+            r1cs,
+            witness: None,
+            scalar: PhantomData,
+        })
+    }
+
+    /// Assigns the variables from `witness`, indexed by wire, as read from a `.wtns` file.
+    pub fn with_witness(mut self, witness: &'a [FieldElement<FS>]) -> Self {
+        self.witness = Some(witness);
+        self
+    }
+
+    fn value(&self, wire: usize) -> Result<S, SynthesisError> {
+        let witness = match self.witness {
+            Some(witness) => witness,
+            None => return Ok(S::ZERO),
+        };
+
+        witness
+            .get(wire)
+            .ok_or(SynthesisError::AssignmentMissing)?
+            .to_ff()
+            .ok_or(SynthesisError::Unsatisfiable)
+    }
+}
+
+impl<S: PrimeField, const FS: usize> Circuit<S> for BellmanCircuit<'_, S, FS> {
+    fn synthesize<CS: ConstraintSystem<S>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let header = &self.r1cs.header;
+        // The public outputs and inputs come right after the constant wire.
+        let public_end = header.region_starts()[2] as usize;
+
+        let mut variables = Vec::with_capacity(header.n_wires as usize);
+        variables.push(CS::one());
+        for wire in 1..header.n_wires as usize {
+            let name = || format!("w_{}", wire);
+            let variable = if wire < public_end {
+                cs.alloc_input(name, || self.value(wire))?
+            } else {
+                cs.alloc(name, || self.value(wire))?
+            };
+            variables.push(variable);
+        }
+
+        let terms = |lc: &LinearCombination<FS>| {
+            lc.iter()
+                .map(|(coeff, wire)| {
+                    let coeff = coeff.to_ff().ok_or(SynthesisError::Unsatisfiable)?;
+                    let variable = *variables
+                        .get(*wire as usize)
+                        .ok_or(SynthesisError::Unsatisfiable)?;
+                    Ok((coeff, variable))
+                })
+                .collect::<Result<Vec<(S, Variable)>, SynthesisError>>()
+        };
+        for (i, c) in self.r1cs.constraints.0.iter().enumerate() {
+            let (a, b, c) = (terms(&c.0)?, terms(&c.1)?, terms(&c.2)?);
+            cs.enforce(
+                || format!("constraint {}", i),
+                |lc| a.iter().fold(lc, |lc, &term| lc + term),
+                |lc| b.iter().fold(lc, |lc, &term| lc + term),
+                |lc| c.iter().fold(lc, |lc, &term| lc + term),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primes;
+    use bellman::gadgets::test::TestConstraintSystem;
+    use bls12_381::Scalar;
+
+    /// The bundled circuit, moved to the BLS12-381 scalar field.
+    fn bls_circuit() -> R1csFile<32> {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let mut file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        file.header.prime = primes::BLS12_381_SCALAR;
+        file
+    }
+
+    #[test]
+    fn test_synthesize() {
+        let file = bls_circuit();
+
+        // Solves the three constraints for w_2 = w_3 = 9 and w_4 = w_6 = 1.
+        let [w2, w3, w4, w6] = [9u64, 9, 1, 1].map(Scalar::from);
+        let w5 = (Scalar::from(68) * Scalar::from(290).invert().unwrap() - Scalar::from(8) * w6)
+            * Scalar::from(3).invert().unwrap();
+        let w1 = -(Scalar::from(3) * w5 + Scalar::from(8) * w4) * Scalar::from(4).invert().unwrap();
+        let witness: Vec<_> = [Scalar::one(), w1, w2, w3, w4, w5, w6]
+            .iter()
+            .map(|&w| FieldElement::from_ff(w).unwrap())
+            .collect();
+
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        BellmanCircuit::<Scalar, 32>::new(&file)
+            .unwrap()
+            .with_witness(&witness)
+            .synthesize(&mut cs)
+            .unwrap();
+        assert_eq!(cs.num_inputs(), 4);
+        assert_eq!(cs.num_constraints(), 3);
+        assert!(cs.is_satisfied());
+
+        let mut wrong = witness.clone();
+        wrong[2] = FieldElement::from_u64(8);
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        BellmanCircuit::<Scalar, 32>::new(&file)
+            .unwrap()
+            .with_witness(&wrong)
+            .synthesize(&mut cs)
+            .unwrap();
+        assert_eq!(cs.which_is_unsatisfied(), Some("constraint 0"));
+
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        BellmanCircuit::<Scalar, 32>::new(&file)
+            .unwrap()
+            .synthesize(&mut cs)
+            .unwrap();
+        assert_eq!(cs.num_constraints(), 3);
+    }
+
+    #[test]
+    fn test_prime_mismatch() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        assert!(matches!(
+            BellmanCircuit::<Scalar, 32>::new(&file),
+            Err(ValidationError::ModulusMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,399 @@
+//! Zero-copy parsing of files already held in memory.
+
+use std::convert::TryFrom;
+use std::io::Cursor;
+
+use iden3_binfile::BinFileReader;
+
+use crate::{
+    metadata, Constraint, Constraints, CountingReader, CustomGatesApplication, CustomGatesList,
+    CustomSection, FieldElement, Header, Matrix, Metadata, R1csError, R1csFile, Result,
+    SectionHeader, SectionType, WireMap, FORMAT,
+};
+
+/// A term of a borrowed linear combination: the coefficient bytes and the wire index.
+pub type TermRef<'a, const FS: usize> = (&'a [u8; FS], u32);
+
+/// An r1cs file parsed from a byte buffer, with constraint coefficients pointing into the buffer.
+///
+/// Only the constraints and unknown sections borrow from the buffer; the other sections are small
+/// and decoded as in [`R1csFile`]. Sections are read in the same way as
+/// [`R1csFile::read_seek`], in any order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct R1csFileRef<'a, const FS: usize> {
+    pub header: Header<FS>,
+    pub constraints: Vec<ConstraintRef<'a, FS>>,
+    pub map: WireMap,
+    pub custom_gates_list: Option<CustomGatesList<FS>>,
+    pub custom_gates_application: Option<CustomGatesApplication>,
+    pub metadata: Option<Metadata>,
+    pub custom_sections: Vec<CustomSectionRef<'a>>,
+    pub version: u32,
+}
+
+/// A constraint whose coefficients point into the parsed buffer.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConstraintRef<'a, const FS: usize>(
+    pub Vec<TermRef<'a, FS>>,
+    pub Vec<TermRef<'a, FS>>,
+    pub Vec<TermRef<'a, FS>>,
+);
+
+/// A section of a type this crate doesn't interpret, pointing into the parsed buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CustomSectionRef<'a> {
+    pub id: u32,
+    pub data: &'a [u8],
+}
+
+impl<'a, const FS: usize> R1csFileRef<'a, FS> {
+    /// Parses a whole file held in `data`.
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        let mut file = BinFileReader::new(Cursor::new(data), &FORMAT)?;
+        let version = file.version();
+        let table = file.section_table()?;
+
+        let mut header = None;
+        let mut constraints: Option<Vec<ConstraintRef<'a, FS>>> = None;
+        let mut constraint_sections = 0;
+        let mut map = None;
+        let mut custom_gates_list = None;
+        let mut custom_gates_application = None;
+        let mut metadata = None;
+        let mut custom_sections = Vec::new();
+
+        for entry in table {
+            let offset = entry.offset;
+            let section_header = &SectionHeader {
+                id: entry.id,
+                size: entry.size,
+            };
+            let ty = section_header.ty();
+            let body = section_body(data, section_header, offset)?;
+            let mut r = CountingReader {
+                inner: body,
+                position: offset,
+            };
+
+            let read = match ty {
+                Some(SectionType::Header) => {
+                    crate::ensure_unique(&header, SectionType::Header, offset)?;
+                    Header::read(&mut r).map(|h| header = Some(h))
+                }
+                Some(SectionType::Constraint) => {
+                    constraint_sections += 1;
+                    let constraints = constraints.get_or_insert_with(Vec::new);
+                    read_constraints(body, offset, constraints)
+                }
+                Some(SectionType::Wire2LabelIdMap) => {
+                    crate::ensure_unique(&map, SectionType::Wire2LabelIdMap, offset)?;
+                    WireMap::read(&mut r, section_header, None).map(|m| map = Some(m))
+                }
+                Some(SectionType::CustomGatesList) => {
+                    crate::ensure_unique(&custom_gates_list, SectionType::CustomGatesList, offset)?;
+                    CustomGatesList::read(&mut r, section_header)
+                        .map(|list| custom_gates_list = Some(list))
+                }
+                Some(SectionType::CustomGatesApplication) => {
+                    crate::ensure_unique(
+                        &custom_gates_application,
+                        SectionType::CustomGatesApplication,
+                        offset,
+                    )?;
+                    CustomGatesApplication::read(&mut r, section_header)
+                        .map(|application| custom_gates_application = Some(application))
+                }
+                Some(SectionType::Metadata) => {
+                    crate::ensure_unique(&metadata, SectionType::Metadata, offset)?;
+                    metadata::read(&mut r, section_header).map(|m| metadata = Some(m))
+                }
+                None => {
+                    custom_sections.push(CustomSectionRef {
+                        id: section_header.id,
+                        data: body,
+                    });
+                    Ok(())
+                }
+            };
+            read.map_err(|e| e.eof_in(ty, r.position()))?;
+        }
+
+        let header = header.ok_or(R1csError::MissingSection(SectionType::Header))?;
+        let constraints = constraints.ok_or(R1csError::MissingSection(SectionType::Constraint))?;
+
+        // Same rule as the owned parser: split constraints are only accepted if they add up.
+        let section = constraints.len() as u64;
+        if constraint_sections > 1 && u64::from(header.n_constraints) != section {
+            return Err(R1csError::Inconsistent(
+                crate::ParseWarning::ConstraintCountMismatch {
+                    header: header.n_constraints,
+                    section,
+                },
+            ));
+        }
+
+        Ok(R1csFileRef {
+            header,
+            constraints,
+            map: map.unwrap_or_default(),
+            custom_gates_list,
+            custom_gates_application,
+            metadata,
+            custom_sections,
+            version,
+        })
+    }
+
+    /// Copies the borrowed data into an [`R1csFile`].
+    pub fn to_owned(&self) -> R1csFile<FS> {
+        let header = Header {
+            prime: self.header.prime,
+            ..self.header
+        };
+        let custom_gates_list = self.custom_gates_list.as_ref().map(|list| {
+            CustomGatesList(
+                list.0
+                    .iter()
+                    .map(|gate| crate::CustomGate {
+                        name: gate.name.clone(),
+                        parameters: gate.parameters.to_vec(),
+                    })
+                    .collect(),
+            )
+        });
+        let custom_gates_application = self.custom_gates_application.as_ref().map(|application| {
+            CustomGatesApplication(
+                application
+                    .0
+                    .iter()
+                    .map(|a| crate::CustomGateApplication {
+                        gate: a.gate,
+                        signals: a.signals.clone(),
+                    })
+                    .collect(),
+            )
+        });
+
+        R1csFile {
+            header,
+            constraints: Constraints(self.constraints.iter().map(|c| c.to_owned()).collect()),
+            map: WireMap(self.map.0.clone()),
+            custom_gates_list,
+            custom_gates_application,
+            metadata: self.metadata.clone(),
+            custom_sections: self
+                .custom_sections
+                .iter()
+                .map(|s| CustomSection {
+                    id: s.id,
+                    data: s.data.to_vec(),
+                })
+                .collect(),
+            version: self.version,
+        }
+    }
+}
+
+impl<'a, const FS: usize> ConstraintRef<'a, FS> {
+    /// The linear combination of this constraint in `matrix`.
+    pub fn combination(&self, matrix: Matrix) -> &[TermRef<'a, FS>] {
+        match matrix {
+            Matrix::A => &self.0,
+            Matrix::B => &self.1,
+            Matrix::C => &self.2,
+        }
+    }
+
+    /// Copies the coefficients into a [`Constraint`].
+    pub fn to_owned(&self) -> Constraint<FS> {
+        let copy = |comb: &[TermRef<'a, FS>]| {
+            comb.iter()
+                .map(|(factor, index)| (FieldElement::from(**factor), *index))
+                .collect()
+        };
+
+        Constraint(copy(&self.0), copy(&self.1), copy(&self.2))
+    }
+}
+
+/// The body of a section, checked to lie within `data`.
+fn section_body<'a>(
+    data: &'a [u8],
+    section_header: &SectionHeader,
+    offset: u64,
+) -> Result<&'a [u8]> {
+    let end = offset.saturating_add(section_header.size);
+    match usize::try_from(end) {
+        Ok(end) if end <= data.len() => Ok(&data[offset as usize..end]),
+        _ => Err(R1csError::UnexpectedEof {
+            section: section_header.ty(),
+            offset: data.len() as u64,
+        }),
+    }
+}
+
+/// Decodes a constraints section body, appending to `constraints`.
+fn read_constraints<'a, const FS: usize>(
+    body: &'a [u8],
+    offset: u64,
+    constraints: &mut Vec<ConstraintRef<'a, FS>>,
+) -> Result<()> {
+    let mut r = Slice {
+        data: body,
+        position: 0,
+    };
+
+    while !r.data[r.position..].is_empty() {
+        let a = read_combination(&mut r, offset)?;
+        let b = read_combination(&mut r, offset)?;
+        let c = read_combination(&mut r, offset)?;
+        constraints.push(ConstraintRef(a, b, c));
+    }
+
+    Ok(())
+}
+
+fn read_combination<'a, const FS: usize>(
+    r: &mut Slice<'a>,
+    offset: u64,
+) -> Result<Vec<TermRef<'a, FS>>> {
+    let n = u32::from_le_bytes(*r.take::<4>(offset)?);
+
+    // Validate the count before reading any term.
+    let remaining = (r.data.len() - r.position) as u64;
+    if u64::from(n) * (4 + FS as u64) > remaining {
+        return Err(R1csError::UnexpectedEof {
+            section: Some(SectionType::Constraint),
+            offset: offset + r.data.len() as u64,
+        });
+    }
+
+    let mut factors = Vec::with_capacity(n as usize);
+    for _ in 0..n {
+        let index = u32::from_le_bytes(*r.take::<4>(offset)?);
+        factors.push((r.take::<FS>(offset)?, index));
+    }
+
+    Ok(factors)
+}
+
+/// A cursor over a section body handing out references into it.
+struct Slice<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Slice<'a> {
+    /// The next `N` bytes. `offset` is the offset of the body in the file, for errors.
+    fn take<const N: usize>(&mut self, offset: u64) -> Result<&'a [u8; N]> {
+        let bytes = self.data[self.position..]
+            .get(..N)
+            .and_then(|bytes| <&[u8; N]>::try_from(bytes).ok())
+            .ok_or(R1csError::UnexpectedEof {
+                section: Some(SectionType::Constraint),
+                offset: offset + self.data.len() as u64,
+            })?;
+        self.position += N;
+
+        Ok(bytes)
+    }
+}
+
+/// A read-only memory mapping of an r1cs file, to be parsed with [`R1csFileRef::parse`].
+///
+/// [`R1csFileRef`] borrows from the mapping, so the mapping has to outlive it.
+#[cfg(feature = "mmap")]
+pub struct MappedFile(memmap2::Mmap);
+
+#[cfg(feature = "mmap")]
+impl MappedFile {
+    /// Maps the file at `path`.
+    ///
+    /// The file must not be modified while mapped, otherwise parsed data may change under the
+    /// borrowed view.
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the mapping is read-only, and modifying the underlying file while it is mapped
+        // is documented as unsupported above.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+
+        Ok(MappedFile(mmap))
+    }
+
+    /// Parses the mapped file.
+    pub fn parse<const FS: usize>(&self) -> Result<R1csFileRef<'_, FS>> {
+        R1csFileRef::parse(&self.0)
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl std::ops::Deref for MappedFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<'a, const FS: usize> R1csFileRef<'a, FS> {
+    /// Maps the file at `path`, see [`MappedFile::open`].
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> Result<MappedFile> {
+        MappedFile::open(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_borrowed_matches_owned() {
+        let fixtures: [&[u8]; 3] = [
+            include_bytes!("../tests/simple_circuit.r1cs"),
+            include_bytes!("../tests/test_circuit.r1cs"),
+            include_bytes!("../tests/split_constraints.r1cs"),
+        ];
+
+        for data in fixtures {
+            let borrowed = R1csFileRef::<32>::parse(data).unwrap();
+            let owned = R1csFile::<32>::read(data).unwrap();
+            assert_eq!(borrowed.to_owned(), owned);
+
+            // Coefficients point into the input.
+            let range = data.as_ptr_range();
+            for c in &borrowed.constraints {
+                for matrix in Matrix::ALL {
+                    for (factor, _) in c.combination(matrix) {
+                        assert!(range.contains(&factor.as_ptr()));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_borrowed_errors() {
+        let data = include_bytes!("../tests/simple_circuit.r1cs");
+
+        match R1csFileRef::<32>::parse(&data[..data.len() - 1]) {
+            Err(R1csError::UnexpectedEof { .. }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match R1csFileRef::<8>::parse(data) {
+            Err(R1csError::FieldSizeMismatch { found: 32, .. }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mapped_file() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/simple_circuit.r1cs");
+        let mapped = R1csFileRef::<32>::open(path).unwrap();
+        let borrowed = mapped.parse::<32>().unwrap();
+
+        let owned = R1csFile::<32>::read(std::fs::File::open(path).unwrap()).unwrap();
+        assert_eq!(borrowed.to_owned(), owned);
+    }
+}
@@ -0,0 +1,266 @@
+//! Building files with a header consistent with their constraints.
+
+use std::convert::TryFrom;
+use std::ops::Range;
+
+use crate::validate::wire_out_of_range;
+use crate::{
+    Constraint, Constraints, FieldElement, Header, LinearCombination, R1csError, R1csFile, Result,
+    ValidationError, WireMap, VERSION,
+};
+
+/// Builds an [`R1csFile`], deriving the header counts and the wire map from the wires and
+/// constraints added to it.
+///
+/// Wires are laid out as circom does: wire 0 is the constant one, followed by the public outputs,
+/// the public inputs, the private inputs and then the wires from
+/// [`R1csFileBuilder::alloc_wire`]. Signal counts must therefore be set before allocating wires.
+#[derive(Debug, Clone)]
+pub struct R1csFileBuilder<const FS: usize> {
+    /// The prime, zero until set, and the signal counts. The other counts are set by `build`.
+    header: Header<FS>,
+    n_internal: u64,
+    constraints: Vec<Constraint<FS>>,
+}
+
+impl<const FS: usize> Default for R1csFileBuilder<FS> {
+    fn default() -> Self {
+        R1csFileBuilder {
+            header: Header {
+                prime: FieldElement::from_u64(0),
+                n_wires: 0,
+                n_pub_out: 0,
+                n_pub_in: 0,
+                n_prvt_in: 0,
+                n_labels: 0,
+                n_constraints: 0,
+            },
+            n_internal: 0,
+            constraints: Vec::new(),
+        }
+    }
+}
+
+impl<const FS: usize> R1csFileBuilder<FS> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn prime(mut self, prime: FieldElement<FS>) -> Self {
+        self.header.prime = prime;
+        self
+    }
+
+    /// Sets the number of public outputs, wires `1..=n`.
+    ///
+    /// # Panics
+    ///
+    /// If wires were already allocated.
+    pub fn public_outputs(mut self, n: u32) -> Self {
+        self.assert_no_wires();
+        self.header.n_pub_out = n;
+        self
+    }
+
+    /// Sets the number of public inputs, which follow the public outputs.
+    ///
+    /// # Panics
+    ///
+    /// If wires were already allocated.
+    pub fn public_inputs(mut self, n: u32) -> Self {
+        self.assert_no_wires();
+        self.header.n_pub_in = n;
+        self
+    }
+
+    /// Sets the number of private inputs, which follow the public inputs.
+    ///
+    /// # Panics
+    ///
+    /// If wires were already allocated.
+    pub fn private_inputs(mut self, n: u32) -> Self {
+        self.assert_no_wires();
+        self.header.n_prvt_in = n;
+        self
+    }
+
+    /// Allocates an internal wire, returning its index.
+    pub fn alloc_wire(&mut self) -> u32 {
+        self.alloc_wires(1).start
+    }
+
+    /// Allocates `n` consecutive internal wires.
+    ///
+    /// Wires past `u32::MAX` are clamped to it, [`R1csFileBuilder::build`] then fails.
+    pub fn alloc_wires(&mut self, n: u32) -> Range<u32> {
+        let start = self.n_wires();
+        self.n_internal += u64::from(n);
+
+        let clamp = |wire: u64| u32::try_from(wire).unwrap_or(u32::MAX);
+        clamp(start)..clamp(start + u64::from(n))
+    }
+
+    /// Adds the constraint `a * b = c`, returning its index.
+    pub fn add_constraint(
+        &mut self,
+        a: impl Into<LinearCombination<FS>>,
+        b: impl Into<LinearCombination<FS>>,
+        c: impl Into<LinearCombination<FS>>,
+    ) -> usize {
+        self.constraints
+            .push(Constraint(a.into(), b.into(), c.into()));
+        self.constraints.len() - 1
+    }
+
+    /// Builds the file with an identity wire map.
+    ///
+    /// Fails with [`R1csError::Invalid`] if the prime is missing or zero, if the wires or the
+    /// constraints overflow the 32-bit header counts, or if a constraint references a wire that
+    /// was never allocated.
+    pub fn build(self) -> Result<R1csFile<FS>> {
+        let signals = self.n_wires();
+        let n_wires = u32::try_from(signals).map_err(|_| {
+            R1csError::Invalid(ValidationError::TooManySignals {
+                signals,
+                n_wires: u32::MAX,
+            })
+        })?;
+        let n_constraints = u32::try_from(self.constraints.len()).map_err(|_| {
+            R1csError::Invalid(ValidationError::TooManyConstraints {
+                found: self.constraints.len(),
+            })
+        })?;
+        if self.header.prime.is_zero() {
+            return Err(R1csError::Invalid(ValidationError::ZeroPrime));
+        }
+
+        if let Some(e) = wire_out_of_range(&self.constraints, 0, n_wires) {
+            return Err(R1csError::Invalid(e));
+        }
+
+        let header = Header {
+            n_wires,
+            n_labels: u64::from(n_wires),
+            n_constraints,
+            ..self.header
+        };
+
+        Ok(R1csFile {
+            header,
+            constraints: Constraints(self.constraints),
+            map: WireMap::identity(n_wires),
+            custom_gates_list: None,
+            custom_gates_application: None,
+            metadata: None,
+            custom_sections: Vec::new(),
+            version: VERSION,
+        })
+    }
+
+    /// The wire count, which may not fit in the header.
+    fn n_wires(&self) -> u64 {
+        self.header.region_starts()[3] + self.n_internal
+    }
+
+    fn assert_no_wires(&self) {
+        assert!(
+            self.n_internal == 0,
+            "signal counts must be set before allocating wires"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Matrix;
+
+    #[test]
+    fn test_build_simple_circuit() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let header = &file.header;
+
+        let mut builder = R1csFileBuilder::new()
+            .prime(FieldElement::bn254_scalar_prime())
+            .public_outputs(header.n_pub_out)
+            .public_inputs(header.n_pub_in)
+            .private_inputs(header.n_prvt_in);
+        let n_signals = header.check_layout().unwrap()[3];
+        let internal = builder.alloc_wires(header.n_wires - n_signals);
+        assert_eq!(internal, n_signals..header.n_wires);
+
+        let copy = |c: &Constraint<32>, matrix| -> LinearCombination<32> {
+            c.combination(matrix).iter().copied().collect()
+        };
+        for (i, c) in file.constraints.0.iter().enumerate() {
+            let index =
+                builder.add_constraint(copy(c, Matrix::A), copy(c, Matrix::B), copy(c, Matrix::C));
+            assert_eq!(index, i);
+        }
+
+        let built = builder.build().unwrap();
+        assert_eq!(built.validate(), Ok(()));
+        assert_eq!(built.header.n_wires, header.n_wires);
+        assert_eq!(built.header.n_constraints, header.n_constraints);
+        assert_eq!(built.constraints, file.constraints);
+
+        let data = built.serialize();
+        assert_eq!(R1csFile::<32>::read(data.as_slice()).unwrap(), built);
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let mut builder = R1csFileBuilder::<8>::new()
+            .prime(FieldElement::goldilocks_prime())
+            .public_inputs(1);
+        let wire = builder.alloc_wire();
+        assert_eq!(wire, 2);
+        builder.add_constraint(
+            vec![(FieldElement::from_u64(1), wire)],
+            vec![(FieldElement::from_u64(1), wire + 1)],
+            vec![],
+        );
+
+        assert!(matches!(
+            builder.build(),
+            Err(R1csError::Invalid(ValidationError::WireOutOfRange {
+                constraint: 0,
+                matrix: Matrix::B,
+                term: 0,
+                wire: 3,
+            }))
+        ));
+        assert!(matches!(
+            R1csFileBuilder::<8>::new().build(),
+            Err(R1csError::Invalid(ValidationError::ZeroPrime))
+        ));
+    }
+
+    #[test]
+    fn test_build_too_many_signals() {
+        let builder = R1csFileBuilder::<8>::new()
+            .prime(FieldElement::goldilocks_prime())
+            .public_outputs(u32::MAX);
+        assert!(matches!(
+            builder.build(),
+            Err(R1csError::Invalid(ValidationError::TooManySignals {
+                signals: 0x1_0000_0000,
+                n_wires: u32::MAX,
+            }))
+        ));
+
+        let mut builder = R1csFileBuilder::<8>::new()
+            .prime(FieldElement::goldilocks_prime())
+            .public_inputs(u32::MAX - 2);
+        assert_eq!(builder.alloc_wire(), u32::MAX - 1);
+        assert_eq!(builder.alloc_wires(3), u32::MAX..u32::MAX);
+        assert!(matches!(
+            builder.build(),
+            Err(R1csError::Invalid(ValidationError::TooManySignals {
+                signals: 0x1_0000_0002,
+                ..
+            }))
+        ));
+    }
+}
@@ -0,0 +1,120 @@
+//! Linear combinations of wires, the rows of the constraint matrices.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter::FromIterator;
+
+use crate::FieldElement;
+
+/// A linear combination of wires, stored as `(coefficient, wire)` pairs in file order.
+///
+/// Dereferences to the slice of pairs, and converts from and into the raw vector it wraps.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct LinearCombination<const FS: usize>(pub Vec<(FieldElement<FS>, u32)>);
+
+/// A term of a [`LinearCombination`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Term<'a, const FS: usize> {
+    pub coeff: &'a FieldElement<FS>,
+    pub wire: u32,
+}
+
+impl<const FS: usize> LinearCombination<FS> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A combination of a single term.
+    pub fn term(coeff: FieldElement<FS>, wire: u32) -> Self {
+        LinearCombination(vec![(coeff, wire)])
+    }
+
+    pub fn push_term(&mut self, coeff: FieldElement<FS>, wire: u32) {
+        self.0.push((coeff, wire));
+    }
+
+    pub fn iter_terms(&self) -> impl ExactSizeIterator<Item = Term<'_, FS>> {
+        self.0
+            .iter()
+            .map(|(coeff, wire)| Term { coeff, wire: *wire })
+    }
+
+    /// Appends the terms of `other`. Terms on the same wire are kept separate, as adding their
+    /// coefficients requires the field modulus.
+    pub fn merge(mut self, other: LinearCombination<FS>) -> Self {
+        self.0.extend(other.0);
+        self
+    }
+
+    /// Sorts the terms by wire, keeping the order of terms on the same wire.
+    pub fn sorted(mut self) -> Self {
+        self.0.sort_by_key(|(_, wire)| *wire);
+        self
+    }
+}
+
+impl<const FS: usize> core::ops::Deref for LinearCombination<FS> {
+    type Target = [(FieldElement<FS>, u32)];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<const FS: usize> core::ops::DerefMut for LinearCombination<FS> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<const FS: usize> From<Vec<(FieldElement<FS>, u32)>> for LinearCombination<FS> {
+    fn from(terms: Vec<(FieldElement<FS>, u32)>) -> Self {
+        LinearCombination(terms)
+    }
+}
+
+impl<const FS: usize> From<LinearCombination<FS>> for Vec<(FieldElement<FS>, u32)> {
+    fn from(combination: LinearCombination<FS>) -> Self {
+        combination.0
+    }
+}
+
+impl<const FS: usize> FromIterator<(FieldElement<FS>, u32)> for LinearCombination<FS> {
+    fn from_iter<I: IntoIterator<Item = (FieldElement<FS>, u32)>>(terms: I) -> Self {
+        LinearCombination(terms.into_iter().collect())
+    }
+}
+
+impl<'a, const FS: usize> IntoIterator for &'a LinearCombination<FS> {
+    type Item = &'a (FieldElement<FS>, u32);
+    type IntoIter = core::slice::Iter<'a, (FieldElement<FS>, u32)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_combination() {
+        let fe = FieldElement::<8>::from_u64;
+        let mut lc = LinearCombination::term(fe(3), 5);
+        lc.push_term(fe(1), 2);
+        let lc = lc.merge(vec![(fe(7), 5), (fe(2), 0)].into()).sorted();
+
+        let terms: Vec<_> = lc
+            .iter_terms()
+            .map(|t| (t.coeff.as_bytes()[0], t.wire))
+            .collect();
+        assert_eq!(terms, [(2, 0), (1, 2), (3, 5), (7, 5)]);
+        assert_eq!(lc.len(), 4);
+
+        let raw: Vec<_> = lc.into();
+        assert_eq!(raw[0], (fe(2), 0));
+    }
+}
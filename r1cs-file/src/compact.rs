@@ -0,0 +1,144 @@
+//! Removing wires no constraint references.
+
+use crate::{Constraint, R1csFile};
+
+/// The outcome of [`R1csFile::compact`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactionReport {
+    /// Number of wires removed.
+    pub removed: u32,
+    /// The new index of every old wire, `None` for removed wires.
+    pub mapping: Vec<Option<u32>>,
+}
+
+impl CompactionReport {
+    /// The new index of the old wire `wire`, `None` if it was removed.
+    pub fn new_index(&self, wire: u32) -> Option<u32> {
+        self.mapping.get(wire as usize).copied().flatten()
+    }
+
+    /// Drops the entries of removed wires from `values`, indexed by old wire, such as a witness.
+    /// Values past the end of the mapping are dropped.
+    pub fn remap<T: Clone>(&self, values: &[T]) -> Vec<T> {
+        values
+            .iter()
+            .zip(&self.mapping)
+            .filter(|(_, new)| new.is_some())
+            .map(|(value, _)| value.clone())
+            .collect()
+    }
+}
+
+impl CompactionReport {
+    /// Numbers the wires marked in `used` contiguously, in order.
+    pub(crate) fn from_used(used: &[bool]) -> Self {
+        let mut next = 0;
+        let mapping: Vec<_> = used
+            .iter()
+            .map(|used| {
+                used.then(|| {
+                    next += 1;
+                    next - 1
+                })
+            })
+            .collect();
+
+        CompactionReport {
+            removed: mapping.len() as u32 - next,
+            mapping,
+        }
+    }
+}
+
+/// Marks the wires referenced by `constraints` in `used`, growing it for wires past its end.
+pub(crate) fn mark_used<'a, const FS: usize>(
+    used: &mut Vec<bool>,
+    constraints: impl IntoIterator<Item = &'a Constraint<FS>>,
+) {
+    for c in constraints {
+        for (_, wire) in c.0.iter().chain(c.1.iter()).chain(c.2.iter()) {
+            let wire = *wire as usize;
+            if wire >= used.len() {
+                used.resize(wire + 1, false);
+            }
+            used[wire] = true;
+        }
+    }
+}
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Removes the wires no constraint term references and renumbers the remaining ones
+    /// contiguously, in order.
+    ///
+    /// Wire 0 and the public output, public input and private input wires are always kept. The
+    /// constraints, the wire map and `n_wires` are updated; wires referenced past `n_wires` are
+    /// kept and counted in the new `n_wires`.
+    pub fn compact(&mut self) -> CompactionReport {
+        let header = &self.header;
+        let signals = header.region_starts()[3];
+
+        let mut used = vec![false; header.n_wires as usize];
+        for (wire, used) in used.iter_mut().enumerate() {
+            *used = (wire as u64) < signals;
+        }
+        mark_used(&mut used, &self.constraints.0);
+        let report = CompactionReport::from_used(&used);
+        let mapping = &report.mapping;
+
+        for c in &mut self.constraints.0 {
+            for combination in [&mut c.0, &mut c.1, &mut c.2] {
+                for (_, wire) in combination.iter_mut() {
+                    *wire = mapping[*wire as usize].expect("referenced wires are kept");
+                }
+            }
+        }
+
+        self.map.0 = report.remap(&self.map.0);
+        self.header.n_wires = mapping.len() as u32 - report.removed;
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FieldElement, R1csFile, Term};
+
+    #[test]
+    fn test_compact() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let original = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let mut file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        assert_eq!(file.compact().removed, 0);
+        assert_eq!(file, original);
+
+        // Wires 7 and 9 are unused, wire 8 is referenced.
+        file.header.n_wires = 10;
+        file.map.0.extend([7, 8, 9]);
+        let coeff = FieldElement::from_u64(1);
+        file.constraints.0[1].c_mut().push_term(coeff, 8);
+
+        let report = file.compact();
+        assert_eq!(report.removed, 2);
+        assert_eq!(report.new_index(6), Some(6));
+        assert_eq!(report.new_index(7), None);
+        assert_eq!(report.new_index(8), Some(7));
+        assert_eq!(report.new_index(9), None);
+        assert_eq!(
+            report.remap(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]),
+            [0, 1, 2, 3, 4, 5, 6, 8]
+        );
+
+        assert_eq!(file.header.n_wires, 8);
+        assert_eq!(file.map.0[6..], [original.map.0[6], 8]);
+        assert!(file.constraints.0[1]
+            .c()
+            .iter_terms()
+            .any(|Term { wire, .. }| wire == 7));
+        assert_eq!(file.validate(), Ok(()));
+        assert_eq!(
+            R1csFile::<32>::read(file.serialize().as_slice()).unwrap(),
+            file
+        );
+    }
+}
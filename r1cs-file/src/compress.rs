@@ -0,0 +1,85 @@
+//! Reading and writing compressed files, behind the `compress` feature.
+
+use std::io::{Cursor, Read, Write};
+
+use crate::{R1csFile, Result};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compression format of an r1cs stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// Detected from the magic bytes when reading, uncompressed data included. Writing picks zstd.
+    Auto,
+    Gzip,
+    Zstd,
+}
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Reads a compressed file, see [`Compression`].
+    pub fn read_compressed<R: Read>(mut r: R, compression: Compression) -> Result<Self> {
+        match compression {
+            Compression::Gzip => Self::read(flate2::read::GzDecoder::new(r)),
+            Compression::Zstd => Self::read(zstd::Decoder::new(r)?),
+            Compression::Auto => {
+                let mut magic = Vec::with_capacity(4);
+                (&mut r).take(4).read_to_end(&mut magic)?;
+                let r = Cursor::new(magic).chain(r);
+
+                let magic = r.get_ref().0.get_ref();
+                if magic.starts_with(&GZIP_MAGIC) {
+                    Self::read(flate2::read::GzDecoder::new(r))
+                } else if magic.as_slice() == ZSTD_MAGIC {
+                    Self::read(zstd::Decoder::new(r)?)
+                } else {
+                    // Plain data, a wrong magic number is reported by the parser.
+                    Self::read(r)
+                }
+            }
+        }
+    }
+
+    /// Writes a compressed file. [`Compression::Auto`] writes zstd.
+    pub fn write_compressed<W: Write>(&self, w: W, compression: Compression) -> Result<()> {
+        match compression {
+            Compression::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(w, flate2::Compression::default());
+                self.write(&mut encoder)?;
+                encoder.finish()?;
+            }
+            Compression::Zstd | Compression::Auto => {
+                let mut encoder = zstd::Encoder::new(w, 0)?;
+                self.write(&mut encoder)?;
+                encoder.finish()?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compressed_round_trip() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        for compression in [Compression::Gzip, Compression::Zstd] {
+            let mut compressed = Vec::new();
+            file.write_compressed(&mut compressed, compression).unwrap();
+            assert_ne!(compressed, data);
+
+            let read = |c| R1csFile::<32>::read_compressed(compressed.as_slice(), c).unwrap();
+            assert_eq!(read(compression), file);
+            assert_eq!(read(Compression::Auto), file);
+        }
+
+        let plain = R1csFile::<32>::read_compressed(data.as_slice(), Compression::Auto).unwrap();
+        assert_eq!(plain, file);
+        assert!(R1csFile::<32>::read_compressed(&b"r1"[..], Compression::Auto).is_err());
+    }
+}
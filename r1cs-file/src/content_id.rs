@@ -0,0 +1,111 @@
+//! Identifiers of constraints derived from their content, stable across recompilations that
+//! reorder constraints.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::{Constraint, FieldElement, R1csFile};
+
+/// FNV-1a 128-bit parameters.
+const FNV_OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+const FNV_PRIME: u128 = 0x0000000001000000000000000000013b;
+
+impl<const FS: usize> Constraint<FS> {
+    /// A 128-bit identifier of the constraint over the field of `prime`.
+    ///
+    /// The identifier is a hash of the prime and of the constraint encoded as in a file with the
+    /// terms of each linear combination sorted, so it doesn't depend on the position of the
+    /// constraint or on the order of its terms. Repeated wires aren't merged and coefficients
+    /// aren't reduced: normalize the constraint first, see [`Constraint::normalize`], to identify
+    /// constraints equal over the field. The hash is FNV-1a, stable across platforms and
+    /// versions but not collision resistant.
+    pub fn content_id(&self, prime: &FieldElement<FS>) -> [u8; 16] {
+        let mut w = Fnv::new();
+        w.write_all(prime.as_bytes())
+            .and_then(|()| self.sorted_terms().write(&mut w).map_err(io::Error::from))
+            .expect("hashing doesn't fail");
+
+        w.finish()
+    }
+}
+
+impl<const FS: usize> R1csFile<FS> {
+    /// The indices of the constraints bearing each content id, see [`Constraint::content_id`].
+    ///
+    /// Duplicated constraints share an id, their indices are in ascending order.
+    pub fn content_id_index(&self) -> HashMap<[u8; 16], Vec<u32>> {
+        let prime = &self.header.prime;
+        let mut index: HashMap<_, Vec<u32>> = HashMap::with_capacity(self.constraints.len());
+        for (i, c) in (0u32..).zip(&self.constraints.0) {
+            index.entry(c.content_id(prime)).or_default().push(i);
+        }
+
+        index
+    }
+}
+
+/// FNV-1a 128 over the bytes written.
+pub(crate) struct Fnv(u128);
+
+impl Fnv {
+    pub(crate) fn new() -> Self {
+        Fnv(FNV_OFFSET)
+    }
+
+    pub(crate) fn finish(&self) -> [u8; 16] {
+        self.0.to_le_bytes()
+    }
+}
+
+impl Write for Fnv {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            self.0 = (self.0 ^ u128::from(byte)).wrapping_mul(FNV_PRIME);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_id() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let prime = &file.header.prime;
+
+        let first = &file.constraints[0];
+        let mut reordered = first.clone();
+        reordered.0.reverse();
+        reordered.1.reverse();
+        assert_ne!(&reordered, first);
+        assert_eq!(reordered.content_id(prime), first.content_id(prime));
+        assert_ne!(
+            file.constraints[1].content_id(prime),
+            first.content_id(prime)
+        );
+        assert_ne!(
+            first.content_id(&FieldElement::from_u64(101)),
+            first.content_id(prime)
+        );
+
+        // Ids don't change between versions.
+        assert_eq!(
+            first.content_id(prime),
+            [189, 127, 112, 8, 184, 210, 183, 15, 136, 68, 70, 221, 11, 47, 200, 204]
+        );
+
+        let mut duplicated = file.clone();
+        duplicated.constraints.0.push(reordered);
+        let index = duplicated.content_id_index();
+        assert_eq!(index.len(), 3);
+        assert_eq!(index[&first.content_id(prime)], [0, 3]);
+        assert_eq!(index[&file.constraints[2].content_id(prime)], [2]);
+    }
+}
@@ -0,0 +1,170 @@
+//! Removing duplicated constraints.
+
+use std::collections::hash_map::{Entry, HashMap};
+
+use crate::R1csFile;
+
+/// Options for [`R1csFile::dedup_constraints`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DedupOptions {
+    /// Only compute the report, leaving the file as it is.
+    pub dry_run: bool,
+}
+
+/// The outcome of [`R1csFile::dedup_constraints`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DedupReport {
+    /// Number of constraints removed.
+    pub removed: u32,
+    /// The new index of every old constraint. Removed constraints map to the new index of their
+    /// first occurrence, which is kept.
+    pub mapping: Vec<u32>,
+}
+
+impl DedupReport {
+    /// The new index of the old constraint `constraint`, `None` if there is no such constraint.
+    pub fn new_index(&self, constraint: u32) -> Option<u32> {
+        self.mapping.get(constraint as usize).copied()
+    }
+}
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Removes the constraints equal to an earlier one, keeping the first occurrence and the order
+    /// of the kept constraints, and updates `n_constraints`.
+    ///
+    /// Constraints are compared with the terms of each linear combination sorted, so two
+    /// constraints listing the same terms in a different order are duplicates. Terms aren't
+    /// otherwise normalized: repeated wires aren't merged and coefficients aren't reduced, see
+    /// [`crate::Constraints::normalize_all`] for that.
+    pub fn dedup_constraints(&mut self, opts: DedupOptions) -> DedupReport {
+        let constraints = &self.constraints.0;
+        let mut first = HashMap::with_capacity(constraints.len());
+        let mut keep = Vec::with_capacity(constraints.len());
+        let mut next = 0;
+        let mapping: Vec<_> = constraints
+            .iter()
+            .map(|c| match first.entry(c.sorted_terms()) {
+                Entry::Occupied(entry) => {
+                    keep.push(false);
+                    *entry.get()
+                }
+                Entry::Vacant(entry) => {
+                    keep.push(true);
+                    next += 1;
+                    *entry.insert(next - 1)
+                }
+            })
+            .collect();
+        let report = DedupReport {
+            removed: mapping.len() as u32 - next,
+            mapping,
+        };
+
+        if !opts.dry_run && report.removed > 0 {
+            let mut keep = keep.into_iter();
+            self.constraints
+                .0
+                .retain(|_| keep.next().expect("one flag per constraint"));
+            self.header.n_constraints = self.constraints.len() as u32;
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Constraint;
+
+    /// `tests/test_circuit.r1cs` with constraints repeated, returned with the number of repeats.
+    fn duplicated(file: &R1csFile<32>) -> (R1csFile<32>, u32) {
+        let mut duplicated = file.clone();
+        let mut repeats = 0;
+        duplicated.constraints.0.clear();
+        for (i, c) in file.constraints.0.iter().enumerate() {
+            duplicated.constraints.0.push(c.clone());
+            if i % 3 == 0 {
+                // The same terms in reverse order.
+                let mut reversed = c.clone();
+                for combination in [&mut reversed.0, &mut reversed.1, &mut reversed.2] {
+                    combination.0.reverse();
+                }
+                duplicated.constraints.0.push(reversed);
+                repeats += 1;
+            }
+        }
+        duplicated.constraints.0.push(file.constraints[0].clone());
+        duplicated.header.n_constraints = duplicated.constraints.len() as u32;
+
+        (duplicated, repeats + 1)
+    }
+
+    #[test]
+    fn test_dedup_constraints() {
+        let data = std::fs::read("tests/test_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let mut copy = file.clone();
+        assert_eq!(copy.dedup_constraints(DedupOptions::default()).removed, 0);
+        assert_eq!(copy, file);
+
+        let (mut duplicated, repeats) = duplicated(&file);
+        assert!(repeats > 300);
+        let before = duplicated.clone();
+        let dry_run = duplicated.dedup_constraints(DedupOptions { dry_run: true });
+        assert_eq!(dry_run.removed, repeats);
+        assert_eq!(duplicated, before);
+
+        let report = duplicated.dedup_constraints(DedupOptions::default());
+        assert_eq!(report, dry_run);
+        assert_eq!(duplicated, file);
+        assert_eq!(duplicated.validate(), Ok(()));
+
+        // Constraint 0, its reversed copy and the copy at the end all map to constraint 0.
+        assert_eq!(report.new_index(0), Some(0));
+        assert_eq!(report.new_index(1), Some(0));
+        assert_eq!(report.new_index(2), Some(1));
+        assert_eq!(
+            report.new_index(before.constraints.len() as u32 - 1),
+            Some(0)
+        );
+        assert_eq!(report.new_index(before.constraints.len() as u32), None);
+        for (old, new) in report.mapping.iter().enumerate() {
+            assert_eq!(
+                before.constraints[old].sorted_terms(),
+                file.constraints[*new as usize].sorted_terms()
+            );
+        }
+    }
+
+    #[test]
+    fn test_dedup_exact_only() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let mut file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        // A * B = C and B * A = C hold together but aren't the same constraint.
+        let Constraint(a, b, c) = file.constraints[0].clone();
+        file.constraints.0.push(Constraint(b, a, c));
+        file.header.n_constraints += 1;
+        let report = file.dedup_constraints(DedupOptions::default());
+        assert_eq!(report.removed, 0);
+        assert_eq!(report.mapping, [0, 1, 2, 3]);
+    }
+
+    #[cfg(feature = "wtns")]
+    #[test]
+    fn test_dedup_keeps_witness() {
+        use crate::check_witness;
+        use crate::satisfy::tests::chain_witness;
+        use wtns_file::WtnsFile;
+
+        let data = std::fs::read("tests/test_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let prime = file.header.prime;
+        let wtns = WtnsFile::from_vec(chain_witness(&prime), prime);
+
+        let (mut duplicated, _) = duplicated(&file);
+        duplicated.dedup_constraints(DedupOptions::default());
+        assert_eq!(check_witness(&duplicated, &wtns), Ok(()));
+    }
+}
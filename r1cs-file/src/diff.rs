@@ -0,0 +1,360 @@
+//! Structural comparison of two files.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{Constraint, Modulus, R1csFile};
+
+/// Options for [`r1cs_diff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// Normalize constraints before comparing them, so that the order of terms and duplicated
+    /// wires don't count as differences. See [`Constraint::normalize`].
+    pub normalize: bool,
+    /// Match constraints by their content id, see [`Constraint::content_id`], instead of their
+    /// encoding. Only 16 bytes are kept per constraint of the first file, at the cost of a
+    /// negligible chance of matching different constraints.
+    pub by_content_id: bool,
+    /// The number of concrete examples kept per kind of difference.
+    pub max_examples: usize,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            normalize: true,
+            by_content_id: false,
+            max_examples: 10,
+        }
+    }
+}
+
+/// Differences found by [`r1cs_diff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct R1csDiff {
+    /// Header fields that differ.
+    pub header: Vec<HeaderDifference>,
+    /// Indices of constraints of the first file not found in the second one.
+    pub only_in_a: Differences<usize>,
+    /// Indices of constraints of the second file not found in the first one.
+    pub only_in_b: Differences<usize>,
+    /// Wires whose labels differ, as `(wire, label in a, label in b)`. A label is `None` past
+    /// the end of the wire map.
+    pub wire_map: Differences<(usize, Option<u64>, Option<u64>)>,
+}
+
+/// A header field that differs, with both values formatted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HeaderDifference {
+    pub field: &'static str,
+    pub a: String,
+    pub b: String,
+}
+
+/// The number of differences of one kind, with the first few of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Differences<T> {
+    pub count: u64,
+    pub examples: Vec<T>,
+}
+
+impl<T> Default for Differences<T> {
+    fn default() -> Self {
+        Differences {
+            count: 0,
+            examples: Vec::new(),
+        }
+    }
+}
+
+impl<T> Differences<T> {
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    fn push(&mut self, difference: T, max_examples: usize) {
+        self.count += 1;
+        if self.examples.len() < max_examples {
+            self.examples.push(difference);
+        }
+    }
+}
+
+impl R1csDiff {
+    /// Whether the files are structurally identical.
+    pub fn is_empty(&self) -> bool {
+        self.header.is_empty()
+            && self.only_in_a.is_empty()
+            && self.only_in_b.is_empty()
+            && self.wire_map.is_empty()
+    }
+}
+
+/// Compares the headers, constraints and wire maps of `a` and `b`.
+///
+/// Constraints are compared as multisets: a constraint is reported if it appears more often in
+/// one file than in the other, regardless of its position. Custom gates and custom sections are
+/// not compared.
+pub fn r1cs_diff<const FS: usize>(
+    a: &R1csFile<FS>,
+    b: &R1csFile<FS>,
+    opts: &DiffOptions,
+) -> R1csDiff {
+    let mut diff = R1csDiff::default();
+
+    let (ha, hb) = (&a.header, &b.header);
+    let mut header = |field, a: String, b: String| {
+        if a != b {
+            diff.header.push(HeaderDifference { field, a, b });
+        }
+    };
+    header(
+        "prime",
+        format!("{:#x}", ha.prime),
+        format!("{:#x}", hb.prime),
+    );
+    header("n_wires", ha.n_wires.to_string(), hb.n_wires.to_string());
+    header(
+        "n_pub_out",
+        ha.n_pub_out.to_string(),
+        hb.n_pub_out.to_string(),
+    );
+    header("n_pub_in", ha.n_pub_in.to_string(), hb.n_pub_in.to_string());
+    header(
+        "n_prvt_in",
+        ha.n_prvt_in.to_string(),
+        hb.n_prvt_in.to_string(),
+    );
+    header("n_labels", ha.n_labels.to_string(), hb.n_labels.to_string());
+    header(
+        "n_constraints",
+        ha.n_constraints.to_string(),
+        hb.n_constraints.to_string(),
+    );
+
+    // A side whose prime isn't odd has its constraints compared as they are.
+    let modulus = |prime| Modulus::new(prime).ok().filter(|_| opts.normalize);
+    let (modulus_a, modulus_b) = (modulus(ha.prime), modulus(hb.prime));
+    // Content ids are computed with the same prime on both sides, differing primes are reported
+    // in the header.
+    let key = |c: &Constraint<FS>, modulus: &Option<Modulus<FS>>| {
+        let normalized;
+        let c = match modulus {
+            Some(modulus) => {
+                let mut c = c.clone();
+                c.normalize(modulus);
+                normalized = c;
+                &normalized
+            }
+            None => c,
+        };
+        if opts.by_content_id {
+            return c.content_id(&ha.prime).to_vec();
+        }
+        let mut data = Vec::with_capacity(c.size());
+        c.write(&mut data).expect("writing to a Vec doesn't fail");
+        data
+    };
+
+    let mut unmatched: HashMap<Vec<u8>, Vec<usize>> = HashMap::new();
+    for (i, c) in a.constraints.0.iter().enumerate().rev() {
+        unmatched.entry(key(c, &modulus_a)).or_default().push(i);
+    }
+    for (i, c) in b.constraints.0.iter().enumerate() {
+        match unmatched.get_mut(&key(c, &modulus_b)).and_then(Vec::pop) {
+            Some(_) => {}
+            None => diff.only_in_b.push(i, opts.max_examples),
+        }
+    }
+    let mut only_in_a: Vec<_> = unmatched.into_values().flatten().collect();
+    only_in_a.sort_unstable();
+    for i in only_in_a {
+        diff.only_in_a.push(i, opts.max_examples);
+    }
+
+    // A stripped map stands for the identity map.
+    let (ma, mb) = (&a.effective_map().0, &b.effective_map().0);
+    for wire in 0..ma.len().max(mb.len()) {
+        let (la, lb) = (ma.get(wire).copied(), mb.get(wire).copied());
+        if la != lb {
+            diff.wire_map.push((wire, la, lb), opts.max_examples);
+        }
+    }
+
+    diff
+}
+
+impl fmt::Display for R1csDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("No differences");
+        }
+
+        for d in &self.header {
+            writeln!(f, "Header {}: {} -> {}", d.field, d.a, d.b)?;
+        }
+
+        let constraints = [("first", &self.only_in_a), ("second", &self.only_in_b)];
+        for (file, differences) in constraints {
+            if !differences.is_empty() {
+                write!(
+                    f,
+                    "{} constraints only in the {} file:",
+                    differences.count, file
+                )?;
+                for i in &differences.examples {
+                    write!(f, " #{}", i)?;
+                }
+                if differences.count > differences.examples.len() as u64 {
+                    write!(f, " ...")?;
+                }
+                writeln!(f)?;
+            }
+        }
+
+        if !self.wire_map.is_empty() {
+            write!(f, "{} wire labels differ:", self.wire_map.count)?;
+            let label = |label: &Option<u64>| match label {
+                Some(label) => label.to_string(),
+                None => "-".to_owned(),
+            };
+            for (wire, a, b) in &self.wire_map.examples {
+                write!(f, " wire {} ({} -> {})", wire, label(a), label(b))?;
+            }
+            if self.wire_map.count > self.wire_map.examples.len() as u64 {
+                write!(f, " ...")?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LinearCombination, WireMap};
+
+    #[test]
+    fn test_diff() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let a = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let mut b = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let opts = DiffOptions::default();
+
+        let diff = r1cs_diff(&a, &b, &opts);
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "No differences");
+
+        // Reordering constraints and terms is not a difference once normalized.
+        b.constraints.0.swap(0, 2);
+        let c = &mut b.constraints.0[2];
+        c.0.reverse();
+        c.1.reverse();
+        assert!(r1cs_diff(&a, &b, &opts).is_empty());
+        let raw = DiffOptions {
+            normalize: false,
+            ..opts
+        };
+        let diff = r1cs_diff(&a, &b, &raw);
+        assert_eq!(diff.only_in_a.examples, [0]);
+        assert_eq!(diff.only_in_b.examples, [2]);
+        let by_content_id = DiffOptions {
+            by_content_id: true,
+            ..raw
+        };
+        assert!(r1cs_diff(&a, &b, &by_content_id).is_empty());
+
+        b.constraints.0.push(Constraint::new(
+            LinearCombination::new(),
+            LinearCombination::new(),
+            LinearCombination::new(),
+        ));
+        b.header.n_constraints = 4;
+        b.map.0[3] = 7;
+        b.map.0.push(8);
+
+        let diff = r1cs_diff(
+            &a,
+            &b,
+            &DiffOptions {
+                max_examples: 1,
+                ..opts
+            },
+        );
+        assert!(!diff.is_empty());
+        assert_eq!(
+            diff.header,
+            [HeaderDifference {
+                field: "n_constraints",
+                a: "3".to_owned(),
+                b: "4".to_owned(),
+            }]
+        );
+        assert!(diff.only_in_a.is_empty());
+        assert_eq!(diff.only_in_b.examples, [3]);
+        assert_eq!(diff.wire_map.count, 2);
+        assert_eq!(diff.wire_map.examples, [(3, Some(a.map.0[3]), Some(7))]);
+        assert_eq!(
+            diff.to_string(),
+            format!(
+                "Header n_constraints: 3 -> 4\n\
+                 1 constraints only in the second file: #3\n\
+                 2 wire labels differ: wire 3 ({} -> 7) ...\n",
+                a.map.0[3]
+            )
+        );
+
+        // A stripped map doesn't differ from the identity map it stands for.
+        let identity = R1csFile {
+            map: WireMap::identity(a.header.n_wires),
+            ..a.clone()
+        };
+        let stripped = R1csFile {
+            map: WireMap::default(),
+            ..a.clone()
+        };
+        assert!(r1cs_diff(&identity, &stripped, &opts).is_empty());
+        assert_eq!(r1cs_diff(&a, &stripped, &opts).wire_map.count, 6);
+    }
+
+    #[test]
+    fn test_diff_shuffled() {
+        let data = std::fs::read("tests/test_circuit.r1cs").unwrap();
+        let a = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        // The constraints in a scrambled order, with the terms of every other one reversed.
+        let mut b = a.clone();
+        let n = b.constraints.len();
+        b.constraints.0 = (0..n)
+            .map(|i| {
+                let mut c = a.constraints[i * 7 % n].clone();
+                if i % 2 == 0 {
+                    c.0.reverse();
+                    c.2.reverse();
+                }
+                c
+            })
+            .collect();
+        assert_ne!(b.constraints, a.constraints);
+
+        let opts = DiffOptions {
+            normalize: false,
+            by_content_id: true,
+            ..DiffOptions::default()
+        };
+        let diff = r1cs_diff(&a, &b, &opts);
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "No differences");
+
+        b.constraints.0[1].1 .0[0].1 += 1;
+        let diff = r1cs_diff(&a, &b, &opts);
+        assert_eq!(diff.only_in_a.count, 1);
+        assert_eq!(diff.only_in_a.examples, [7]);
+        assert_eq!(diff.only_in_b.examples, [1]);
+    }
+}
@@ -0,0 +1,257 @@
+//! Human-readable rendering of constraints.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::Write;
+
+use crate::{Constraint, FieldElement, Header, LinearCombination, R1csFile, Result};
+
+/// Renders a [`Constraint`] or a [`LinearCombination`] with coefficients close to the prime shown
+/// as negative numbers, see [`Constraint::display_with_prime`].
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayWithPrime<'a, T, const FS: usize> {
+    value: &'a T,
+    prime: &'a FieldElement<FS>,
+}
+
+impl<const FS: usize> Constraint<FS> {
+    /// Renders the constraint with coefficients `p - x` shown as `-x` when `x` is small, `p` being
+    /// the header prime.
+    pub fn display_with_prime<'a>(
+        &'a self,
+        header: &'a Header<FS>,
+    ) -> DisplayWithPrime<'a, Self, FS> {
+        DisplayWithPrime {
+            value: self,
+            prime: &header.prime,
+        }
+    }
+}
+
+impl<const FS: usize> LinearCombination<FS> {
+    /// Renders the combination with coefficients `p - x` shown as `-x` when `x` is small, `p`
+    /// being the header prime.
+    pub fn display_with_prime<'a>(
+        &'a self,
+        header: &'a Header<FS>,
+    ) -> DisplayWithPrime<'a, Self, FS> {
+        DisplayWithPrime {
+            value: self,
+            prime: &header.prime,
+        }
+    }
+}
+
+/// Renders the constraint as `(A) * (B) = (C)`, e.g. `(3*w_5 + w_2) * (w_0) = (w_6)`.
+///
+/// Coefficients are printed in decimal if they fit in a `u128` and in hexadecimal otherwise.
+impl<const FS: usize> fmt::Display for Constraint<FS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_constraint(self, None, &fmt_wire, f)
+    }
+}
+
+/// Renders the combination as a sum of terms, e.g. `3*w_5 + w_2`, or `0` if it is empty.
+impl<const FS: usize> fmt::Display for LinearCombination<FS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_combination(self, None, &fmt_wire, f)
+    }
+}
+
+impl<const FS: usize> fmt::Display for DisplayWithPrime<'_, Constraint<FS>, FS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_constraint(self.value, Some(self.prime), &fmt_wire, f)
+    }
+}
+
+impl<const FS: usize> fmt::Display for DisplayWithPrime<'_, LinearCombination<FS>, FS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_combination(self.value, Some(self.prime), &fmt_wire, f)
+    }
+}
+
+/// Writes a wire of a rendered constraint.
+pub(crate) type WireFormatter<'a> = dyn Fn(u32, &mut fmt::Formatter<'_>) -> fmt::Result + 'a;
+
+pub(crate) fn fmt_wire(wire: u32, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "w_{}", wire)
+}
+
+pub(crate) fn fmt_constraint<const FS: usize>(
+    c: &Constraint<FS>,
+    prime: Option<&FieldElement<FS>>,
+    wire: &WireFormatter<'_>,
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    f.write_str("(")?;
+    fmt_combination(&c.0, prime, wire, f)?;
+    f.write_str(") * (")?;
+    fmt_combination(&c.1, prime, wire, f)?;
+    f.write_str(") = (")?;
+    fmt_combination(&c.2, prime, wire, f)?;
+    f.write_str(")")
+}
+
+fn fmt_combination<const FS: usize>(
+    lc: &LinearCombination<FS>,
+    prime: Option<&FieldElement<FS>>,
+    fmt_wire: &WireFormatter<'_>,
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    if lc.is_empty() {
+        return f.write_str("0");
+    }
+
+    for (i, (coeff, wire)) in lc.iter().enumerate() {
+        let (negative, value) = match prime.and_then(|prime| negated(coeff, prime)) {
+            Some(value) => (true, Some(value)),
+            None => (false, to_u128(coeff)),
+        };
+        match (i, negative) {
+            (0, false) => {}
+            (0, true) => f.write_str("-")?,
+            (_, false) => f.write_str(" + ")?,
+            (_, true) => f.write_str(" - ")?,
+        }
+
+        match value {
+            Some(1) => {}
+            Some(value) => write!(f, "{}*", value)?,
+            None => write!(f, "{:#x}*", coeff)?,
+        }
+        fmt_wire(*wire, f)?;
+    }
+
+    Ok(())
+}
+
+/// The value of `e` if it fits in a `u128`.
+pub(crate) fn to_u128<const FS: usize>(e: &FieldElement<FS>) -> Option<u128> {
+    if e.iter().skip(16).any(|byte| *byte != 0) {
+        return None;
+    }
+
+    let mut bytes = [0u8; 16];
+    let len = FS.min(16);
+    bytes[..len].copy_from_slice(&e[..len]);
+    Some(u128::from_le_bytes(bytes))
+}
+
+/// `prime - e`, if `e` is below `prime`, doesn't fit in a `u128` itself, and the difference does.
+fn negated<const FS: usize>(e: &FieldElement<FS>, prime: &FieldElement<FS>) -> Option<u128> {
+    if to_u128(e).is_some() || e.cmp_value(prime) != Ordering::Less {
+        return None;
+    }
+
+    let mut difference = [0u8; FS];
+    let mut borrow = 0i16;
+    for ((byte, &p), &x) in difference.iter_mut().zip(prime.iter()).zip(e.iter()) {
+        let d = i16::from(p) - i16::from(x) - borrow;
+        *byte = d as u8;
+        borrow = i16::from(d < 0);
+    }
+
+    to_u128(&FieldElement::from(difference))
+}
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Writes a summary of the header followed by the first `limit` constraints, or all of them,
+    /// one per line.
+    pub fn dump<W: Write>(&self, mut w: W, limit: Option<usize>) -> Result<()> {
+        let header = &self.header;
+        match header.curve() {
+            Some(curve) => writeln!(w, "Prime: {:?}", curve)?,
+            None => writeln!(w, "Prime: {:#x}", header.prime)?,
+        }
+        writeln!(w, "# of Wires: {}", header.n_wires)?;
+        writeln!(w, "# of Constraints: {}", header.n_constraints)?;
+        writeln!(w, "# of Private Inputs: {}", header.n_prvt_in)?;
+        writeln!(w, "# of Public Inputs: {}", header.n_pub_in)?;
+        writeln!(w, "# of Outputs: {}", header.n_pub_out)?;
+        writeln!(w, "# of Labels: {}", header.n_labels)?;
+
+        let constraints = &self.constraints.0;
+        let shown = limit.unwrap_or(constraints.len()).min(constraints.len());
+        for (i, c) in constraints[..shown].iter().enumerate() {
+            writeln!(w, "[{}] {}", i, c.display_with_prime(header))?;
+        }
+        if shown < constraints.len() {
+            writeln!(w, "... {} more constraints", constraints.len() - shown)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryInto;
+
+    fn fe(n: u64) -> FieldElement<32> {
+        FieldElement::from_u64(n)
+    }
+
+    #[test]
+    fn test_display() {
+        let header = Header {
+            prime: FieldElement::bn254_scalar_prime(),
+            n_wires: 7,
+            n_pub_out: 1,
+            n_pub_in: 0,
+            n_prvt_in: 2,
+            n_labels: 7,
+            n_constraints: 1,
+        };
+        let minus = |n: u64| {
+            let mut bytes = *header.prime;
+            let low = u64::from_le_bytes(bytes[..8].try_into().unwrap()) - n;
+            bytes[..8].copy_from_slice(&low.to_le_bytes());
+            FieldElement::from(bytes)
+        };
+
+        let c = Constraint::new(
+            vec![(fe(3), 5), (fe(1), 2)],
+            LinearCombination::term(fe(1), 0),
+            vec![(fe(u64::MAX), 6), (minus(2), 1)],
+        );
+        assert_eq!(
+            c.to_string(),
+            "(3*w_5 + w_2) * (w_0) = (18446744073709551615*w_6 + \
+             0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593efffffff*w_1)"
+        );
+        assert_eq!(
+            c.display_with_prime(&header).to_string(),
+            "(3*w_5 + w_2) * (w_0) = (18446744073709551615*w_6 - 2*w_1)"
+        );
+        assert_eq!(LinearCombination::<32>::new().to_string(), "0");
+        assert_eq!(
+            LinearCombination::from(vec![(minus(1), 3), (fe(1), 4)])
+                .display_with_prime(&header)
+                .to_string(),
+            "-w_3 + w_4"
+        );
+    }
+
+    #[test]
+    fn test_dump() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        let mut out = Vec::new();
+        file.dump(&mut out, Some(1)).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(
+            out,
+            "Prime: Bn254\n\
+             # of Wires: 7\n\
+             # of Constraints: 3\n\
+             # of Private Inputs: 3\n\
+             # of Public Inputs: 2\n\
+             # of Outputs: 1\n\
+             # of Labels: 1000\n\
+             [0] (3*w_5 + 8*w_6) * (2*w_0 + 20*w_2 + 12*w_3) = (5*w_0 + 7*w_2)\n\
+             ... 2 more constraints\n"
+        );
+    }
+}
@@ -0,0 +1,175 @@
+//! Graphviz export of the graph of constraints and the wires they reference.
+
+use std::collections::BTreeSet;
+use std::io::{BufWriter, Write};
+use std::ops::Range;
+
+use crate::{AnnotatedR1cs, Matrix, R1csFile, Result, SymFile, WireUsageIndex};
+
+/// Options for [`R1csFile::to_dot`].
+#[derive(Debug, Default, Clone)]
+pub struct DotOptions<'a> {
+    /// Only the constraints in this range.
+    pub constraints: Option<Range<usize>>,
+    /// Only the constraints referencing this wire.
+    pub neighborhood: Option<u32>,
+    /// Label each edge with the matrix of its term.
+    pub matrix_labels: bool,
+    /// Color each edge by the matrix of its term: A red, B green and C blue.
+    pub matrix_colors: bool,
+    /// Name the wires after their signals, when they have one.
+    pub symbols: Option<&'a SymFile>,
+}
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Writes the circuit as an undirected bipartite graph in the DOT language: a box per
+    /// constraint, an ellipse per wire referenced by these constraints, and an edge per term.
+    ///
+    /// The constraints written are those selected by both [`DotOptions::constraints`] and
+    /// [`DotOptions::neighborhood`], all of them by default.
+    pub fn to_dot<W: Write>(&self, w: W, opts: DotOptions<'_>) -> Result<()> {
+        let mut w = BufWriter::new(w);
+        let constraints = &self.constraints.0;
+
+        let range = opts.constraints.clone().unwrap_or(0..constraints.len());
+        let range = range.start.min(constraints.len())..range.end.min(constraints.len());
+        let selected: Vec<usize> = match opts.neighborhood {
+            Some(wire) => {
+                let usage = WireUsageIndex::build(self);
+                let selected: BTreeSet<usize> = usage
+                    .constraints_for(wire)
+                    .iter()
+                    .map(|&(c, _)| c as usize)
+                    .filter(|c| range.contains(c))
+                    .collect();
+                selected.into_iter().collect()
+            }
+            None => range.collect(),
+        };
+        let names = opts.symbols.map(|sym| AnnotatedR1cs::new(self, sym));
+
+        writeln!(w, "graph r1cs {{")?;
+        let mut wires = BTreeSet::new();
+        for &i in &selected {
+            writeln!(w, "  c{} [label=\"c{}\", shape=box];", i, i)?;
+            wires.extend(constraints[i].terms().map(|(_, term)| term.wire));
+        }
+        for wire in wires {
+            let name = names.as_ref().and_then(|names| names.wire_name(wire));
+            match name {
+                Some(name) => writeln!(w, "  w{} [label=\"{}\"];", wire, escape(name))?,
+                None => writeln!(w, "  w{} [label=\"w_{}\"];", wire, wire)?,
+            }
+        }
+
+        for &i in &selected {
+            for (matrix, term) in constraints[i].terms() {
+                let mut attributes = Vec::new();
+                if opts.matrix_labels {
+                    attributes.push(format!("label=\"{}\"", matrix));
+                }
+                if opts.matrix_colors {
+                    attributes.push(format!("color={}", color(matrix)));
+                }
+
+                if attributes.is_empty() {
+                    writeln!(w, "  c{} -- w{};", i, term.wire)?;
+                } else {
+                    writeln!(w, "  c{} -- w{} [{}];", i, term.wire, attributes.join(", "))?;
+                }
+            }
+        }
+        writeln!(w, "}}")?;
+
+        w.flush()?;
+        Ok(())
+    }
+}
+
+fn color(matrix: Matrix) -> &'static str {
+    match matrix {
+        Matrix::A => "red",
+        Matrix::B => "green",
+        Matrix::C => "blue",
+    }
+}
+
+/// Escapes `s` for a double-quoted DOT string.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dot<const FS: usize>(file: &R1csFile<FS>, opts: DotOptions<'_>) -> String {
+        let mut out = Vec::new();
+        file.to_dot(&mut out, opts).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let data = std::fs::read("tests/simple_circuit.sym").unwrap();
+        let sym = SymFile::read(data.as_slice()).unwrap();
+
+        let opts = DotOptions {
+            constraints: Some(1..5),
+            matrix_labels: true,
+            matrix_colors: true,
+            symbols: Some(&sym),
+            ..DotOptions::default()
+        };
+        assert_eq!(
+            dot(&file, opts),
+            "graph r1cs {\n  \
+               c1 [label=\"c1\", shape=box];\n  \
+               c2 [label=\"c2\", shape=box];\n  \
+               w0 [label=\"w_0\"];\n  \
+               w1 [label=\"main.out\"];\n  \
+               w2 [label=\"main.a\"];\n  \
+               w3 [label=\"main.b\"];\n  \
+               w4 [label=\"main.c\"];\n  \
+               w5 [label=\"main.d\"];\n  \
+               w6 [label=\"main.mul.out\"];\n  \
+               c1 -- w1 [label=\"A\", color=red];\n  \
+               c1 -- w4 [label=\"A\", color=red];\n  \
+               c1 -- w5 [label=\"A\", color=red];\n  \
+               c1 -- w3 [label=\"B\", color=green];\n  \
+               c1 -- w6 [label=\"B\", color=green];\n  \
+               c2 -- w6 [label=\"A\", color=red];\n  \
+               c2 -- w0 [label=\"B\", color=green];\n  \
+               c2 -- w2 [label=\"B\", color=green];\n  \
+               c2 -- w3 [label=\"B\", color=green];\n  \
+               c2 -- w6 [label=\"C\", color=blue];\n\
+             }\n"
+        );
+
+        let opts = DotOptions {
+            neighborhood: Some(1),
+            ..DotOptions::default()
+        };
+        assert_eq!(
+            dot(&file, opts),
+            "graph r1cs {\n  \
+               c1 [label=\"c1\", shape=box];\n  \
+               w1 [label=\"w_1\"];\n  \
+               w3 [label=\"w_3\"];\n  \
+               w4 [label=\"w_4\"];\n  \
+               w5 [label=\"w_5\"];\n  \
+               w6 [label=\"w_6\"];\n  \
+               c1 -- w1;\n  \
+               c1 -- w4;\n  \
+               c1 -- w5;\n  \
+               c1 -- w3;\n  \
+               c1 -- w6;\n\
+             }\n"
+        );
+
+        let full = dot(&file, DotOptions::default());
+        assert_eq!(full.matches(" -- ").count(), 17);
+    }
+}
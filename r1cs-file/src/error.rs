@@ -0,0 +1,345 @@
+#[cfg(feature = "std")]
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+
+use iden3_binfile::{io, BinFileError};
+
+use crate::{SectionType, ValidationError};
+
+pub type Result<T, E = R1csError> = core::result::Result<T, E>;
+
+/// Errors produced while reading or writing r1cs files.
+///
+/// Errors detected in the input carry the byte offset (from the start of the file) at which they
+/// were detected.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum R1csError {
+    InvalidMagic {
+        found: [u8; 4],
+    },
+    UnsupportedVersion(u32),
+    FieldSizeMismatch {
+        expected: u32,
+        found: u32,
+        offset: u64,
+    },
+    /// The field size has no [`crate::R1csFileAny`] variant.
+    UnsupportedFieldSize {
+        found: u32,
+        offset: u64,
+    },
+    /// The input ended early. `section` is `None` outside of section bodies.
+    UnexpectedEof {
+        section: Option<SectionType>,
+        offset: u64,
+    },
+    DuplicateSection {
+        section: SectionType,
+        offset: u64,
+    },
+    MissingSection(SectionType),
+    /// A section comes before another one it depends on, which a streaming reader can't handle.
+    SectionOutOfOrder {
+        section: SectionType,
+        offset: u64,
+    },
+    /// A section size points past the largest representable file offset.
+    SectionSizeOverflow {
+        offset: u64,
+    },
+    /// A section body was not consumed completely by its decoder.
+    TrailingSectionData {
+        section: SectionType,
+        offset: u64,
+    },
+    InvalidString {
+        section: SectionType,
+        offset: u64,
+    },
+    /// A key of the metadata section appears twice, the second time at `offset`.
+    DuplicateMetadataKey {
+        offset: u64,
+    },
+    /// A limit set in [`crate::ReadOptions`] was exceeded.
+    LimitExceeded {
+        limit: Limit,
+        value: u64,
+        max: u64,
+        offset: u64,
+    },
+    /// A line of a `.sym` file that isn't `label,wire,component,name`.
+    InvalidSymLine {
+        line: usize,
+    },
+    /// A line of a circuit in text form that doesn't follow the grammar of
+    /// [`crate::R1csFile::read_text`].
+    InvalidText {
+        line: usize,
+        message: String,
+    },
+    /// Sections disagree with each other, see [`crate::ParseMode::Strict`].
+    Inconsistent(ParseWarning),
+    /// Data passed in to be written is invalid.
+    Invalid(ValidationError),
+    Io(io::Error),
+    /// An error reading or writing the file at `path`.
+    #[cfg(feature = "std")]
+    File {
+        path: PathBuf,
+        error: Box<R1csError>,
+    },
+}
+
+/// Resource limits configurable in [`crate::ReadOptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Limit {
+    Constraints,
+    TermsPerLc,
+    SectionSize,
+    SkippedSections,
+    SkippedBytes,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Limit::Constraints => "max_constraints",
+            Limit::TermsPerLc => "max_terms_per_lc",
+            Limit::SectionSize => "max_section_size",
+            Limit::SkippedSections => "max_skipped_sections",
+            Limit::SkippedBytes => "max_skipped_bytes",
+        };
+
+        f.write_str(name)
+    }
+}
+
+/// Inconsistencies tolerated by [`crate::ParseMode::Lenient`], and defaults filled in when
+/// importing JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseWarning {
+    /// The header constraint count differs from the number of constraints in the constraints
+    /// section. The constraints section is kept as is and the header is left untouched.
+    ConstraintCountMismatch { header: u32, section: u64 },
+    /// The constraints section size is not a whole number of constraints. The incomplete
+    /// constraint at `offset` is dropped.
+    PartialConstraint { offset: u64, bytes: u64 },
+    /// Bytes found after the last section.
+    TrailingData { offset: u64, bytes: u64 },
+    /// An optional key is missing from imported JSON and was given a default value.
+    MissingJsonKey { key: &'static str },
+    /// The top byte of the header prime is zero: the prime only takes `prime_bytes` of the
+    /// `field_size` bytes of an element, which suggests the file was written for a smaller field
+    /// size.
+    PaddedPrime { field_size: u32, prime_bytes: u32 },
+    /// A section ends at byte `size`, past [`crate::MAX_SUPPORTED_FILE_SIZE`].
+    FileTooLarge { size: u64 },
+    /// A section holds `count` elements, more than [`crate::MAX_SUPPORTED_ELEMENT_COUNT`].
+    TooManyElements { section: SectionType, count: u64 },
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseWarning::ConstraintCountMismatch { header, section } => write!(
+                f,
+                "Header declares {} constraints but the constraints section holds {}",
+                header, section
+            ),
+            ParseWarning::PartialConstraint { offset, bytes } => write!(
+                f,
+                "Constraints section ends {} bytes into a constraint at offset {}",
+                bytes, offset
+            ),
+            ParseWarning::TrailingData { offset, bytes } => write!(
+                f,
+                "{} trailing bytes after the last section at offset {}",
+                bytes, offset
+            ),
+            ParseWarning::MissingJsonKey { key } => {
+                write!(f, "Key {} is missing, using a default value", key)
+            }
+            ParseWarning::PaddedPrime {
+                field_size,
+                prime_bytes,
+            } => write!(
+                f,
+                "Prime takes {} of the {} bytes of the field size",
+                prime_bytes, field_size
+            ),
+            ParseWarning::FileTooLarge { size } => write!(
+                f,
+                "Sections extend to byte {}, past the supported file size of {} bytes",
+                size,
+                crate::MAX_SUPPORTED_FILE_SIZE
+            ),
+            ParseWarning::TooManyElements { section, count } => write!(
+                f,
+                "The {} section holds {} elements, more than the supported {}",
+                section,
+                count,
+                crate::MAX_SUPPORTED_ELEMENT_COUNT
+            ),
+        }
+    }
+}
+
+impl R1csError {
+    /// Turns an unexpected end of input into [`R1csError::UnexpectedEof`] at `offset`.
+    pub(crate) fn eof_in(self, section: Option<SectionType>, offset: u64) -> Self {
+        match self {
+            R1csError::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                R1csError::UnexpectedEof { section, offset }
+            }
+            e => e,
+        }
+    }
+
+    /// The [`io::ErrorKind`] used when converting into an [`io::Error`].
+    fn io_kind(&self) -> io::ErrorKind {
+        match self {
+            R1csError::Io(e) => e.kind(),
+            R1csError::UnexpectedEof { .. } => io::ErrorKind::UnexpectedEof,
+            R1csError::Invalid(_) => io::ErrorKind::InvalidInput,
+            #[cfg(feature = "std")]
+            R1csError::File { error, .. } => error.io_kind(),
+            _ => io::ErrorKind::InvalidData,
+        }
+    }
+}
+
+impl fmt::Display for R1csError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            R1csError::InvalidMagic { found } => {
+                write!(f, "Invalid magic number {:?} at offset 0", found)
+            }
+            R1csError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported version {} at offset 4", version)
+            }
+            R1csError::FieldSizeMismatch {
+                expected,
+                found,
+                offset,
+            } => write!(
+                f,
+                "Wrong field size at offset {}: expected {}, found {}",
+                offset, expected, found
+            ),
+            R1csError::UnsupportedFieldSize { found, offset } => write!(
+                f,
+                "Unsupported field size {} at offset {}: expected 8, 32, 48 or 64",
+                found, offset
+            ),
+            R1csError::UnexpectedEof {
+                section: Some(section),
+                offset,
+            } => write!(
+                f,
+                "Unexpected end of file in {} section at offset {}",
+                section, offset
+            ),
+            R1csError::UnexpectedEof {
+                section: None,
+                offset,
+            } => write!(f, "Unexpected end of file at offset {}", offset),
+            R1csError::DuplicateSection { section, offset } => {
+                write!(f, "Duplicated {} section at offset {}", section, offset)
+            }
+            R1csError::MissingSection(section) => write!(f, "Missing {} section", section),
+            R1csError::SectionOutOfOrder { section, offset } => write!(
+                f,
+                "Unexpected {} section at offset {} before the header",
+                section, offset
+            ),
+            R1csError::SectionSizeOverflow { offset } => write!(
+                f,
+                "Section size at offset {} overflows the file offset",
+                offset
+            ),
+            R1csError::TrailingSectionData { section, offset } => write!(
+                f,
+                "Trailing data in {} section at offset {}",
+                section, offset
+            ),
+            R1csError::InvalidString { section, offset } => write!(
+                f,
+                "Invalid UTF-8 string in {} section at offset {}",
+                section, offset
+            ),
+            R1csError::DuplicateMetadataKey { offset } => {
+                write!(f, "Duplicated metadata key at offset {}", offset)
+            }
+            R1csError::LimitExceeded {
+                limit,
+                value,
+                max,
+                offset,
+            } => write!(
+                f,
+                "Limit {} exceeded at offset {}: {} is larger than {}",
+                limit, offset, value, max
+            ),
+            R1csError::InvalidSymLine { line } => {
+                write!(f, "Malformed symbol at line {}", line)
+            }
+            R1csError::InvalidText { line, message } => write!(f, "Line {}: {}", line, message),
+            R1csError::Inconsistent(warning) => write!(f, "Inconsistent file: {}", warning),
+            R1csError::Invalid(e) => write!(f, "Invalid input: {}", e),
+            R1csError::Io(e) => write!(f, "I/O error: {}", e),
+            #[cfg(feature = "std")]
+            R1csError::File { path, error } => write!(f, "{}: {}", path.display(), error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for R1csError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            R1csError::Invalid(e) => Some(e),
+            R1csError::Io(e) => Some(e),
+            R1csError::File { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for R1csError {
+    fn from(e: io::Error) -> Self {
+        R1csError::Io(e)
+    }
+}
+
+impl From<BinFileError> for R1csError {
+    fn from(e: BinFileError) -> Self {
+        match e {
+            BinFileError::InvalidMagic { found } => R1csError::InvalidMagic { found },
+            BinFileError::UnsupportedVersion(version) => R1csError::UnsupportedVersion(version),
+            BinFileError::SectionSizeOverflow { offset } => {
+                R1csError::SectionSizeOverflow { offset }
+            }
+            BinFileError::UnexpectedEof { offset } => R1csError::UnexpectedEof {
+                section: None,
+                offset,
+            },
+            BinFileError::Io(e) => R1csError::Io(e),
+            e => R1csError::Io(e.into()),
+        }
+    }
+}
+
+impl From<R1csError> for io::Error {
+    fn from(e: R1csError) -> Self {
+        match e {
+            R1csError::Io(e) => e,
+            e => io::Error::new(e.io_kind(), e),
+        }
+    }
+}
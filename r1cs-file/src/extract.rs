@@ -0,0 +1,160 @@
+//! Extracting subcircuits for debugging.
+
+use std::ops::Range;
+
+use crate::compact::mark_used;
+use crate::merge::renumber;
+use crate::{CompactionReport, Constraints, Header, R1csFile, WireMap};
+
+/// Options for [`R1csFile::extract_with`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ExtractOptions {
+    /// Keep the public outputs, public inputs and private inputs touched by the extracted
+    /// constraints in their original class. By default every touched wire but the constant one
+    /// becomes a private input.
+    pub keep_signals: bool,
+}
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Extracts the constraints in `constraints` into a new circuit, see
+    /// [`R1csFile::extract_with`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    pub fn extract(&self, constraints: Range<usize>) -> (R1csFile<FS>, CompactionReport) {
+        self.extract_with(constraints, &ExtractOptions::default())
+    }
+
+    /// Extracts the constraints at the indices in `constraints`, in that order, into a new
+    /// circuit over the wires they touch.
+    ///
+    /// The touched wires and wire 0 are renumbered contiguously, in order, and keep their labels.
+    /// The returned report maps the wires of `self` to those of the new circuit, for slicing a
+    /// witness to match. Custom gates and custom sections are not carried over.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an index is out of bounds.
+    pub fn extract_with(
+        &self,
+        constraints: impl IntoIterator<Item = usize>,
+        opts: &ExtractOptions,
+    ) -> (R1csFile<FS>, CompactionReport) {
+        let selected: Vec<_> = constraints
+            .into_iter()
+            .map(|i| &self.constraints.0[i])
+            .collect();
+
+        let mut used = vec![false; (self.header.n_wires as usize).max(1)];
+        used[0] = true;
+        mark_used(&mut used, selected.iter().copied());
+        let report = CompactionReport::from_used(&used);
+        let n_wires = used.len() as u32 - report.removed;
+
+        let constraints = selected
+            .iter()
+            .map(|c| {
+                renumber(c, |wire| {
+                    report.mapping[wire as usize].expect("referenced wires are kept")
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let header = &self.header;
+        let (n_pub_out, n_pub_in, n_prvt_in) = if opts.keep_signals {
+            let touched = |wires: Range<u64>| {
+                report
+                    .mapping
+                    .iter()
+                    .enumerate()
+                    .filter(|(wire, new)| wires.contains(&(*wire as u64)) && new.is_some())
+                    .count() as u32
+            };
+            let [pub_out, pub_in, prvt_in, internal] = header.region_starts();
+            (
+                touched(pub_out..pub_in),
+                touched(pub_in..prvt_in),
+                touched(prvt_in..internal),
+            )
+        } else {
+            (0, 0, n_wires - 1)
+        };
+
+        let file = R1csFile {
+            header: Header {
+                prime: header.prime,
+                n_wires,
+                n_pub_out,
+                n_pub_in,
+                n_prvt_in,
+                n_labels: header.n_labels,
+                n_constraints: constraints.len() as u32,
+            },
+            constraints: Constraints(constraints),
+            map: WireMap(report.remap(&self.map.0)),
+            custom_gates_list: None,
+            custom_gates_application: None,
+            metadata: None,
+            custom_sections: Vec::new(),
+            version: self.version,
+        };
+
+        (file, report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that `sub` holds the constraints of `file` at `indices` renumbered by `report`.
+    fn assert_extracted(
+        file: &R1csFile<32>,
+        sub: &R1csFile<32>,
+        report: &CompactionReport,
+        indices: &[usize],
+    ) {
+        assert_eq!(sub.validate(), Ok(()));
+        assert_eq!(sub.header.n_wires, file.header.n_wires - report.removed);
+        assert_eq!(sub.map.0, report.remap(&file.map.0));
+        for (c, i) in sub.constraints.0.iter().zip(indices) {
+            let original = &file.constraints.0[*i];
+            assert_eq!(c.num_terms(), original.num_terms());
+            for (new, old) in c.terms().zip(original.terms()) {
+                assert_eq!(new.0, old.0);
+                assert_eq!(new.1.coeff, old.1.coeff);
+                assert_eq!(Some(new.1.wire), report.new_index(old.1.wire));
+            }
+        }
+    }
+
+    #[test]
+    fn test_extract() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        let (all, report) = file.extract(0..3);
+        assert_eq!(report.removed, 0);
+        assert_eq!(all.constraints, file.constraints);
+        assert_eq!(all.map, file.map);
+        assert_eq!(all.header.n_prvt_in, 6);
+        assert_eq!(all.validate(), Ok(()));
+
+        for i in 0..3 {
+            let (sub, report) = file.extract(i..i + 1);
+            assert_eq!(sub.header.n_constraints, 1);
+            assert_extracted(&file, &sub, &report, &[i]);
+        }
+
+        let opts = ExtractOptions { keep_signals: true };
+        let (sub, report) = file.extract_with([2, 0], &opts);
+        assert_extracted(&file, &sub, &report, &[2, 0]);
+        let h = &sub.header;
+        let kept = |wires: Range<u32>| wires.filter(|w| report.new_index(*w).is_some()).count();
+        assert_eq!(
+            (h.n_pub_out, h.n_pub_in, h.n_prvt_in),
+            (kept(1..2) as u32, kept(2..4) as u32, kept(4..7) as u32)
+        );
+    }
+}
@@ -0,0 +1,50 @@
+//! Checks against `ff` prime fields, behind the `ff` feature.
+
+use ff::PrimeField;
+
+use crate::{Header, ValidationError};
+
+impl<const FS: usize> Header<FS> {
+    /// Checks that the prime is the modulus of the `ff` field `F`.
+    pub fn check_ff_prime<F: PrimeField>(&self) -> Result<(), ValidationError> {
+        if self.prime.is_ff_modulus::<F>() {
+            return Ok(());
+        }
+
+        Err(ValidationError::ModulusMismatch {
+            prime: format!("{:#x}", self.prime),
+            modulus: F::MODULUS.to_owned(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{primes, R1csFile};
+    use bls12_381::Scalar;
+
+    #[test]
+    fn test_check_ff_prime() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        let coeff = file.constraints.0[2].1[0].0;
+        assert_eq!(coeff.to_ff::<Scalar>(), Some(Scalar::from(6)));
+
+        let err = file.header.check_ff_prime::<Scalar>().unwrap_err();
+        assert_eq!(
+            err,
+            ValidationError::ModulusMismatch {
+                prime: format!("{:#x}", file.header.prime),
+                modulus: Scalar::MODULUS.to_owned(),
+            }
+        );
+
+        let bls = Header {
+            prime: primes::BLS12_381_SCALAR,
+            ..file.header
+        };
+        assert_eq!(bls.check_ff_prime::<Scalar>(), Ok(()));
+    }
+}
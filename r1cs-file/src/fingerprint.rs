@@ -0,0 +1,146 @@
+//! Stable digests identifying a circuit.
+
+#[cfg(feature = "digest")]
+use std::io;
+use std::io::Write;
+
+use byteorder::{LittleEndian, WriteBytesExt};
+#[cfg(feature = "digest")]
+use digest::{Digest, Output};
+
+use crate::{CustomGatesApplication, CustomGatesList, Header, R1csFile, SectionType, WireMap};
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Hashes a canonical encoding of the circuit with `D`.
+    ///
+    /// The canonical encoding is the file without its preamble, with the sections laid out as
+    /// follows, each with its section header:
+    ///
+    /// 1. the header;
+    /// 2. the constraints, the terms of each linear combination sorted by wire index and then by
+    ///    coefficient value;
+    /// 3. the wire map;
+    /// 4. the custom gates list and custom gates application, if present.
+    ///
+    /// Metadata and custom sections are left out, so files differing only in section order, term
+    /// order, provenance and unknown sections have the same fingerprint.
+    #[cfg(feature = "digest")]
+    pub fn fingerprint<D: Digest>(&self) -> Output<D> {
+        let mut w = DigestWriter(D::new());
+        self.write_fingerprinted(&mut w)
+            .expect("writing to a digest doesn't fail");
+
+        w.0.finalize()
+    }
+
+    /// Writes the canonical encoding hashed by the fingerprint.
+    pub(crate) fn write_fingerprinted<W: Write>(&self, mut w: W) -> crate::Result<()> {
+        write_fingerprint_head(&mut w, &self.header, self.constraints.size())?;
+        for c in &self.constraints.0 {
+            c.sorted_terms().write(&mut w)?;
+        }
+
+        write_fingerprint_tail(
+            w,
+            &self.map,
+            self.custom_gates_list.as_ref(),
+            self.custom_gates_application.as_ref(),
+        )
+    }
+}
+
+/// Writes the canonical encoding up to the constraints, for callers streaming them. The
+/// constraints follow with their terms sorted, then [`write_fingerprint_tail`].
+pub(crate) fn write_fingerprint_head<W: Write, const FS: usize>(
+    mut w: W,
+    header: &Header<FS>,
+    constraints_size: u64,
+) -> crate::Result<()> {
+    section_header(&mut w, SectionType::Header, Header::<FS>::size())?;
+    header.write(&mut w)?;
+    section_header(&mut w, SectionType::Constraint, constraints_size)
+}
+
+/// Writes the canonical encoding of the sections following the constraints.
+pub(crate) fn write_fingerprint_tail<W: Write, const FS: usize>(
+    mut w: W,
+    map: &WireMap,
+    custom_gates_list: Option<&CustomGatesList<FS>>,
+    custom_gates_application: Option<&CustomGatesApplication>,
+) -> crate::Result<()> {
+    section_header(&mut w, SectionType::Wire2LabelIdMap, map.size())?;
+    map.write(&mut w)?;
+    if let Some(list) = custom_gates_list {
+        section_header(&mut w, SectionType::CustomGatesList, list.size())?;
+        list.write(&mut w)?;
+    }
+    if let Some(application) = custom_gates_application {
+        section_header(
+            &mut w,
+            SectionType::CustomGatesApplication,
+            application.size(),
+        )?;
+        application.write(&mut w)?;
+    }
+
+    Ok(())
+}
+
+fn section_header<W: Write>(mut w: W, ty: SectionType, size: u64) -> crate::Result<()> {
+    w.write_u32::<LittleEndian>(ty as u32)?;
+    w.write_u64::<LittleEndian>(size)?;
+
+    Ok(())
+}
+
+#[cfg(feature = "digest")]
+struct DigestWriter<D>(D);
+
+#[cfg(feature = "digest")]
+impl<D: Digest> Write for DigestWriter<D> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "digest"))]
+mod tests {
+    use super::*;
+    use crate::{CustomSection, FieldElement};
+    use sha2::Sha256;
+
+    #[test]
+    fn test_fingerprint() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let fingerprint = file.fingerprint::<Sha256>();
+
+        // Sections reordered, terms reversed and an unknown section added.
+        let sections = R1csFile::<32>::scan_sections(io::Cursor::new(&data)).unwrap();
+        let mut reordered = data[..12].to_vec();
+        for s in [&sections[2], &sections[0], &sections[1]] {
+            reordered
+                .extend_from_slice(&data[s.offset as usize..(s.offset + 12 + s.size) as usize]);
+        }
+        let mut other = R1csFile::<32>::read(reordered.as_slice()).unwrap();
+        for c in &mut other.constraints.0 {
+            c.0.reverse();
+            c.1.reverse();
+            c.2.reverse();
+        }
+        other.custom_sections.push(CustomSection {
+            id: 100,
+            data: vec![1, 2, 3],
+        });
+        assert_ne!(other.serialize(), data);
+        assert_eq!(other.fingerprint::<Sha256>(), fingerprint);
+
+        other.constraints.0[1].a_mut()[0].0 = FieldElement::from_u64(5);
+        assert_ne!(other.fingerprint::<Sha256>(), fingerprint);
+    }
+}
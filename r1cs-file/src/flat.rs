@@ -0,0 +1,266 @@
+//! Constraints stored in a few contiguous buffers.
+
+use std::convert::TryFrom;
+use std::io::{Read, Seek, Write};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use iden3_binfile::BinFileReader;
+
+use crate::{
+    Constraint, Constraints, CountingReader, FieldElement, Matrix, R1csError, Result, SectionType,
+    TermRef, FORMAT, MAX_PREALLOCATION,
+};
+
+/// Constraints stored as one coefficient buffer and one wire buffer instead of three vectors per
+/// constraint.
+///
+/// Holds the same data as [`Constraints`], which it converts to and from losslessly, with three
+/// allocations in total.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlatConstraints<const FS: usize> {
+    /// `FS` bytes per term.
+    coefficients: Vec<u8>,
+    wires: Vec<u32>,
+    /// Index of the first term of every linear combination, followed by the number of terms.
+    bounds: Vec<usize>,
+}
+
+/// A constraint of [`FlatConstraints`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstraintView<'a, const FS: usize> {
+    coefficients: &'a [u8],
+    wires: &'a [u32],
+    /// The 4 bounds of the A, B and C combinations.
+    bounds: &'a [usize],
+}
+
+impl<const FS: usize> Default for FlatConstraints<FS> {
+    fn default() -> Self {
+        FlatConstraints {
+            coefficients: Vec::new(),
+            wires: Vec::new(),
+            bounds: vec![0],
+        }
+    }
+}
+
+impl<const FS: usize> FlatConstraints<FS> {
+    pub fn len(&self) -> usize {
+        (self.bounds.len() - 1) / 3
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<ConstraintView<'_, FS>> {
+        let bounds = self.bounds.get(index * 3..index * 3 + 4)?;
+
+        Some(ConstraintView {
+            coefficients: &self.coefficients,
+            wires: &self.wires,
+            bounds,
+        })
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = ConstraintView<'_, FS>> + '_ {
+        (0..self.len()).map(move |i| ConstraintView {
+            coefficients: &self.coefficients,
+            wires: &self.wires,
+            bounds: &self.bounds[i * 3..i * 3 + 4],
+        })
+    }
+
+    /// Appends a constraint given as its three linear combinations.
+    pub fn push<'t, I>(&mut self, a: I, b: I, c: I)
+    where
+        I: IntoIterator<Item = TermRef<'t, FS>>,
+    {
+        for combination in [a, b, c] {
+            for (factor, wire) in combination {
+                self.coefficients.extend_from_slice(factor);
+                self.wires.push(wire);
+            }
+            self.bounds.push(self.wires.len());
+        }
+    }
+
+    /// Reads a constraints section body of `size` bytes.
+    ///
+    /// Error offsets are counted from the start of the body.
+    pub fn read<R: Read>(r: R, size: u64) -> Result<Self> {
+        let mut r = CountingReader::new(r);
+        let mut constraints = FlatConstraints::default();
+        constraints.read_into(&mut r, size)?;
+
+        Ok(constraints)
+    }
+
+    /// Reads every constraints section of a file, seeking past the other sections.
+    pub fn read_file<R: Read + Seek>(r: R) -> Result<Self> {
+        let mut file = BinFileReader::new(r, &FORMAT)?;
+
+        let mut constraints = None;
+        for entry in file.section_table()? {
+            if SectionType::from_id(entry.id) == Some(SectionType::Constraint) {
+                let mut r = CountingReader::at(file.section_at(&entry)?, entry.offset);
+                constraints
+                    .get_or_insert_with(FlatConstraints::default)
+                    .read_into(&mut r, entry.size)?;
+            }
+        }
+
+        constraints.ok_or(R1csError::MissingSection(SectionType::Constraint))
+    }
+
+    fn read_into<R: Read>(&mut self, r: &mut CountingReader<R>, size: u64) -> Result<()> {
+        let end = r.position() + size;
+        let mut read = || -> Result<()> {
+            while r.position() < end {
+                for _ in 0..3 {
+                    let n = r.read_u32::<LittleEndian>()?;
+                    // Validate the count before reading any term.
+                    if r.position() + u64::from(n) * (4 + FS as u64) > end {
+                        return Err(R1csError::UnexpectedEof {
+                            section: Some(SectionType::Constraint),
+                            offset: end,
+                        });
+                    }
+
+                    let n = n as usize;
+                    self.wires.reserve(n.min(MAX_PREALLOCATION as usize));
+                    for _ in 0..n {
+                        self.wires.push(r.read_u32::<LittleEndian>()?);
+                        let start = self.coefficients.len();
+                        self.coefficients.resize(start + FS, 0);
+                        r.read_exact(&mut self.coefficients[start..])?;
+                    }
+                    self.bounds.push(self.wires.len());
+                }
+            }
+
+            Ok(())
+        };
+
+        read().map_err(|e| e.eof_in(Some(SectionType::Constraint), r.position()))
+    }
+
+    /// Writes the constraints section body, byte for byte as for the equivalent [`Constraints`].
+    pub fn write<W: Write>(&self, mut w: W) -> Result<()> {
+        for constraint in self.iter() {
+            for matrix in Matrix::ALL {
+                let terms = constraint.combination(matrix);
+                w.write_u32::<LittleEndian>(terms.len() as u32)?;
+                for (factor, wire) in terms {
+                    w.write_u32::<LittleEndian>(wire)?;
+                    w.write_all(factor)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, const FS: usize> ConstraintView<'a, FS> {
+    /// The terms of the linear combination in `matrix`.
+    pub fn combination(
+        &self,
+        matrix: Matrix,
+    ) -> impl ExactSizeIterator<Item = TermRef<'a, FS>> + 'a {
+        let i = matrix as usize;
+        let (start, end) = (self.bounds[i], self.bounds[i + 1]);
+        let coefficients = self.coefficients[start * FS..end * FS].chunks_exact(FS);
+
+        coefficients
+            .map(|c| <&[u8; FS]>::try_from(c).unwrap())
+            .zip(self.wires[start..end].iter().copied())
+    }
+
+    pub fn to_owned(&self) -> Constraint<FS> {
+        let copy = |matrix| {
+            self.combination(matrix)
+                .map(|(factor, wire)| (FieldElement::from(*factor), wire))
+                .collect()
+        };
+
+        Constraint(copy(Matrix::A), copy(Matrix::B), copy(Matrix::C))
+    }
+}
+
+impl<const FS: usize> From<&Constraints<FS>> for FlatConstraints<FS> {
+    fn from(constraints: &Constraints<FS>) -> Self {
+        let mut flat = FlatConstraints::default();
+        for c in &constraints.0 {
+            flat.push(terms(&c.0), terms(&c.1), terms(&c.2));
+        }
+
+        flat
+    }
+}
+
+fn terms<const FS: usize>(
+    combination: &[(FieldElement<FS>, u32)],
+) -> impl Iterator<Item = TermRef<'_, FS>> {
+    combination.iter().map(|(factor, wire)| (&**factor, *wire))
+}
+
+impl<const FS: usize> From<&FlatConstraints<FS>> for Constraints<FS> {
+    fn from(flat: &FlatConstraints<FS>) -> Self {
+        Constraints(flat.iter().map(|c| c.to_owned()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LinearCombination, R1csFile};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_flat_matches_nested() {
+        for path in ["tests/test_circuit.r1cs", "tests/split_constraints.r1cs"] {
+            let data = std::fs::read(path).unwrap();
+            let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+            let flat = FlatConstraints::<32>::read_file(Cursor::new(&data)).unwrap();
+            assert_eq!(flat.len(), file.constraints.0.len());
+            assert_eq!(Constraints::from(&flat), file.constraints);
+            assert_eq!(FlatConstraints::from(&file.constraints), flat);
+
+            let mut nested = Vec::new();
+            file.constraints.write(&mut nested).unwrap();
+            let mut written = Vec::new();
+            flat.write(&mut written).unwrap();
+            assert_eq!(written, nested);
+
+            let body = FlatConstraints::<32>::read(written.as_slice(), written.len() as u64);
+            assert_eq!(body.unwrap(), flat);
+        }
+    }
+
+    #[test]
+    fn test_flat_empty_combinations() {
+        let constraints = Constraints(vec![
+            Constraint::default(),
+            Constraint(
+                LinearCombination::new(),
+                LinearCombination::term(FieldElement::<8>::from_u64(5), 2),
+                LinearCombination::new(),
+            ),
+        ]);
+        let flat = FlatConstraints::from(&constraints);
+        assert_eq!(flat.len(), 2);
+        assert_eq!(flat.get(0).unwrap().combination(Matrix::A).len(), 0);
+        assert_eq!(flat.get(1).unwrap().combination(Matrix::B).len(), 1);
+        assert!(flat.get(2).is_none());
+        assert_eq!(Constraints::from(&flat), constraints);
+
+        let mut written = Vec::new();
+        flat.write(&mut written).unwrap();
+        assert!(
+            FlatConstraints::<8>::read(&written[..written.len() - 1], written.len() as u64)
+                .is_err()
+        );
+    }
+}
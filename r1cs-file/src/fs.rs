@@ -0,0 +1,103 @@
+//! Reading and writing files by path.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::{R1csError, R1csFile, Result};
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Reads the file at `path` through a buffered reader.
+    ///
+    /// Errors are wrapped in [`R1csError::File`] with the path.
+    pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let read = || -> Result<Self> {
+            let file = File::open(path)?;
+            R1csFile::read(BufReader::new(file))
+        };
+
+        read().map_err(|e| R1csError::file(path, e))
+    }
+
+    /// Writes the file to `path` through a buffered writer.
+    ///
+    /// The file is written to a temporary file in the same directory first, then renamed over
+    /// `path`, so `path` never holds a partially written file. Errors are wrapped in
+    /// [`R1csError::File`] with the path.
+    pub fn write_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        write_atomic(path, |w| self.write(w)).map_err(|e| R1csError::file(path, e))
+    }
+}
+
+impl R1csError {
+    pub(crate) fn file(path: &Path, error: R1csError) -> Self {
+        R1csError::File {
+            path: path.to_owned(),
+            error: Box::new(error),
+        }
+    }
+}
+
+/// Writes `path` by way of a temporary file renamed once complete.
+pub(crate) fn write_atomic(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<()>,
+) -> Result<()> {
+    let tmp = temp_path(path);
+    let result = (|| -> Result<()> {
+        let mut w = BufWriter::new(File::create(&tmp)?);
+        write(&mut w)?;
+        w.flush()?;
+        w.get_ref().sync_all()?;
+        std::fs::rename(&tmp, path)?;
+
+        Ok(())
+    })();
+
+    if result.is_err() {
+        // The temporary file may not exist, and the original error matters more.
+        let _ = std::fs::remove_file(&tmp);
+    }
+
+    result
+}
+
+/// A hidden file next to `path`, unique to this process.
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_round_trip() {
+        let dir = std::env::temp_dir().join(format!("r1cs-file-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("simple_circuit.r1cs");
+
+        let file = R1csFile::<32>::read_file("tests/simple_circuit.r1cs").unwrap();
+        file.write_file(&path).unwrap();
+        assert_eq!(
+            std::fs::read(&path).unwrap(),
+            std::fs::read("tests/simple_circuit.r1cs").unwrap()
+        );
+        assert_eq!(R1csFile::<32>::read_file(&path).unwrap(), file);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        let missing = dir.join("missing.r1cs");
+        let e = R1csFile::<32>::read_file(&missing).unwrap_err();
+        assert!(matches!(&e, R1csError::File { path, .. } if *path == missing));
+        assert!(e.to_string().contains("missing.r1cs"));
+
+        // A failed write leaves no temporary file behind.
+        assert!(file.write_file(dir.join("no-such-dir/out.r1cs")).is_err());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
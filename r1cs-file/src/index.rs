@@ -0,0 +1,390 @@
+//! Random access to constraints through an index of their offsets.
+
+use std::convert::TryFrom;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use iden3_binfile::BinFileReader;
+
+use crate::{
+    Constraint, CountingReader, R1csError, R1csFile, ReadOptions, Result, SectionType, FORMAT,
+    MAX_PREALLOCATION,
+};
+
+const INDEX_MAGIC: &[u8; 4] = b"r1ci";
+const INDEX_VERSION: u32 = 1;
+
+/// Offsets of every `stride`-th constraint of a file, for [`R1csFile::load_constraints`].
+///
+/// Loading a range of constraints seeks to the closest preceding indexed constraint and skips at
+/// most `stride - 1` constraints by their term counts, so a smaller stride trades index size for
+/// less reading.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintIndex {
+    field_size: u32,
+    /// Offset of the header section body.
+    header_offset: u64,
+    stride: u32,
+    n_constraints: u64,
+    /// Offsets and sizes of the constraints section bodies, in file order.
+    sections: Vec<(u64, u64)>,
+    /// Offsets of constraints `0`, `stride`, `2 * stride`...
+    offsets: Vec<u64>,
+}
+
+impl ConstraintIndex {
+    pub const DEFAULT_STRIDE: u32 = 256;
+
+    /// Indexes a file with [`ConstraintIndex::DEFAULT_STRIDE`].
+    pub fn build<R: Read + Seek>(r: R) -> Result<Self> {
+        Self::build_with_stride(r, Self::DEFAULT_STRIDE)
+    }
+
+    /// Indexes every `stride`-th constraint of a file.
+    ///
+    /// Only the term counts are read, the terms themselves are skipped by seeking.
+    pub fn build_with_stride<R: Read + Seek>(r: R, stride: u32) -> Result<Self> {
+        let stride = stride.max(1);
+        let mut r = BinFileReader::new(r, &FORMAT)?;
+        let table = r.section_table()?;
+
+        let mut header_offset = None;
+        let mut sections = Vec::new();
+        for entry in &table {
+            match SectionType::from_id(entry.id) {
+                Some(SectionType::Header) => {
+                    crate::ensure_unique(&header_offset, SectionType::Header, entry.offset)?;
+                    header_offset = Some(entry.offset);
+                }
+                Some(SectionType::Constraint) => sections.push((entry.offset, entry.size)),
+                _ => {}
+            }
+        }
+        let header_offset = header_offset.ok_or(R1csError::MissingSection(SectionType::Header))?;
+        if sections.is_empty() {
+            return Err(R1csError::MissingSection(SectionType::Constraint));
+        }
+
+        r.seek(SeekFrom::Start(header_offset))?;
+        let field_size = r
+            .read_u32::<LittleEndian>()
+            .map_err(|e| R1csError::from(e).eof_in(Some(SectionType::Header), r.position()))?;
+
+        let mut n_constraints = 0;
+        let mut offsets = Vec::new();
+        for (offset, size) in &sections {
+            let end = offset + size;
+            let mut position = *offset;
+            while position < end {
+                if n_constraints % u64::from(stride) == 0 {
+                    offsets.push(position);
+                }
+
+                for _ in 0..3 {
+                    r.seek(SeekFrom::Start(position))?;
+                    let n = r.read_u32::<LittleEndian>().map_err(|e| {
+                        R1csError::from(e).eof_in(Some(SectionType::Constraint), r.position())
+                    })?;
+                    position += 4 + u64::from(n) * (4 + u64::from(field_size));
+                }
+                if position > end {
+                    return Err(R1csError::UnexpectedEof {
+                        section: Some(SectionType::Constraint),
+                        offset: end,
+                    });
+                }
+                n_constraints += 1;
+            }
+        }
+
+        Ok(ConstraintIndex {
+            field_size,
+            header_offset,
+            stride,
+            n_constraints,
+            sections,
+            offsets,
+        })
+    }
+
+    /// Number of constraints in the indexed file.
+    pub fn len(&self) -> u64 {
+        self.n_constraints
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n_constraints == 0
+    }
+
+    pub fn stride(&self) -> u32 {
+        self.stride
+    }
+
+    pub fn serialize<W: Write>(&self, mut w: W) -> Result<()> {
+        w.write_all(INDEX_MAGIC)?;
+        w.write_u32::<LittleEndian>(INDEX_VERSION)?;
+        w.write_u32::<LittleEndian>(self.field_size)?;
+        w.write_u64::<LittleEndian>(self.header_offset)?;
+        w.write_u32::<LittleEndian>(self.stride)?;
+        w.write_u64::<LittleEndian>(self.n_constraints)?;
+
+        w.write_u32::<LittleEndian>(self.sections.len() as u32)?;
+        for (offset, size) in &self.sections {
+            w.write_u64::<LittleEndian>(*offset)?;
+            w.write_u64::<LittleEndian>(*size)?;
+        }
+
+        w.write_u64::<LittleEndian>(self.offsets.len() as u64)?;
+        for offset in &self.offsets {
+            w.write_u64::<LittleEndian>(*offset)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn deserialize<R: Read>(r: R) -> Result<Self> {
+        let mut r = CountingReader::new(r);
+        let read = |r: &mut CountingReader<R>| -> Result<Self> {
+            let mut magic = [0u8; 4];
+            r.read_exact(&mut magic)?;
+            if magic != *INDEX_MAGIC {
+                return Err(R1csError::InvalidMagic { found: magic });
+            }
+            let version = r.read_u32::<LittleEndian>()?;
+            if version != INDEX_VERSION {
+                return Err(R1csError::UnsupportedVersion(version));
+            }
+
+            let field_size = r.read_u32::<LittleEndian>()?;
+            let header_offset = r.read_u64::<LittleEndian>()?;
+            let stride = r.read_u32::<LittleEndian>()?.max(1);
+            let n_constraints = r.read_u64::<LittleEndian>()?;
+
+            let n_sections = u64::from(r.read_u32::<LittleEndian>()?);
+            let mut sections = Vec::with_capacity(n_sections.min(MAX_PREALLOCATION) as usize);
+            for _ in 0..n_sections {
+                let offset = r.read_u64::<LittleEndian>()?;
+                let size = r.read_u64::<LittleEndian>()?;
+                sections.push((offset, size));
+            }
+
+            let n_offsets = r.read_u64::<LittleEndian>()?;
+            let mut offsets = Vec::with_capacity(n_offsets.min(MAX_PREALLOCATION) as usize);
+            for _ in 0..n_offsets {
+                offsets.push(r.read_u64::<LittleEndian>()?);
+            }
+
+            Ok(ConstraintIndex {
+                field_size,
+                header_offset,
+                stride,
+                n_constraints,
+                sections,
+                offsets,
+            })
+        };
+
+        read(&mut r).map_err(|e| e.eof_in(None, r.position()))
+    }
+}
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Reads the constraints in `range` using an index built for the same file.
+    ///
+    /// Fails if `range` is past the number of constraints in the index.
+    pub fn load_constraints<R: Read + Seek>(
+        r: R,
+        index: &ConstraintIndex,
+        range: Range<u64>,
+    ) -> Result<Vec<Constraint<FS>>> {
+        if index.field_size != FS as u32 {
+            return Err(R1csError::FieldSizeMismatch {
+                expected: FS as u32,
+                found: index.field_size,
+                offset: index.header_offset,
+            });
+        }
+        if range.start > range.end || range.end > index.n_constraints {
+            let message = format!(
+                "constraint range {:?} is out of bounds for {} constraints",
+                range, index.n_constraints
+            );
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        }
+
+        let mut constraints =
+            Vec::with_capacity((range.end - range.start).min(MAX_PREALLOCATION) as usize);
+        if range.is_empty() {
+            return Ok(constraints);
+        }
+
+        let checkpoint = range.start / u64::from(index.stride);
+        let position = index.offsets[checkpoint as usize];
+        let mut section = index
+            .sections
+            .iter()
+            .position(|(offset, size)| (*offset..offset + size).contains(&position))
+            .ok_or_else(|| stale_index(position))?;
+
+        let mut r = CountingReader::new(r);
+        r.seek(SeekFrom::Start(position))?;
+        let opts = ReadOptions::default();
+        let mut load = |r: &mut CountingReader<R>| -> Result<()> {
+            let mut n = checkpoint * u64::from(index.stride);
+            while n < range.end {
+                let (offset, size) = index.sections[section];
+                if r.position() == offset + size {
+                    section += 1;
+                    let (next, _) = *index
+                        .sections
+                        .get(section)
+                        .ok_or_else(|| stale_index(r.position()))?;
+                    r.seek(SeekFrom::Start(next))?;
+                    continue;
+                }
+
+                if n < range.start {
+                    for _ in 0..3 {
+                        let terms = r.read_u32::<LittleEndian>()?;
+                        let skip = i64::try_from(u64::from(terms) * (4 + FS as u64))
+                            .map_err(|_| stale_index(r.position()))?;
+                        r.seek(SeekFrom::Current(skip))?;
+                    }
+                } else {
+                    let remaining = (offset + size).saturating_sub(r.position());
+                    match Constraint::read(&mut r.take(remaining), &opts)? {
+                        Some(c) => constraints.push(c),
+                        None => return Err(stale_index(r.position())),
+                    }
+                }
+                n += 1;
+            }
+
+            Ok(())
+        };
+
+        load(&mut r).map_err(|e| e.eof_in(Some(SectionType::Constraint), r.position()))?;
+
+        Ok(constraints)
+    }
+}
+
+/// The file doesn't match the index it is read with.
+fn stale_index(offset: u64) -> R1csError {
+    R1csError::UnexpectedEof {
+        section: Some(SectionType::Constraint),
+        offset,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Constraints, FieldElement, Header, WireMap};
+    use std::io::Cursor;
+
+    /// Reader counting the bytes read through it.
+    struct Metered<R> {
+        inner: R,
+        read: u64,
+    }
+
+    impl<R: Read> Read for Metered<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            self.read += n as u64;
+            Ok(n)
+        }
+    }
+
+    impl<R: Seek> Seek for Metered<R> {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn test_load_windows() {
+        for path in ["tests/test_circuit.r1cs", "tests/split_constraints.r1cs"] {
+            let data = std::fs::read(path).unwrap();
+            let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+            let n = file.constraints.0.len() as u64;
+
+            for stride in [1, 3, 1000] {
+                let index = ConstraintIndex::build_with_stride(Cursor::new(&data), stride).unwrap();
+                assert_eq!(index.len(), n);
+
+                let mut cached = Vec::new();
+                index.serialize(&mut cached).unwrap();
+                let index = ConstraintIndex::deserialize(cached.as_slice()).unwrap();
+
+                for start in 0..n {
+                    for end in start..=n.min(start + 4) {
+                        let loaded = R1csFile::<32>::load_constraints(
+                            Cursor::new(&data),
+                            &index,
+                            start..end,
+                        )
+                        .unwrap();
+                        assert_eq!(loaded, file.constraints.0[start as usize..end as usize]);
+                    }
+                }
+            }
+
+            let index = ConstraintIndex::build(Cursor::new(&data)).unwrap();
+            assert!(
+                R1csFile::<32>::load_constraints(Cursor::new(&data), &index, 0..n + 1).is_err()
+            );
+            assert!(matches!(
+                R1csFile::<8>::load_constraints(Cursor::new(&data), &index, 0..1),
+                Err(R1csError::FieldSizeMismatch { found: 32, .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_load_window_reads_little() {
+        let term = |i: u32| (FieldElement::from_u64(u64::from(i)), i);
+        let file = R1csFile::<32> {
+            header: Header {
+                prime: FieldElement::bn254_scalar_prime(),
+                n_wires: 20_000,
+                n_pub_out: 0,
+                n_pub_in: 0,
+                n_prvt_in: 0,
+                n_labels: 0,
+                n_constraints: 10_000,
+            },
+            constraints: Constraints(
+                (0..10_000)
+                    .map(|i| {
+                        Constraint(
+                            vec![term(i), term(i + 1)].into(),
+                            vec![term(i)].into(),
+                            Default::default(),
+                        )
+                    })
+                    .collect(),
+            ),
+            map: WireMap(vec![]),
+            custom_gates_list: None,
+            custom_gates_application: None,
+            metadata: None,
+            custom_sections: vec![],
+            version: 1,
+        };
+        let data = file.serialize();
+        let index = ConstraintIndex::build(Cursor::new(&data)).unwrap();
+
+        let mut r = Metered {
+            inner: Cursor::new(&data),
+            read: 0,
+        };
+        let loaded = R1csFile::<32>::load_constraints(&mut r, &index, 5000..6000).unwrap();
+        assert_eq!(loaded, file.constraints.0[5000..6000]);
+
+        let window: usize = loaded.iter().map(Constraint::size).sum();
+        assert!(r.read < window as u64 + 4096);
+    }
+}
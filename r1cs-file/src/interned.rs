@@ -0,0 +1,333 @@
+//! Constraints with their coefficients deduplicated.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::{Read, Seek, Write};
+use std::mem::size_of;
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use iden3_binfile::BinFileReader;
+
+use crate::{
+    Constraint, Constraints, CountingReader, FieldElement, Matrix, MatrixTerm, R1csError, Result,
+    SectionType, FORMAT, MAX_PREALLOCATION,
+};
+
+/// Constraints storing every distinct coefficient once, each term holding an index into the pool
+/// of coefficients and a wire.
+///
+/// Holds the same data as [`Constraints`], which it converts to and from losslessly. Circuits
+/// compiled by circom use a handful of coefficients (1, -1 and a few small constants) for most of
+/// their terms, so a term takes 8 bytes instead of `FS + 4`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InternedConstraints<const FS: usize> {
+    /// Distinct coefficients, in order of first use.
+    pool: Vec<FieldElement<FS>>,
+    /// Index in `pool` of every coefficient.
+    indices: HashMap<FieldElement<FS>, u32>,
+    /// Pool index and wire of every term.
+    terms: Vec<(u32, u32)>,
+    /// Index of the first term of every linear combination, followed by the number of terms.
+    bounds: Vec<usize>,
+}
+
+/// A constraint of [`InternedConstraints`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InternedConstraintView<'a, const FS: usize> {
+    pool: &'a [FieldElement<FS>],
+    terms: &'a [(u32, u32)],
+    /// The 4 bounds of the A, B and C combinations.
+    bounds: &'a [usize],
+}
+
+impl<const FS: usize> Default for InternedConstraints<FS> {
+    fn default() -> Self {
+        InternedConstraints {
+            pool: Vec::new(),
+            indices: HashMap::new(),
+            terms: Vec::new(),
+            bounds: vec![0],
+        }
+    }
+}
+
+impl<const FS: usize> InternedConstraints<FS> {
+    pub fn len(&self) -> usize {
+        (self.bounds.len() - 1) / 3
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The distinct coefficients, in order of first use.
+    pub fn pool(&self) -> &[FieldElement<FS>] {
+        &self.pool
+    }
+
+    pub fn get(&self, index: usize) -> Option<InternedConstraintView<'_, FS>> {
+        let bounds = self.bounds.get(index * 3..index * 3 + 4)?;
+
+        Some(self.view(bounds))
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = InternedConstraintView<'_, FS>> + '_ {
+        (0..self.len()).map(move |i| self.view(&self.bounds[i * 3..i * 3 + 4]))
+    }
+
+    fn view<'a>(&'a self, bounds: &'a [usize]) -> InternedConstraintView<'a, FS> {
+        InternedConstraintView {
+            pool: &self.pool,
+            terms: &self.terms,
+            bounds,
+        }
+    }
+
+    /// Iterates over the terms of every constraint, in the same order and with the same items as
+    /// [`crate::R1csFile::iter_terms`].
+    pub fn iter_terms(&self) -> impl Iterator<Item = MatrixTerm<'_, FS>> + '_ {
+        self.iter_matrices(&Matrix::ALL)
+    }
+
+    /// Iterates over the terms of `matrix`, row by row, as [`crate::R1csFile::iter_matrix`].
+    pub fn iter_matrix(&self, matrix: Matrix) -> impl Iterator<Item = MatrixTerm<'_, FS>> + '_ {
+        let i = matrix as usize;
+        self.iter_matrices(&Matrix::ALL[i..i + 1])
+    }
+
+    fn iter_matrices<'a>(
+        &'a self,
+        matrices: &'a [Matrix],
+    ) -> impl Iterator<Item = MatrixTerm<'a, FS>> + 'a {
+        self.iter().enumerate().flat_map(move |(constraint, c)| {
+            matrices.iter().flat_map(move |&matrix| {
+                c.combination(matrix).map(move |(coeff, wire)| MatrixTerm {
+                    constraint: constraint as u32,
+                    matrix,
+                    wire,
+                    coeff,
+                })
+            })
+        })
+    }
+
+    /// Appends a constraint given as its three linear combinations.
+    pub fn push<'t, I>(&mut self, a: I, b: I, c: I)
+    where
+        I: IntoIterator<Item = (&'t FieldElement<FS>, u32)>,
+    {
+        for combination in [a, b, c] {
+            for (coeff, wire) in combination {
+                let index = self.intern(coeff);
+                self.terms.push((index, wire));
+            }
+            self.bounds.push(self.terms.len());
+        }
+    }
+
+    /// The index of `coeff` in the pool, adding it if it isn't there yet.
+    fn intern(&mut self, coeff: &FieldElement<FS>) -> u32 {
+        if let Some(index) = self.indices.get(coeff) {
+            return *index;
+        }
+
+        // There are fewer distinct coefficients than terms, which are counted by a `u32`.
+        let index = u32::try_from(self.pool.len()).expect("too many distinct coefficients");
+        self.pool.push(*coeff);
+        self.indices.insert(*coeff, index);
+        index
+    }
+
+    /// Bytes allocated on the heap, counting the capacity of the buffers and an estimate of the
+    /// coefficient index.
+    pub fn heap_size(&self) -> usize {
+        let index_entry = size_of::<FieldElement<FS>>() + size_of::<u32>() + 1;
+
+        self.pool.capacity() * size_of::<FieldElement<FS>>()
+            + self.indices.capacity() * index_entry
+            + self.terms.capacity() * size_of::<(u32, u32)>()
+            + self.bounds.capacity() * size_of::<usize>()
+    }
+
+    /// Reads a constraints section body of `size` bytes.
+    ///
+    /// Error offsets are counted from the start of the body.
+    pub fn read<R: Read>(r: R, size: u64) -> Result<Self> {
+        let mut r = CountingReader::new(r);
+        let mut constraints = InternedConstraints::default();
+        constraints.read_into(&mut r, size)?;
+
+        Ok(constraints)
+    }
+
+    /// Reads every constraints section of a file, seeking past the other sections.
+    pub fn read_file<R: Read + Seek>(r: R) -> Result<Self> {
+        let mut file = BinFileReader::new(r, &FORMAT)?;
+
+        let mut constraints = None;
+        for entry in file.section_table()? {
+            if SectionType::from_id(entry.id) == Some(SectionType::Constraint) {
+                let mut r = CountingReader::at(file.section_at(&entry)?, entry.offset);
+                constraints
+                    .get_or_insert_with(InternedConstraints::default)
+                    .read_into(&mut r, entry.size)?;
+            }
+        }
+
+        constraints.ok_or(R1csError::MissingSection(SectionType::Constraint))
+    }
+
+    fn read_into<R: Read>(&mut self, r: &mut CountingReader<R>, size: u64) -> Result<()> {
+        let end = r.position() + size;
+        let mut read = || -> Result<()> {
+            while r.position() < end {
+                for _ in 0..3 {
+                    let n = r.read_u32::<LittleEndian>()?;
+                    // Validate the count before reading any term.
+                    if r.position() + u64::from(n) * (4 + FS as u64) > end {
+                        return Err(R1csError::UnexpectedEof {
+                            section: Some(SectionType::Constraint),
+                            offset: end,
+                        });
+                    }
+
+                    self.terms
+                        .reserve((n as usize).min(MAX_PREALLOCATION as usize));
+                    for _ in 0..n {
+                        let wire = r.read_u32::<LittleEndian>()?;
+                        let index = self.intern(&FieldElement::read(&mut *r)?);
+                        self.terms.push((index, wire));
+                    }
+                    self.bounds.push(self.terms.len());
+                }
+            }
+
+            Ok(())
+        };
+
+        read().map_err(|e| e.eof_in(Some(SectionType::Constraint), r.position()))
+    }
+
+    /// Writes the constraints section body, byte for byte as for the equivalent [`Constraints`].
+    pub fn write<W: Write>(&self, mut w: W) -> Result<()> {
+        for constraint in self.iter() {
+            for matrix in Matrix::ALL {
+                let terms = constraint.combination(matrix);
+                w.write_u32::<LittleEndian>(terms.len() as u32)?;
+                for (coeff, wire) in terms {
+                    w.write_u32::<LittleEndian>(wire)?;
+                    coeff.write(&mut w)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, const FS: usize> InternedConstraintView<'a, FS> {
+    /// The terms of the linear combination in `matrix`.
+    pub fn combination(
+        &self,
+        matrix: Matrix,
+    ) -> impl ExactSizeIterator<Item = (&'a FieldElement<FS>, u32)> + 'a {
+        let i = matrix as usize;
+        let pool = self.pool;
+
+        self.terms[self.bounds[i]..self.bounds[i + 1]]
+            .iter()
+            .map(move |(index, wire)| (&pool[*index as usize], *wire))
+    }
+
+    pub fn to_owned(&self) -> Constraint<FS> {
+        let copy = |matrix| {
+            self.combination(matrix)
+                .map(|(coeff, wire)| (*coeff, wire))
+                .collect()
+        };
+
+        Constraint(copy(Matrix::A), copy(Matrix::B), copy(Matrix::C))
+    }
+}
+
+impl<const FS: usize> From<&Constraints<FS>> for InternedConstraints<FS> {
+    fn from(constraints: &Constraints<FS>) -> Self {
+        let mut interned = InternedConstraints::default();
+        for c in &constraints.0 {
+            interned.push(terms(&c.0), terms(&c.1), terms(&c.2));
+        }
+
+        interned
+    }
+}
+
+fn terms<const FS: usize>(
+    combination: &[(FieldElement<FS>, u32)],
+) -> impl Iterator<Item = (&FieldElement<FS>, u32)> {
+    combination.iter().map(|(coeff, wire)| (coeff, *wire))
+}
+
+impl<const FS: usize> From<&InternedConstraints<FS>> for Constraints<FS> {
+    fn from(interned: &InternedConstraints<FS>) -> Self {
+        Constraints(interned.iter().map(|c| c.to_owned()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::R1csFile;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_interned_matches_nested() {
+        for path in ["tests/test_circuit.r1cs", "tests/split_constraints.r1cs"] {
+            let data = std::fs::read(path).unwrap();
+            let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+            let interned = InternedConstraints::<32>::read_file(Cursor::new(&data)).unwrap();
+            assert_eq!(interned.len(), file.constraints.len());
+            assert_eq!(Constraints::from(&interned), file.constraints);
+            assert_eq!(InternedConstraints::from(&file.constraints), interned);
+            assert!(interned.iter_terms().eq(file.iter_terms()));
+            for matrix in Matrix::ALL {
+                assert!(interned.iter_matrix(matrix).eq(file.iter_matrix(matrix)));
+            }
+
+            let mut nested = Vec::new();
+            file.constraints.write(&mut nested).unwrap();
+            let mut written = Vec::new();
+            interned.write(&mut written).unwrap();
+            assert_eq!(written, nested);
+
+            let body = InternedConstraints::<32>::read(written.as_slice(), written.len() as u64);
+            assert_eq!(body.unwrap(), interned);
+            assert!(InternedConstraints::<32>::read(
+                &written[..written.len() - 1],
+                written.len() as u64
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn test_interned_pool() {
+        let one = FieldElement::<32>::from_u64(1);
+        let five = FieldElement::from_u64(5);
+        let constraints = Constraints(
+            (0..100)
+                .map(|i| Constraint::new(vec![(one, i), (five, i + 1)], vec![(one, 0)], vec![]))
+                .collect(),
+        );
+
+        let interned = InternedConstraints::from(&constraints);
+        assert_eq!(interned.pool(), [one, five]);
+        assert_eq!(interned.get(3).unwrap().to_owned(), constraints[3]);
+        assert!(interned.get(100).is_none());
+        assert!(InternedConstraints::<32>::default().is_empty());
+
+        let nested = constraints.len() * size_of::<Constraint<32>>()
+            + 300 * size_of::<(FieldElement<32>, u32)>();
+        assert!(interned.heap_size() * 2 < nested);
+    }
+}
@@ -0,0 +1,500 @@
+//! The JSON format of `snarkjs r1cs export json`, behind the `json` feature.
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    Constraint, Constraints, CustomGate, CustomGateApplication, CustomGatesApplication,
+    CustomGatesList, FieldElement, Header, LinearCombination, Matrix, ParseFieldElementError,
+    ParseWarning, R1csError, R1csFile, Result, ValidationError, WireMap, VERSION,
+    VERSION_CUSTOM_GATES,
+};
+
+/// The exported file, fields in the order snarkjs writes them.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SnarkjsR1cs {
+    n8: usize,
+    prime: String,
+    n_vars: u32,
+    n_outputs: u32,
+    n_pub_inputs: u32,
+    n_prv_inputs: u32,
+    n_labels: u64,
+    n_constraints: u32,
+    use_custom_gates: bool,
+    constraints: Vec<[BTreeMap<u32, String>; 3]>,
+    map: Vec<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    custom_gates: Option<Vec<SnarkjsCustomGate>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    custom_gates_uses: Option<Vec<SnarkjsCustomGateUse>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SnarkjsCustomGate {
+    template_name: String,
+    parameters: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct SnarkjsCustomGateUse {
+    id: u32,
+    signals: Vec<u64>,
+}
+
+impl<const FS: usize> R1csFile<FS> {
+    /// The circuit in the JSON format of `snarkjs r1cs export json`.
+    ///
+    /// Field elements are decimal strings, and each constraint is an array of three objects
+    /// mapping wire indices to coefficients. `serde_json` sorts object keys unless its
+    /// `preserve_order` feature is enabled; [`R1csFile::to_json_writer`] always writes them in
+    /// snarkjs order.
+    pub fn to_json_value(&self) -> serde_json::Value {
+        serde_json::to_value(self.to_snarkjs()).expect("the export has string keys only")
+    }
+
+    /// Writes the circuit in the JSON format of `snarkjs r1cs export json`, see
+    /// [`R1csFile::to_json_value`].
+    pub fn to_json_writer<W: Write>(&self, w: W) -> Result<()> {
+        serde_json::to_writer(w, &self.to_snarkjs()).map_err(io::Error::from)?;
+
+        Ok(())
+    }
+
+    fn to_snarkjs(&self) -> SnarkjsR1cs {
+        let header = &self.header;
+        let use_custom_gates =
+            self.custom_gates_list.is_some() && self.custom_gates_application.is_some();
+
+        let custom_gates = self.custom_gates_list.as_ref().map(|list| {
+            list.0
+                .iter()
+                .map(|gate| SnarkjsCustomGate {
+                    template_name: gate.name.clone(),
+                    parameters: gate.parameters.iter().map(|p| p.to_dec_string()).collect(),
+                })
+                .collect()
+        });
+        let custom_gates_uses = self.custom_gates_application.as_ref().map(|application| {
+            application
+                .0
+                .iter()
+                .map(|gate| SnarkjsCustomGateUse {
+                    id: gate.gate,
+                    signals: gate.signals.clone(),
+                })
+                .collect()
+        });
+
+        SnarkjsR1cs {
+            n8: FS,
+            prime: header.prime.to_dec_string(),
+            n_vars: header.n_wires,
+            n_outputs: header.n_pub_out,
+            n_pub_inputs: header.n_pub_in,
+            n_prv_inputs: header.n_prvt_in,
+            n_labels: header.n_labels,
+            n_constraints: header.n_constraints,
+            use_custom_gates,
+            constraints: self
+                .constraints
+                .0
+                .iter()
+                .map(|c| {
+                    [
+                        to_snarkjs_lc(&c.0),
+                        to_snarkjs_lc(&c.1),
+                        to_snarkjs_lc(&c.2),
+                    ]
+                })
+                .collect(),
+            map: self.map.0.clone(),
+            custom_gates: custom_gates.filter(|_| use_custom_gates),
+            custom_gates_uses: custom_gates_uses.filter(|_| use_custom_gates),
+        }
+    }
+}
+
+/// A linear combination as a JavaScript object: keys in ascending order, and a repeated wire
+/// overwriting the earlier term.
+fn to_snarkjs_lc<const FS: usize>(lc: &LinearCombination<FS>) -> BTreeMap<u32, String> {
+    lc.iter()
+        .map(|(coeff, wire)| (*wire, coeff.to_dec_string()))
+        .collect()
+}
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Parses a circuit exported by `snarkjs r1cs export json`, see
+    /// [`R1csFile::from_json_reader_with_warnings`].
+    pub fn from_json_reader<R: Read>(r: R) -> Result<Self> {
+        Self::from_json_reader_with_warnings(r).map(|(file, _)| file)
+    }
+
+    /// Parses a circuit exported by `snarkjs r1cs export json`.
+    ///
+    /// `prime` and `constraints` are required. Other missing keys are reported as
+    /// [`ParseWarning::MissingJsonKey`] and default to what the rest of the input implies: the
+    /// wire count to the largest wire referenced plus one, the signal counts to zero, the
+    /// constraint count to the number of constraints, and the wire map to the identity.
+    ///
+    /// Coefficients not below the prime fail with [`ValidationError::NonCanonicalCoefficient`],
+    /// and the result is checked with [`R1csFile::validate`]. Malformed JSON fails with an
+    /// [`io::ErrorKind::InvalidData`] error.
+    pub fn from_json_reader_with_warnings<R: Read>(r: R) -> Result<(Self, Vec<ParseWarning>)> {
+        let value: Value = serde_json::from_reader(r).map_err(io::Error::from)?;
+        let obj = value
+            .as_object()
+            .ok_or_else(|| invalid_data("expected a JSON object"))?;
+        let mut warnings = Vec::new();
+        let mut optional = |key: &'static str| {
+            let value = obj.get(key);
+            if value.is_none() {
+                warnings.push(ParseWarning::MissingJsonKey { key });
+            }
+            value
+        };
+
+        if let Some(n8) = optional("n8") {
+            let n8 = to_u64(n8, "n8")?;
+            if n8 != FS as u64 {
+                return Err(R1csError::FieldSizeMismatch {
+                    expected: FS as u32,
+                    found: u32::try_from(n8).unwrap_or(u32::MAX),
+                    offset: 0,
+                });
+            }
+        }
+
+        let prime = required(obj, "prime")?;
+        let prime =
+            to_element::<FS>(prime).map_err(|e| invalid_data(format!("invalid prime: {}", e)))?;
+
+        let constraints = required(obj, "constraints")?
+            .as_array()
+            .ok_or_else(|| invalid_data("constraints: expected an array"))?
+            .iter()
+            .enumerate()
+            .map(|(i, c)| to_constraint(c, i, &prime))
+            .collect::<Result<Vec<_>>>()?;
+
+        let n_pub_out = optional("nOutputs")
+            .map(|v| to_u32(v, "nOutputs"))
+            .transpose()?;
+        let n_pub_in = optional("nPubInputs")
+            .map(|v| to_u32(v, "nPubInputs"))
+            .transpose()?;
+        let n_prvt_in = optional("nPrvInputs")
+            .map(|v| to_u32(v, "nPrvInputs"))
+            .transpose()?;
+        let n_constraints = optional("nConstraints")
+            .map(|v| to_u32(v, "nConstraints"))
+            .transpose()?;
+        let n_wires = optional("nVars").map(|v| to_u32(v, "nVars")).transpose()?;
+        let map = optional("map")
+            .map(|v| {
+                v.as_array()
+                    .ok_or_else(|| invalid_data("map: expected an array"))?
+                    .iter()
+                    .map(|label| to_u64(label, "map"))
+                    .collect::<Result<Vec<_>>>()
+            })
+            .transpose()?;
+        let n_labels = optional("nLabels")
+            .map(|v| to_u64(v, "nLabels"))
+            .transpose()?;
+
+        let n_wires = match (n_wires, &map) {
+            (Some(n_wires), _) => n_wires,
+            (None, Some(map)) => {
+                u32::try_from(map.len()).map_err(|_| invalid_data("map: too many entries"))?
+            }
+            (None, None) => constraints
+                .iter()
+                .flat_map(|c| Matrix::ALL.iter().map(move |&m| c.combination(m)))
+                .flat_map(|lc| lc.iter().map(|(_, wire)| wire + 1))
+                .max()
+                .unwrap_or(1),
+        };
+        let map = map.unwrap_or_else(|| (0..u64::from(n_wires)).collect());
+        let n_labels = n_labels.unwrap_or_else(|| map.iter().max().map_or(0, |l| l + 1));
+
+        let (custom_gates_list, custom_gates_application) =
+            match (obj.get("customGates"), obj.get("customGatesUses")) {
+                (Some(gates), Some(uses)) => (
+                    Some(to_custom_gates(gates)?),
+                    Some(to_custom_gates_application(uses)?),
+                ),
+                _ => (None, None),
+            };
+
+        let version = if custom_gates_list.is_some() {
+            VERSION_CUSTOM_GATES
+        } else {
+            VERSION
+        };
+        let file = R1csFile {
+            header: Header {
+                prime,
+                n_wires,
+                n_pub_out: n_pub_out.unwrap_or(0),
+                n_pub_in: n_pub_in.unwrap_or(0),
+                n_prvt_in: n_prvt_in.unwrap_or(0),
+                n_labels,
+                n_constraints: match n_constraints {
+                    Some(n) => n,
+                    None => u32::try_from(constraints.len())
+                        .map_err(|_| invalid_data("constraints: too many entries"))?,
+                },
+            },
+            constraints: Constraints(constraints),
+            map: WireMap(map),
+            custom_gates_list,
+            custom_gates_application,
+            metadata: None,
+            custom_sections: Vec::new(),
+            version,
+        };
+
+        if let Err(errors) = file.validate() {
+            return Err(R1csError::Invalid(errors[0].clone()));
+        }
+        file.check_header().map_err(R1csError::Invalid)?;
+
+        Ok((file, warnings))
+    }
+}
+
+fn invalid_data(msg: impl Into<String>) -> R1csError {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into()).into()
+}
+
+fn required<'a>(obj: &'a Map<String, Value>, key: &str) -> Result<&'a Value> {
+    obj.get(key)
+        .ok_or_else(|| invalid_data(format!("missing key {}", key)))
+}
+
+/// A non-negative integer, as a JSON number or a decimal string.
+fn to_u64(value: &Value, key: &str) -> Result<u64> {
+    let n = match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    };
+
+    n.ok_or_else(|| invalid_data(format!("{}: expected a non-negative integer", key)))
+}
+
+fn to_u32(value: &Value, key: &str) -> Result<u32> {
+    u32::try_from(to_u64(value, key)?)
+        .map_err(|_| invalid_data(format!("{}: value out of range", key)))
+}
+
+/// A field element, as a decimal string or a JSON number.
+fn to_element<const FS: usize>(
+    value: &Value,
+) -> std::result::Result<FieldElement<FS>, ParseFieldElementError> {
+    match value {
+        Value::String(s) => FieldElement::from_dec_str(s),
+        Value::Number(n) => FieldElement::from_dec_str(&n.to_string()),
+        _ => Err(ParseFieldElementError::Empty),
+    }
+}
+
+fn to_constraint<const FS: usize>(
+    value: &Value,
+    constraint: usize,
+    prime: &FieldElement<FS>,
+) -> Result<Constraint<FS>> {
+    let rows = value
+        .as_array()
+        .filter(|rows| rows.len() == 3)
+        .ok_or_else(|| {
+            invalid_data(format!("constraint {}: expected three objects", constraint))
+        })?;
+
+    let mut combinations = Matrix::ALL.iter().zip(rows).map(|(&matrix, row)| {
+        let row = row.as_object().ok_or_else(|| {
+            invalid_data(format!("constraint {}: expected an object", constraint))
+        })?;
+
+        row.iter()
+            .enumerate()
+            .map(|(term, (wire, coeff))| {
+                let wire = wire.parse::<u32>().map_err(|_| {
+                    invalid_data(format!(
+                        "constraint {}: invalid wire {:?}",
+                        constraint, wire
+                    ))
+                })?;
+                let non_canonical = || {
+                    R1csError::Invalid(ValidationError::NonCanonicalCoefficient {
+                        constraint,
+                        matrix,
+                        term,
+                    })
+                };
+                let coeff = match to_element::<FS>(coeff) {
+                    Ok(coeff) if coeff < *prime => coeff,
+                    Ok(_) | Err(ParseFieldElementError::WrongSize { .. }) => {
+                        return Err(non_canonical())
+                    }
+                    Err(e) => {
+                        return Err(invalid_data(format!(
+                            "constraint {}: invalid coefficient: {}",
+                            constraint, e
+                        )))
+                    }
+                };
+
+                Ok((coeff, wire))
+            })
+            .collect::<Result<LinearCombination<FS>>>()
+    });
+
+    let a = combinations.next().expect("three rows")?;
+    let b = combinations.next().expect("three rows")?;
+    let c = combinations.next().expect("three rows")?;
+    Ok(Constraint(a, b, c))
+}
+
+fn to_custom_gates<const FS: usize>(value: &Value) -> Result<CustomGatesList<FS>> {
+    let gates = value
+        .as_array()
+        .ok_or_else(|| invalid_data("customGates: expected an array"))?;
+
+    gates
+        .iter()
+        .map(|gate| {
+            let name = gate
+                .get("templateName")
+                .and_then(Value::as_str)
+                .ok_or_else(|| invalid_data("customGates: missing templateName"))?;
+            let parameters = gate
+                .get("parameters")
+                .and_then(Value::as_array)
+                .ok_or_else(|| invalid_data("customGates: missing parameters"))?
+                .iter()
+                .map(|p| to_element(p).map_err(|e| invalid_data(format!("customGates: {}", e))))
+                .collect::<Result<_>>()?;
+
+            Ok(CustomGate {
+                name: name.to_owned(),
+                parameters,
+            })
+        })
+        .collect::<Result<_>>()
+        .map(CustomGatesList)
+}
+
+fn to_custom_gates_application(value: &Value) -> Result<CustomGatesApplication> {
+    let uses = value
+        .as_array()
+        .ok_or_else(|| invalid_data("customGatesUses: expected an array"))?;
+
+    uses.iter()
+        .map(|usage| {
+            let gate = usage
+                .get("id")
+                .ok_or_else(|| invalid_data("customGatesUses: missing id"))?;
+            let signals = usage
+                .get("signals")
+                .and_then(Value::as_array)
+                .ok_or_else(|| invalid_data("customGatesUses: missing signals"))?
+                .iter()
+                .map(|s| to_u64(s, "customGatesUses"))
+                .collect::<Result<_>>()?;
+
+            Ok(CustomGateApplication {
+                gate: to_u32(gate, "customGatesUses")?,
+                signals,
+            })
+        })
+        .collect::<Result<_>>()
+        .map(CustomGatesApplication)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snarkjs_json() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        let golden = std::fs::read_to_string("tests/simple_circuit.json").unwrap();
+        let value = file.to_json_value();
+        assert_eq!(
+            value,
+            serde_json::from_str::<serde_json::Value>(&golden).unwrap()
+        );
+        assert_eq!(value["nVars"], 7);
+        assert_eq!(value["constraints"][0][0]["5"], "3");
+
+        let mut json = Vec::new();
+        file.to_json_writer(&mut json).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert!(json.starts_with(r#"{"n8":32,"prime":"21888242871839275222246405745257275088548364400416034343698204186575808495617","nVars":7,"#));
+        assert!(json.ends_with(r#""map":[0,3,10,11,12,15,324]}"#));
+    }
+
+    #[test]
+    fn test_snarkjs_json_import() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        let mut json = Vec::new();
+        file.to_json_writer(&mut json).unwrap();
+        let (imported, warnings) =
+            R1csFile::<32>::from_json_reader_with_warnings(json.as_slice()).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(imported, file);
+
+        let mut binary = Vec::new();
+        imported.write(&mut binary).unwrap();
+        assert_eq!(R1csFile::<32>::read(binary.as_slice()).unwrap(), file);
+
+        let prime = file.header.prime.to_dec_string();
+        let minimal = format!(
+            r#"{{"prime":"{}","constraints":[[{{"0":"1"}},{{"2":"3"}},{{}}]]}}"#,
+            prime
+        );
+        let (imported, warnings) =
+            R1csFile::<32>::from_json_reader_with_warnings(minimal.as_bytes()).unwrap();
+        assert_eq!(warnings.len(), 8);
+        assert_eq!(imported.header.n_wires, 3);
+        assert_eq!(imported.header.n_labels, 3);
+        assert_eq!(imported.header.n_constraints, 1);
+        assert_eq!(imported.map.0, [0, 1, 2]);
+
+        let too_large = format!(
+            r#"{{"prime":"{}","constraints":[[{{}},{{"2":"{}"}},{{}}]]}}"#,
+            prime, prime
+        );
+        assert!(matches!(
+            R1csFile::<32>::from_json_reader(too_large.as_bytes()),
+            Err(R1csError::Invalid(
+                ValidationError::NonCanonicalCoefficient {
+                    constraint: 0,
+                    matrix: Matrix::B,
+                    term: 0,
+                }
+            ))
+        ));
+        assert!(matches!(
+            R1csFile::<8>::from_json_reader(json.as_slice()),
+            Err(R1csError::FieldSizeMismatch {
+                expected: 8,
+                found: 32,
+                ..
+            })
+        ));
+        assert!(R1csFile::<32>::from_json_reader(&b"[]"[..]).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+//! The wire layout implied by the header: the constant wire, then the public outputs, the public
+//! inputs, the private inputs and the internal wires.
+
+use core::ops::Range;
+
+use crate::{Header, R1csError, Result, ValidationError};
+
+/// The role of a wire in the layout of [`Header::wire_kind`]. Signals and internal wires hold
+/// their index within their region, counted from 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WireKind {
+    /// The constant wire 0, always 1.
+    One,
+    PublicOutput(u32),
+    PublicInput(u32),
+    PrivateInput(u32),
+    Internal(u32),
+}
+
+impl<const FS: usize> Header<FS> {
+    /// The first wire of the public outputs, public inputs, private inputs and internal wires, in
+    /// this order, without checking them against `n_wires`.
+    pub(crate) fn region_starts(&self) -> [u64; 4] {
+        let public_inputs = 1 + u64::from(self.n_pub_out);
+        let private_inputs = public_inputs + u64::from(self.n_pub_in);
+
+        [
+            1,
+            public_inputs,
+            private_inputs,
+            private_inputs + u64::from(self.n_prvt_in),
+        ]
+    }
+
+    /// [`Header::region_starts`], failing with [`ValidationError::TooManySignals`] if the signals
+    /// don't fit in `n_wires`.
+    pub(crate) fn check_layout(&self) -> Result<[u32; 4], ValidationError> {
+        let starts = self.region_starts();
+        if starts[3] > u64::from(self.n_wires) {
+            return Err(ValidationError::TooManySignals {
+                signals: starts[3],
+                n_wires: self.n_wires,
+            });
+        }
+
+        Ok(starts.map(|start| start as u32))
+    }
+
+    fn layout(&self) -> Result<[u32; 4]> {
+        self.check_layout().map_err(R1csError::Invalid)
+    }
+
+    /// The role of `wire`.
+    ///
+    /// Fails with [`ValidationError::WireNotInCircuit`] if `wire` is not below `n_wires`, or
+    /// with [`ValidationError::TooManySignals`] if the signals don't fit in `n_wires`.
+    pub fn wire_kind(&self, wire: u32) -> Result<WireKind> {
+        if wire >= self.n_wires {
+            return Err(R1csError::Invalid(ValidationError::WireNotInCircuit {
+                wire,
+                n_wires: self.n_wires,
+            }));
+        }
+
+        let [outputs, public_inputs, private_inputs, internal] = self.layout()?;
+        Ok(if wire == 0 {
+            WireKind::One
+        } else if wire < public_inputs {
+            WireKind::PublicOutput(wire - outputs)
+        } else if wire < private_inputs {
+            WireKind::PublicInput(wire - public_inputs)
+        } else if wire < internal {
+            WireKind::PrivateInput(wire - private_inputs)
+        } else {
+            WireKind::Internal(wire - internal)
+        })
+    }
+
+    pub fn public_output_wires(&self) -> Result<Range<u32>> {
+        let starts = self.layout()?;
+        Ok(starts[0]..starts[1])
+    }
+
+    pub fn public_input_wires(&self) -> Result<Range<u32>> {
+        let starts = self.layout()?;
+        Ok(starts[1]..starts[2])
+    }
+
+    pub fn private_input_wires(&self) -> Result<Range<u32>> {
+        let starts = self.layout()?;
+        Ok(starts[2]..starts[3])
+    }
+
+    /// The public outputs followed by the public inputs, the wires after wire 0 a verifier sees.
+    pub fn public_wires(&self) -> Result<Range<u32>> {
+        let starts = self.layout()?;
+        Ok(starts[0]..starts[2])
+    }
+
+    pub fn internal_wires(&self) -> Result<Range<u32>> {
+        Ok(self.first_internal_wire()?..self.n_wires)
+    }
+
+    /// The wire after the constant wire and the signals, `n_wires` if there is no internal wire.
+    pub fn first_internal_wire(&self) -> Result<u32> {
+        Ok(self.layout()?[3])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::R1csFile;
+
+    #[test]
+    fn test_wire_layout() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let mut header = R1csFile::<32>::read(data.as_slice()).unwrap().header;
+        assert_eq!(
+            (header.n_pub_out, header.n_pub_in, header.n_prvt_in),
+            (1, 2, 3)
+        );
+        assert_eq!(header.n_wires, 7);
+
+        assert_eq!(header.public_output_wires().unwrap(), 1..2);
+        assert_eq!(header.public_input_wires().unwrap(), 2..4);
+        assert_eq!(header.private_input_wires().unwrap(), 4..7);
+        assert_eq!(header.public_wires().unwrap(), 1..4);
+        assert_eq!(header.first_internal_wire().unwrap(), 7);
+        assert_eq!(header.internal_wires().unwrap(), 7..7);
+
+        let kinds: Vec<_> = (0..7).map(|wire| header.wire_kind(wire).unwrap()).collect();
+        assert_eq!(
+            kinds,
+            [
+                WireKind::One,
+                WireKind::PublicOutput(0),
+                WireKind::PublicInput(0),
+                WireKind::PublicInput(1),
+                WireKind::PrivateInput(0),
+                WireKind::PrivateInput(1),
+                WireKind::PrivateInput(2),
+            ]
+        );
+        assert!(matches!(
+            header.wire_kind(7),
+            Err(R1csError::Invalid(ValidationError::WireNotInCircuit {
+                wire: 7,
+                n_wires: 7
+            }))
+        ));
+
+        // With internal wires, the first one comes right after the last private input.
+        header.n_wires = 9;
+        assert_eq!(header.wire_kind(6).unwrap(), WireKind::PrivateInput(2));
+        assert_eq!(header.wire_kind(7).unwrap(), WireKind::Internal(0));
+        assert_eq!(header.wire_kind(8).unwrap(), WireKind::Internal(1));
+        assert_eq!(header.internal_wires().unwrap(), 7..9);
+
+        header.n_wires = 6;
+        assert!(matches!(
+            header.public_wires(),
+            Err(R1csError::Invalid(ValidationError::TooManySignals {
+                signals: 7,
+                n_wires: 6
+            }))
+        ));
+        assert!(header.wire_kind(0).is_err());
+    }
+}
@@ -1,115 +1,1182 @@
 //! R1CS binary format parser/serializer
 //!
 //! Format specification: https://github.com/iden3/r1csfile/blob/master/doc/r1cs_bin_format.md
-
-use std::io::{Error, ErrorKind, Read, Result, Write};
-
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+//!
+//! The crate is `no_std` with `alloc` when the default `std` feature is disabled. Files are then
+//! read from and written to the minimal byte sources and sinks of [`iden3_binfile::io`], such as
+//! byte slices and vectors, and only the types of the format, [`R1csFile::read`],
+//! [`R1csFile::read_with`], [`R1csFile::write`] and the validation are available.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::hash_map::Entry;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::io::{Seek, SeekFrom};
+
+use byteorder::LittleEndian;
+use iden3_binfile::io::{self, Read, ReadBytesExt, Write, WriteBytesExt};
+use iden3_binfile::{BinFileReader, BinFileWriter, Format};
+
+#[cfg(feature = "std")]
+mod annotate;
+#[cfg(feature = "std")]
+mod any;
+#[cfg(feature = "std")]
+mod append;
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls;
+#[cfg(feature = "ark")]
+mod ark;
+#[cfg(feature = "std")]
+mod artifacts;
+#[cfg(feature = "async")]
+mod async_io;
+#[cfg(feature = "bellman")]
+mod bellman_impls;
+#[cfg(feature = "std")]
+mod borrowed;
+#[cfg(feature = "std")]
+mod builder;
+mod combination;
+#[cfg(feature = "std")]
+mod compact;
+#[cfg(feature = "compress")]
+mod compress;
+#[cfg(feature = "std")]
+mod content_id;
+#[cfg(feature = "std")]
+mod dedup;
+#[cfg(feature = "std")]
+mod diff;
+#[cfg(feature = "std")]
+mod display;
+#[cfg(feature = "std")]
+mod dot;
+mod error;
+#[cfg(feature = "std")]
+mod extract;
+#[cfg(feature = "ff")]
+mod ff_impls;
+#[cfg(feature = "std")]
+mod fingerprint;
+#[cfg(feature = "std")]
+mod flat;
+#[cfg(feature = "std")]
+mod fs;
+#[cfg(feature = "std")]
+mod index;
+#[cfg(feature = "std")]
+mod interned;
+#[cfg(feature = "json")]
+mod json;
+mod layout;
+#[cfg(feature = "std")]
+mod map;
+#[cfg(feature = "std")]
+mod memory;
+#[cfg(feature = "std")]
+mod merge;
+mod metadata;
+mod modulus;
+#[cfg(feature = "matrix-market")]
+mod mtx;
+mod normalize;
+#[cfg(feature = "std")]
+mod permute;
+mod prime;
+#[cfg(all(feature = "wtns", feature = "json"))]
+mod public_json;
+#[cfg(all(feature = "wtns", feature = "json"))]
+mod rapidsnark;
+#[cfg(feature = "std")]
+mod reader;
+#[cfg(feature = "ark-relations")]
+mod relations;
+#[cfg(feature = "std")]
+mod repair;
+#[cfg(feature = "wtns")]
+mod sampled;
+#[cfg(feature = "wtns")]
+mod satisfy;
+#[cfg(feature = "wtns")]
+mod session;
+#[cfg(feature = "std")]
+mod sparse;
+#[cfg(feature = "std")]
+mod stats;
+#[cfg(feature = "std")]
+mod sym;
+mod terms;
+#[cfg(feature = "std")]
+mod text;
+#[cfg(feature = "std")]
+mod usage;
+mod validate;
+#[cfg(feature = "std")]
+mod validated;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "wtns")]
+mod witness;
+#[cfg(feature = "std")]
+mod zkey;
+
+#[cfg(feature = "std")]
+pub use annotate::{AnnotatedR1cs, SymbolWarning};
+#[cfg(feature = "std")]
+pub use any::R1csFileAny;
+#[cfg(feature = "ark")]
+pub use ark::FieldConstraint;
+#[cfg(feature = "std")]
+pub use artifacts::{
+    compare_artifact_dirs, Artifact, ArtifactSummary, CircuitComparison, CircuitStatus, CrossCheck,
+    DirCompareOptions, DirComparisonReport, StatDifference,
+};
+#[cfg(feature = "bellman")]
+pub use bellman_impls::BellmanCircuit;
+#[cfg(feature = "mmap")]
+pub use borrowed::MappedFile;
+#[cfg(feature = "std")]
+pub use borrowed::{ConstraintRef, CustomSectionRef, R1csFileRef, TermRef};
+#[cfg(feature = "std")]
+pub use builder::R1csFileBuilder;
+pub use combination::{LinearCombination, Term};
+#[cfg(feature = "std")]
+pub use compact::CompactionReport;
+#[cfg(feature = "compress")]
+pub use compress::Compression;
+#[cfg(feature = "std")]
+pub use dedup::{DedupOptions, DedupReport};
+#[cfg(feature = "std")]
+pub use diff::{r1cs_diff, DiffOptions, Differences, HeaderDifference, R1csDiff};
+#[cfg(feature = "std")]
+pub use display::DisplayWithPrime;
+#[cfg(feature = "std")]
+pub use dot::DotOptions;
+pub use error::{Limit, ParseWarning, R1csError, Result};
+#[cfg(feature = "std")]
+pub use extract::ExtractOptions;
+#[cfg(feature = "std")]
+pub use flat::{ConstraintView, FlatConstraints};
+#[cfg(feature = "std")]
+pub use index::ConstraintIndex;
+#[cfg(feature = "std")]
+pub use interned::{InternedConstraintView, InternedConstraints};
+pub use layout::WireKind;
+#[cfg(feature = "std")]
+pub use memory::MemoryEstimate;
+#[cfg(feature = "std")]
+pub use merge::{MergeOptions, SignalLayout};
+pub use metadata::Metadata;
+pub use modulus::{Modulus, Sign};
+#[cfg(feature = "matrix-market")]
+pub use mtx::CoeffMode;
+#[cfg(feature = "std")]
+pub use permute::PermuteOptions;
+pub use prime::PrimeCheckError;
+#[cfg(all(feature = "wtns", feature = "json"))]
+pub use public_json::{export_public_json, export_public_json_validated, parse_public_json};
+#[cfg(all(feature = "wtns", feature = "json"))]
+pub use rapidsnark::{prepare_rapidsnark_inputs, RapidsnarkError, RapidsnarkInputs};
+#[cfg(feature = "std")]
+pub use reader::R1csReader;
+#[cfg(feature = "ark-relations")]
+pub use relations::R1csAdapter;
+#[cfg(feature = "std")]
+pub use repair::{HeaderChange, RepairReport};
+#[cfg(feature = "wtns")]
+pub use sampled::{check_witness_sampled, SampledCheckReport};
+#[cfg(feature = "wtns")]
+pub use satisfy::{check_witness, check_witness_all, SatisfactionError, UnsatisfiedConstraint};
+#[cfg(feature = "wtns")]
+pub use session::{CircuitSession, SessionError, SessionOptions};
+#[cfg(feature = "std")]
+pub use sparse::{DuplicateEntries, SparseMatrix, SparseOptions};
+#[cfg(feature = "std")]
+pub use stats::CircuitStats;
+#[cfg(feature = "std")]
+pub use sym::{SymEntry, SymFile};
+pub use terms::{MatrixTerm, MatrixTerms};
+#[cfg(feature = "std")]
+pub use usage::WireUsageIndex;
+pub use validate::{ValidateOptions, ValidationError};
+#[cfg(feature = "std")]
+pub use validated::ValidatedR1cs;
+#[cfg(feature = "wtns")]
+pub use witness::{WitnessSplitError, WitnessView};
+#[cfg(feature = "std")]
+pub use zkey::{MismatchReport, ZkeyHeader, ZkeyMismatch};
+#[cfg(feature = "ark")]
+pub use zkp_fields::FieldConversionError;
+pub use zkp_fields::{
+    primes, Curve, Endianness, FieldElement, ParseFieldElementError, MAX_SUPPORTED_FIELD_SIZE,
+    MIN_SUPPORTED_FIELD_SIZE,
+};
+
+/// Largest file size in bytes this crate supports. Sections declaring an end past it are reported
+/// as [`ParseWarning::FileTooLarge`].
+pub const MAX_SUPPORTED_FILE_SIZE: u64 = 1 << 40;
+/// Largest number of constraints or wire map entries this crate supports, the largest count a
+/// header can declare. Sections holding more are reported as [`ParseWarning::TooManyElements`].
+pub const MAX_SUPPORTED_ELEMENT_COUNT: u64 = u32::MAX as u64;
+
+/// Upper bound on the number of elements preallocated from sizes declared in the file.
+const MAX_PREALLOCATION: u64 = 1 << 16;
 
 const MAGIC: &[u8; 4] = b"r1cs";
 const VERSION: u32 = 1;
-
-#[derive(Debug, PartialEq, Eq)]
+/// Version introduced by circom 2 for files carrying custom gates sections.
+const VERSION_CUSTOM_GATES: u32 = 2;
+const FORMAT: Format = Format {
+    magic: *MAGIC,
+    versions: VERSION..=VERSION_CUSTOM_GATES,
+    max_sections: None,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct R1csFile<const FS: usize> {
     pub header: Header<FS>,
     pub constraints: Constraints<FS>,
+    /// Labels of the wires. Empty if the file has no wire map section, which some minimizing tools
+    /// strip as provers don't need it, see [`R1csFile::effective_map`]. An empty map is not written.
     pub map: WireMap,
+    /// Custom gate templates used by the circuit (version 2 files only).
+    pub custom_gates_list: Option<CustomGatesList<FS>>,
+    /// Applications of the custom gates to circuit signals (version 2 files only).
+    pub custom_gates_application: Option<CustomGatesApplication>,
+    /// Provenance of the circuit, from the metadata section. See [`Metadata`] for the encoding.
+    pub metadata: Option<Metadata>,
+    /// Sections of unrecognized types, in file order.
+    pub custom_sections: Vec<CustomSection>,
+    /// Version of the file format, 1, or 2 for files with custom gates. Files are written with the
+    /// version they were read with, version 2 if custom gates are present.
+    #[cfg_attr(feature = "serde", serde(default = "default_version"))]
+    pub version: u32,
+}
+
+/// The version of values serialized before [`R1csFile::version`] was added.
+#[cfg(feature = "serde")]
+fn default_version() -> u32 {
+    VERSION
+}
+
+/// Options for [`R1csFile::read_with`] and [`R1csFile::read_seek_with`].
+#[derive(Debug, Default)]
+pub struct ReadOptions {
+    /// Skip sections of unrecognized types instead of keeping them in
+    /// [`R1csFile::custom_sections`].
+    pub drop_unknown_sections: bool,
+    /// How inconsistencies between sections are handled.
+    pub mode: ParseMode,
+    /// Maximum number of constraints, checked against both the header and the constraints
+    /// section.
+    pub max_constraints: Option<u64>,
+    /// Maximum number of terms in a single linear combination.
+    pub max_terms_per_lc: Option<u32>,
+    /// Maximum declared size of any section, in bytes.
+    pub max_section_size: Option<u64>,
+    /// Maximum number of unknown sections skipped with `drop_unknown_sections`.
+    pub max_skipped_sections: Option<u64>,
+    /// Maximum total declared size of the unknown sections skipped with `drop_unknown_sections`.
+    pub max_skipped_bytes: Option<u64>,
+    /// Called periodically while reading the constraints and the wire map.
+    pub progress: Option<ProgressHook>,
+    /// Decode constraints sections on the rayon thread pool. Each section is buffered in memory
+    /// first, and progress is only reported once it is decoded.
+    #[cfg(feature = "rayon")]
+    pub parallel: bool,
+}
+
+/// Reading progress passed to a [`ProgressHook`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// The section being read.
+    pub section: SectionType,
+    /// Bytes read from the start of the file.
+    pub bytes_read: u64,
+    pub constraints_read: u64,
+    /// The header constraint count, zero if the header hasn't been read yet.
+    pub total_constraints: u64,
+}
+
+/// A progress callback invoked every `every` constraints or wire map entries, and at the end of
+/// each of these sections.
+pub struct ProgressHook {
+    every: u64,
+    callback: ProgressCallback,
+}
+
+#[cfg(feature = "std")]
+type ProgressCallback = std::sync::Mutex<Box<dyn FnMut(Progress) + Send>>;
+/// Without `std` there are no threads to share the hook between.
+#[cfg(not(feature = "std"))]
+type ProgressCallback = core::cell::RefCell<Box<dyn FnMut(Progress) + Send>>;
+
+impl ProgressHook {
+    pub fn new(every: u64, callback: impl FnMut(Progress) + Send + 'static) -> Self {
+        ProgressHook {
+            every: every.max(1),
+            callback: ProgressCallback::new(Box::new(callback)),
+        }
+    }
+
+    fn tick(&self, count: u64, progress: impl FnOnce() -> Progress) {
+        if count.is_multiple_of(self.every) {
+            self.report(progress());
+        }
+    }
+
+    /// Reports the end of a section, unless the last `tick` already did.
+    fn finish(&self, count: u64, progress: Progress) {
+        if count == 0 || !count.is_multiple_of(self.every) {
+            self.report(progress);
+        }
+    }
+
+    fn report(&self, progress: Progress) {
+        // A panicking callback has no state of ours to leave inconsistent.
+        #[cfg(feature = "std")]
+        let mut callback = self
+            .callback
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        #[cfg(not(feature = "std"))]
+        let mut callback = self.callback.borrow_mut();
+        callback(progress);
+    }
+}
+
+impl core::fmt::Debug for ProgressHook {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ProgressHook")
+            .field("every", &self.every)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ReadOptions {
+    /// Sets a progress callback, see [`ProgressHook`].
+    pub fn progress(mut self, every: u64, callback: impl FnMut(Progress) + Send + 'static) -> Self {
+        self.progress = Some(ProgressHook::new(every, callback));
+        self
+    }
+
+    /// Enables parallel decoding of the constraints, see [`ReadOptions::parallel`].
+    #[cfg(feature = "rayon")]
+    pub fn parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    fn check_limit(&self, limit: Limit, value: u64, offset: u64) -> Result<()> {
+        let max = match limit {
+            Limit::Constraints => self.max_constraints,
+            Limit::TermsPerLc => self.max_terms_per_lc.map(u64::from),
+            Limit::SectionSize => self.max_section_size,
+            Limit::SkippedSections => self.max_skipped_sections,
+            Limit::SkippedBytes => self.max_skipped_bytes,
+        };
+
+        match max {
+            Some(max) if value > max => Err(R1csError::LimitExceeded {
+                limit,
+                value,
+                max,
+                offset,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Handling of files whose sections disagree with each other.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    /// The behavior of [`R1csFile::read`]: consistency is not checked, trailing bytes are not
+    /// read and a constraint cut off by the end of its section is an error.
+    #[default]
+    Unchecked,
+    /// Every inconsistency is an [`R1csError::Inconsistent`] error.
+    Strict,
+    /// Inconsistencies are reported as [`ParseWarning`]s. Section sizes are trusted over the
+    /// header counts.
+    Lenient,
+}
+
+/// Options for [`R1csFile::write_with`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Whether the wire map section is written.
+    pub wire_map: WireMapSection,
+}
+
+/// When the wire map section is written, see [`WriteOptions::wire_map`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WireMapSection {
+    /// The behavior of [`R1csFile::write`]: the section is written unless the map is empty.
+    #[default]
+    IfPresent,
+    /// The section is left out, for provers that don't need it.
+    Omit,
+    /// The section is always written, an empty map as the identity map of
+    /// [`R1csFile::effective_map`].
+    Always,
 }
 
 impl<const FS: usize> R1csFile<FS> {
-    pub fn read<R: Read>(mut r: R) -> Result<Self> {
-        let mut magic = [0u8; 4];
-        r.read_exact(&mut magic)?;
-        if magic != *MAGIC {
-            return Err(Error::new(ErrorKind::InvalidData, "Invalid magic number"));
+    pub fn read<R: Read>(r: R) -> Result<Self> {
+        Self::read_with(r, &ReadOptions::default()).map(|(file, _)| file)
+    }
+
+    /// Reads a file from a stream.
+    ///
+    /// Warnings are only produced in [`ParseMode::Lenient`], the other modes return an empty list.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "r1cs_read",
+            level = "debug",
+            skip_all,
+            fields(field_size = FS, num_sections = tracing::field::Empty)
+        )
+    )]
+    pub fn read_with<R: Read>(r: R, opts: &ReadOptions) -> Result<(Self, Vec<ParseWarning>)> {
+        let mut file = BinFileReader::new(r, &FORMAT)?;
+        let mut sections = Sections {
+            version: file.version(),
+            ..Sections::default()
+        };
+        sections.read_stream(&mut file, opts)?;
+
+        if opts.mode != ParseMode::Unchecked {
+            file.skip_section()?;
+            let offset = file.position();
+            let bytes = io::copy(&mut file, &mut io::sink())?;
+            if bytes > 0 {
+                sections.report(opts.mode, ParseWarning::TrailingData { offset, bytes })?;
+            }
+        }
+
+        sections.into_file(opts.mode)
+    }
+
+    /// Reads as much of a possibly truncated or corrupted stream as can be decoded.
+    ///
+    /// Everything decoded before the first error is returned together with that error, which is
+    /// `None` if the whole file was read. Unlike [`R1csFile::read`], missing sections are reported
+    /// as an error but don't prevent returning the others.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "r1cs_read_partial",
+            level = "debug",
+            skip_all,
+            fields(field_size = FS, num_sections = tracing::field::Empty)
+        )
+    )]
+    pub fn read_partial<R: Read>(r: R) -> (PartialR1cs<FS>, Option<R1csError>) {
+        let mut sections = Sections::default();
+        let read = BinFileReader::new(r, &FORMAT)
+            .map_err(R1csError::from)
+            .and_then(|mut file| sections.read_stream(&mut file, &ReadOptions::default()));
+        let error = match read {
+            Ok(()) => sections.missing().map(R1csError::MissingSection),
+            Err(e) => Some(e),
+        };
+
+        let stopped_at = match sections.reading {
+            Some(SectionType::Constraint) => sections.constraints.as_ref().map(|c| c.0.len()),
+            _ => None,
+        };
+        let partial = PartialR1cs {
+            header: sections.header,
+            constraints: sections.constraints.unwrap_or_default(),
+            map: sections.map,
+            custom_gates_list: sections.custom_gates_list,
+            custom_gates_application: sections.custom_gates_application,
+            metadata: sections.metadata,
+            custom_sections: sections.custom_sections,
+            stopped_at,
+        };
+
+        (partial, error)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn read_seek<R: Read + Seek>(r: R) -> Result<Self> {
+        Self::read_seek_with(r, &ReadOptions::default()).map(|(file, _)| file)
+    }
+
+    /// Reads a file from a seekable source.
+    ///
+    /// The section table is walked first, seeking past section bodies, and the sections are then
+    /// parsed wherever they are located, header first. The spec allows sections in any order, so
+    /// this is the preferred way to load files from disk; [`R1csFile::read`] is meant for
+    /// non-seekable streams.
+    #[cfg(feature = "std")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "r1cs_read_seek",
+            level = "debug",
+            skip_all,
+            fields(field_size = FS, num_sections = tracing::field::Empty)
+        )
+    )]
+    pub fn read_seek_with<R: Read + Seek>(
+        r: R,
+        opts: &ReadOptions,
+    ) -> Result<(Self, Vec<ParseWarning>)> {
+        let mut file = BinFileReader::new(r, &FORMAT)?;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("num_sections", file.num_sections());
+
+        let mut table = file.section_table()?;
+        let end = file.position();
+        // Stable sort: the header goes first, everything else keeps the file order.
+        table.sort_by_key(|entry| SectionType::from_id(entry.id) != Some(SectionType::Header));
+
+        let mut sections = Sections {
+            version: file.version(),
+            ..Sections::default()
+        };
+        for entry in table {
+            let section_header = SectionHeader {
+                id: entry.id,
+                size: entry.size,
+            };
+            if section_header.ty().is_none() && opts.drop_unknown_sections {
+                // The table walk already seeked past the body, there is nothing to read.
+                opts.check_limit(Limit::SectionSize, entry.size, entry.offset - 12)?;
+                sections.skip_section(&section_header, entry.offset, opts)?;
+                continue;
+            }
+
+            let mut r = CountingReader::at(file.section_at(&entry)?, entry.offset);
+            sections.read_section(&mut r, &section_header, opts)?;
         }
 
-        let version = r.read_u32::<LittleEndian>()?;
-        if version != VERSION {
-            return Err(Error::new(ErrorKind::InvalidData, "Unsupported version"));
+        if opts.mode != ParseMode::Unchecked {
+            let len = file.seek(SeekFrom::End(0))?;
+            if len > end {
+                let warning = ParseWarning::TrailingData {
+                    offset: end,
+                    bytes: len - end,
+                };
+                sections.report(opts.mode, warning)?;
+            }
         }
 
-        // TODO: Should we support multiple sections of the same type?
-        //
-        // For now assume there is at most one section of each kind.
-        let num_sections = r.read_u32::<LittleEndian>()?;
+        sections.into_file(opts.mode)
+    }
+
+    /// Lists the sections in file order without parsing their bodies.
+    ///
+    /// Only the section headers are read, section bodies are skipped by seeking.
+    #[cfg(feature = "std")]
+    pub fn scan_sections<R: Read + Seek>(r: R) -> Result<Vec<SectionInfo>> {
+        let sections = BinFileReader::new(r, &FORMAT)?
+            .section_table()?
+            .into_iter()
+            .map(|entry| SectionInfo {
+                ty: entry.id,
+                known: SectionType::from_id(entry.id),
+                offset: entry.offset - 12,
+                size: entry.size,
+            })
+            .collect();
+
+        Ok(sections)
+    }
+
+    /// Reads only the header section, seeking past every other section body.
+    ///
+    /// Nothing is allocated for the constraints or the wire map, so this is cheap even for huge
+    /// files. The header may be located anywhere in the file.
+    #[cfg(feature = "std")]
+    pub fn read_header<R: Read + Seek>(r: R) -> Result<Header<FS>> {
+        let mut file = BinFileReader::new(r, &FORMAT)?;
 
         let mut header = None;
-        let mut constraints = None;
-        let mut map = None;
-
-        for _ in 0..num_sections {
-            let section_header = SectionHeader::read(&mut r)?;
-
-            match section_header.ty {
-                SectionType::Header => {
-                    if let None = header {
-                        header = Some(Header::read(&mut r)?);
-                    } else {
-                        return Err(Error::new(
-                            ErrorKind::InvalidData,
-                            "Duplicated header section found",
-                        ));
-                    }
+        for entry in file.section_table()? {
+            if SectionType::from_id(entry.id) == Some(SectionType::Header) {
+                ensure_unique(&header, SectionType::Header, entry.offset)?;
+                header = Some(entry);
+            }
+        }
+
+        let entry = header.ok_or(R1csError::MissingSection(SectionType::Header))?;
+        let mut r = CountingReader::at(file.section_at(&entry)?, entry.offset);
+
+        Header::read(&mut r).map_err(|e| e.eof_in(Some(SectionType::Header), r.position()))
+    }
+
+    /// Writes the file, after checking that the header counts agree with the sections.
+    ///
+    /// [`R1csError::Invalid`] is returned without writing anything if the number of constraints
+    /// or the wire map length differs from the header, or if the signals don't fit in
+    /// `n_wires`. The field size is fixed by `FS` and always consistent. Use
+    /// [`R1csFile::write_unchecked`] to write the file as is.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "r1cs_write",
+            level = "debug",
+            skip_all,
+            fields(
+                field_size = FS,
+                n_wires = self.header.n_wires,
+                n_constraints = self.constraints.0.len()
+            )
+        )
+    )]
+    pub fn write<W: Write>(&self, w: W) -> Result<()> {
+        self.check_header().map_err(R1csError::Invalid)?;
+        self.write_unchecked(w)
+    }
+
+    /// Writes the file after the checks of [`R1csFile::write`], with the sections chosen by
+    /// `opts`.
+    pub fn write_with<W: Write>(&self, w: W, opts: &WriteOptions) -> Result<()> {
+        self.check_header().map_err(R1csError::Invalid)?;
+        self.write_sections(w, false, opts)
+    }
+
+    /// [`R1csFile::write`], also rejecting what [`ParseMode::Strict`] rejects in the header: a
+    /// prime whose top byte is zero, see [`ParseWarning::PaddedPrime`].
+    pub fn write_strict<W: Write>(&self, w: W) -> Result<()> {
+        if let Some(prime_bytes) = self.header.padded_prime_bytes() {
+            return Err(R1csError::Invalid(ValidationError::PaddedPrime {
+                field_size: FS as u32,
+                prime_bytes,
+            }));
+        }
+
+        self.write(w)
+    }
+
+    /// Writes the file as is, even if the header disagrees with the sections, for producing
+    /// malformed test vectors.
+    pub fn write_unchecked<W: Write>(&self, w: W) -> Result<()> {
+        self.write_sections(w, false, &WriteOptions::default())
+    }
+
+    /// Writes the file in canonical form, after the same checks as [`R1csFile::write`], so that
+    /// two values holding the same circuit are written byte for byte the same.
+    ///
+    /// The canonical form differs from [`R1csFile::write`] in two ways only:
+    ///
+    /// - the terms of each linear combination are sorted by wire index, terms on the same wire by
+    ///   coefficient value;
+    /// - the metadata and custom sections come after the known sections sorted by section id,
+    ///   custom sections of the same id in their stored order.
+    ///
+    /// Known sections are written in spec order, as by [`R1csFile::write`]: header, constraints,
+    /// wire map, custom gates list and custom gates application. Constraints keep their stored
+    /// order, and the value itself is left untouched.
+    pub fn write_canonical<W: Write>(&self, w: W) -> Result<()> {
+        self.check_header().map_err(R1csError::Invalid)?;
+        self.write_sections(w, true, &WriteOptions::default())
+    }
+
+    /// Writes the sections chosen by `opts`, in canonical form if `canonical` is set.
+    pub(crate) fn write_sections<W: Write>(
+        &self,
+        w: W,
+        canonical: bool,
+        opts: &WriteOptions,
+    ) -> Result<()> {
+        let has_custom_gates =
+            self.custom_gates_list.is_some() || self.custom_gates_application.is_some();
+        let version = if has_custom_gates {
+            VERSION_CUSTOM_GATES
+        } else {
+            self.version
+        };
+        let map = match opts.wire_map {
+            WireMapSection::IfPresent => Some(Cow::Borrowed(&self.map)).filter(|m| !m.is_empty()),
+            WireMapSection::Omit => None,
+            WireMapSection::Always => Some(self.effective_map()),
+        };
+        let num_sections = 2
+            + map.is_some() as u32
+            + self.custom_gates_list.is_some() as u32
+            + self.custom_gates_application.is_some() as u32
+            + self.metadata.is_some() as u32
+            + self.custom_sections.len() as u32;
+
+        let mut w = BinFileWriter::new(w, MAGIC, version, num_sections)?;
+
+        w.write_section(SectionType::Header as u32, Header::<FS>::size(), |w| {
+            self.header.write(w)
+        })?;
+        w.write_section(
+            SectionType::Constraint as u32,
+            self.constraints.size(),
+            |w| {
+                if !canonical {
+                    return self.constraints.write(w);
                 }
-                SectionType::Constraint => {
-                    if let None = constraints {
-                        constraints = Some(Constraints::read(&mut r, &section_header)?);
-                    } else {
-                        return Err(Error::new(
-                            ErrorKind::InvalidData,
-                            "Duplicated constraints section found",
-                        ));
-                    }
+                for c in &self.constraints.0 {
+                    c.sorted_terms().write(&mut *w)?;
+                }
+
+                Ok(())
+            },
+        )?;
+        if let Some(map) = map {
+            w.write_section(SectionType::Wire2LabelIdMap as u32, map.size(), |w| {
+                map.write(w)
+            })?;
+        }
+
+        if let Some(list) = &self.custom_gates_list {
+            w.write_section(SectionType::CustomGatesList as u32, list.size(), |w| {
+                list.write(w)
+            })?;
+        }
+
+        if let Some(application) = &self.custom_gates_application {
+            let id = SectionType::CustomGatesApplication as u32;
+            w.write_section(id, application.size(), |w| application.write(w))?;
+        }
+
+        let metadata = self.metadata.as_ref().map(|metadata| {
+            let mut data = Vec::with_capacity(metadata::size(metadata) as usize);
+            metadata::write(metadata, &mut data).expect("writing to a Vec doesn't fail");
+            data
+        });
+        let mut extra: Vec<(u32, &[u8])> = metadata
+            .iter()
+            .map(|data| (SectionType::Metadata as u32, data.as_slice()))
+            .chain(
+                self.custom_sections
+                    .iter()
+                    .map(|s| (s.id, s.data.as_slice())),
+            )
+            .collect();
+        if canonical {
+            extra.sort_by_key(|(id, _)| *id);
+        }
+
+        for (id, data) in extra {
+            w.write_section(id, data.len() as u64, |w| {
+                w.write_all(data).map_err(R1csError::from)
+            })?;
+        }
+
+        w.finish()?;
+        Ok(())
+    }
+
+    /// The cheap header invariants checked by [`R1csFile::write`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "r1cs_check_header", level = "trace", skip_all)
+    )]
+    fn check_header(&self) -> Result<(), ValidationError> {
+        if !FORMAT.versions.contains(&self.version) {
+            return Err(ValidationError::UnsupportedVersion {
+                version: self.version,
+            });
+        }
+        let header = &self.header;
+        if header.n_constraints as usize != self.constraints.0.len() {
+            return Err(ValidationError::ConstraintCountMismatch {
+                header: header.n_constraints,
+                found: self.constraints.0.len(),
+            });
+        }
+        if !self.map.is_empty() && header.n_wires as usize != self.map.0.len() {
+            return Err(ValidationError::WireMapLengthMismatch {
+                n_wires: header.n_wires,
+                found: self.map.0.len(),
+            });
+        }
+
+        header.check_layout()?;
+
+        Ok(())
+    }
+
+    /// The exact number of bytes written by [`R1csFile::write`].
+    pub fn serialized_size(&self) -> usize {
+        let sections = [
+            Some(Header::<FS>::size()),
+            Some(self.constraints.size()),
+            Some(self.map.size()).filter(|_| !self.map.is_empty()),
+            self.custom_gates_list.as_ref().map(CustomGatesList::size),
+            self.custom_gates_application
+                .as_ref()
+                .map(CustomGatesApplication::size),
+            self.metadata.as_ref().map(metadata::size),
+        ];
+        let custom = self.custom_sections.iter().map(|s| s.data.len() as u64);
+        let bodies = sections.iter().flatten().copied().chain(custom);
+
+        // Magic, version and section count, then a 12-byte header per section.
+        bodies.map(|size| 12 + size).sum::<u64>() as usize + 12
+    }
+
+    /// Serializes the file into a buffer allocated up front with [`R1csFile::serialized_size`].
+    ///
+    /// Like [`R1csFile::write_unchecked`], the header is not checked against the sections.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.serialized_size());
+        self.write_unchecked(&mut data)
+            .expect("writing to a Vec doesn't fail");
+
+        data
+    }
+
+    /// The wire map, or the identity map of `n_wires` wires if the file has none.
+    pub fn effective_map(&self) -> Cow<'_, WireMap> {
+        if self.map.is_empty() {
+            Cow::Owned(WireMap::identity(self.header.n_wires))
+        } else {
+            Cow::Borrowed(&self.map)
+        }
+    }
+}
+
+/// The sections decoded by [`R1csFile::read_partial`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialR1cs<const FS: usize> {
+    pub header: Option<Header<FS>>,
+    /// Constraints decoded before decoding stopped.
+    pub constraints: Constraints<FS>,
+    pub map: Option<WireMap>,
+    pub custom_gates_list: Option<CustomGatesList<FS>>,
+    pub custom_gates_application: Option<CustomGatesApplication>,
+    pub metadata: Option<Metadata>,
+    pub custom_sections: Vec<CustomSection>,
+    /// Index of the constraint that could not be decoded, if decoding stopped in a constraints
+    /// section.
+    pub stopped_at: Option<usize>,
+}
+
+/// Sections collected while reading a file.
+#[derive(Default)]
+struct Sections<const FS: usize> {
+    header: Option<Header<FS>>,
+    constraints: Option<Constraints<FS>>,
+    map: Option<WireMap>,
+    custom_gates_list: Option<CustomGatesList<FS>>,
+    custom_gates_application: Option<CustomGatesApplication>,
+    metadata: Option<Metadata>,
+    custom_sections: Vec<CustomSection>,
+    warnings: Vec<ParseWarning>,
+    skipped_sections: u64,
+    skipped_bytes: u64,
+    constraint_sections: u32,
+    /// Whether [`ParseWarning::FileTooLarge`] was already reported.
+    too_large: bool,
+    /// Type of the section being read, kept if reading it failed.
+    reading: Option<SectionType>,
+    version: u32,
+}
+
+impl<const FS: usize> Sections<FS> {
+    /// Reads the sections of a stream, in file order.
+    fn read_stream<R: Read>(
+        &mut self,
+        file: &mut BinFileReader<R>,
+        opts: &ReadOptions,
+    ) -> Result<()> {
+        // Constraints may be split across several sections, which are concatenated in file order.
+        // Any other known section may appear at most once.
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("num_sections", file.num_sections());
+
+        while let Some((id, size, section)) = file.next_section()? {
+            let offset = section.position();
+            let mut r = CountingReader::at(section, offset);
+            self.read_section(&mut r, &SectionHeader { id, size }, opts)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the body of a section whose header has just been read.
+    fn read_section<R: Read>(
+        &mut self,
+        r: &mut CountingReader<R>,
+        section_header: &SectionHeader,
+        opts: &ReadOptions,
+    ) -> Result<()> {
+        let offset = r.position();
+        let ty = section_header.ty();
+        opts.check_limit(Limit::SectionSize, section_header.size, offset - 12)?;
+        let end = offset.saturating_add(section_header.size);
+        if end > MAX_SUPPORTED_FILE_SIZE && !self.too_large {
+            self.too_large = true;
+            self.report(opts.mode, ParseWarning::FileTooLarge { size: end })?;
+        }
+
+        self.reading = ty;
+        self.read_section_body(r, section_header, opts, offset)
+            .map_err(|e| e.eof_in(ty, r.position()))?;
+        self.reading = None;
+
+        Ok(())
+    }
+
+    fn read_section_body<R: Read>(
+        &mut self,
+        r: &mut CountingReader<R>,
+        section_header: &SectionHeader,
+        opts: &ReadOptions,
+        offset: u64,
+    ) -> Result<()> {
+        match section_header.ty() {
+            Some(SectionType::Header) => {
+                ensure_unique(&self.header, SectionType::Header, offset)?;
+                let header = Header::read(r)?;
+                opts.check_limit(Limit::Constraints, u64::from(header.n_constraints), offset)?;
+                if let Some(prime_bytes) = header.padded_prime_bytes() {
+                    let warning = ParseWarning::PaddedPrime {
+                        field_size: FS as u32,
+                        prime_bytes,
+                    };
+                    self.report(opts.mode, warning)?;
+                }
+                self.header = Some(header);
+            }
+            Some(SectionType::Constraint) => {
+                self.constraint_sections += 1;
+                let constraints = self.constraints.get_or_insert_with(Constraints::default);
+                let total = self.header.as_ref().map_or(0, |h| h.n_constraints);
+                let partial = constraints.read_into(r, section_header, opts, total.into())?;
+                let count = constraints.0.len() as u64;
+                if count > MAX_SUPPORTED_ELEMENT_COUNT {
+                    let warning = ParseWarning::TooManyElements {
+                        section: SectionType::Constraint,
+                        count,
+                    };
+                    self.report(opts.mode, warning)?;
                 }
-                SectionType::Wire2LabelIdMap => {
-                    if let None = map {
-                        map = Some(WireMap::read(&mut r, &section_header)?);
-                    } else {
-                        return Err(Error::new(
-                            ErrorKind::InvalidData,
-                            "Duplicated wire map section found",
-                        ));
+                if partial > 0 {
+                    if opts.mode == ParseMode::Unchecked {
+                        return Err(R1csError::UnexpectedEof {
+                            section: Some(SectionType::Constraint),
+                            offset: r.position(),
+                        });
                     }
+
+                    let warning = ParseWarning::PartialConstraint {
+                        offset: r.position() - partial,
+                        bytes: partial,
+                    };
+                    self.report(opts.mode, warning)?;
+                }
+            }
+            Some(SectionType::Wire2LabelIdMap) => {
+                ensure_unique(&self.map, SectionType::Wire2LabelIdMap, offset)?;
+                let count = section_header.size / 8;
+                if count > MAX_SUPPORTED_ELEMENT_COUNT {
+                    let warning = ParseWarning::TooManyElements {
+                        section: SectionType::Wire2LabelIdMap,
+                        count,
+                    };
+                    self.report(opts.mode, warning)?;
                 }
-                SectionType::Unknown => {
-                    return Err(Error::new(ErrorKind::InvalidData, "Unknown section"))
+                let progress = opts.progress.as_ref().map(|hook| {
+                    let total = self.header.as_ref().map_or(0, |h| h.n_constraints);
+                    let read = self.constraints.as_ref().map_or(0, |c| c.0.len());
+                    let progress = Progress {
+                        section: SectionType::Wire2LabelIdMap,
+                        bytes_read: r.position(),
+                        constraints_read: read as u64,
+                        total_constraints: total.into(),
+                    };
+                    (hook, progress)
+                });
+                self.map = Some(WireMap::read(r, section_header, progress)?);
+            }
+            Some(SectionType::CustomGatesList) => {
+                ensure_unique(
+                    &self.custom_gates_list,
+                    SectionType::CustomGatesList,
+                    offset,
+                )?;
+                self.custom_gates_list = Some(CustomGatesList::read(r, section_header)?);
+            }
+            Some(SectionType::CustomGatesApplication) => {
+                ensure_unique(
+                    &self.custom_gates_application,
+                    SectionType::CustomGatesApplication,
+                    offset,
+                )?;
+                self.custom_gates_application =
+                    Some(CustomGatesApplication::read(r, section_header)?);
+            }
+            Some(SectionType::Metadata) => {
+                ensure_unique(&self.metadata, SectionType::Metadata, offset)?;
+                self.metadata = Some(metadata::read(r, section_header)?);
+            }
+            None => {
+                if opts.drop_unknown_sections {
+                    self.skip_section(section_header, offset, opts)?;
+                    section_header.skip(r)?;
+                } else {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(
+                        id = section_header.id,
+                        size = section_header.size,
+                        "keeping unknown section"
+                    );
+                    self.custom_sections
+                        .push(CustomSection::read(r, section_header)?);
                 }
             }
         }
 
-        match (header, constraints, map) {
-            (Some(header), Some(constraints), Some(map)) => Ok(R1csFile {
-                header,
-                constraints,
-                map,
-            }),
-            (None, _, _) => Err(Error::new(ErrorKind::InvalidData, "Missing header section")),
-            (_, None, _) => Err(Error::new(
-                ErrorKind::InvalidData,
-                "Missing constraints section",
-            )),
-            (_, _, None) => Err(Error::new(
-                ErrorKind::InvalidData,
-                "Missing wire map section",
-            )),
+        Ok(())
+    }
+
+    /// Accounts for an unknown section being dropped, before its body is skipped.
+    fn skip_section(
+        &mut self,
+        section_header: &SectionHeader,
+        offset: u64,
+        opts: &ReadOptions,
+    ) -> Result<()> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            id = section_header.id,
+            size = section_header.size,
+            "skipping unknown section"
+        );
+        self.skipped_sections += 1;
+        self.skipped_bytes = self.skipped_bytes.saturating_add(section_header.size);
+        opts.check_limit(Limit::SkippedSections, self.skipped_sections, offset - 12)?;
+        opts.check_limit(Limit::SkippedBytes, self.skipped_bytes, offset - 12)
+    }
+
+    /// The first mandatory section that was not read. The wire map is optional.
+    fn missing(&self) -> Option<SectionType> {
+        if self.header.is_none() {
+            Some(SectionType::Header)
+        } else if self.constraints.is_none() {
+            Some(SectionType::Constraint)
+        } else {
+            None
+        }
+    }
+
+    /// Handles an inconsistency according to `mode`.
+    fn report(&mut self, mode: ParseMode, warning: ParseWarning) -> Result<()> {
+        match mode {
+            ParseMode::Unchecked => Ok(()),
+            ParseMode::Strict => Err(R1csError::Inconsistent(warning)),
+            ParseMode::Lenient => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(%warning, "parse warning");
+                self.warnings.push(warning);
+                Ok(())
+            }
         }
     }
 
-    pub fn write<W: Write>(&self, mut w: W) -> Result<()> {
-        w.write_all(MAGIC)?;
-        w.write_u32::<LittleEndian>(VERSION)?;
-        w.write_u32::<LittleEndian>(3)?; // number of sections
+    fn into_file(mut self, mode: ParseMode) -> Result<(R1csFile<FS>, Vec<ParseWarning>)> {
+        if let Some(section) = self.missing() {
+            return Err(R1csError::MissingSection(section));
+        }
+        let (header, constraints) = match (self.header.take(), self.constraints.take()) {
+            (Some(header), Some(constraints)) => (header, constraints),
+            _ => unreachable!("checked by Sections::missing"),
+        };
+        let map = self.map.take().unwrap_or_default();
+
+        let section = constraints.0.len() as u64;
+        if u64::from(header.n_constraints) != section {
+            let warning = ParseWarning::ConstraintCountMismatch {
+                header: header.n_constraints,
+                section,
+            };
+            // Split constraints are only accepted if they add up.
+            if mode == ParseMode::Unchecked && self.constraint_sections > 1 {
+                return Err(R1csError::Inconsistent(warning));
+            }
+            self.report(mode, warning)?;
+        }
 
-        self.header.write(&mut w)?;
-        self.constraints.write(&mut w)?;
-        self.map.write(&mut w)?;
+        let file = R1csFile {
+            header,
+            constraints,
+            map,
+            custom_gates_list: self.custom_gates_list,
+            custom_gates_application: self.custom_gates_application,
+            metadata: self.metadata,
+            custom_sections: self.custom_sections,
+            version: self.version,
+        };
 
-        Ok(())
+        Ok((file, self.warnings))
+    }
+}
+
+fn ensure_unique<T>(slot: &Option<T>, section: SectionType, offset: u64) -> Result<()> {
+    if slot.is_some() {
+        return Err(R1csError::DuplicateSection { section, offset });
+    }
+
+    Ok(())
+}
+
+/// Reader wrapper keeping track of the absolute position for error reporting.
+struct CountingReader<R> {
+    inner: R,
+    position: u64,
+}
+
+impl<R> CountingReader<R> {
+    #[cfg(feature = "std")]
+    fn new(inner: R) -> Self {
+        CountingReader { inner, position: 0 }
+    }
+
+    /// A reader whose first byte is at `position` in the file.
+    fn at(inner: R, position: u64) -> Self {
+        CountingReader { inner, position }
+    }
+
+    fn position(&self) -> u64 {
+        self.position
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.position += n as u64;
+
+        Ok(n)
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[cfg(feature = "std")]
+impl<R: Seek> Seek for CountingReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = self.inner.seek(pos)?;
+
+        Ok(self.position)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Header<const FS: usize> {
     pub prime: FieldElement<FS>,
     pub n_wires: u32,
@@ -121,13 +1188,47 @@ pub struct Header<const FS: usize> {
 }
 
 impl<const FS: usize> Header<FS> {
-    fn read<R: Read>(mut r: R) -> Result<Self> {
+    /// The well-known field this file is defined over, if any.
+    pub fn curve(&self) -> Option<Curve> {
+        Curve::from_prime_bytes(self.prime.as_bytes())
+    }
+
+    /// The number of bytes the prime takes if its top byte is zero, which suggests the file was
+    /// written for a smaller field size. Primes of the smallest field size are not padded.
+    fn padded_prime_bytes(&self) -> Option<u32> {
+        if FS > MIN_SUPPORTED_FIELD_SIZE && self.prime.byte(FS - 1) == Some(0) {
+            let prime_bytes = self
+                .prime
+                .iter()
+                .rposition(|b| *b != 0)
+                .map_or(0, |i| i + 1);
+            Some(prime_bytes as u32)
+        } else {
+            None
+        }
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "r1cs_header",
+            level = "trace",
+            skip_all,
+            fields(n_wires = tracing::field::Empty, n_constraints = tracing::field::Empty)
+        )
+    )]
+    fn read<R: Read>(r: &mut CountingReader<R>) -> Result<Self> {
+        let offset = r.position();
         let field_size = r.read_u32::<LittleEndian>()?;
         if field_size != FS as u32 {
-            return Err(Error::new(ErrorKind::InvalidData, "Wrong field size"));
+            return Err(R1csError::FieldSizeMismatch {
+                expected: FS as u32,
+                found: field_size,
+                offset,
+            });
         }
 
-        let prime = FieldElement::read(&mut r)?;
+        let prime = read_element(&mut *r)?;
         let n_wires = r.read_u32::<LittleEndian>()?;
         let n_pub_out = r.read_u32::<LittleEndian>()?;
         let n_pub_in = r.read_u32::<LittleEndian>()?;
@@ -135,6 +1236,11 @@ impl<const FS: usize> Header<FS> {
         let n_labels = r.read_u64::<LittleEndian>()?;
         let n_constraints = r.read_u32::<LittleEndian>()?;
 
+        #[cfg(feature = "tracing")]
+        tracing::Span::current()
+            .record("n_wires", n_wires)
+            .record("n_constraints", n_constraints);
+
         Ok(Header {
             prime,
             n_wires,
@@ -147,15 +1253,8 @@ impl<const FS: usize> Header<FS> {
     }
 
     fn write<W: Write>(&self, mut w: W) -> Result<()> {
-        let header = SectionHeader {
-            ty: SectionType::Header,
-            size: 6 * 4 + 8 + FS as u64,
-        };
-
-        header.write(&mut w)?;
-
         w.write_u32::<LittleEndian>(FS as u32)?;
-        self.prime.write(&mut w)?;
+        w.write_all(self.prime.as_bytes())?;
         w.write_u32::<LittleEndian>(self.n_wires)?;
         w.write_u32::<LittleEndian>(self.n_pub_out)?;
         w.write_u32::<LittleEndian>(self.n_pub_in)?;
@@ -165,204 +1264,859 @@ impl<const FS: usize> Header<FS> {
 
         Ok(())
     }
+
+    /// Size of the section body.
+    fn size() -> u64 {
+        6 * 4 + 8 + FS as u64
+    }
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Constraints<const FS: usize>(pub Vec<Constraint<FS>>);
 
 impl<const FS: usize> Constraints<FS> {
-    fn read<R: Read>(r: R, section_header: &SectionHeader) -> Result<Self> {
-        let mut section_data = r.take(section_header.size);
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
 
-        let mut constraints = Vec::new();
-        while section_data.limit() > 0 {
-            let c = Constraint::read(&mut section_data)?;
-            constraints.push(c);
-        }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
 
-        Ok(Constraints(constraints))
+    /// The constraint at `index`, `None` past the end.
+    pub fn get(&self, index: usize) -> Option<&Constraint<FS>> {
+        self.0.get(index)
     }
 
-    fn write<W: Write>(&self, mut w: W) -> Result<()> {
-        let header = SectionHeader {
-            ty: SectionType::Constraint,
-            size: self.0.iter().map(|c| c.size()).sum::<usize>() as u64,
+    pub fn iter(&self) -> core::slice::Iter<'_, Constraint<FS>> {
+        self.0.iter()
+    }
+
+    /// Reads a section body, appending to the constraints read so far. Returns the size of a
+    /// constraint cut off by the end of the section (zero if the section holds whole constraints
+    /// only).
+    ///
+    /// Constraints decoded before an error are kept. `total` is the header constraint count, only
+    /// used for progress reporting.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "r1cs_constraints",
+            level = "trace",
+            skip_all,
+            fields(size = section_header.size, n_constraints = tracing::field::Empty)
+        )
+    )]
+    fn read_into<R: Read>(
+        &mut self,
+        r: &mut CountingReader<R>,
+        section_header: &SectionHeader,
+        opts: &ReadOptions,
+        total: u64,
+    ) -> Result<u64> {
+        #[cfg(feature = "rayon")]
+        if opts.parallel {
+            return self.read_parallel(r, section_header, opts, total);
+        }
+
+        let mut section_data = r.take(section_header.size);
+        let progress = |constraints_read: usize, bytes_read: u64| Progress {
+            section: SectionType::Constraint,
+            bytes_read,
+            constraints_read: constraints_read as u64,
+            total_constraints: total,
         };
 
-        header.write(&mut w)?;
+        let first = self.0.len();
+        let mut partial = 0;
+        while section_data.limit() > 0 {
+            let remaining = section_data.limit();
+            let offset = section_data.get_ref().position();
+            opts.check_limit(Limit::Constraints, self.0.len() as u64 + 1, offset)?;
+
+            match Constraint::read(&mut section_data, opts) {
+                Ok(Some(c)) => {
+                    self.0.push(c);
+                    if let Some(hook) = &opts.progress {
+                        let (read, bytes_read) = (self.0.len(), section_data.get_ref().position());
+                        hook.tick((read - first) as u64, || progress(read, bytes_read));
+                    }
+                }
+                // The section ran out, not the input.
+                Ok(None) => {
+                    partial = remaining;
+                    io::copy(&mut section_data, &mut io::sink())?;
+                    if section_data.limit() > 0 {
+                        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+                    }
+                }
+                Err(R1csError::Io(e))
+                    if e.kind() == io::ErrorKind::UnexpectedEof && section_data.limit() == 0 =>
+                {
+                    partial = remaining;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("n_constraints", self.0.len() - first);
 
-        for c in &self.0 {
-            c.write(&mut w)?;
+        if let Some(hook) = &opts.progress {
+            let read = self.0.len() - first;
+            hook.finish(read as u64, progress(self.0.len(), r.position()));
         }
 
-        Ok(())
+        Ok(partial)
     }
-}
 
-#[derive(Debug, Default, PartialEq, Eq)]
-pub struct Constraint<const FS: usize>(
-    pub Vec<(FieldElement<FS>, u32)>,
-    pub Vec<(FieldElement<FS>, u32)>,
-    pub Vec<(FieldElement<FS>, u32)>,
-);
+    /// [`Constraints::read_into`] decoding the section in parallel once it is in memory.
+    #[cfg(feature = "rayon")]
+    fn read_parallel<R: Read>(
+        &mut self,
+        r: &mut CountingReader<R>,
+        section_header: &SectionHeader,
+        opts: &ReadOptions,
+        total: u64,
+    ) -> Result<u64> {
+        use core::convert::TryFrom;
+        use rayon::prelude::*;
+
+        let offset = r.position();
+        let mut data = Vec::new();
+        r.take(section_header.size).read_to_end(&mut data)?;
+        if (data.len() as u64) < section_header.size {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
 
-impl<const FS: usize> Constraint<FS> {
-    fn read<R: Read>(mut r: R) -> Result<Self> {
-        let a = Self::read_combination(&mut r)?;
-        let b = Self::read_combination(&mut r)?;
-        let c = Self::read_combination(&mut r)?;
+        // Find the constraint boundaries from the term counts alone, which is cheap compared to
+        // decoding the terms.
+        let mut bounds = Vec::new();
+        let mut start = 0;
+        let mut partial = 0;
+        'scan: while start < data.len() {
+            let n = self.0.len() + bounds.len() + 1;
+            opts.check_limit(Limit::Constraints, n as u64, offset + start as u64)?;
+
+            let mut end = start as u64;
+            for _ in 0..3 {
+                let count = match data.get(end as usize..end as usize + 4) {
+                    Some(count) => u32::from_le_bytes(<[u8; 4]>::try_from(count).unwrap()),
+                    None => {
+                        partial = (data.len() - start) as u64;
+                        break 'scan;
+                    }
+                };
+                opts.check_limit(Limit::TermsPerLc, u64::from(count), offset + end)?;
 
-        Ok(Constraint(a, b, c))
-    }
+                end += 4 + u64::from(count) * (4 + FS as u64);
+                if end > data.len() as u64 {
+                    partial = (data.len() - start) as u64;
+                    break 'scan;
+                }
+            }
 
-    fn read_combination<R: Read>(mut r: R) -> Result<Vec<(FieldElement<FS>, u32)>> {
-        let n = r.read_u32::<LittleEndian>()?;
-        let mut factors = Vec::new();
+            bounds.push(start..end as usize);
+            start = end as usize;
+        }
 
-        for _ in 0..n {
-            let index = r.read_u32::<LittleEndian>()?;
-            let factor = FieldElement::read(&mut r)?;
-            factors.push((factor, index));
+        let constraints = bounds
+            .into_par_iter()
+            .map(|range| {
+                let mut r = CountingReader {
+                    inner: &data[range.clone()],
+                    position: offset + range.start as u64,
+                };
+                let mut constraint_data = (&mut r).take(range.len() as u64);
+                Constraint::read(&mut constraint_data, opts)
+                    .map(|c| c.expect("constraint bounds are checked by the scan"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("n_constraints", constraints.len());
+
+        self.0.extend(constraints);
+
+        if let Some(hook) = &opts.progress {
+            hook.report(Progress {
+                section: SectionType::Constraint,
+                bytes_read: r.position(),
+                constraints_read: self.0.len() as u64,
+                total_constraints: total,
+            });
         }
 
-        Ok(factors)
+        Ok(partial)
     }
 
     fn write<W: Write>(&self, mut w: W) -> Result<()> {
-        let mut write = |comb: &Vec<(FieldElement<FS>, u32)>| -> Result<()> {
-            w.write_u32::<LittleEndian>(comb.len() as u32)?;
+        for c in &self.0 {
+            c.write(&mut w)?;
+        }
 
-            for (factor, index) in comb {
-                w.write_u32::<LittleEndian>(*index)?;
-                factor.write(&mut w)?;
-            }
+        Ok(())
+    }
 
-            Ok(())
-        };
+    /// Size of the section body.
+    fn size(&self) -> u64 {
+        self.0.iter().map(|c| c.size() as u64).sum()
+    }
+}
 
-        write(&self.0)?;
-        write(&self.1)?;
-        write(&self.2)?;
+impl<const FS: usize> core::ops::Index<usize> for Constraints<FS> {
+    type Output = Constraint<FS>;
 
-        Ok(())
+    fn index(&self, index: usize) -> &Constraint<FS> {
+        &self.0[index]
     }
+}
 
-    fn size(&self) -> usize {
-        let a = self.0.iter().map(|(f, _)| f.len()).sum::<usize>() + self.0.len() * 4;
-        let b = self.1.iter().map(|(f, _)| f.len()).sum::<usize>() + self.1.len() * 4;
-        let c = self.2.iter().map(|(f, _)| f.len()).sum::<usize>() + self.2.len() * 4;
+impl<const FS: usize> IntoIterator for Constraints<FS> {
+    type Item = Constraint<FS>;
+    type IntoIter = alloc::vec::IntoIter<Constraint<FS>>;
 
-        a + b + c + 3 * 4
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
     }
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
-pub struct WireMap(pub Vec<u64>);
+impl<'a, const FS: usize> IntoIterator for &'a Constraints<FS> {
+    type Item = &'a Constraint<FS>;
+    type IntoIter = core::slice::Iter<'a, Constraint<FS>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Constraint<const FS: usize>(
+    pub LinearCombination<FS>,
+    pub LinearCombination<FS>,
+    pub LinearCombination<FS>,
+);
+
+/// One of the three matrices of the R1CS, each constraint holding a row of each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Matrix {
+    A,
+    B,
+    C,
+}
+
+impl Matrix {
+    pub const ALL: [Matrix; 3] = [Matrix::A, Matrix::B, Matrix::C];
+}
+
+impl core::fmt::Display for Matrix {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let name = match self {
+            Matrix::A => "A",
+            Matrix::B => "B",
+            Matrix::C => "C",
+        };
+
+        f.write_str(name)
+    }
+}
+
+impl<const FS: usize> Constraint<FS> {
+    /// The constraint `a * b = c`.
+    pub fn new(
+        a: impl Into<LinearCombination<FS>>,
+        b: impl Into<LinearCombination<FS>>,
+        c: impl Into<LinearCombination<FS>>,
+    ) -> Self {
+        Constraint(a.into(), b.into(), c.into())
+    }
+
+    pub fn a(&self) -> &LinearCombination<FS> {
+        &self.0
+    }
+
+    pub fn b(&self) -> &LinearCombination<FS> {
+        &self.1
+    }
+
+    pub fn c(&self) -> &LinearCombination<FS> {
+        &self.2
+    }
+
+    pub fn a_mut(&mut self) -> &mut LinearCombination<FS> {
+        &mut self.0
+    }
+
+    pub fn b_mut(&mut self) -> &mut LinearCombination<FS> {
+        &mut self.1
+    }
+
+    pub fn c_mut(&mut self) -> &mut LinearCombination<FS> {
+        &mut self.2
+    }
+
+    /// Iterates over the terms of A, B and C in that order, tagged with their matrix.
+    pub fn terms(&self) -> impl Iterator<Item = (Matrix, Term<'_, FS>)> {
+        Matrix::ALL.iter().flat_map(move |&matrix| {
+            self.combination(matrix)
+                .iter_terms()
+                .map(move |term| (matrix, term))
+        })
+    }
+
+    /// Total number of terms in the three linear combinations.
+    pub fn num_terms(&self) -> usize {
+        self.0.len() + self.1.len() + self.2.len()
+    }
+
+    /// Whether all three linear combinations are empty.
+    pub fn is_empty(&self) -> bool {
+        self.num_terms() == 0
+    }
+
+    /// The linear combination of this constraint in `matrix`.
+    pub fn combination(&self, matrix: Matrix) -> &LinearCombination<FS> {
+        match matrix {
+            Matrix::A => &self.0,
+            Matrix::B => &self.1,
+            Matrix::C => &self.2,
+        }
+    }
+
+    /// The constraint with the terms of each linear combination sorted by wire index, then by
+    /// coefficient value.
+    pub(crate) fn sorted_terms(&self) -> Self {
+        let mut c = self.clone();
+        for combination in [&mut c.0, &mut c.1, &mut c.2] {
+            combination.sort_by(|(a, wire_a), (b, wire_b)| (wire_a, a).cmp(&(wire_b, b)));
+        }
+
+        c
+    }
+
+    /// Reads a constraint, `None` if its term counts run past the end of the section.
+    fn read<R: Read>(
+        r: &mut io::Take<&mut CountingReader<R>>,
+        opts: &ReadOptions,
+    ) -> Result<Option<Self>> {
+        let a = match Self::read_combination(r, opts)? {
+            Some(a) => a,
+            None => return Ok(None),
+        };
+        let b = match Self::read_combination(r, opts)? {
+            Some(b) => b,
+            None => return Ok(None),
+        };
+        let c = match Self::read_combination(r, opts)? {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+
+        Ok(Some(Constraint(a, b, c)))
+    }
+
+    fn read_combination<R: Read>(
+        r: &mut io::Take<&mut CountingReader<R>>,
+        opts: &ReadOptions,
+    ) -> Result<Option<LinearCombination<FS>>> {
+        let offset = r.get_ref().position();
+        let n = r.read_u32::<LittleEndian>()?;
+        opts.check_limit(Limit::TermsPerLc, u64::from(n), offset)?;
+
+        // Validate the count before reading any term.
+        if u64::from(n) * (4 + FS as u64) > r.limit() {
+            return Ok(None);
+        }
+
+        // The section size is untrusted too, grow past the bound as terms are actually read.
+        let mut factors = Vec::with_capacity(u64::from(n).min(MAX_PREALLOCATION) as usize);
+
+        for _ in 0..n {
+            let index = r.read_u32::<LittleEndian>()?;
+            let factor = read_element(&mut *r)?;
+            factors.push((factor, index));
+        }
+
+        Ok(Some(LinearCombination(factors)))
+    }
+
+    fn write<W: Write>(&self, mut w: W) -> Result<()> {
+        let mut write = |comb: &LinearCombination<FS>| -> Result<()> {
+            w.write_u32::<LittleEndian>(comb.len() as u32)?;
+
+            for (factor, index) in comb {
+                w.write_u32::<LittleEndian>(*index)?;
+                w.write_all(factor.as_bytes())?;
+            }
+
+            Ok(())
+        };
+
+        write(&self.0)?;
+        write(&self.1)?;
+        write(&self.2)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        3 * 4 + self.num_terms() * (4 + FS)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct WireMap(pub Vec<u64>);
 
 impl WireMap {
-    fn read<R: Read>(mut r: R, section_header: &SectionHeader) -> Result<Self> {
+    /// The map of `n_wires` wires to labels `0..n_wires`, as emitted for unoptimized circuits.
+    pub fn identity(n_wires: u32) -> Self {
+        WireMap((0..u64::from(n_wires)).collect())
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.0
+            .iter()
+            .enumerate()
+            .all(|(wire, label)| wire as u64 == *label)
+    }
+
+    /// The label of `wire`, `None` if the wire is not in the map.
+    pub fn label_of(&self, wire: u32) -> Option<u64> {
+        self.0.get(wire as usize).copied()
+    }
+
+    /// The number of wires in the map.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The labels of the wires, in wire order.
+    pub fn iter(&self) -> core::slice::Iter<'_, u64> {
+        self.0.iter()
+    }
+
+    /// Builds the label -> wire map, failing with a [`ValidationError::DuplicateLabel`] for every
+    /// label several wires map to. See [`WireMap::reverse`] to keep all of them instead.
+    #[cfg(feature = "std")]
+    pub fn inverse(&self) -> Result<HashMap<u64, u32>, Vec<ValidationError>> {
+        let mut inverse = HashMap::with_capacity(self.0.len());
+        let mut duplicates: BTreeMap<u64, Vec<u32>> = BTreeMap::new();
+        for (wire, label) in self.0.iter().enumerate() {
+            match inverse.entry(*label) {
+                Entry::Vacant(entry) => {
+                    entry.insert(wire as u32);
+                }
+                Entry::Occupied(entry) => duplicates
+                    .entry(*label)
+                    .or_insert_with(|| vec![*entry.get()])
+                    .push(wire as u32),
+            }
+        }
+
+        if duplicates.is_empty() {
+            return Ok(inverse);
+        }
+
+        Err(duplicates
+            .into_iter()
+            .map(|(label, wires)| ValidationError::DuplicateLabel { label, wires })
+            .collect())
+    }
+
+    /// Builds the label -> wires index.
+    ///
+    /// Several wires may map to the same label after optimization, so every wire is kept.
+    pub fn reverse(&self) -> ReverseWireMap {
+        let mut wires: BTreeMap<u64, Vec<u32>> = BTreeMap::new();
+        for (wire, label) in self.0.iter().enumerate() {
+            wires.entry(*label).or_default().push(wire as u32);
+        }
+
+        ReverseWireMap(wires)
+    }
+
+    /// Reads the section body, reporting progress from the `progress` template.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "r1cs_wire_map",
+            level = "trace",
+            skip_all,
+            fields(size = section_header.size)
+        )
+    )]
+    fn read<R: Read>(
+        r: &mut CountingReader<R>,
+        section_header: &SectionHeader,
+        mut progress: Option<(&ProgressHook, Progress)>,
+    ) -> Result<Self> {
         let num_labels = section_header.size / 8;
-        let mut label_ids = Vec::with_capacity(num_labels as usize);
+        let mut label_ids = Vec::with_capacity(num_labels.min(MAX_PREALLOCATION) as usize);
 
         for _ in 0..num_labels {
             label_ids.push(r.read_u64::<LittleEndian>()?);
+            if let Some((hook, p)) = &mut progress {
+                p.bytes_read = r.position();
+                hook.tick(label_ids.len() as u64, || *p);
+            }
+        }
+
+        if let Some((hook, mut p)) = progress {
+            p.bytes_read = r.position();
+            hook.finish(label_ids.len() as u64, p);
         }
 
         Ok(WireMap(label_ids))
     }
 
     fn write<W: Write>(&self, mut w: W) -> Result<()> {
-        let header = SectionHeader {
-            ty: SectionType::Wire2LabelIdMap,
-            size: self.0.len() as u64 * 8,
-        };
-
-        header.write(&mut w)?;
-
         for label_id in &self.0 {
             w.write_u64::<LittleEndian>(*label_id)?;
         }
 
         Ok(())
     }
+
+    /// Size of the section body.
+    fn size(&self) -> u64 {
+        self.0.len() as u64 * 8
+    }
 }
 
-struct SectionHeader {
-    ty: SectionType,
-    size: u64,
+impl<'a> IntoIterator for &'a WireMap {
+    type Item = &'a u64;
+    type IntoIter = core::slice::Iter<'a, u64>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
 }
 
-impl SectionHeader {
-    fn read<R: Read>(mut r: R) -> Result<Self> {
-        let ty = SectionType::read(&mut r)?;
-        let size = r.read_u64::<LittleEndian>()?;
+/// Label -> wires index built by [`WireMap::reverse`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReverseWireMap(BTreeMap<u64, Vec<u32>>);
+
+impl ReverseWireMap {
+    /// The lowest wire mapped to `label`.
+    pub fn first(&self, label: u64) -> Option<u32> {
+        self.all(label).first().copied()
+    }
+
+    /// The highest wire mapped to `label`.
+    pub fn last(&self, label: u64) -> Option<u32> {
+        self.all(label).last().copied()
+    }
+
+    /// All wires mapped to `label` in ascending order, empty if the label is unknown.
+    pub fn all(&self, label: u64) -> &[u32] {
+        self.0.get(&label).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Iterates over labels in ascending order together with their wires.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &[u32])> {
+        self.0
+            .iter()
+            .map(|(label, wires)| (*label, wires.as_slice()))
+    }
+
+    /// Number of distinct labels.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// The custom gates list section: templates implemented as custom gates and their parameters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct CustomGatesList<const FS: usize>(pub Vec<CustomGate<FS>>);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CustomGate<const FS: usize> {
+    pub name: String,
+    pub parameters: Vec<FieldElement<FS>>,
+}
 
-        Ok(SectionHeader { ty, size })
+impl<const FS: usize> CustomGatesList<FS> {
+    fn read<R: Read>(r: &mut CountingReader<R>, section_header: &SectionHeader) -> Result<Self> {
+        let mut section_data = r.take(section_header.size);
+
+        let n = section_data.read_u32::<LittleEndian>()?;
+        let mut gates = Vec::new();
+
+        for _ in 0..n {
+            let offset = section_data.get_ref().position();
+            let name = read_c_string(&mut section_data).ok_or(R1csError::InvalidString {
+                section: SectionType::CustomGatesList,
+                offset,
+            })??;
+            let n_parameters = section_data.read_u32::<LittleEndian>()?;
+
+            let mut parameters = Vec::new();
+            for _ in 0..n_parameters {
+                parameters.push(read_element(&mut section_data)?);
+            }
+
+            gates.push(CustomGate { name, parameters });
+        }
+
+        ensure_consumed(&section_data, SectionType::CustomGatesList)?;
+
+        Ok(CustomGatesList(gates))
     }
 
     fn write<W: Write>(&self, mut w: W) -> Result<()> {
-        w.write_u32::<LittleEndian>(self.ty as u32)?;
-        w.write_u64::<LittleEndian>(self.size)?;
+        w.write_u32::<LittleEndian>(self.0.len() as u32)?;
+        for gate in &self.0 {
+            w.write_all(gate.name.as_bytes())?;
+            w.write_u8(0)?;
+            w.write_u32::<LittleEndian>(gate.parameters.len() as u32)?;
+
+            for parameter in &gate.parameters {
+                w.write_all(parameter.as_bytes())?;
+            }
+        }
 
         Ok(())
     }
+
+    /// Size of the section body.
+    fn size(&self) -> u64 {
+        let gates = self
+            .0
+            .iter()
+            .map(|gate| gate.name.len() + 1 + 4 + gate.parameters.len() * FS)
+            .sum::<usize>();
+
+        4 + gates as u64
+    }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-#[repr(u32)]
-enum SectionType {
-    Header = 1,
-    Constraint = 2,
-    Wire2LabelIdMap = 3,
-    Unknown = u32::MAX,
+/// The custom gates application section: which signals each custom gate is applied to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct CustomGatesApplication(pub Vec<CustomGateApplication>);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CustomGateApplication {
+    /// Index into [`CustomGatesList`].
+    pub gate: u32,
+    pub signals: Vec<u64>,
 }
 
-impl SectionType {
-    fn read<R: Read>(mut r: R) -> Result<Self> {
-        let num = r.read_u32::<LittleEndian>()?;
+impl CustomGatesApplication {
+    fn read<R: Read>(r: &mut CountingReader<R>, section_header: &SectionHeader) -> Result<Self> {
+        let mut section_data = r.take(section_header.size);
 
-        let ty = match num {
-            1 => SectionType::Header,
-            2 => SectionType::Constraint,
-            3 => SectionType::Wire2LabelIdMap,
-            _ => SectionType::Unknown,
-        };
+        let n = section_data.read_u32::<LittleEndian>()?;
+        let mut applications = Vec::new();
+
+        for _ in 0..n {
+            let gate = section_data.read_u32::<LittleEndian>()?;
+            let n_signals = section_data.read_u32::<LittleEndian>()?;
+
+            let mut signals = Vec::new();
+            for _ in 0..n_signals {
+                signals.push(section_data.read_u64::<LittleEndian>()?);
+            }
+
+            applications.push(CustomGateApplication { gate, signals });
+        }
+
+        ensure_consumed(&section_data, SectionType::CustomGatesApplication)?;
 
-        Ok(ty)
+        Ok(CustomGatesApplication(applications))
+    }
+
+    fn write<W: Write>(&self, mut w: W) -> Result<()> {
+        w.write_u32::<LittleEndian>(self.0.len() as u32)?;
+        for application in &self.0 {
+            w.write_u32::<LittleEndian>(application.gate)?;
+            w.write_u32::<LittleEndian>(application.signals.len() as u32)?;
+
+            for signal in &application.signals {
+                w.write_u64::<LittleEndian>(*signal)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Size of the section body.
+    fn size(&self) -> u64 {
+        let applications = self
+            .0
+            .iter()
+            .map(|application| 4 + 4 + application.signals.len() * 8)
+            .sum::<usize>();
+
+        4 + applications as u64
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct FieldElement<const FS: usize>([u8; FS]);
+/// Reads the `FS` little-endian bytes of an element, like [`FieldElement::read`] but through
+/// [`iden3_binfile::io`].
+fn read_element<R: Read, const FS: usize>(mut r: R) -> io::Result<FieldElement<FS>> {
+    let mut bytes = [0u8; FS];
+    r.read_exact(&mut bytes)?;
+
+    Ok(FieldElement::from(bytes))
+}
 
-impl<const FS: usize> FieldElement<FS> {
-    pub fn as_bytes(&self) -> &[u8] {
-        &self.0[..]
+/// Reads a null-terminated string, returning `None` if it isn't valid UTF-8.
+fn read_c_string<R: Read>(mut r: R) -> Option<Result<String>> {
+    let mut bytes = Vec::new();
+    loop {
+        match r.read_u8() {
+            Ok(0) => break,
+            Ok(b) => bytes.push(b),
+            Err(e) => return Some(Err(e.into())),
+        }
     }
 
-    fn read<R: Read>(mut r: R) -> Result<Self> {
-        let mut buf = [0; FS];
-        r.read_exact(&mut buf)?;
+    String::from_utf8(bytes).ok().map(Ok)
+}
 
-        Ok(FieldElement(buf))
+/// Checks that a section body was read completely.
+fn ensure_consumed<R>(
+    section_data: &io::Take<&mut CountingReader<R>>,
+    section: SectionType,
+) -> Result<()> {
+    if section_data.limit() != 0 {
+        return Err(R1csError::TrailingSectionData {
+            section,
+            offset: section_data.get_ref().position(),
+        });
     }
 
-    fn write<W: Write>(&self, mut w: W) -> Result<()> {
-        w.write_all(&self.0[..])
+    Ok(())
+}
+
+/// A section of a type this crate doesn't interpret, kept as raw bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CustomSection {
+    pub id: u32,
+    pub data: Vec<u8>,
+}
+
+impl CustomSection {
+    fn read<R: Read>(r: &mut CountingReader<R>, section_header: &SectionHeader) -> Result<Self> {
+        let mut data = Vec::new();
+        r.take(section_header.size).read_to_end(&mut data)?;
+
+        if data.len() as u64 != section_header.size {
+            return Err(R1csError::UnexpectedEof {
+                section: None,
+                offset: r.position(),
+            });
+        }
+
+        Ok(CustomSection {
+            id: section_header.id,
+            data,
+        })
     }
 }
 
-impl<const FS: usize> From<[u8; FS]> for FieldElement<FS> {
-    fn from(array: [u8; FS]) -> Self {
-        FieldElement(array)
+/// A section found by [`R1csFile::scan_sections`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectionInfo {
+    /// The raw section type id.
+    pub ty: u32,
+    /// The section type, `None` for ids unknown to this crate.
+    pub known: Option<SectionType>,
+    /// Offset of the section header from the start of the file. The body follows the 12-byte
+    /// header.
+    pub offset: u64,
+    /// Size of the body in bytes.
+    pub size: u64,
+}
+
+struct SectionHeader {
+    id: u32,
+    size: u64,
+}
+
+impl SectionHeader {
+    /// The section type, `None` for unknown sections.
+    fn ty(&self) -> Option<SectionType> {
+        SectionType::from_id(self.id)
+    }
+
+    /// Skips the section body.
+    fn skip<R: Read>(&self, r: &mut CountingReader<R>) -> Result<()> {
+        let skipped = io::copy(&mut r.take(self.size), &mut io::sink())?;
+
+        if skipped != self.size {
+            return Err(R1csError::UnexpectedEof {
+                section: None,
+                offset: r.position(),
+            });
+        }
+
+        Ok(())
     }
 }
 
-impl<const FS: usize> std::ops::Deref for FieldElement<FS> {
-    type Target = [u8; FS];
+/// Section types known to this crate, with their ids in the file format.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum SectionType {
+    Header = 1,
+    Constraint = 2,
+    Wire2LabelIdMap = 3,
+    CustomGatesList = 4,
+    CustomGatesApplication = 5,
+    /// Provenance key/value pairs, an extension of this crate, see [`Metadata`]. The id is the
+    /// bytes `meta` on disk.
+    Metadata = 0x6174_656d,
+}
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+impl SectionType {
+    /// Maps a section id to its type, `None` for unknown ids.
+    pub fn from_id(id: u32) -> Option<Self> {
+        match id {
+            1 => Some(SectionType::Header),
+            2 => Some(SectionType::Constraint),
+            3 => Some(SectionType::Wire2LabelIdMap),
+            4 => Some(SectionType::CustomGatesList),
+            5 => Some(SectionType::CustomGatesApplication),
+            0x6174_656d => Some(SectionType::Metadata),
+            _ => None,
+        }
+    }
+}
+
+impl core::fmt::Display for SectionType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let name = match self {
+            SectionType::Header => "header",
+            SectionType::Constraint => "constraints",
+            SectionType::Wire2LabelIdMap => "wire map",
+            SectionType::CustomGatesList => "custom gates list",
+            SectionType::CustomGatesApplication => "custom gates application",
+            SectionType::Metadata => "metadata",
+        };
+
+        f.write_str(name)
     }
 }
 
@@ -412,6 +2166,58 @@ mod tests {
         assert_eq!(file.map.0[1], 3);
     }
 
+    #[test]
+    fn test_constraint_accessors() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        let c = &file.constraints.0[0];
+        assert_eq!(c.a().len(), 2);
+        assert_eq!(c.a()[0], (FieldElement::from_u64(3), 5));
+        assert_eq!(file.constraints.0[2].b()[0], (FieldElement::from_u64(6), 0));
+        assert!(file.constraints.0[1].c().is_empty());
+        assert_eq!(c.num_terms(), c.a().len() + c.b().len() + c.c().len());
+        assert!(!c.is_empty());
+        let (matrix, term) = c.terms().nth(2).unwrap();
+        assert_eq!((matrix, term.wire), (Matrix::B, c.b()[0].1));
+        assert_eq!(c.terms().count(), c.num_terms());
+
+        assert_eq!(file.constraints.len(), 3);
+        assert_eq!(&file.constraints[1], file.constraints.get(1).unwrap());
+        assert!(file.constraints.get(3).is_none());
+        assert_eq!((&file.constraints).into_iter().count(), 3);
+        assert_eq!(file.map.len(), 7);
+        assert_eq!(file.map.iter().nth(1), Some(&3));
+        let last = file.constraints.0[2].clone();
+        assert_eq!(file.constraints.into_iter().last(), Some(last));
+    }
+
+    #[test]
+    fn test_field_constants() {
+        assert_eq!(
+            FieldElement::<32>::bn254_scalar_prime(),
+            FieldElement::from(hex!(
+                "010000f093f5e1439170b97948e833285d588181b64550b829a031e1724e6430"
+            ))
+        );
+        assert_eq!(primes::BN254_SCALAR, FieldElement::bn254_scalar_prime());
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        assert_eq!(file.header.curve(), Some(Curve::Bn254));
+        assert_eq!(
+            FieldElement::<32>::from_u64(3),
+            FieldElement::from(hex!(
+                "0300000000000000000000000000000000000000000000000000000000000000"
+            ))
+        );
+        assert_eq!(
+            FieldElement::<32>::from_u64(6),
+            FieldElement::from(hex!(
+                "0600000000000000000000000000000000000000000000000000000000000000"
+            ))
+        );
+    }
+
     #[test]
     fn test_serialize() {
         let data = std::fs::read("tests/test_circuit.r1cs").unwrap();
@@ -424,4 +2230,1296 @@ mod tests {
         assert_eq!(data.len(), serialized_file.len());
         assert_eq!(data, serialized_file);
     }
+
+    /// Splits a serialized file into its preamble and its raw sections (header included).
+    fn split_sections(data: &[u8]) -> (Vec<u8>, Vec<Vec<u8>>) {
+        let preamble = data[..12].to_vec();
+        let mut sections = Vec::new();
+        let mut rest = &data[12..];
+
+        while !rest.is_empty() {
+            let size = <LittleEndian as byteorder::ByteOrder>::read_u64(&rest[4..12]) as usize;
+            sections.push(rest[..12 + size].to_vec());
+            rest = &rest[12 + size..];
+        }
+
+        (preamble, sections)
+    }
+
+    #[test]
+    fn test_read_reordered_sections() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let expected = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        // Wire map, then constraints, then header.
+        let (preamble, sections) = split_sections(&data);
+        let reordered: Vec<u8> = std::iter::once(preamble)
+            .chain(sections.into_iter().rev())
+            .flatten()
+            .collect();
+        assert_ne!(reordered, data);
+
+        let file = R1csFile::<32>::read_seek(std::io::Cursor::new(&reordered)).unwrap();
+        assert_eq!(file, expected);
+        assert_eq!(
+            R1csFile::<32>::read(reordered.as_slice()).unwrap(),
+            expected
+        );
+
+        // Writing restores the spec order.
+        let mut serialized = Vec::new();
+        file.write(&mut serialized).unwrap();
+        assert_eq!(serialized, data);
+    }
+
+    #[test]
+    fn test_missing_wire_map() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let original = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        // The header and constraints sections only.
+        let (mut preamble, sections) = split_sections(&data);
+        preamble[8..12].copy_from_slice(&2u32.to_le_bytes());
+        let stripped: Vec<u8> = std::iter::once(preamble)
+            .chain(sections[..2].iter().cloned())
+            .flatten()
+            .collect();
+
+        let file = R1csFile::<32>::read(stripped.as_slice()).unwrap();
+        assert!(file.map.is_empty());
+        assert_eq!(file.constraints, original.constraints);
+        let seek = R1csFile::<32>::read_seek(std::io::Cursor::new(&stripped)).unwrap();
+        assert_eq!(seek, file);
+        let borrowed = R1csFileRef::<32>::parse(&stripped).unwrap();
+        assert_eq!(borrowed.to_owned(), file);
+        let mut reader = R1csReader::<_, 32>::new(stripped.as_slice()).unwrap();
+        assert!(reader.wire_map().unwrap().is_empty());
+
+        assert_eq!(file.validate(), Ok(()));
+        assert_eq!(
+            *file.effective_map(),
+            WireMap::identity(file.header.n_wires)
+        );
+        assert_eq!(*original.effective_map(), original.map);
+
+        // The map section is left out on write, unless asked for.
+        let mut written = Vec::new();
+        file.write(&mut written).unwrap();
+        assert_eq!(written, stripped);
+        assert_eq!(file.serialized_size(), stripped.len());
+        let write = |file: &R1csFile<32>, wire_map| {
+            let mut written = Vec::new();
+            file.write_with(&mut written, &WriteOptions { wire_map })
+                .unwrap();
+            written
+        };
+        assert_eq!(write(&file, WireMapSection::IfPresent), stripped);
+        assert_eq!(write(&file, WireMapSection::Omit), stripped);
+        let identity = R1csFile {
+            map: WireMap::identity(file.header.n_wires),
+            ..file.clone()
+        };
+        assert_eq!(write(&file, WireMapSection::Always), identity.serialize());
+        assert_eq!(write(&original, WireMapSection::Always), data);
+        assert_eq!(write(&original, WireMapSection::Omit), stripped);
+
+        // A present map of the wrong length is still rejected.
+        let mut short = original;
+        short.map.0.pop();
+        assert!(matches!(
+            short.validate().unwrap_err()[..],
+            [ValidationError::WireMapLengthMismatch { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_custom_sections_round_trip() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let (mut preamble, mut sections) = split_sections(&data);
+
+        let custom = |id: u32, body: &[u8]| {
+            let mut section = Vec::new();
+            section.extend_from_slice(&id.to_le_bytes());
+            section.extend_from_slice(&(body.len() as u64).to_le_bytes());
+            section.extend_from_slice(body);
+            section
+        };
+        sections.insert(1, custom(42, b"vendor data"));
+        sections.push(custom(7, b""));
+        preamble[8..12].copy_from_slice(&5u32.to_le_bytes());
+        let extended: Vec<u8> = std::iter::once(preamble)
+            .chain(sections)
+            .flatten()
+            .collect();
+
+        let file = R1csFile::<32>::read(extended.as_slice()).unwrap();
+        assert_eq!(
+            file.custom_sections,
+            [
+                CustomSection {
+                    id: 42,
+                    data: b"vendor data".to_vec()
+                },
+                CustomSection {
+                    id: 7,
+                    data: vec![]
+                },
+            ]
+        );
+        assert_eq!(
+            R1csFile::<32>::read_seek(std::io::Cursor::new(&extended)).unwrap(),
+            file
+        );
+
+        let mut serialized = Vec::new();
+        file.write(&mut serialized).unwrap();
+        assert_eq!(serialized.len(), extended.len());
+        assert_eq!(R1csFile::<32>::read(serialized.as_slice()).unwrap(), file);
+
+        let opts = ReadOptions {
+            drop_unknown_sections: true,
+            ..ReadOptions::default()
+        };
+        let (dropped, _) = R1csFile::<32>::read_with(extended.as_slice(), &opts).unwrap();
+        let (dropped_seek, _) =
+            R1csFile::<32>::read_seek_with(std::io::Cursor::new(&extended), &opts).unwrap();
+        assert!(dropped.custom_sections.is_empty());
+        assert_eq!(dropped, R1csFile::<32>::read(data.as_slice()).unwrap());
+        assert_eq!(dropped_seek, dropped);
+    }
+
+    #[test]
+    fn test_scan_sections() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let (mut preamble, mut sections) = split_sections(&data);
+
+        let mut unknown = 0x4242u32.to_le_bytes().to_vec();
+        unknown.extend_from_slice(&3u64.to_le_bytes());
+        unknown.extend_from_slice(b"abc");
+        sections.insert(2, unknown);
+        preamble[8..12].copy_from_slice(&4u32.to_le_bytes());
+        let extended: Vec<u8> = std::iter::once(preamble)
+            .chain(sections)
+            .flatten()
+            .collect();
+
+        let info = |ty, known, offset, size| SectionInfo {
+            ty,
+            known,
+            offset,
+            size,
+        };
+        assert_eq!(
+            R1csFile::<32>::scan_sections(std::io::Cursor::new(&extended)).unwrap(),
+            [
+                info(1, Some(SectionType::Header), 12, 64),
+                info(2, Some(SectionType::Constraint), 88, 648),
+                info(0x4242, None, 748, 3),
+                info(3, Some(SectionType::Wire2LabelIdMap), 763, 56),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_header() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let expected = R1csFile::<32>::read(data.as_slice()).unwrap().header;
+
+        let header = R1csFile::<32>::read_header(std::io::Cursor::new(&data)).unwrap();
+        assert_eq!(header, expected);
+
+        // Header last, with the constraint section body zeroed out: the header is still found
+        // and the constraints are never decoded.
+        let (preamble, mut sections) = split_sections(&data);
+        let constraints = &mut sections[1];
+        constraints[12..].iter_mut().for_each(|b| *b = 0xff);
+        sections.rotate_left(1);
+        let reordered: Vec<u8> = std::iter::once(preamble)
+            .chain(sections)
+            .flatten()
+            .collect();
+
+        assert!(R1csFile::<32>::read(reordered.as_slice()).is_err());
+        let header = R1csFile::<32>::read_header(std::io::Cursor::new(&reordered)).unwrap();
+        assert_eq!(header, expected);
+    }
+
+    #[test]
+    fn test_error_offsets() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+
+        let mut bad_magic = data.clone();
+        bad_magic[0] = b'x';
+        assert!(matches!(
+            R1csFile::<32>::read(bad_magic.as_slice()),
+            Err(R1csError::InvalidMagic { found }) if &found == b"x1cs"
+        ));
+
+        // The header section body starts right after the preamble and the section header.
+        assert!(matches!(
+            R1csFile::<16>::read(data.as_slice()),
+            Err(R1csError::FieldSizeMismatch {
+                expected: 16,
+                found: 32,
+                offset: 24,
+            })
+        ));
+
+        let truncated = &data[..data.len() - 3];
+        match R1csFile::<32>::read(truncated) {
+            Err(R1csError::UnexpectedEof {
+                section: Some(SectionType::Wire2LabelIdMap),
+                offset,
+            }) => assert_eq!(offset, truncated.len() as u64),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+
+        let (preamble, sections) = split_sections(&data);
+        let mut duplicated: Vec<u8> = std::iter::once(preamble)
+            .chain(sections.iter().cloned())
+            .chain(std::iter::once(sections[0].clone()))
+            .flatten()
+            .collect();
+        duplicated[8..12].copy_from_slice(&4u32.to_le_bytes());
+        let second_header = (data.len() + 12) as u64;
+        assert!(matches!(
+            R1csFile::<32>::read(duplicated.as_slice()),
+            Err(R1csError::DuplicateSection {
+                section: SectionType::Header,
+                offset,
+            }) if offset == second_header
+        ));
+    }
+
+    fn read_in_mode(data: &[u8], mode: ParseMode) -> Result<(R1csFile<32>, Vec<ParseWarning>)> {
+        let opts = ReadOptions {
+            mode,
+            ..ReadOptions::default()
+        };
+        let read = R1csFile::<32>::read_with(data, &opts);
+        let read_seek = R1csFile::<32>::read_seek_with(std::io::Cursor::new(data), &opts);
+        assert_eq!(
+            read.as_ref().map_err(ToString::to_string),
+            read_seek.as_ref().map_err(ToString::to_string)
+        );
+
+        read
+    }
+
+    #[test]
+    fn test_parse_modes() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let expected = R1csFile::<32>::read(data.as_slice()).unwrap();
+        for mode in [ParseMode::Strict, ParseMode::Lenient] {
+            let (file, warnings) = read_in_mode(&data, mode).unwrap();
+            assert_eq!(file, expected);
+            assert!(warnings.is_empty());
+        }
+
+        // Header constraint count off by one.
+        let (preamble, mut sections) = split_sections(&data);
+        sections[0][72..76].copy_from_slice(&4u32.to_le_bytes());
+        let miscounted: Vec<u8> = std::iter::once(preamble.clone())
+            .chain(sections)
+            .flatten()
+            .collect();
+        let warning = ParseWarning::ConstraintCountMismatch {
+            header: 4,
+            section: 3,
+        };
+        assert!(R1csFile::<32>::read(miscounted.as_slice()).is_ok());
+        assert!(matches!(
+            read_in_mode(&miscounted, ParseMode::Strict),
+            Err(R1csError::Inconsistent(w)) if w == warning
+        ));
+        let (file, warnings) = read_in_mode(&miscounted, ParseMode::Lenient).unwrap();
+        assert_eq!(file.constraints, expected.constraints);
+        assert_eq!(warnings, [warning]);
+
+        // Constraints section extended with the start of a fourth constraint.
+        let (_, mut sections) = split_sections(&data);
+        let constraints = &mut sections[1];
+        let size = constraints.len() as u64 - 12 + 4;
+        constraints[4..12].copy_from_slice(&size.to_le_bytes());
+        constraints.extend_from_slice(&1u32.to_le_bytes());
+        let partial_offset = (preamble.len() + sections[0].len() + sections[1].len() - 4) as u64;
+        let partial: Vec<u8> = std::iter::once(preamble.clone())
+            .chain(sections)
+            .flatten()
+            .collect();
+        let warning = ParseWarning::PartialConstraint {
+            offset: partial_offset,
+            bytes: 4,
+        };
+        assert!(matches!(
+            R1csFile::<32>::read(partial.as_slice()),
+            Err(R1csError::UnexpectedEof {
+                section: Some(SectionType::Constraint),
+                ..
+            })
+        ));
+        assert!(matches!(
+            read_in_mode(&partial, ParseMode::Strict),
+            Err(R1csError::Inconsistent(w)) if w == warning
+        ));
+        let (file, warnings) = read_in_mode(&partial, ParseMode::Lenient).unwrap();
+        assert_eq!(file, expected);
+        assert_eq!(warnings, [warning]);
+
+        // Garbage after the last section.
+        let mut trailing = data.clone();
+        trailing.extend_from_slice(b"garbage");
+        let warning = ParseWarning::TrailingData {
+            offset: data.len() as u64,
+            bytes: 7,
+        };
+        assert_eq!(R1csFile::<32>::read(trailing.as_slice()).unwrap(), expected);
+        assert!(matches!(
+            read_in_mode(&trailing, ParseMode::Strict),
+            Err(R1csError::Inconsistent(w)) if w == warning
+        ));
+        let (file, warnings) = read_in_mode(&trailing, ParseMode::Lenient).unwrap();
+        assert_eq!(file, expected);
+        assert_eq!(warnings, [warning]);
+    }
+
+    #[test]
+    fn test_parse_modes_padded_prime() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let widened: R1csFile<64> =
+            file.map_coefficients_to(|coeff, _, _| coeff.widen(), |e| e.widen());
+        let mut padded = Vec::new();
+        widened.write(&mut padded).unwrap();
+        assert!(matches!(
+            widened.write_strict(&mut Vec::new()),
+            Err(R1csError::Invalid(ValidationError::PaddedPrime {
+                field_size: 64,
+                prime_bytes: 32
+            }))
+        ));
+        let mut strict = Vec::new();
+        file.write_strict(&mut strict).unwrap();
+        assert_eq!(strict, data);
+
+        let read = |mode| {
+            let opts = ReadOptions {
+                mode,
+                ..ReadOptions::default()
+            };
+            R1csFile::<64>::read_with(padded.as_slice(), &opts)
+        };
+        let warning = ParseWarning::PaddedPrime {
+            field_size: 64,
+            prime_bytes: 32,
+        };
+        assert_eq!(read(ParseMode::Unchecked).unwrap().0, widened);
+        assert!(matches!(
+            read(ParseMode::Strict),
+            Err(R1csError::Inconsistent(w)) if w == warning
+        ));
+        let (lenient, warnings) = read(ParseMode::Lenient).unwrap();
+        assert_eq!(lenient, widened);
+        assert_eq!(warnings, [warning]);
+        assert_eq!(read_in_mode(&data, ParseMode::Strict).unwrap().0, file);
+    }
+
+    #[test]
+    fn test_parse_modes_supported_sizes() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let (preamble, sections) = split_sections(&data);
+        let map_offset = (preamble.len() + sections[0].len() + sections[1].len() + 12) as u64;
+        let with_map_size = |size: u64| -> Vec<u8> {
+            let mut sections = sections.clone();
+            sections[2][4..12].copy_from_slice(&size.to_le_bytes());
+            std::iter::once(preamble.clone())
+                .chain(sections)
+                .flatten()
+                .collect()
+        };
+
+        // A wire map with one entry more than a header can describe.
+        let count = MAX_SUPPORTED_ELEMENT_COUNT + 1;
+        let huge_map = with_map_size(count * 8);
+        let warning = ParseWarning::TooManyElements {
+            section: SectionType::Wire2LabelIdMap,
+            count,
+        };
+        assert!(matches!(
+            read_in_mode(&huge_map, ParseMode::Strict),
+            Err(R1csError::Inconsistent(w)) if w == warning
+        ));
+        // Lenient reads on and runs into the end of the input.
+        assert!(matches!(
+            read_in_mode(&huge_map, ParseMode::Lenient),
+            Err(R1csError::UnexpectedEof {
+                section: Some(SectionType::Wire2LabelIdMap),
+                ..
+            })
+        ));
+        assert_eq!(
+            warning.to_string(),
+            format!(
+                "The wire map section holds {} elements, more than the supported {}",
+                count,
+                u32::MAX
+            )
+        );
+
+        // A wire map ending past the supported file size.
+        let huge_file = with_map_size(MAX_SUPPORTED_FILE_SIZE);
+        let warning = ParseWarning::FileTooLarge {
+            size: map_offset + MAX_SUPPORTED_FILE_SIZE,
+        };
+        assert!(matches!(
+            read_in_mode(&huge_file, ParseMode::Strict),
+            Err(R1csError::Inconsistent(w)) if w == warning
+        ));
+        assert!(matches!(
+            read_in_mode(&huge_file, ParseMode::Lenient),
+            Err(R1csError::UnexpectedEof { .. })
+        ));
+    }
+
+    #[test]
+    fn test_read_limits() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let (preamble, sections) = split_sections(&data);
+        let constraints_offset = (preamble.len() + sections[0].len()) as u64;
+        let limited = |opts: ReadOptions| R1csFile::<32>::read_with(data.as_slice(), &opts);
+
+        assert!(limited(ReadOptions {
+            max_constraints: Some(3),
+            max_terms_per_lc: Some(3),
+            max_section_size: Some(1024),
+            ..ReadOptions::default()
+        })
+        .is_ok());
+        assert!(matches!(
+            limited(ReadOptions {
+                max_section_size: Some(128),
+                ..ReadOptions::default()
+            }),
+            Err(R1csError::LimitExceeded {
+                limit: Limit::SectionSize,
+                max: 128,
+                offset,
+                ..
+            }) if offset == constraints_offset
+        ));
+        assert!(matches!(
+            limited(ReadOptions {
+                max_terms_per_lc: Some(2),
+                ..ReadOptions::default()
+            }),
+            Err(R1csError::LimitExceeded {
+                limit: Limit::TermsPerLc,
+                value: 3,
+                ..
+            })
+        ));
+
+        // A header claiming 2^31 constraints is rejected before the constraints are read.
+        let mut hostile_header = sections.clone();
+        hostile_header[0][72..76].copy_from_slice(&(1u32 << 31).to_le_bytes());
+        let hostile: Vec<u8> = std::iter::once(preamble.clone())
+            .chain(hostile_header)
+            .flatten()
+            .collect();
+        let opts = ReadOptions {
+            max_constraints: Some(1 << 20),
+            ..ReadOptions::default()
+        };
+        assert!(matches!(
+            R1csFile::<32>::read_with(hostile.as_slice(), &opts),
+            Err(R1csError::LimitExceeded {
+                limit: Limit::Constraints,
+                value,
+                ..
+            }) if value == 1 << 31
+        ));
+
+        // A huge term count is checked against the section size before anything is allocated.
+        let mut hostile_terms = sections;
+        hostile_terms[1][12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        let hostile: Vec<u8> = std::iter::once(preamble)
+            .chain(hostile_terms)
+            .flatten()
+            .collect();
+        assert!(matches!(
+            R1csFile::<32>::read(hostile.as_slice()),
+            Err(R1csError::UnexpectedEof {
+                section: Some(SectionType::Constraint),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_skip_many_unknown_sections() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let (mut preamble, mut sections) = split_sections(&data);
+
+        // Lots of tiny junk sections, interleaved with the real ones.
+        let n_junk = 10_000u32;
+        for i in 0..n_junk {
+            let mut junk = (1000 + i).to_le_bytes().to_vec();
+            junk.extend_from_slice(&u64::from(i % 3).to_le_bytes());
+            junk.resize(junk.len() + (i % 3) as usize, 0xaa);
+            sections.insert((i % 4) as usize, junk);
+        }
+        preamble[8..12].copy_from_slice(&(3 + n_junk).to_le_bytes());
+        let junk: Vec<u8> = std::iter::once(preamble)
+            .chain(sections)
+            .flatten()
+            .collect();
+
+        let expected = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let opts = ReadOptions {
+            drop_unknown_sections: true,
+            max_skipped_sections: Some(u64::from(n_junk)),
+            max_skipped_bytes: Some(u64::from(n_junk)),
+            ..ReadOptions::default()
+        };
+        let (file, _) = R1csFile::<32>::read_with(junk.as_slice(), &opts).unwrap();
+        assert_eq!(file, expected);
+        let (file, _) = R1csFile::<32>::read_seek_with(std::io::Cursor::new(&junk), &opts).unwrap();
+        assert_eq!(file, expected);
+
+        let opts = ReadOptions {
+            drop_unknown_sections: true,
+            max_skipped_sections: Some(100),
+            ..ReadOptions::default()
+        };
+        assert!(matches!(
+            R1csFile::<32>::read_with(junk.as_slice(), &opts),
+            Err(R1csError::LimitExceeded {
+                limit: Limit::SkippedSections,
+                value: 101,
+                ..
+            })
+        ));
+        assert!(matches!(
+            R1csFile::<32>::read_seek_with(std::io::Cursor::new(&junk), &opts),
+            Err(R1csError::LimitExceeded {
+                limit: Limit::SkippedSections,
+                value: 101,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_skip_absurd_section_size() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let (mut preamble, mut sections) = split_sections(&data);
+
+        let mut absurd = 42u32.to_le_bytes().to_vec();
+        absurd.extend_from_slice(&(u64::MAX / 2).to_le_bytes());
+        absurd.extend_from_slice(b"not nearly that much data");
+        sections.push(absurd);
+        preamble[8..12].copy_from_slice(&4u32.to_le_bytes());
+        let absurd: Vec<u8> = std::iter::once(preamble)
+            .chain(sections)
+            .flatten()
+            .collect();
+        let absurd_offset = data.len() as u64;
+
+        let opts = ReadOptions {
+            drop_unknown_sections: true,
+            max_skipped_bytes: Some(1 << 30),
+            ..ReadOptions::default()
+        };
+        for result in [
+            R1csFile::<32>::read_with(absurd.as_slice(), &opts),
+            R1csFile::<32>::read_seek_with(std::io::Cursor::new(&absurd), &opts),
+        ] {
+            assert!(matches!(
+                result,
+                Err(R1csError::LimitExceeded {
+                    limit: Limit::SkippedBytes,
+                    offset,
+                    ..
+                }) if offset == absurd_offset
+            ));
+        }
+
+        // Without a limit, skipping stops at the end of the input.
+        let opts = ReadOptions {
+            drop_unknown_sections: true,
+            ..ReadOptions::default()
+        };
+        assert!(matches!(
+            R1csFile::<32>::read_with(absurd.as_slice(), &opts),
+            Err(R1csError::UnexpectedEof { section: None, .. })
+        ));
+    }
+
+    #[test]
+    fn test_split_constraint_sections() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let split = std::fs::read("tests/split_constraints.r1cs").unwrap();
+        let expected = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        let file = R1csFile::<32>::read(split.as_slice()).unwrap();
+        assert_eq!(file, expected);
+        assert_eq!(
+            R1csFile::<32>::read_seek(std::io::Cursor::new(&split)).unwrap(),
+            expected
+        );
+
+        // Written back as a single constraint section.
+        let mut serialized = Vec::new();
+        file.write(&mut serialized).unwrap();
+        assert_eq!(serialized, data);
+
+        let mut miscounted = split;
+        miscounted[84..88].copy_from_slice(&2u32.to_le_bytes());
+        assert!(matches!(
+            R1csFile::<32>::read(miscounted.as_slice()),
+            Err(R1csError::Inconsistent(
+                ParseWarning::ConstraintCountMismatch {
+                    header: 2,
+                    section: 3,
+                }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_header() {
+        let data = std::fs::read("tests/duplicate_header.r1cs").unwrap();
+        let second_header = 12 + (12 + 64) + (12 + 648) + 12;
+
+        assert!(matches!(
+            R1csFile::<32>::read(data.as_slice()),
+            Err(R1csError::DuplicateSection {
+                section: SectionType::Header,
+                offset: o,
+            }) if o == second_header
+        ));
+        assert!(matches!(
+            R1csFile::<32>::read_header(std::io::Cursor::new(&data)),
+            Err(R1csError::DuplicateSection {
+                section: SectionType::Header,
+                offset: o,
+            }) if o == second_header
+        ));
+    }
+
+    #[test]
+    fn test_read_partial() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let expected = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        let (partial, error) = R1csFile::<32>::read_partial(data.as_slice());
+        assert!(error.is_none());
+        assert_eq!(partial.header, Some(expected.header));
+        assert_eq!(partial.constraints, expected.constraints);
+        assert_eq!(partial.map, Some(expected.map));
+        assert_eq!(partial.stopped_at, None);
+
+        // Cut off in the second constraint: the first one is kept.
+        let first_constraint = 12 + 12 + 64 + 12 + (3 * 4 + 7 * 36);
+        let truncated = &data[..first_constraint + 10];
+        let (partial, error) = R1csFile::<32>::read_partial(truncated);
+        assert!(matches!(
+            error,
+            Some(R1csError::UnexpectedEof {
+                section: Some(SectionType::Constraint),
+                ..
+            })
+        ));
+        assert!(partial.header.is_some());
+        assert_eq!(partial.constraints.0.len(), 1);
+        assert_eq!(partial.stopped_at, Some(1));
+        assert_eq!(partial.map, None);
+        assert!(R1csFile::<32>::read(truncated).is_err());
+
+        // Cut off before the wire map section.
+        let (partial, error) = R1csFile::<32>::read_partial(&data[..data.len() - 68]);
+        assert!(matches!(
+            error,
+            Some(R1csError::UnexpectedEof { section: None, .. })
+        ));
+        assert_eq!(partial.constraints.0.len(), 3);
+        assert_eq!(partial.stopped_at, None);
+    }
+
+    #[test]
+    fn test_progress() {
+        use std::sync::{Arc, Mutex};
+
+        let data = std::fs::read("tests/test_circuit.r1cs").unwrap();
+        let expected = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let opts = ReadOptions::default().progress(10, move |p| sink.lock().unwrap().push(p));
+        let (file, _) = R1csFile::<32>::read_with(data.as_slice(), &opts).unwrap();
+        assert_eq!(file, expected);
+
+        let seen = seen.lock().unwrap();
+        let n_constraints = u64::from(expected.header.n_constraints);
+        assert!(seen.len() as u64 > n_constraints / 10);
+        assert!(seen.iter().all(|p| p.total_constraints == n_constraints));
+        assert!(seen
+            .windows(2)
+            .all(|w| w[0].bytes_read < w[1].bytes_read
+                && w[0].constraints_read <= w[1].constraints_read));
+
+        let last_constraints = seen
+            .iter()
+            .rev()
+            .find(|p| p.section == SectionType::Constraint)
+            .unwrap();
+        assert_eq!(last_constraints.constraints_read, n_constraints);
+        let last = seen.last().unwrap();
+        assert_eq!(last.section, SectionType::Wire2LabelIdMap);
+        assert_eq!(last.bytes_read, data.len() as u64);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_matches_sequential() {
+        for path in [
+            "tests/simple_circuit.r1cs",
+            "tests/test_circuit.r1cs",
+            "tests/split_constraints.r1cs",
+        ] {
+            let data = std::fs::read(path).unwrap();
+            let expected = R1csFile::<32>::read(data.as_slice()).unwrap();
+            let opts = ReadOptions::default().parallel(true);
+            let (file, _) = R1csFile::<32>::read_with(data.as_slice(), &opts).unwrap();
+            assert_eq!(file, expected);
+        }
+
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let limited = |parallel| ReadOptions {
+            max_terms_per_lc: Some(2),
+            ..ReadOptions::default().parallel(parallel)
+        };
+        let sequential = R1csFile::<32>::read_with(data.as_slice(), &limited(false)).unwrap_err();
+        let parallel = R1csFile::<32>::read_with(data.as_slice(), &limited(true)).unwrap_err();
+        assert_eq!(sequential.to_string(), parallel.to_string());
+
+        let truncated = &data[..data.len() - 100];
+        let opts = ReadOptions::default().parallel(true);
+        assert!(matches!(
+            R1csFile::<32>::read_with(truncated, &opts),
+            Err(R1csError::UnexpectedEof {
+                section: Some(SectionType::Constraint),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_write_checks_header() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        let check = |f: &dyn Fn(&mut R1csFile<32>), expected: ValidationError| {
+            let mut broken = file.clone();
+            f(&mut broken);
+
+            let mut written = Vec::new();
+            match broken.write(&mut written) {
+                Err(R1csError::Invalid(e)) => assert_eq!(e, expected),
+                other => panic!("unexpected result: {:?}", other),
+            }
+            assert!(written.is_empty());
+
+            broken.write_unchecked(&mut written).unwrap();
+            assert_eq!(written, broken.serialize());
+        };
+
+        check(
+            &|f| f.header.n_constraints = 5,
+            ValidationError::ConstraintCountMismatch {
+                header: 5,
+                found: 3,
+            },
+        );
+        check(
+            &|f| {
+                f.map.0.pop();
+            },
+            ValidationError::WireMapLengthMismatch {
+                n_wires: 7,
+                found: 6,
+            },
+        );
+        check(
+            &|f| f.header.n_prvt_in = 4,
+            ValidationError::TooManySignals {
+                signals: 8,
+                n_wires: 7,
+            },
+        );
+    }
+
+    #[test]
+    fn test_serialized_size() {
+        for path in ["tests/simple_circuit.r1cs", "tests/test_circuit.r1cs"] {
+            let data = std::fs::read(path).unwrap();
+            let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+            assert_eq!(file.serialized_size(), data.len());
+            assert_eq!(file.serialize(), data);
+        }
+
+        let mut file = R1csFile::<8> {
+            header: Header {
+                prime: FieldElement::goldilocks_prime(),
+                n_wires: 2,
+                n_pub_out: 0,
+                n_pub_in: 1,
+                n_prvt_in: 0,
+                n_labels: 2,
+                n_constraints: 2,
+            },
+            constraints: Constraints(vec![
+                Constraint::default(),
+                Constraint(
+                    LinearCombination::term(FieldElement::from_u64(1), 1),
+                    LinearCombination::new(),
+                    LinearCombination::new(),
+                ),
+            ]),
+            map: WireMap(vec![]),
+            custom_gates_list: None,
+            custom_gates_application: None,
+            metadata: None,
+            custom_sections: vec![CustomSection {
+                id: 42,
+                data: vec![1, 2, 3],
+            }],
+            version: VERSION,
+        };
+        assert_eq!(file.serialized_size(), file.serialize().len());
+
+        file.custom_gates_list = Some(CustomGatesList(vec![CustomGate {
+            name: "G".to_owned(),
+            parameters: vec![FieldElement::from_u64(3)],
+        }]));
+        file.custom_gates_application = Some(CustomGatesApplication(vec![CustomGateApplication {
+            gate: 0,
+            signals: vec![1],
+        }]));
+        assert_eq!(file.serialized_size(), file.serialize().len());
+    }
+
+    #[test]
+    fn test_write_canonical() {
+        let one = FieldElement::<8>::from_u64(1);
+        let two = FieldElement::from_u64(2);
+        let mut a = R1csFile::<8> {
+            header: Header {
+                prime: FieldElement::goldilocks_prime(),
+                n_wires: 3,
+                n_pub_out: 0,
+                n_pub_in: 1,
+                n_prvt_in: 0,
+                n_labels: 3,
+                n_constraints: 2,
+            },
+            constraints: Constraints(vec![
+                Constraint::new(vec![(one, 2), (two, 1), (one, 1)], vec![(one, 0)], vec![]),
+                Constraint::new(vec![(two, 0)], vec![(one, 2), (one, 0)], vec![(one, 1)]),
+            ]),
+            map: WireMap::identity(3),
+            custom_gates_list: None,
+            custom_gates_application: None,
+            metadata: Some(Metadata::from([("k".to_owned(), "v".to_owned())])),
+            custom_sections: vec![
+                CustomSection {
+                    id: 42,
+                    data: vec![1],
+                },
+                CustomSection {
+                    id: 7,
+                    data: vec![2],
+                },
+            ],
+            version: VERSION,
+        };
+        let mut b = a.clone();
+        for c in &mut b.constraints.0 {
+            for combination in [&mut c.0, &mut c.1, &mut c.2] {
+                combination.reverse();
+            }
+        }
+        b.custom_sections.reverse();
+
+        let written = |file: &R1csFile<8>, canonical: bool| {
+            let mut data = Vec::new();
+            if canonical {
+                file.write_canonical(&mut data).unwrap();
+            } else {
+                file.write(&mut data).unwrap();
+            }
+            data
+        };
+        let before = a.clone();
+        assert_eq!(written(&a, true), written(&b, true));
+        assert_eq!(a, before);
+        assert_ne!(written(&a, false), written(&b, false));
+        assert_eq!(
+            R1csFile::<8>::read(written(&a, false).as_slice()).unwrap(),
+            a
+        );
+
+        let canonical = R1csFile::<8>::read(written(&b, true).as_slice()).unwrap();
+        assert_eq!(
+            canonical.constraints[0].a().to_vec(),
+            [(one, 1), (two, 1), (one, 2)]
+        );
+        let ids: Vec<_> = R1csFile::<8>::scan_sections(std::io::Cursor::new(written(&b, true)))
+            .unwrap()
+            .iter()
+            .map(|s| s.ty)
+            .collect();
+        assert_eq!(ids, [1, 2, 3, 7, 42, SectionType::Metadata as u32]);
+
+        a.header.n_constraints = 3;
+        assert!(matches!(
+            a.write_canonical(Vec::new()),
+            Err(R1csError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_custom_gates_round_trip() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let mut file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        file.custom_gates_list = Some(CustomGatesList(vec![
+            CustomGate {
+                name: "CMul".to_owned(),
+                parameters: vec![],
+            },
+            CustomGate {
+                name: "Poseidon12".to_owned(),
+                parameters: vec![FieldElement::from_u64(12), FieldElement::from_u64(8)],
+            },
+        ]));
+        file.custom_gates_application = Some(CustomGatesApplication(vec![
+            CustomGateApplication {
+                gate: 1,
+                signals: vec![4, 5, 6],
+            },
+            CustomGateApplication {
+                gate: 0,
+                signals: vec![],
+            },
+        ]));
+
+        let mut serialized = Vec::new();
+        file.write(&mut serialized).unwrap();
+
+        // Custom gates require version 2, whatever the version of the file read.
+        assert_eq!(file.version, 1);
+        assert_eq!(&serialized[4..12], [2, 0, 0, 0, 5, 0, 0, 0]);
+        let read = R1csFile::<32>::read(serialized.as_slice()).unwrap();
+        assert_eq!(read.version, 2);
+        assert_eq!(
+            read,
+            R1csFile {
+                version: 2,
+                ..file.clone()
+            }
+        );
+        assert_eq!(
+            R1csFile::<32>::read_seek(std::io::Cursor::new(&serialized)).unwrap(),
+            read
+        );
+
+        // Without the custom gates the file is written with its own version.
+        file.custom_gates_list = None;
+        file.custom_gates_application = None;
+        let mut serialized = Vec::new();
+        file.write(&mut serialized).unwrap();
+        assert_eq!(serialized, data);
+        file.version = 2;
+        let mut serialized = Vec::new();
+        file.write(&mut serialized).unwrap();
+        assert_eq!(&serialized[4..8], [2, 0, 0, 0]);
+        assert_eq!(R1csFile::<32>::read(serialized.as_slice()).unwrap(), file);
+
+        file.version = 3;
+        assert_eq!(
+            file.check_header(),
+            Err(ValidationError::UnsupportedVersion { version: 3 })
+        );
+    }
+
+    #[test]
+    fn test_circom2_round_trip() {
+        // Compiled by circom 2, from the test vectors of ark-circom. circom writes version 1 files
+        // unless the circuit uses custom templates.
+        let data = std::fs::read("tests/circom2_multiplier2.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        assert_eq!(file.version, 1);
+        assert_eq!(file.custom_gates_list, None);
+        // circom writes the constraints before the header, every section is otherwise kept byte
+        // for byte.
+        let written = file.serialize();
+        assert_eq!(written.len(), data.len());
+        assert_eq!(written[..12], data[..12]);
+        let bodies = |data: &[u8]| {
+            let sections = R1csFile::<32>::scan_sections(std::io::Cursor::new(data)).unwrap();
+            let mut bodies: Vec<_> = sections
+                .iter()
+                .map(|s| {
+                    let start = s.offset as usize + 12;
+                    (s.ty, data[start..start + s.size as usize].to_vec())
+                })
+                .collect();
+            bodies.sort();
+            bodies
+        };
+        assert_eq!(bodies(&written), bodies(&data));
+        assert_eq!(R1csFile::<32>::read(written.as_slice()).unwrap(), file);
+
+        // The same circuit as a version 2 file with empty custom gate sections, as circom writes
+        // it for a circuit declaring custom templates without using them.
+        let mut data = data;
+        assert_eq!(data[4..12], [1, 0, 0, 0, 3, 0, 0, 0]);
+        data[4] = 2;
+        data[8] = 5;
+        for ty in [4u32, 5] {
+            data.extend_from_slice(&ty.to_le_bytes());
+            data.extend_from_slice(&4u64.to_le_bytes());
+            data.extend_from_slice(&0u32.to_le_bytes());
+        }
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        assert_eq!(file.version, 2);
+        assert_eq!(file.custom_gates_list, Some(CustomGatesList(vec![])));
+        assert_eq!(
+            file.custom_gates_application,
+            Some(CustomGatesApplication(vec![]))
+        );
+        let written = file.serialize();
+        assert_eq!(written[..12], data[..12]);
+        assert_eq!(bodies(&written), bodies(&data));
+    }
+
+    #[test]
+    fn test_reverse_wire_map() {
+        // Wires 2 and 4 alias label 7, wires 1, 3 and 5 alias label 1.
+        let map = WireMap(vec![0, 1, 7, 1, 7, 1]);
+        let reverse = map.reverse();
+
+        assert_eq!(reverse.len(), 3);
+        assert_eq!(reverse.first(7), Some(2));
+        assert_eq!(reverse.last(7), Some(4));
+        assert_eq!(reverse.all(7), [2, 4]);
+        assert_eq!(reverse.all(1), [1, 3, 5]);
+        assert_eq!(reverse.all(0), [0]);
+        assert_eq!(reverse.all(42), [] as [u32; 0]);
+        assert_eq!(reverse.first(42), None);
+        assert_eq!(
+            reverse.iter().collect::<Vec<_>>(),
+            [(0, &[0][..]), (1, &[1, 3, 5][..]), (7, &[2, 4][..])]
+        );
+    }
+
+    #[test]
+    fn test_wire_map_helpers() {
+        let identity = WireMap::identity(4);
+        assert_eq!(identity.0, [0, 1, 2, 3]);
+        assert!(identity.is_identity());
+        assert_eq!(identity.label_of(3), Some(3));
+        assert_eq!(identity.label_of(4), None);
+        assert_eq!(identity.inverse().unwrap()[&2], 2);
+
+        let map = WireMap(vec![0, 1, 7, 1, 7, 1]);
+        assert!(!map.is_identity());
+        assert_eq!(
+            map.inverse(),
+            Err(vec![
+                ValidationError::DuplicateLabel {
+                    label: 1,
+                    wires: vec![1, 3, 5],
+                },
+                ValidationError::DuplicateLabel {
+                    label: 7,
+                    wires: vec![2, 4],
+                },
+            ])
+        );
+
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let mut file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        assert_eq!(file.map.validate(&file.header), Ok(()));
+        file.header.n_labels = 1;
+        file.header.n_wires += 1;
+        let errors = file.map.validate(&file.header).unwrap_err();
+        assert!(matches!(
+            errors[0],
+            ValidationError::WireMapLengthMismatch { .. }
+        ));
+        assert!(errors[1..]
+            .iter()
+            .all(|e| matches!(e, ValidationError::LabelOutOfRange { .. })));
+    }
+
+    #[test]
+    fn test_prime_matches_witness() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        let witness =
+            wtns_file::WtnsFile::from_vec(vec![FieldElement::from_u64(1)], file.header.prime);
+        assert_eq!(file.header.prime, witness.header.prime);
+        assert_eq!(file.constraints.0[0].0[0].0, FieldElement::from_u64(3));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        let json = serde_json::to_string(&file).unwrap();
+        assert!(json.starts_with(
+            r#"{"header":{"prime":"30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001","n_wires":7,"#
+        ));
+        assert_eq!(serde_json::from_str::<R1csFile<32>>(&json).unwrap(), file);
+
+        let binary = bincode::serialize(&file).unwrap();
+        assert_eq!(bincode::deserialize::<R1csFile<32>>(&binary).unwrap(), file);
+    }
+
+    /// Recording of the tracing instrumentation, for tests checking which spans fire.
+    #[cfg(feature = "tracing")]
+    pub(crate) mod recorded {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing_subscriber::layer::{Context, SubscriberExt};
+        use tracing_subscriber::Layer;
+
+        /// Names of the spans and messages of the events, in order.
+        #[derive(Clone, Default)]
+        pub(crate) struct Recorded(Arc<Mutex<Vec<String>>>);
+
+        impl Recorded {
+            /// Runs `f`, recording its spans and events.
+            pub(crate) fn record(&self, f: impl FnOnce()) {
+                let subscriber = tracing_subscriber::registry().with(self.clone());
+                tracing::subscriber::with_default(subscriber, f);
+            }
+
+            /// What was recorded since the last call.
+            pub(crate) fn take(&self) -> Vec<String> {
+                std::mem::take(&mut *self.0.lock().unwrap())
+            }
+        }
+
+        impl Visit for Recorded {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "message" {
+                    self.0.lock().unwrap().push(format!("{:?}", value));
+                }
+            }
+        }
+
+        impl<S: tracing::Subscriber> Layer<S> for Recorded {
+            fn on_new_span(
+                &self,
+                attrs: &tracing::span::Attributes<'_>,
+                _id: &tracing::span::Id,
+                _ctx: Context<'_, S>,
+            ) {
+                let name = attrs.metadata().name().to_string();
+                self.0.lock().unwrap().push(name);
+            }
+
+            fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+                event.record(&mut self.clone());
+            }
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing_spans() {
+        let recorded = recorded::Recorded::default();
+        let take = || recorded.take();
+
+        recorded.record(|| {
+            let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+            let mut file = R1csFile::<32>::read(data.as_slice()).unwrap();
+            file.write(std::io::sink()).unwrap();
+            assert_eq!(
+                take(),
+                [
+                    "r1cs_read",
+                    "r1cs_header",
+                    "r1cs_constraints",
+                    "r1cs_wire_map",
+                    "r1cs_write",
+                    "r1cs_check_header"
+                ]
+            );
+
+            // Validated circuits skip the checks already made by validation.
+            let validated = file
+                .clone()
+                .into_validated(&ValidateOptions::default())
+                .unwrap();
+            assert_eq!(take(), ["r1cs_validate", "r1cs_check_canonical"]);
+            validated.write_validated(std::io::sink()).unwrap();
+            assert_eq!(take(), ["r1cs_write_validated"]);
+            R1csFile::merge(&file, &file, MergeOptions::default()).unwrap();
+            assert_eq!(take(), ["r1cs_check_wires", "r1cs_check_wires"]);
+            ValidatedR1cs::merge_validated(&validated, &validated, MergeOptions::default())
+                .unwrap();
+            assert!(take().is_empty());
+
+            file.custom_sections.push(CustomSection {
+                id: 0x100,
+                data: vec![1, 2, 3],
+            });
+            file.header.n_constraints += 1;
+            let written = file.serialize();
+            let opts = ReadOptions {
+                mode: ParseMode::Lenient,
+                drop_unknown_sections: true,
+                ..ReadOptions::default()
+            };
+            R1csFile::<32>::read_with(written.as_slice(), &opts).unwrap();
+            let events = take();
+            assert!(events.contains(&"skipping unknown section".to_string()));
+            assert!(events.contains(&"parse warning".to_string()));
+
+            assert!(file.validate().is_err());
+            assert_eq!(take(), ["r1cs_validate", "validation finding"]);
+            assert!(file.check_canonical_coefficients().is_ok());
+            assert_eq!(take(), ["r1cs_check_canonical"]);
+
+            // Running out of constraints reads the wire map section that follows them.
+            let mut reader = R1csReader::<_, 32>::new(data.as_slice()).unwrap();
+            assert_eq!(reader.constraints().count(), 3);
+            reader.wire_map().unwrap();
+            assert_eq!(
+                take(),
+                [
+                    "r1cs_stream",
+                    "r1cs_header",
+                    "r1cs_wire_map",
+                    "r1cs_stream_wire_map"
+                ]
+            );
+
+            #[cfg(feature = "wtns")]
+            {
+                let wtns = wtns_file::WtnsFile::from_vec(
+                    vec![FieldElement::from_u64(1); 7],
+                    file.header.prime,
+                );
+                assert!(check_witness(&file, &wtns).is_err());
+                assert_eq!(take(), ["r1cs_check_witness"]);
+            }
+        });
+    }
 }
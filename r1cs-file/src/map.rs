@@ -0,0 +1,172 @@
+//! Bulk transformation of the coefficients, e.g. to the representation a prover expects.
+
+use crate::{
+    Constraint, Constraints, CustomGate, CustomGatesList, FieldElement, Header, LinearCombination,
+    Matrix, R1csFile,
+};
+
+impl<const FS: usize> R1csFile<FS> {
+    /// A copy of the file with every coefficient replaced by `f(coeff, matrix, wire)`.
+    ///
+    /// `f` is called in the order of [`R1csFile::iter_terms`]. The header, the wires of every term,
+    /// the wire map and the other sections are kept as they are.
+    pub fn map_coefficients<F>(&self, f: F) -> R1csFile<FS>
+    where
+        F: FnMut(&FieldElement<FS>, Matrix, u32) -> FieldElement<FS>,
+    {
+        self.map_coefficients_to(f, |e| *e)
+    }
+
+    /// [`R1csFile::map_coefficients`] without copying the file.
+    pub fn map_coefficients_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&FieldElement<FS>, Matrix, u32) -> FieldElement<FS>,
+    {
+        for constraint in &mut self.constraints.0 {
+            let Constraint(a, b, c) = constraint;
+            for (&matrix, combination) in Matrix::ALL.iter().zip([a, b, c]) {
+                for (coeff, wire) in &mut combination.0 {
+                    *coeff = f(coeff, matrix, *wire);
+                }
+            }
+        }
+    }
+
+    /// [`R1csFile::map_coefficients`] to elements of `FS2` bytes, converting the header prime and
+    /// the custom gate parameters, which aren't coefficients, with `convert`.
+    pub fn map_coefficients_to<const FS2: usize, F, G>(
+        &self,
+        mut f: F,
+        mut convert: G,
+    ) -> R1csFile<FS2>
+    where
+        F: FnMut(&FieldElement<FS>, Matrix, u32) -> FieldElement<FS2>,
+        G: FnMut(&FieldElement<FS>) -> FieldElement<FS2>,
+    {
+        let header = &self.header;
+        let constraints = self
+            .constraints
+            .0
+            .iter()
+            .map(|Constraint(a, b, c)| {
+                let mut map = |matrix, combination: &LinearCombination<FS>| {
+                    let terms = combination.0.iter();
+                    LinearCombination(
+                        terms
+                            .map(|(coeff, wire)| (f(coeff, matrix, *wire), *wire))
+                            .collect(),
+                    )
+                };
+
+                Constraint(map(Matrix::A, a), map(Matrix::B, b), map(Matrix::C, c))
+            })
+            .collect();
+        let custom_gates_list = self.custom_gates_list.as_ref().map(|list| {
+            let gates = list.0.iter().map(|gate| CustomGate {
+                name: gate.name.clone(),
+                parameters: gate.parameters.iter().map(&mut convert).collect(),
+            });
+            CustomGatesList(gates.collect())
+        });
+
+        R1csFile {
+            header: Header {
+                prime: convert(&header.prime),
+                n_wires: header.n_wires,
+                n_pub_out: header.n_pub_out,
+                n_pub_in: header.n_pub_in,
+                n_prvt_in: header.n_prvt_in,
+                n_labels: header.n_labels,
+                n_constraints: header.n_constraints,
+            },
+            constraints: Constraints(constraints),
+            map: self.map.clone(),
+            custom_gates_list,
+            custom_gates_application: self.custom_gates_application.clone(),
+            metadata: self.metadata.clone(),
+            custom_sections: self.custom_sections.clone(),
+            version: self.version,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Modulus;
+
+    fn read() -> R1csFile<32> {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        R1csFile::read(data.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_map_identity() {
+        let file = read();
+        let mut calls = Vec::new();
+        let mapped = file.map_coefficients(|coeff, matrix, wire| {
+            calls.push((matrix, wire));
+            *coeff
+        });
+        assert_eq!(mapped, file);
+        assert!(calls
+            .into_iter()
+            .eq(file.iter_terms().map(|term| (term.matrix, term.wire))));
+
+        let mut copy = file.clone();
+        copy.map_coefficients_mut(|coeff, _, _| *coeff);
+        assert_eq!(copy, file);
+    }
+
+    #[test]
+    fn test_map_double() {
+        let file = read();
+        let modulus = Modulus::new(file.header.prime).unwrap();
+        let double = |coeff: &FieldElement<32>, _, _| modulus.add(coeff, coeff);
+
+        let doubled = file.map_coefficients(double);
+        assert_eq!(doubled.header, file.header);
+        assert_eq!(doubled.map, file.map);
+        let mut in_place = file.clone();
+        in_place.map_coefficients_mut(double);
+        assert_eq!(in_place, doubled);
+
+        // The first constraint is (3 w5 + 8 w6) * (2 + 20 w2 + 12 w3) = (5 + 7 w2).
+        let e = FieldElement::from_u64;
+        let first = &doubled.constraints[0];
+        assert_eq!(first.0 .0, [(e(6), 5), (e(16), 6)]);
+        assert_eq!(first.1 .0, [(e(4), 0), (e(40), 2), (e(24), 3)]);
+        assert_eq!(first.2 .0, [(e(10), 0), (e(14), 2)]);
+
+        // Doubling wraps around the prime, -1 becomes -2.
+        let mut file = file;
+        file.constraints.0[0].0 .0[0].0 = modulus.neg(&e(1));
+        let doubled = file.map_coefficients(double);
+        assert_eq!(doubled.constraints[0].0 .0[0].0, modulus.neg(&e(2)));
+
+        for (term, original) in doubled.iter_terms().zip(file.iter_terms()) {
+            assert_eq!(
+                (term.constraint, term.matrix, term.wire),
+                (original.constraint, original.matrix, original.wire)
+            );
+            assert_eq!(*term.coeff, modulus.add(original.coeff, original.coeff));
+        }
+    }
+
+    #[test]
+    fn test_map_widen() {
+        let file = read();
+        let widened: R1csFile<64> =
+            file.map_coefficients_to(|coeff, _, _| coeff.widen(), |e| e.widen());
+        assert_eq!(widened.header.prime, file.header.prime.widen());
+        assert_eq!(widened.header.n_wires, file.header.n_wires);
+        assert_eq!(widened.header.n_constraints, file.header.n_constraints);
+        assert_eq!(widened.map, file.map);
+
+        let narrowed: R1csFile<32> = widened.map_coefficients_to(
+            |coeff, _, _| coeff.try_truncate().unwrap(),
+            |e| e.try_truncate().unwrap(),
+        );
+        assert_eq!(narrowed, file);
+    }
+}
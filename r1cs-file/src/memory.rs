@@ -0,0 +1,120 @@
+use std::io::{Read, Seek};
+use std::mem::size_of;
+
+use iden3_binfile::BinFileReader;
+
+use crate::{
+    Constraint, CountingReader, FieldElement, Header, R1csError, R1csFile, Result, SectionType,
+    FORMAT,
+};
+
+/// Memory needed to hold a parsed file, computed by [`R1csFile::estimate_memory`].
+///
+/// The in-memory figures are upper bounds: they assume the constraints section holds nothing but
+/// terms and account for the growth of vectors whose final length isn't known in advance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryEstimate {
+    /// The constraint vector, excluding the terms.
+    pub constraints: u64,
+    /// The terms of all linear combinations.
+    pub terms: u64,
+    pub wire_map: u64,
+    /// Total size of the constraints sections in the file.
+    pub constraints_section_size: u64,
+    /// Size of the wire map section in the file.
+    pub wire_map_section_size: u64,
+}
+
+impl MemoryEstimate {
+    /// Upper bound for the whole parsed file.
+    pub fn total(&self) -> u64 {
+        self.constraints
+            .saturating_add(self.terms)
+            .saturating_add(self.wire_map)
+    }
+}
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Estimates the memory a parsed file would take, only reading the header and the section
+    /// table.
+    pub fn estimate_memory<R: Read + Seek>(r: R) -> Result<MemoryEstimate> {
+        let mut file = BinFileReader::new(r, &FORMAT)?;
+
+        let mut header = None;
+        let mut constraints_section_size = 0u64;
+        let mut wire_map_section_size = 0u64;
+        for entry in file.section_table()? {
+            match SectionType::from_id(entry.id) {
+                Some(SectionType::Header) => header = Some(entry),
+                Some(SectionType::Constraint) => {
+                    constraints_section_size = constraints_section_size.saturating_add(entry.size)
+                }
+                Some(SectionType::Wire2LabelIdMap) => wire_map_section_size = entry.size,
+                _ => {}
+            }
+        }
+
+        let entry = header.ok_or(R1csError::MissingSection(SectionType::Header))?;
+        let mut r = CountingReader::at(file.section_at(&entry)?, entry.offset);
+        let header = Header::<FS>::read(&mut r)
+            .map_err(|e| e.eof_in(Some(SectionType::Header), r.position()))?;
+
+        // Each term takes a 4-byte wire index and a coefficient on disk. Combination vectors are
+        // allocated with their exact length.
+        let max_terms = constraints_section_size / (4 + FS as u64);
+        let term_size = size_of::<(FieldElement<FS>, u32)>() as u64;
+
+        // The constraint and wire map vectors grow by doubling.
+        let n_constraints = u64::from(header.n_constraints).next_power_of_two();
+        let n_labels = u64::from(header.n_wires)
+            .max(wire_map_section_size / 8)
+            .next_power_of_two();
+
+        Ok(MemoryEstimate {
+            constraints: n_constraints.saturating_mul(size_of::<Constraint<FS>>() as u64),
+            terms: max_terms.saturating_mul(term_size),
+            wire_map: n_labels.saturating_mul(size_of::<u64>() as u64),
+            constraints_section_size,
+            wire_map_section_size,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Matrix;
+
+    fn allocated<const FS: usize>(file: &R1csFile<FS>) -> u64 {
+        let term_size = size_of::<(FieldElement<FS>, u32)>();
+        let terms: usize = file
+            .constraints
+            .0
+            .iter()
+            .flat_map(|c| {
+                Matrix::ALL
+                    .iter()
+                    .map(move |m| c.combination(*m).0.capacity())
+            })
+            .map(|terms| terms * term_size)
+            .sum();
+        let constraints = file.constraints.0.capacity() * size_of::<Constraint<FS>>();
+        let wire_map = file.map.0.capacity() * size_of::<u64>();
+
+        (terms + constraints + wire_map) as u64
+    }
+
+    #[test]
+    fn test_estimate_memory() {
+        for path in ["tests/simple_circuit.r1cs", "tests/test_circuit.r1cs"] {
+            let data = std::fs::read(path).unwrap();
+            let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+            let estimate = R1csFile::<32>::estimate_memory(std::io::Cursor::new(&data)).unwrap();
+
+            let actual = allocated(&file);
+            assert!(estimate.total() >= actual, "{}: {:?}", path, estimate);
+            assert!(estimate.total() <= 3 * actual, "{}: {:?}", path, estimate);
+            assert_eq!(estimate.wire_map_section_size, file.map.0.len() as u64 * 8);
+        }
+    }
+}
@@ -0,0 +1,325 @@
+//! Composing two circuits over the same prime.
+
+use crate::append::invalid_input;
+use crate::validate::wire_out_of_range;
+use crate::{
+    Constraint, Constraints, Header, LinearCombination, R1csError, R1csFile, Result,
+    ValidationError, WireMap, VERSION,
+};
+
+/// Options for [`R1csFile::merge`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MergeOptions {
+    pub signals: SignalLayout,
+}
+
+/// How the public and private signals of the merged circuits are laid out.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SignalLayout {
+    /// The wires of the second circuit but the constant one follow those of the first one. The
+    /// signals of the first circuit are kept and those of the second one become internal wires.
+    #[default]
+    Append,
+    /// The public outputs, public inputs and private inputs of both circuits are grouped by kind,
+    /// those of the first circuit first, followed by the internal wires of both circuits.
+    Combine,
+}
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Merges the constraints of `a` and `b` into one circuit sharing the constant wire 0.
+    ///
+    /// The wires of `b` are renumbered after those of `a` as set by `opts`, and its labels are
+    /// offset by `a.header.n_labels`. The header counts are recomputed and custom sections of both
+    /// files are kept, `a`'s first.
+    ///
+    /// Returns [`R1csError::Invalid`] if the primes differ or if either file is inconsistent with
+    /// its header, and an error of kind [`std::io::ErrorKind::InvalidInput`] if either file uses
+    /// custom gates or the counts overflow.
+    pub fn merge(a: &R1csFile<FS>, b: &R1csFile<FS>, opts: MergeOptions) -> Result<R1csFile<FS>> {
+        if *a.header.prime != *b.header.prime {
+            return Err(R1csError::Invalid(ValidationError::PrimeMismatch));
+        }
+        for file in [a, b] {
+            file.check_mergeable()?;
+        }
+
+        Self::merge_consistent(a, b, opts)
+    }
+
+    /// [`R1csFile::merge`] of files known to be consistent with their headers and over the same
+    /// prime.
+    pub(crate) fn merge_consistent(
+        a: &R1csFile<FS>,
+        b: &R1csFile<FS>,
+        opts: MergeOptions,
+    ) -> Result<R1csFile<FS>> {
+        if [a, b].iter().any(|file| file.has_custom_gates()) {
+            return Err(invalid_input(
+                "merging files with custom gates is not supported",
+            ));
+        }
+
+        let (ha, hb) = (&a.header, &b.header);
+        let n_wires = ha
+            .n_wires
+            .checked_add(hb.n_wires - 1)
+            .ok_or_else(|| invalid_input("wire count overflows"))?;
+        let n_constraints = ha
+            .n_constraints
+            .checked_add(hb.n_constraints)
+            .ok_or_else(|| invalid_input("constraint count overflows"))?;
+        let n_labels = ha
+            .n_labels
+            .checked_add(hb.n_labels)
+            .ok_or_else(|| invalid_input("label count overflows"))?;
+
+        let (signals, map_a, map_b) = match opts.signals {
+            SignalLayout::Append => {
+                let offset = ha.n_wires - 1;
+                let map_b = (0..hb.n_wires)
+                    .map(|wire| if wire == 0 { 0 } else { wire + offset })
+                    .collect();
+                let signals = [ha.n_pub_out, ha.n_pub_in, ha.n_prvt_in];
+                (signals, (0..ha.n_wires).collect(), map_b)
+            }
+            SignalLayout::Combine => {
+                let signals = [
+                    checked_sum(ha.n_pub_out, hb.n_pub_out)?,
+                    checked_sum(ha.n_pub_in, hb.n_pub_in)?,
+                    checked_sum(ha.n_prvt_in, hb.n_prvt_in)?,
+                ];
+                let (map_a, map_b) = combined_layout(ha, hb);
+                (signals, map_a, map_b)
+            }
+        };
+        let [n_pub_out, n_pub_in, n_prvt_in] = signals;
+        let header = Header {
+            prime: ha.prime,
+            n_wires,
+            n_pub_out,
+            n_pub_in,
+            n_prvt_in,
+            n_labels,
+            n_constraints,
+        };
+
+        let mut constraints = Vec::with_capacity(n_constraints as usize);
+        constraints.extend(
+            a.constraints
+                .0
+                .iter()
+                .map(|c| renumber(c, |wire| map_a[wire as usize])),
+        );
+        constraints.extend(
+            b.constraints
+                .0
+                .iter()
+                .map(|c| renumber(c, |wire| map_b[wire as usize])),
+        );
+
+        let mut map = vec![0; n_wires as usize];
+        // Files without a wire map are merged as if their wires had the identity map.
+        for (wire, label) in a.effective_map().iter().enumerate() {
+            map[map_a[wire] as usize] = *label;
+        }
+        for (wire, label) in b.effective_map().iter().enumerate().skip(1) {
+            map[map_b[wire] as usize] = label + ha.n_labels;
+        }
+
+        let custom_sections = a
+            .custom_sections
+            .iter()
+            .chain(&b.custom_sections)
+            .cloned()
+            .collect();
+
+        Ok(R1csFile {
+            header,
+            constraints: Constraints(constraints),
+            map: WireMap(map),
+            custom_gates_list: None,
+            custom_gates_application: None,
+            metadata: None,
+            custom_sections,
+            version: VERSION,
+        })
+    }
+
+    fn check_mergeable(&self) -> Result<()> {
+        let n_wires = self.header.n_wires;
+        if n_wires == 0 {
+            return Err(invalid_input("file has no constant wire"));
+        }
+        if !self.map.is_empty() && self.map.0.len() != n_wires as usize {
+            return Err(R1csError::Invalid(ValidationError::WireMapLengthMismatch {
+                n_wires,
+                found: self.map.0.len(),
+            }));
+        }
+        if let Some(e) = wire_out_of_range(&self.constraints.0, 0, n_wires) {
+            return Err(R1csError::Invalid(e));
+        }
+
+        Ok(())
+    }
+
+    fn has_custom_gates(&self) -> bool {
+        self.custom_gates_list.is_some() || self.custom_gates_application.is_some()
+    }
+}
+
+/// The new index of every wire of `a` and `b` under [`SignalLayout::Combine`].
+fn combined_layout<const FS: usize>(a: &Header<FS>, b: &Header<FS>) -> (Vec<u32>, Vec<u32>) {
+    let regions = |h: &Header<FS>| {
+        [
+            h.n_pub_out,
+            h.n_pub_in,
+            h.n_prvt_in,
+            h.n_wires - 1 - h.n_pub_out - h.n_pub_in - h.n_prvt_in,
+        ]
+    };
+    let (regions_a, regions_b) = (regions(a), regions(b));
+
+    let (mut map_a, mut map_b) = (vec![0], vec![0]);
+    let mut next = 1;
+    for (len_a, len_b) in regions_a.iter().zip(&regions_b) {
+        map_a.extend(next..next + len_a);
+        next += len_a;
+        map_b.extend(next..next + len_b);
+        next += len_b;
+    }
+
+    (map_a, map_b)
+}
+
+/// A copy of `c` with every wire `w` replaced by `map(w)`.
+pub(crate) fn renumber<const FS: usize>(
+    c: &Constraint<FS>,
+    map: impl Fn(u32) -> u32,
+) -> Constraint<FS> {
+    let lc = |lc: &LinearCombination<FS>| {
+        lc.iter()
+            .map(|(coeff, wire)| (*coeff, map(*wire)))
+            .collect::<LinearCombination<FS>>()
+    };
+
+    Constraint(lc(&c.0), lc(&c.1), lc(&c.2))
+}
+
+fn checked_sum(a: u32, b: u32) -> Result<u32> {
+    a.checked_add(b)
+        .ok_or_else(|| invalid_input("signal count overflows"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FieldElement, R1csFileBuilder};
+
+    fn one() -> FieldElement<32> {
+        FieldElement::from_u64(1)
+    }
+
+    /// A circuit with one public output, one private input and one internal wire, whose only
+    /// constraint references every wire.
+    fn gadget() -> R1csFile<32> {
+        let mut builder = R1csFileBuilder::new()
+            .prime(FieldElement::bn254_scalar_prime())
+            .public_outputs(1)
+            .private_inputs(1);
+        let internal = builder.alloc_wire();
+        builder.add_constraint(
+            LinearCombination::term(one(), 0),
+            LinearCombination::term(one(), 2),
+            vec![(one(), 1), (one(), internal)],
+        );
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_merge() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let a = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let b = gadget();
+
+        let merged = R1csFile::merge(&a, &b, MergeOptions::default()).unwrap();
+        assert_eq!(merged.validate(), Ok(()));
+        assert_eq!(merged.header.n_wires, 10);
+        assert_eq!(merged.header.n_constraints, 4);
+        assert_eq!(merged.header.n_labels, 1004);
+        assert_eq!(
+            (
+                merged.header.n_pub_out,
+                merged.header.n_pub_in,
+                merged.header.n_prvt_in
+            ),
+            (1, 2, 3)
+        );
+        assert_eq!(merged.constraints.0[..3], a.constraints.0[..]);
+        assert_eq!(merged.constraints.0[3].a()[..], [(one(), 0)]);
+        assert_eq!(merged.constraints.0[3].c()[..], [(one(), 7), (one(), 9)]);
+        assert_eq!(merged.map.0[..7], a.map.0[..]);
+        assert_eq!(merged.map.0[7..], [1001, 1002, 1003]);
+
+        let merged = R1csFile::merge(
+            &b,
+            &a,
+            MergeOptions {
+                signals: SignalLayout::Combine,
+            },
+        )
+        .unwrap();
+        assert_eq!(merged.validate(), Ok(()));
+        assert_eq!(
+            (
+                merged.header.n_pub_out,
+                merged.header.n_pub_in,
+                merged.header.n_prvt_in
+            ),
+            (2, 2, 4)
+        );
+        // The gadget's output, then the circuit's output, inputs and private inputs, then the
+        // gadget's private input and internal wire.
+        assert_eq!(merged.constraints.0[0].c()[..], [(one(), 1), (one(), 9)]);
+        assert_eq!(merged.constraints.0[0].b()[..], [(one(), 5)]);
+        let label = |wire: usize| a.map.0[wire] + 4;
+        assert_eq!(
+            merged.map.0,
+            [
+                0,
+                1,
+                label(1),
+                label(2),
+                label(3),
+                2,
+                label(4),
+                label(5),
+                label(6),
+                3
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_edge_cases() {
+        let mut empty = gadget();
+        empty.constraints.0.clear();
+        empty.header.n_constraints = 0;
+
+        let merged = R1csFile::merge(&empty, &gadget(), MergeOptions::default()).unwrap();
+        assert_eq!(merged.validate(), Ok(()));
+        assert_eq!(merged.constraints.0[0].a()[..], [(one(), 0)]);
+        assert_eq!(merged.constraints.0[0].c()[..], [(one(), 4), (one(), 6)]);
+
+        let merged = R1csFile::merge(&gadget(), &empty, MergeOptions::default()).unwrap();
+        assert_eq!(merged.validate(), Ok(()));
+        assert_eq!(merged.constraints, gadget().constraints);
+
+        let mut other = gadget();
+        other.header.prime = FieldElement::from_u64(7);
+        assert!(matches!(
+            R1csFile::merge(&gadget(), &other, MergeOptions::default()),
+            Err(R1csError::Invalid(ValidationError::PrimeMismatch))
+        ));
+    }
+}
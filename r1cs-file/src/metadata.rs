@@ -0,0 +1,230 @@
+//! The metadata section: provenance of the circuit as string key/value pairs.
+//!
+//! Metadata is stored in a section of id [`SectionType::Metadata`], `0x6174656d` (the bytes
+//! `meta` on disk). Readers that don't know the id skip it like any unknown section, as snarkjs
+//! does. The body is laid out as follows, all integers little-endian:
+//!
+//! 1. the number of pairs, a `u32`;
+//! 2. for each pair, the key then the value, each as a `u32` byte length followed by that many
+//!    bytes of UTF-8.
+//!
+//! Keys are unique and written in ascending byte order. Readers reject duplicated keys, invalid
+//! UTF-8 and trailing bytes.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec;
+
+use byteorder::LittleEndian;
+use iden3_binfile::io::{self, Read, ReadBytesExt, Write, WriteBytesExt};
+
+use crate::{ensure_consumed, CountingReader, R1csError, Result, SectionHeader, SectionType};
+
+/// Key/value pairs of the metadata section.
+pub type Metadata = BTreeMap<String, String>;
+
+pub(crate) fn read<R: Read>(
+    r: &mut CountingReader<R>,
+    section_header: &SectionHeader,
+) -> Result<Metadata> {
+    let mut section_data = r.take(section_header.size);
+
+    let n = section_data.read_u32::<LittleEndian>()?;
+    let mut metadata = Metadata::new();
+    for _ in 0..n {
+        let offset = section_data.get_ref().position();
+        let key = read_string(&mut section_data)?;
+        let value = read_string(&mut section_data)?;
+        if metadata.insert(key, value).is_some() {
+            return Err(R1csError::DuplicateMetadataKey { offset });
+        }
+    }
+
+    ensure_consumed(&section_data, SectionType::Metadata)?;
+
+    Ok(metadata)
+}
+
+/// Reads a length-prefixed UTF-8 string.
+fn read_string<R: Read>(r: &mut io::Take<&mut CountingReader<R>>) -> Result<String> {
+    let offset = r.get_ref().position();
+    let len = r.read_u32::<LittleEndian>()?;
+    // The length is bounded by the section, so a bogus length can't allocate more than it holds.
+    if u64::from(len) > r.limit() {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+
+    let mut bytes = vec![0; len as usize];
+    r.read_exact(&mut bytes)?;
+
+    String::from_utf8(bytes).map_err(|_| R1csError::InvalidString {
+        section: SectionType::Metadata,
+        offset,
+    })
+}
+
+pub(crate) fn write<W: Write>(metadata: &Metadata, mut w: W) -> Result<()> {
+    w.write_u32::<LittleEndian>(metadata.len() as u32)?;
+    for (key, value) in metadata {
+        for s in [key, value] {
+            w.write_u32::<LittleEndian>(s.len() as u32)?;
+            w.write_all(s.as_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Size of the section body.
+pub(crate) fn size(metadata: &Metadata) -> u64 {
+    let pairs = metadata
+        .iter()
+        .map(|(key, value)| 8 + key.len() + value.len())
+        .sum::<usize>();
+
+    4 + pairs as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CustomSection, R1csFile};
+    use iden3_binfile::{BinFileReader, Format};
+
+    fn metadata() -> Metadata {
+        [
+            ("circom", "2.1.6"),
+            ("git", "5d789ab3757dcd79eff244ca4998d7ab91683b40"),
+            ("name", "multiplier ✓"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+    }
+
+    #[test]
+    fn test_metadata_round_trip() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let original = R1csFile::<32>::read(data.as_slice()).unwrap();
+        assert_eq!(original.metadata, None);
+
+        let mut file = original.clone();
+        file.metadata = Some(metadata());
+        let written = file.serialize();
+        assert_eq!(written.len(), file.serialized_size());
+        assert_eq!(written[8..12], 4u32.to_le_bytes());
+        assert_eq!(R1csFile::<32>::read(written.as_slice()).unwrap(), file);
+        let seek = R1csFile::<32>::read_seek(std::io::Cursor::new(&written)).unwrap();
+        assert_eq!(seek, file);
+        assert_eq!(
+            crate::R1csFileRef::<32>::parse(&written)
+                .unwrap()
+                .to_owned(),
+            file
+        );
+
+        file.metadata = Some(Metadata::new());
+        let empty = file.serialize();
+        assert_eq!(R1csFile::<32>::read(empty.as_slice()).unwrap(), file);
+
+        // Readers that don't know the section, like snarkjs, see the original file plus a section
+        // of an unknown id.
+        let format = Format {
+            magic: *b"r1cs",
+            versions: 1..=1,
+            max_sections: None,
+        };
+        let mut r = BinFileReader::new(written.as_slice(), &format).unwrap();
+        let mut ids = Vec::new();
+        while let Some((id, _, mut body)) = r.next_section().unwrap() {
+            std::io::copy(&mut body, &mut std::io::sink()).unwrap();
+            ids.push(id);
+        }
+        assert_eq!(ids, [1, 2, 3, 0x6174_656d]);
+        let known = &written[..written.len() - 12 - size(&metadata()) as usize];
+        let mut patched = known.to_vec();
+        patched[8..12].copy_from_slice(&3u32.to_le_bytes());
+        assert_eq!(patched, data);
+    }
+
+    #[test]
+    fn test_invalid_metadata() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let original = R1csFile::<32>::read(data.as_slice()).unwrap();
+        // The file with a metadata section holding `body`.
+        let read = |body: &[u8]| {
+            let mut file = original.clone();
+            file.custom_sections.push(CustomSection {
+                id: SectionType::Metadata as u32,
+                data: body.to_vec(),
+            });
+            R1csFile::<32>::read(file.serialize().as_slice())
+        };
+        let pair = |key: &[u8], value: &[u8]| {
+            let mut pair = (key.len() as u32).to_le_bytes().to_vec();
+            pair.extend_from_slice(key);
+            pair.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            pair.extend_from_slice(value);
+            pair
+        };
+
+        let mut body = 1u32.to_le_bytes().to_vec();
+        body.extend(pair(b"name", b"a"));
+        let file = read(&body).unwrap();
+        assert_eq!(file.metadata.unwrap()["name"], "a");
+        assert!(file.custom_sections.is_empty());
+
+        let mut duplicated = 2u32.to_le_bytes().to_vec();
+        duplicated.extend(pair(b"name", b"a"));
+        duplicated.extend(pair(b"name", b"b"));
+        assert!(matches!(
+            read(&duplicated),
+            Err(R1csError::DuplicateMetadataKey { .. })
+        ));
+
+        let mut invalid = 1u32.to_le_bytes().to_vec();
+        invalid.extend(pair(b"name", b"\xff"));
+        assert!(matches!(
+            read(&invalid),
+            Err(R1csError::InvalidString {
+                section: SectionType::Metadata,
+                ..
+            })
+        ));
+
+        let mut trailing = body.clone();
+        trailing.push(0);
+        assert!(matches!(
+            read(&trailing),
+            Err(R1csError::TrailingSectionData {
+                section: SectionType::Metadata,
+                ..
+            })
+        ));
+
+        let mut truncated = 1u32.to_le_bytes().to_vec();
+        truncated.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            read(&truncated),
+            Err(R1csError::UnexpectedEof {
+                section: Some(SectionType::Metadata),
+                ..
+            })
+        ));
+
+        let mut duplicated_section = original.clone();
+        duplicated_section.metadata = Some(Metadata::new());
+        duplicated_section.custom_sections.push(CustomSection {
+            id: SectionType::Metadata as u32,
+            data: body,
+        });
+        let written = duplicated_section.serialize();
+        assert!(matches!(
+            R1csFile::<32>::read(written.as_slice()),
+            Err(R1csError::DuplicateSection {
+                section: SectionType::Metadata,
+                ..
+            })
+        ));
+    }
+}
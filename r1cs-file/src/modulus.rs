@@ -0,0 +1,330 @@
+//! Arithmetic modulo the prime of a file.
+
+use core::cmp::Ordering;
+
+use crate::{FieldElement, ValidationError, MAX_SUPPORTED_FIELD_SIZE};
+
+/// Maximum number of 64-bit limbs of a field element.
+const MAX_LIMBS: usize = MAX_SUPPORTED_FIELD_SIZE / 8;
+
+type Limbs = [u64; MAX_LIMBS];
+
+/// The sign of a value shown as a signed integer by [`Modulus::to_signed_display`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sign {
+    Positive,
+    Negative,
+}
+
+/// Arithmetic modulo an odd prime `p`, such as the header prime.
+///
+/// Operands must be canonical, that is below `p`, see [`Modulus::is_canonical`]; results are
+/// unspecified otherwise. Multiplication uses Montgomery reduction, the other operations plain
+/// multi-limb arithmetic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Modulus<const FS: usize> {
+    prime: FieldElement<FS>,
+    p: Limbs,
+    /// Number of limbs in use.
+    n: usize,
+    /// `-p^-1 mod 2^64`.
+    inv: u64,
+    /// `R^2 mod p` with `R = 2^(64 n)`, for converting into Montgomery form.
+    r2: Limbs,
+}
+
+impl<const FS: usize> Modulus<FS> {
+    /// Fails with [`ValidationError::ZeroPrime`] or [`ValidationError::EvenPrime`] if `prime` is
+    /// not odd. Primality itself is not checked.
+    pub fn new(prime: FieldElement<FS>) -> Result<Self, ValidationError> {
+        if prime.is_zero() {
+            return Err(ValidationError::ZeroPrime);
+        }
+        if prime.byte(0).is_some_and(|b| b & 1 == 0) {
+            return Err(ValidationError::EvenPrime);
+        }
+
+        let n = FS.div_ceil(8);
+        let p = to_limbs(&prime);
+
+        // Newton iteration for p^-1 mod 2^64, each step doubling the number of correct bits.
+        let mut inv = 1u64;
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(p[0].wrapping_mul(inv)));
+        }
+
+        let mut modulus = Modulus {
+            prime,
+            p,
+            n,
+            inv: inv.wrapping_neg(),
+            r2: [0; MAX_LIMBS],
+        };
+
+        // R^2 mod p by doubling 1 as many times.
+        let mut r2 = [0; MAX_LIMBS];
+        r2[0] = 1;
+        for _ in 0..128 * n {
+            r2 = modulus.add_limbs(&r2, &r2);
+        }
+        modulus.r2 = r2;
+
+        Ok(modulus)
+    }
+
+    pub fn prime(&self) -> &FieldElement<FS> {
+        &self.prime
+    }
+
+    /// Whether `a` is below the prime.
+    pub fn is_canonical(&self, a: &FieldElement<FS>) -> bool {
+        *a < self.prime
+    }
+
+    /// `a + b mod p`.
+    pub fn add(&self, a: &FieldElement<FS>, b: &FieldElement<FS>) -> FieldElement<FS> {
+        from_limbs(&self.add_limbs(&to_limbs(a), &to_limbs(b)))
+    }
+
+    /// `a - b mod p`.
+    pub fn sub(&self, a: &FieldElement<FS>, b: &FieldElement<FS>) -> FieldElement<FS> {
+        let (mut d, borrow) = sub_limbs(&to_limbs(a), &to_limbs(b), self.n);
+        if borrow {
+            d = add_limbs(&d, &self.p, self.n).0;
+        }
+
+        from_limbs(&d)
+    }
+
+    /// `-a mod p`.
+    pub fn neg(&self, a: &FieldElement<FS>) -> FieldElement<FS> {
+        if a.is_zero() {
+            return *a;
+        }
+
+        from_limbs(&sub_limbs(&self.p, &to_limbs(a), self.n).0)
+    }
+
+    /// `a * b mod p`.
+    pub fn mul(&self, a: &FieldElement<FS>, b: &FieldElement<FS>) -> FieldElement<FS> {
+        // mont(a, b) = a b / R, and mont(a b / R, R^2) = a b.
+        let ab = self.mont_mul(&to_limbs(a), &to_limbs(b));
+        from_limbs(&self.mont_mul(&ab, &self.r2))
+    }
+
+    /// `base^exp mod p`, by square-and-multiply over the bits of `exp`.
+    pub(crate) fn pow(&self, base: &FieldElement<FS>, exp: &FieldElement<FS>) -> FieldElement<FS> {
+        let mut one = [0; MAX_LIMBS];
+        one[0] = 1;
+        // Stays in Montgomery form until the end.
+        let base = self.mont_mul(&to_limbs(base), &self.r2);
+        let mut acc = self.mont_mul(&one, &self.r2);
+        for byte in exp.iter().rev() {
+            for bit in (0..8).rev() {
+                acc = self.mont_mul(&acc, &acc);
+                if byte >> bit & 1 == 1 {
+                    acc = self.mont_mul(&acc, &base);
+                }
+            }
+        }
+
+        from_limbs(&self.mont_mul(&acc, &one))
+    }
+
+    /// `a` as a signed integer: values above `(p - 1) / 2` are shown as the negative `a - p`.
+    ///
+    /// Returns the sign and the magnitude.
+    pub fn to_signed_display(&self, a: &FieldElement<FS>) -> (Sign, FieldElement<FS>) {
+        let negated = self.neg(a);
+        if negated < *a {
+            (Sign::Negative, negated)
+        } else {
+            (Sign::Positive, *a)
+        }
+    }
+
+    fn add_limbs(&self, a: &Limbs, b: &Limbs) -> Limbs {
+        let (s, carry) = add_limbs(a, b, self.n);
+        if carry || cmp_limbs(&s, &self.p, self.n) != Ordering::Less {
+            sub_limbs(&s, &self.p, self.n).0
+        } else {
+            s
+        }
+    }
+
+    /// Montgomery multiplication `a b / R mod p`, coarsely integrated operand scanning.
+    fn mont_mul(&self, a: &Limbs, b: &Limbs) -> Limbs {
+        let (n, p) = (self.n, &self.p);
+        let mut t = [0u64; MAX_LIMBS + 2];
+
+        for &bi in &b[..n] {
+            let mut carry = 0u64;
+            for j in 0..n {
+                (t[j], carry) = mac(t[j], a[j], bi, carry);
+            }
+            let (sum, overflow) = t[n].overflowing_add(carry);
+            t[n] = sum;
+            t[n + 1] = u64::from(overflow);
+
+            let m = t[0].wrapping_mul(self.inv);
+            let (_, mut carry) = mac(t[0], m, p[0], 0);
+            for j in 1..n {
+                (t[j - 1], carry) = mac(t[j], m, p[j], carry);
+            }
+            let (sum, overflow) = t[n].overflowing_add(carry);
+            t[n - 1] = sum;
+            t[n] = t[n + 1] + u64::from(overflow);
+        }
+
+        let mut result = [0; MAX_LIMBS];
+        result[..n].copy_from_slice(&t[..n]);
+        if t[n] != 0 || cmp_limbs(&result, p, n) != Ordering::Less {
+            result = sub_limbs(&result, p, n).0;
+        }
+
+        result
+    }
+}
+
+/// `a + b c + carry`, as the low limb and the carry.
+fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let t = u128::from(a) + u128::from(b) * u128::from(c) + u128::from(carry);
+    (t as u64, (t >> 64) as u64)
+}
+
+fn add_limbs(a: &Limbs, b: &Limbs, n: usize) -> (Limbs, bool) {
+    let mut s = [0; MAX_LIMBS];
+    let mut carry = false;
+    for i in 0..n {
+        let (x, c1) = a[i].overflowing_add(b[i]);
+        let (x, c2) = x.overflowing_add(u64::from(carry));
+        s[i] = x;
+        carry = c1 || c2;
+    }
+
+    (s, carry)
+}
+
+fn sub_limbs(a: &Limbs, b: &Limbs, n: usize) -> (Limbs, bool) {
+    let mut d = [0; MAX_LIMBS];
+    let mut borrow = false;
+    for i in 0..n {
+        let (x, b1) = a[i].overflowing_sub(b[i]);
+        let (x, b2) = x.overflowing_sub(u64::from(borrow));
+        d[i] = x;
+        borrow = b1 || b2;
+    }
+
+    (d, borrow)
+}
+
+fn cmp_limbs(a: &Limbs, b: &Limbs, n: usize) -> Ordering {
+    a[..n].iter().rev().cmp(b[..n].iter().rev())
+}
+
+fn to_limbs<const FS: usize>(e: &FieldElement<FS>) -> Limbs {
+    let mut limbs = [0; MAX_LIMBS];
+    for (i, chunk) in e.chunks(8).enumerate() {
+        let mut bytes = [0u8; 8];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        limbs[i] = u64::from_le_bytes(bytes);
+    }
+
+    limbs
+}
+
+fn from_limbs<const FS: usize>(limbs: &Limbs) -> FieldElement<FS> {
+    let mut bytes = [0u8; FS];
+    for (chunk, limb) in bytes.chunks_mut(8).zip(limbs) {
+        chunk.copy_from_slice(&limb.to_le_bytes()[..chunk.len()]);
+    }
+
+    FieldElement::from(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Endianness;
+
+    fn hex(s: &str) -> FieldElement<32> {
+        FieldElement::from_hex(s, Endianness::Big).unwrap()
+    }
+
+    #[test]
+    fn test_bn254() {
+        let m = Modulus::new(FieldElement::bn254_scalar_prime()).unwrap();
+        let a = hex("0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef");
+        let b = m.neg(&FieldElement::from_u64(0xdeadbeefcafebabe));
+        assert_eq!(
+            b,
+            hex("0x30644e72e131a029b85045b68181585d2833e84879b97090653436a425014543")
+        );
+
+        assert_eq!(
+            m.mul(&a, &b),
+            hex("0x1ea376eacd068c7fd1b6f70a77a095eaf26d79b6bb0c65990d44744958560026")
+        );
+        assert_eq!(
+            m.add(&a, &b),
+            hex("0x1234567890abcdef1234567890abcdef1234567890abcdee33869788c5ad1331")
+        );
+        assert_eq!(
+            m.sub(&a, &b),
+            hex("0x1234567890abcdef1234567890abcdef1234567890abcdeff0e215685baa88ad")
+        );
+
+        // 2^128 * 2^128 = 2^256 mod p.
+        let mut bytes = [0u8; 32];
+        bytes[16] = 1;
+        let x = FieldElement::from(bytes);
+        assert_eq!(
+            m.mul(&x, &x),
+            hex("0x0e0a77c19a07df2f666ea36f7879462e36fc76959f60cd29ac96341c4ffffffb")
+        );
+
+        let one = FieldElement::from_u64(1);
+        let minus_one = m.neg(&one);
+        assert_eq!(m.mul(&minus_one, &minus_one), one);
+        assert!(m.add(&minus_one, &one).is_zero());
+        assert_eq!(m.to_signed_display(&minus_one), (Sign::Negative, one));
+        assert_eq!(m.to_signed_display(&a), (Sign::Positive, a));
+        assert!(m.is_canonical(&minus_one));
+        assert!(!m.is_canonical(m.prime()));
+    }
+
+    #[test]
+    fn test_small_fields() {
+        let g: u64 = 0xffff_ffff_0000_0001;
+        let m = Modulus::new(FieldElement::goldilocks_prime()).unwrap();
+        let fe = FieldElement::<8>::from_u64;
+        let wide = |x: u128| fe((x % u128::from(g)) as u64);
+        let values = [0, 1, 2, 0xffff_ffff, 0xffff_fffe_1234_5678, g - 1];
+        for a in values {
+            for b in values {
+                let (wa, wb) = (u128::from(a), u128::from(b));
+                assert_eq!(m.mul(&fe(a), &fe(b)), wide(wa * wb));
+                assert_eq!(m.add(&fe(a), &fe(b)), wide(wa + wb));
+                assert_eq!(m.sub(&fe(a), &fe(b)), wide(wa + u128::from(g) - wb));
+            }
+        }
+
+        // A single, half-filled limb.
+        let p = 2_147_483_647u32;
+        let m = Modulus::new(FieldElement::<4>::from(p.to_le_bytes())).unwrap();
+        let fe = |x: u32| FieldElement::<4>::from(x.to_le_bytes());
+        assert_eq!(
+            m.mul(&fe(123_456_789), &fe(987_654_321)),
+            fe((123_456_789u64 * 987_654_321 % u64::from(p)) as u32)
+        );
+
+        assert_eq!(
+            Modulus::new(FieldElement::<8>::from_u64(0)),
+            Err(ValidationError::ZeroPrime)
+        );
+        assert_eq!(
+            Modulus::new(FieldElement::<8>::from_u64(10)),
+            Err(ValidationError::EvenPrime)
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! Matrix Market export of the constraint matrices, behind the `matrix-market` feature.
+
+use std::io::{BufWriter, Write};
+
+use sha2::Sha256;
+
+use crate::{Matrix, R1csFile, Result};
+
+/// How [`R1csFile::write_matrix_market`] writes the entries of a matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoeffMode {
+    /// The positions of the entries only.
+    Pattern,
+    /// The coefficients in decimal, as integers.
+    Integer,
+    /// The coefficients in decimal, declared as reals.
+    ///
+    /// Coefficients are field elements: a small negative value is written as `p - x`, which most
+    /// tools will round when parsing it as a float.
+    Real,
+}
+
+impl CoeffMode {
+    fn field(self) -> &'static str {
+        match self {
+            CoeffMode::Pattern => "pattern",
+            CoeffMode::Integer => "integer",
+            CoeffMode::Real => "real",
+        }
+    }
+}
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Writes `matrix` as a Matrix Market coordinate file, with a row per constraint and a column
+    /// per wire.
+    ///
+    /// Indices are one-based, as the format requires, and a comment line holds the SHA-256
+    /// [`R1csFile::fingerprint`] of the circuit. Terms on the same wire are written as separate
+    /// entries, which readers such as SciPy sum.
+    pub fn write_matrix_market<W: Write>(
+        &self,
+        w: W,
+        matrix: Matrix,
+        coeff_mode: CoeffMode,
+    ) -> Result<()> {
+        let mut w = BufWriter::new(w);
+        let terms = self.iter_matrix(matrix);
+
+        writeln!(
+            w,
+            "%%MatrixMarket matrix coordinate {} general",
+            coeff_mode.field()
+        )?;
+        writeln!(
+            w,
+            "% Matrix {} of circuit sha256:{:x}",
+            matrix,
+            self.fingerprint::<Sha256>()
+        )?;
+        writeln!(
+            w,
+            "{} {} {}",
+            self.constraints.0.len(),
+            self.header.n_wires,
+            terms.len()
+        )?;
+
+        for term in terms {
+            let (row, col) = (u64::from(term.constraint) + 1, u64::from(term.wire) + 1);
+            match coeff_mode {
+                CoeffMode::Pattern => writeln!(w, "{} {}", row, col)?,
+                CoeffMode::Integer | CoeffMode::Real => {
+                    writeln!(w, "{} {} {}", row, col, term.coeff.to_dec_string())?
+                }
+            }
+        }
+
+        w.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_matrix_market() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        let mut out = Vec::new();
+        file.write_matrix_market(&mut out, Matrix::B, CoeffMode::Integer)
+            .unwrap();
+        let expected = std::fs::read_to_string("tests/simple_circuit.b.mtx").unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+
+        let mut out = Vec::new();
+        file.write_matrix_market(&mut out, Matrix::C, CoeffMode::Pattern)
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines[0], "%%MatrixMarket matrix coordinate pattern general");
+        assert_eq!(lines[2..], ["3 7 3", "1 1", "1 3", "3 7"]);
+    }
+}
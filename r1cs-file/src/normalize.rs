@@ -0,0 +1,78 @@
+//! Canonical form of constraints.
+
+use alloc::vec::Vec;
+
+use crate::{Constraint, Constraints, FieldElement, LinearCombination, Modulus};
+
+impl<const FS: usize> LinearCombination<FS> {
+    /// Brings the combination to canonical form: terms sorted by ascending wire index, each wire
+    /// appearing at most once, and no zero coefficient.
+    ///
+    /// Coefficients on the same wire are added modulo the prime, they must be below it.
+    pub fn normalize(&mut self, modulus: &Modulus<FS>) {
+        let mut terms = core::mem::take(&mut self.0);
+        terms.sort_by_key(|(_, wire)| *wire);
+
+        let mut merged: Vec<(FieldElement<FS>, u32)> = Vec::with_capacity(terms.len());
+        for (coeff, wire) in terms {
+            match merged.last_mut() {
+                Some((sum, last)) if *last == wire => *sum = modulus.add(sum, &coeff),
+                _ => merged.push((coeff, wire)),
+            }
+        }
+        merged.retain(|(coeff, _)| !coeff.is_zero());
+
+        self.0 = merged;
+    }
+}
+
+impl<const FS: usize> Constraint<FS> {
+    /// Normalizes the three linear combinations, see [`LinearCombination::normalize`].
+    ///
+    /// Two constraints with the same canonical form are equal as constraints over the field,
+    /// regardless of how the compiler encoded them.
+    pub fn normalize(&mut self, modulus: &Modulus<FS>) {
+        self.0.normalize(modulus);
+        self.1.normalize(modulus);
+        self.2.normalize(modulus);
+    }
+}
+
+impl<const FS: usize> Constraints<FS> {
+    /// Normalizes every constraint, see [`Constraint::normalize`]. The constraint order is kept.
+    pub fn normalize_all(&mut self, modulus: &Modulus<FS>) {
+        for c in &mut self.0 {
+            c.normalize(modulus);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primes::GOLDILOCKS;
+
+    #[test]
+    fn test_normalize() {
+        let modulus = Modulus::new(GOLDILOCKS).unwrap();
+        let fe = FieldElement::<8>::from_u64;
+        let p_minus_1 = fe(0xffffffff00000000);
+        let mut c = Constraint::new(
+            vec![(fe(3), 4), (fe(0), 1), (fe(2), 2), (fe(5), 4)],
+            vec![(fe(1), 7), (p_minus_1, 7)],
+            vec![],
+        );
+        c.normalize(&modulus);
+
+        assert_eq!(c.a().0, [(fe(2), 2), (fe(8), 4)]);
+        assert!(c.b().is_empty());
+        assert!(c.c().is_empty());
+
+        let mut constraints = Constraints(vec![
+            Constraint::new(vec![(fe(1), 2), (fe(1), 1)], vec![], vec![]),
+            Constraint::new(vec![(fe(1), 1), (fe(1), 2)], vec![], vec![]),
+        ]);
+        constraints.normalize_all(&modulus);
+        assert_eq!(constraints.0[0], constraints.0[1]);
+    }
+}
@@ -0,0 +1,214 @@
+//! Renumbering the wires of a circuit.
+
+use crate::validate::wire_out_of_range;
+use crate::{Header, R1csError, R1csFile, Result, ValidationError};
+
+/// Options for [`R1csFile::permute_wires_with`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PermuteOptions {
+    /// Allow moving wire 0, which circom reserves for the constant one.
+    pub allow_moving_constant: bool,
+    /// New header signal counts, for permutations that move wires between the public and private
+    /// regions. Counts left as `None` are kept.
+    pub n_pub_out: Option<u32>,
+    pub n_pub_in: Option<u32>,
+    pub n_prvt_in: Option<u32>,
+}
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Renumbers the wires so that wire `w` becomes `perm[w]`, see
+    /// [`R1csFile::permute_wires_with`].
+    pub fn permute_wires(&mut self, perm: &[u32]) -> Result<()> {
+        self.permute_wires_with(perm, &PermuteOptions::default())
+    }
+
+    /// Renumbers the wires so that wire `w` becomes `perm[w]`, in every constraint term and in the
+    /// wire map.
+    ///
+    /// `perm` must be a bijection on `0..n_wires` keeping wire 0 in place unless
+    /// [`PermuteOptions::allow_moving_constant`] is set. The constraints and the wire map must be
+    /// consistent with `n_wires`. Nothing is modified if any of this doesn't hold, and
+    /// [`R1csError::Invalid`] is returned.
+    pub fn permute_wires_with(&mut self, perm: &[u32], opts: &PermuteOptions) -> Result<()> {
+        let header = self
+            .check_permutation(perm, opts)
+            .map_err(R1csError::Invalid)?;
+        self.apply_permutation(perm, header);
+
+        Ok(())
+    }
+
+    /// Renumbers the wires with a `perm` checked by [`R1csFile::permuted_header`], then sets the
+    /// header it returned.
+    pub(crate) fn apply_permutation(&mut self, perm: &[u32], header: Header<FS>) {
+        for c in &mut self.constraints.0 {
+            for combination in [&mut c.0, &mut c.1, &mut c.2] {
+                for (_, wire) in combination.iter_mut() {
+                    *wire = perm[*wire as usize];
+                }
+            }
+        }
+
+        let mut map = vec![0; self.map.0.len()];
+        for (wire, label) in self.map.0.iter().enumerate() {
+            map[perm[wire] as usize] = *label;
+        }
+        self.map.0 = map;
+
+        self.header = header;
+    }
+
+    /// Checks the constraints and the wire map against `n_wires`, then `perm` and `opts`, see
+    /// [`R1csFile::permuted_header`].
+    fn check_permutation(
+        &self,
+        perm: &[u32],
+        opts: &PermuteOptions,
+    ) -> Result<Header<FS>, ValidationError> {
+        let n_wires = self.header.n_wires;
+        if !self.map.is_empty() && self.map.0.len() != n_wires as usize {
+            return Err(ValidationError::WireMapLengthMismatch {
+                n_wires,
+                found: self.map.0.len(),
+            });
+        }
+        if let Some(e) = wire_out_of_range(&self.constraints.0, 0, n_wires) {
+            return Err(e);
+        }
+
+        self.permuted_header(perm, opts)
+    }
+
+    /// Checks `perm` and `opts`, returning the header with the new signal counts.
+    pub(crate) fn permuted_header(
+        &self,
+        perm: &[u32],
+        opts: &PermuteOptions,
+    ) -> Result<Header<FS>, ValidationError> {
+        let n_wires = self.header.n_wires;
+        if perm.len() != n_wires as usize {
+            return Err(ValidationError::PermutationLengthMismatch {
+                n_wires,
+                found: perm.len(),
+            });
+        }
+
+        let mut taken = vec![false; perm.len()];
+        for (wire, target) in perm.iter().enumerate() {
+            match taken.get_mut(*target as usize) {
+                Some(taken) if !*taken => *taken = true,
+                _ => {
+                    return Err(ValidationError::InvalidPermutation {
+                        wire: wire as u32,
+                        target: *target,
+                    })
+                }
+            }
+        }
+        if !opts.allow_moving_constant && perm.first().is_some_and(|target| *target != 0) {
+            return Err(ValidationError::ConstantWireMoved { target: perm[0] });
+        }
+
+        let header = Header {
+            n_pub_out: opts.n_pub_out.unwrap_or(self.header.n_pub_out),
+            n_pub_in: opts.n_pub_in.unwrap_or(self.header.n_pub_in),
+            n_prvt_in: opts.n_prvt_in.unwrap_or(self.header.n_prvt_in),
+            ..self.header
+        };
+        header.check_layout()?;
+
+        Ok(header)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permute_wires() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let original = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let mut file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        // Rotate wires 1..7 and swap public inputs with private ones.
+        let perm = [0, 2, 3, 4, 5, 6, 1];
+        let opts = PermuteOptions {
+            n_pub_in: Some(3),
+            n_prvt_in: Some(2),
+            ..PermuteOptions::default()
+        };
+        file.permute_wires_with(&perm, &opts).unwrap();
+        assert_eq!(file.constraints.0[0].a()[0].1, perm[5]);
+        assert_eq!(file.map.label_of(perm[3]), original.map.label_of(3));
+        assert_eq!((file.header.n_pub_in, file.header.n_prvt_in), (3, 2));
+        assert_eq!(file.validate(), Ok(()));
+
+        let mut inverse = vec![0; perm.len()];
+        for (wire, target) in perm.iter().enumerate() {
+            inverse[*target as usize] = wire as u32;
+        }
+        let opts = PermuteOptions {
+            n_pub_in: Some(2),
+            n_prvt_in: Some(3),
+            ..PermuteOptions::default()
+        };
+        file.permute_wires_with(&inverse, &opts).unwrap();
+        assert_eq!(file, original);
+    }
+
+    #[test]
+    fn test_permute_wires_errors() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let original = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let mut file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        let invalid = |file: &mut R1csFile<32>, perm: &[u32], opts| match file
+            .permute_wires_with(perm, &opts)
+        {
+            Err(R1csError::Invalid(e)) => e,
+            other => panic!("unexpected result: {:?}", other),
+        };
+        let opts = PermuteOptions::default();
+
+        assert_eq!(
+            invalid(&mut file, &[0, 1, 2], opts),
+            ValidationError::PermutationLengthMismatch {
+                n_wires: 7,
+                found: 3
+            }
+        );
+        assert_eq!(
+            invalid(&mut file, &[0, 1, 2, 3, 4, 5, 5], opts),
+            ValidationError::InvalidPermutation { wire: 6, target: 5 }
+        );
+        assert_eq!(
+            invalid(&mut file, &[0, 1, 2, 3, 4, 5, 7], opts),
+            ValidationError::InvalidPermutation { wire: 6, target: 7 }
+        );
+        assert_eq!(
+            invalid(&mut file, &[1, 0, 2, 3, 4, 5, 6], opts),
+            ValidationError::ConstantWireMoved { target: 1 }
+        );
+        let too_many = PermuteOptions {
+            n_prvt_in: Some(4),
+            ..opts
+        };
+        assert_eq!(
+            invalid(&mut file, &[0, 1, 2, 3, 4, 5, 6], too_many),
+            ValidationError::TooManySignals {
+                signals: 8,
+                n_wires: 7
+            }
+        );
+        assert_eq!(file, original);
+
+        let allowed = PermuteOptions {
+            allow_moving_constant: true,
+            ..opts
+        };
+        file.permute_wires_with(&[1, 0, 2, 3, 4, 5, 6], &allowed)
+            .unwrap();
+        assert_eq!(file.map.0[..2], [original.map.0[1], original.map.0[0]]);
+    }
+}
@@ -0,0 +1,168 @@
+//! Primality check of the header prime.
+
+use core::fmt;
+
+use crate::{FieldElement, Header, Modulus};
+
+/// Values below this are rejected before testing, no circuit is compiled over such a small field.
+const MIN_PRIME: u64 = 1 << 16;
+
+/// Miller-Rabin bases, deterministic up to 3.3 * 10^24 and a strong check above.
+const WITNESSES: [u8; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Why [`Header::check_prime`] rejected the prime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrimeCheckError {
+    /// The prime is below 2^16.
+    TooSmall,
+    Even,
+    /// `witness` proves the prime composite.
+    Composite {
+        witness: u8,
+    },
+}
+
+impl fmt::Display for PrimeCheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrimeCheckError::TooSmall => write!(f, "The prime is below {}", MIN_PRIME),
+            PrimeCheckError::Even => f.write_str("The prime is even"),
+            PrimeCheckError::Composite { witness } => {
+                write!(f, "The prime is composite, as shown by witness {}", witness)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PrimeCheckError {}
+
+impl<const FS: usize> Header<FS> {
+    /// Checks that the prime is prime with the Miller-Rabin test over a fixed set of bases,
+    /// catching corrupted headers that pass the structural checks.
+    ///
+    /// This takes a few hundred modular multiplications per base, much more than
+    /// [`crate::R1csFile::validate`], which only runs it if
+    /// [`crate::ValidateOptions::check_primality`] is set.
+    pub fn check_prime(&self) -> Result<(), PrimeCheckError> {
+        let prime = &self.prime;
+        if prime.to_u64().is_some_and(|p| p < MIN_PRIME) {
+            return Err(PrimeCheckError::TooSmall);
+        }
+        let modulus = Modulus::new(*prime).map_err(|_| PrimeCheckError::Even)?;
+
+        // p - 1 = d 2^s with d odd.
+        let mut minus_one = [0u8; FS];
+        minus_one.copy_from_slice(prime.as_bytes());
+        minus_one[0] ^= 1;
+        let minus_one = FieldElement::from(minus_one);
+        let s = (0..FS * 8)
+            .find(|&i| bit(&minus_one, i))
+            .expect("p - 1 is not zero");
+        let mut d = [0u8; FS];
+        for i in (s..FS * 8).filter(|&i| bit(&minus_one, i)) {
+            d[(i - s) / 8] |= 1 << ((i - s) % 8);
+        }
+        let d = FieldElement::from(d);
+
+        let one = small(1);
+        for witness in WITNESSES {
+            let mut x = modulus.pow(&small(witness), &d);
+            if x == one || x == minus_one {
+                continue;
+            }
+
+            let mut probable = false;
+            for _ in 1..s {
+                x = modulus.mul(&x, &x);
+                if x == minus_one {
+                    probable = true;
+                    break;
+                }
+            }
+            if !probable {
+                return Err(PrimeCheckError::Composite { witness });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn bit<const FS: usize>(e: &FieldElement<FS>, i: usize) -> bool {
+    e.byte(i / 8).is_some_and(|b| b >> (i % 8) & 1 == 1)
+}
+
+fn small<const FS: usize>(value: u8) -> FieldElement<FS> {
+    let mut bytes = [0u8; FS];
+    bytes[0] = value;
+    FieldElement::from(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primes;
+
+    fn header<const FS: usize>(prime: FieldElement<FS>) -> Header<FS> {
+        Header {
+            prime,
+            n_wires: 1,
+            n_pub_out: 0,
+            n_pub_in: 0,
+            n_prvt_in: 0,
+            n_labels: 1,
+            n_constraints: 0,
+        }
+    }
+
+    #[test]
+    fn test_known_primes() {
+        for prime in [
+            primes::BN254_SCALAR,
+            primes::BLS12_381_SCALAR,
+            primes::BLS12_377_SCALAR,
+            primes::PALLAS_BASE,
+            primes::VESTA_BASE,
+        ] {
+            assert_eq!(header(prime).check_prime(), Ok(()));
+
+            // Flipping any bit but the lowest in a single byte gives a composite for these.
+            for i in 0..32 {
+                for mask in [0x02, 0x80] {
+                    let mut bytes = [0u8; 32];
+                    bytes.copy_from_slice(prime.as_bytes());
+                    bytes[i] ^= mask;
+                    let corrupted = header(FieldElement::from(bytes));
+                    assert!(matches!(
+                        corrupted.check_prime(),
+                        Err(PrimeCheckError::Composite { .. })
+                    ));
+                }
+            }
+
+            let mut even = [0u8; 32];
+            even.copy_from_slice(prime.as_bytes());
+            even[0] ^= 1;
+            assert_eq!(
+                header(FieldElement::from(even)).check_prime(),
+                Err(PrimeCheckError::Even)
+            );
+        }
+
+        assert_eq!(header(primes::GOLDILOCKS).check_prime(), Ok(()));
+        // The 4-byte prime 2^31 - 1, and a Carmichael number passing bases 2, 3, 5 and 7.
+        assert_eq!(
+            header(FieldElement::<4>::from(0x7fff_ffffu32.to_le_bytes())).check_prime(),
+            Ok(())
+        );
+        assert!(matches!(
+            header(FieldElement::<8>::from_u64(3_215_031_751)).check_prime(),
+            Err(PrimeCheckError::Composite { .. })
+        ));
+        assert_eq!(
+            header(FieldElement::<8>::from_u64(65_521)).check_prime(),
+            Err(PrimeCheckError::TooSmall)
+        );
+    }
+}
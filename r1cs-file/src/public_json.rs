@@ -0,0 +1,120 @@
+//! The `public.json` file of snarkjs, the public signals of a witness, behind the `wtns` and
+//! `json` features.
+
+use std::io::{self, Read, Write};
+use std::ops::Range;
+
+use serde::Serialize;
+use serde_json::ser::{PrettyFormatter, Serializer};
+use wtns_file::WtnsFile;
+
+use crate::append::invalid_input;
+use crate::{FieldElement, Header, R1csError, Result, ValidatedR1cs};
+
+/// Writes the public outputs followed by the public inputs of `wtns` as a JSON array of decimal
+/// strings, formatted like snarkjs does.
+///
+/// Fails if the witness and the circuit have different primes, or if the witness is too short to
+/// hold the public signals of the circuit.
+pub fn export_public_json<W: Write, const FS: usize>(
+    wtns: &WtnsFile<FS>,
+    header: &Header<FS>,
+    w: W,
+) -> Result<()> {
+    write_public(wtns, header, header.public_wires()?, w)
+}
+
+/// [`export_public_json`] without checking the signal layout of the circuit again.
+pub fn export_public_json_validated<W: Write, const FS: usize>(
+    wtns: &WtnsFile<FS>,
+    r1cs: &ValidatedR1cs<FS>,
+    w: W,
+) -> Result<()> {
+    write_public(wtns, &r1cs.header, r1cs.public_wires(), w)
+}
+
+/// Writes the values of the `public` wires of `wtns`, see [`export_public_json`].
+fn write_public<W: Write, const FS: usize>(
+    wtns: &WtnsFile<FS>,
+    header: &Header<FS>,
+    public: Range<u32>,
+    w: W,
+) -> Result<()> {
+    if wtns.header.prime != header.prime {
+        return Err(invalid_input(
+            "The witness prime differs from the circuit prime",
+        ));
+    }
+    let public = wtns
+        .witness
+        .0
+        .get(public.start as usize..public.end as usize)
+        .ok_or_else(|| {
+            invalid_input("The witness is shorter than the public signals of the circuit")
+        })?;
+
+    let public: Vec<String> = public.iter().map(|v| v.to_dec_string()).collect();
+    let mut ser = Serializer::with_formatter(w, PrettyFormatter::with_indent(b" "));
+    public.serialize(&mut ser).map_err(io::Error::from)?;
+
+    Ok(())
+}
+
+/// Reads a `public.json` file written by [`export_public_json`] or snarkjs.
+pub fn parse_public_json<R: Read, const FS: usize>(r: R) -> Result<Vec<FieldElement<FS>>> {
+    let values: Vec<String> = serde_json::from_reader(r).map_err(io::Error::from)?;
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            FieldElement::from_dec_str(v).map_err(|e| {
+                R1csError::from(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("public signal {}: {}", i, e),
+                ))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{R1csFile, ValidateOptions};
+
+    #[test]
+    fn test_public_json() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let prime = file.header.prime;
+        let golden = std::fs::read("tests/simple_circuit.public.json").unwrap();
+
+        let mut witness: Vec<_> = (1..=7).map(FieldElement::from_u64).collect();
+        let mut minus_one = *prime;
+        minus_one[0] -= 1;
+        witness[2] = FieldElement::from(minus_one);
+        let wtns = WtnsFile::from_vec(witness.clone(), prime);
+
+        let mut out = Vec::new();
+        export_public_json(&wtns, &file.header, &mut out).unwrap();
+        assert_eq!(out, golden);
+        let validated = file
+            .clone()
+            .into_validated(&ValidateOptions::default())
+            .unwrap();
+        let mut out = Vec::new();
+        export_public_json_validated(&wtns, &validated, &mut out).unwrap();
+        assert_eq!(out, golden);
+        assert_eq!(
+            parse_public_json::<_, 32>(golden.as_slice()).unwrap(),
+            &witness[1..4]
+        );
+
+        let short = WtnsFile::from_vec(witness[..3].to_vec(), prime);
+        assert!(export_public_json(&short, &file.header, &mut Vec::new()).is_err());
+        let other = WtnsFile::from_vec(witness, FieldElement::from_u64(7));
+        assert!(export_public_json(&other, &file.header, &mut Vec::new()).is_err());
+        assert!(parse_public_json::<_, 32>(&b"[\"12a\"]"[..]).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+//! Preparing the inputs of the rapidsnark prover, behind the `wtns` and `json` features.
+
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use wtns_file::{WtnsError, WtnsFile};
+
+use crate::{
+    check_witness, export_public_json_validated, FieldElement, Header, ParseMode, R1csError,
+    R1csFile, ReadOptions, Result, SatisfactionError, ValidateOptions, ValidationError,
+};
+
+/// The inputs of a rapidsnark proof, checked and prepared by [`prepare_rapidsnark_inputs`].
+///
+/// rapidsnark is run as `prover <zkey> <wtns> <proof_json> <public_json>`, with a zkey set up for
+/// the circuit at `r1cs`. Running it overwrites `public_json` with the same content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RapidsnarkInputs<const FS: usize> {
+    /// The circuit, as an absolute path.
+    pub r1cs: PathBuf,
+    /// The witness, as an absolute path.
+    pub wtns: PathBuf,
+    /// The public signals of the witness, in the `public.json` format of snarkjs.
+    pub public_json: PathBuf,
+    /// Where the proof is expected, next to `public_json`. It isn't created.
+    pub proof_json: PathBuf,
+    pub header: Header<FS>,
+    /// The public outputs followed by the public inputs, as written to `public_json`.
+    pub public: Vec<FieldElement<FS>>,
+}
+
+/// A reason for [`prepare_rapidsnark_inputs`] to reject its inputs.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RapidsnarkError<const FS: usize> {
+    /// Reading the circuit or writing `public.json` failed. The circuit is read in
+    /// [`ParseMode::Strict`], inconsistent sections are errors.
+    R1cs(R1csError),
+    /// Reading the witness failed.
+    Wtns(WtnsError),
+    /// The circuit failed [`R1csFile::into_validated`].
+    InvalidCircuit(Vec<ValidationError>),
+    /// rapidsnark requires exactly one witness value per wire.
+    WitnessLength { witness_len: usize, n_wires: u32 },
+    /// The witness doesn't satisfy the circuit.
+    Witness(SatisfactionError<FS>),
+}
+
+impl<const FS: usize> fmt::Display for RapidsnarkError<FS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RapidsnarkError::R1cs(e) => e.fmt(f),
+            RapidsnarkError::Wtns(e) => e.fmt(f),
+            RapidsnarkError::InvalidCircuit(errors) => {
+                write!(f, "Invalid circuit: {} errors", errors.len())?;
+                for (i, e) in errors.iter().enumerate() {
+                    let separator = if i == 0 { ", " } else { "; " };
+                    write!(f, "{}{}", separator, e)?;
+                }
+                Ok(())
+            }
+            RapidsnarkError::WitnessLength {
+                witness_len,
+                n_wires,
+            } => write!(
+                f,
+                "The witness has {} values but the circuit has {} wires",
+                witness_len, n_wires
+            ),
+            RapidsnarkError::Witness(e) => write!(f, "Invalid witness: {}", e),
+        }
+    }
+}
+
+impl<const FS: usize> std::error::Error for RapidsnarkError<FS> {}
+
+impl<const FS: usize> From<R1csError> for RapidsnarkError<FS> {
+    fn from(e: R1csError) -> Self {
+        RapidsnarkError::R1cs(e)
+    }
+}
+
+impl<const FS: usize> From<WtnsError> for RapidsnarkError<FS> {
+    fn from(e: WtnsError) -> Self {
+        RapidsnarkError::Wtns(e)
+    }
+}
+
+impl<const FS: usize> From<SatisfactionError<FS>> for RapidsnarkError<FS> {
+    fn from(e: SatisfactionError<FS>) -> Self {
+        RapidsnarkError::Witness(e)
+    }
+}
+
+/// Checks the circuit at `r1cs_path` and the witness at `wtns_path`, then writes the public
+/// signals of the witness to `public.json` in `out_dir`, created if needed.
+///
+/// The circuit is read in [`ParseMode::Strict`] and must pass [`R1csFile::into_validated`]. The
+/// witness must hold one value per wire and satisfy every constraint, see [`check_witness`].
+/// Nothing is written unless every check passes. `public.json` is byte for byte the file snarkjs
+/// and rapidsnark write, see [`export_public_json`].
+pub fn prepare_rapidsnark_inputs<const FS: usize>(
+    r1cs_path: impl AsRef<Path>,
+    wtns_path: impl AsRef<Path>,
+    out_dir: impl AsRef<Path>,
+) -> Result<RapidsnarkInputs<FS>, RapidsnarkError<FS>> {
+    let (r1cs_path, wtns_path) = (r1cs_path.as_ref(), wtns_path.as_ref());
+    let opts = ReadOptions {
+        mode: ParseMode::Strict,
+        ..ReadOptions::default()
+    };
+    let read = || -> Result<R1csFile<FS>> {
+        let file = File::open(r1cs_path)?;
+        Ok(R1csFile::read_with(BufReader::new(file), &opts)?.0)
+    };
+    let r1cs = read().map_err(|e| R1csError::file(r1cs_path, e))?;
+    let r1cs = r1cs
+        .into_validated(&ValidateOptions::default())
+        .map_err(RapidsnarkError::InvalidCircuit)?;
+
+    let wtns = WtnsFile::<FS>::read_file(wtns_path)?;
+    let witness_len = wtns.witness.0.len();
+    if witness_len != r1cs.header.n_wires as usize {
+        return Err(RapidsnarkError::WitnessLength {
+            witness_len,
+            n_wires: r1cs.header.n_wires,
+        });
+    }
+    check_witness(&r1cs, &wtns)?;
+
+    let out_dir = out_dir.as_ref();
+    let public_json = out_dir.join("public.json");
+    let write = || -> Result<()> {
+        std::fs::create_dir_all(out_dir)?;
+        crate::fs::write_atomic(&public_json, |w| {
+            export_public_json_validated(&wtns, &r1cs, w)
+        })
+    };
+    write().map_err(|e| R1csError::file(&public_json, e))?;
+
+    let public = r1cs.public_wires();
+    let public = wtns.witness.0[public.start as usize..public.end as usize].to_vec();
+    let canonicalize = |path: &Path| {
+        path.canonicalize()
+            .map_err(|e| R1csError::file(path, e.into()))
+    };
+
+    Ok(RapidsnarkInputs {
+        r1cs: canonicalize(r1cs_path)?,
+        wtns: canonicalize(wtns_path)?,
+        proof_json: canonicalize(out_dir)?.join("proof.json"),
+        public_json: canonicalize(&public_json)?,
+        header: r1cs.into_inner().header,
+        public,
+    })
+}
@@ -0,0 +1,229 @@
+use std::io::Read;
+
+use iden3_binfile::BinFileReader;
+
+use crate::{
+    Constraint, CountingReader, CustomGatesApplication, CustomGatesList, Header, R1csError,
+    ReadOptions, Result, SectionHeader, SectionType, Sections, WireMap, FORMAT,
+};
+
+/// Streaming reader yielding constraints one at a time.
+///
+/// The header is read eagerly. Constraints are then decoded straight off the reader, holding a
+/// single constraint in memory at a time, while the other sections are read as they are met.
+/// The header must come before the constraints, as in files written by circom.
+pub struct R1csReader<R, const FS: usize> {
+    file: BinFileReader<R>,
+    opts: ReadOptions,
+    sections: Sections<FS>,
+    /// Bytes left in the constraints section being streamed.
+    constraints_left: u64,
+    failed: bool,
+}
+
+impl<R: Read, const FS: usize> R1csReader<R, FS> {
+    /// Reads sections up to and including the header.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "r1cs_stream",
+            level = "debug",
+            skip_all,
+            fields(
+                field_size = FS,
+                n_wires = tracing::field::Empty,
+                n_constraints = tracing::field::Empty
+            )
+        )
+    )]
+    pub fn new(r: R) -> Result<Self> {
+        let mut reader = R1csReader {
+            file: BinFileReader::new(r, &FORMAT)?,
+            opts: ReadOptions::default(),
+            sections: Sections::default(),
+            constraints_left: 0,
+            failed: false,
+        };
+
+        while reader.sections.header.is_none() {
+            let section_header = reader
+                .next_section()?
+                .ok_or(R1csError::MissingSection(SectionType::Header))?;
+            if section_header.ty() == Some(SectionType::Constraint) {
+                return Err(R1csError::SectionOutOfOrder {
+                    section: SectionType::Constraint,
+                    offset: reader.file.position() - 12,
+                });
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::Span::current()
+            .record("n_wires", reader.header().n_wires)
+            .record("n_constraints", reader.header().n_constraints);
+
+        Ok(reader)
+    }
+
+    pub fn header(&self) -> &Header<FS> {
+        // Read by the constructor.
+        self.sections.header.as_ref().unwrap()
+    }
+
+    /// Iterates over the remaining constraints, in file order.
+    ///
+    /// Iteration stops after the first error.
+    pub fn constraints(&mut self) -> impl Iterator<Item = Result<Constraint<FS>>> + '_ {
+        std::iter::from_fn(move || {
+            if self.failed {
+                return None;
+            }
+
+            let next = self.next_constraint().transpose();
+            self.failed = matches!(next, Some(Err(_)));
+            next
+        })
+    }
+
+    /// Reads the remaining sections, skipping constraints that weren't iterated over, and returns
+    /// the wire map, empty if the file has none.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "r1cs_stream_wire_map",
+            level = "debug",
+            skip_all,
+            fields(num_sections = self.file.num_sections())
+        )
+    )]
+    pub fn wire_map(&mut self) -> Result<&WireMap> {
+        // Constraints not iterated over are skipped along with their section.
+        self.constraints_left = 0;
+        while self.next_section()?.is_some() {
+            self.constraints_left = 0;
+        }
+        self.file.skip_section()?;
+
+        Ok(self.sections.map.get_or_insert_with(WireMap::default))
+    }
+
+    /// Like [`R1csReader::wire_map`], also returning the custom gates sections.
+    pub(crate) fn remaining_sections(
+        &mut self,
+    ) -> Result<(
+        &WireMap,
+        Option<&CustomGatesList<FS>>,
+        Option<&CustomGatesApplication>,
+    )> {
+        self.wire_map()?;
+        let sections = &self.sections;
+        // Set by `wire_map`.
+        let map = sections.map.as_ref().unwrap();
+
+        Ok((
+            map,
+            sections.custom_gates_list.as_ref(),
+            sections.custom_gates_application.as_ref(),
+        ))
+    }
+
+    fn next_constraint(&mut self) -> Result<Option<Constraint<FS>>> {
+        loop {
+            if self.constraints_left > 0 {
+                let offset = self.file.position();
+                let mut r = CountingReader::at(&mut self.file, offset);
+                let mut section_data = (&mut r).take(self.constraints_left);
+                let constraint = Constraint::read(&mut section_data, &self.opts).map_err(|e| {
+                    let position = section_data.get_ref().position();
+                    e.eof_in(Some(SectionType::Constraint), position)
+                })?;
+                self.constraints_left = section_data.limit();
+
+                // A constraint cut off by the end of its section.
+                return match constraint {
+                    Some(constraint) => Ok(Some(constraint)),
+                    None => Err(R1csError::UnexpectedEof {
+                        section: Some(SectionType::Constraint),
+                        offset,
+                    }),
+                };
+            }
+
+            if self.next_section()?.is_none() {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Reads the next section unless it holds constraints, which are left to stream. `None` once
+    /// every section has been read.
+    fn next_section(&mut self) -> Result<Option<SectionHeader>> {
+        let (id, size, section) = match self.file.next_section()? {
+            Some(section) => section,
+            None => return Ok(None),
+        };
+        let section_header = SectionHeader { id, size };
+        match section_header.ty() {
+            Some(SectionType::Constraint) => self.constraints_left = size,
+            _ => {
+                let offset = section.position();
+                let mut r = CountingReader::at(section, offset);
+                self.sections
+                    .read_section(&mut r, &section_header, &self.opts)?;
+            }
+        }
+
+        Ok(Some(section_header))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::R1csFile;
+
+    #[test]
+    fn test_stream_constraints() {
+        for path in [
+            "tests/simple_circuit.r1cs",
+            "tests/test_circuit.r1cs",
+            "tests/split_constraints.r1cs",
+        ] {
+            let data = std::fs::read(path).unwrap();
+            let expected = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+            let mut reader = R1csReader::<_, 32>::new(data.as_slice()).unwrap();
+            assert_eq!(*reader.header(), expected.header);
+
+            let mut n = 0;
+            for (constraint, expected) in reader.constraints().zip(&expected.constraints.0) {
+                assert_eq!(constraint.unwrap(), *expected);
+                n += 1;
+            }
+            assert_eq!(n, expected.constraints.0.len());
+            assert_eq!(*reader.wire_map().unwrap(), expected.map);
+        }
+    }
+
+    #[test]
+    fn test_stream_errors() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+
+        // The wire map is still reachable when the constraints are skipped.
+        let mut reader = R1csReader::<_, 32>::new(data.as_slice()).unwrap();
+        reader.constraints().next().unwrap().unwrap();
+        assert_eq!(reader.wire_map().unwrap().0.len(), 7);
+
+        let truncated = &data[..400];
+        let mut reader = R1csReader::<_, 32>::new(truncated).unwrap();
+        let results: Vec<_> = reader.constraints().collect();
+        assert!(results[0].is_ok());
+        assert!(matches!(
+            results.last(),
+            Some(Err(R1csError::UnexpectedEof {
+                section: Some(SectionType::Constraint),
+                ..
+            }))
+        ));
+    }
+}
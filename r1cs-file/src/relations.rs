@@ -0,0 +1,155 @@
+//! Synthesis of circuits into arkworks constraint systems, behind the `ark-relations` feature.
+
+use std::marker::PhantomData;
+
+use ark_ff::PrimeField;
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystemRef, LinearCombination as ArkCombination,
+    SynthesisError, Variable,
+};
+
+use crate::{FieldElement, LinearCombination, R1csFile, ValidationError};
+
+/// A circuit, and optionally its witness, synthesized into an arkworks constraint system over `F`.
+///
+/// Wire 0 is mapped to the constant one of the constraint system, the public output and input
+/// wires to instance variables and the remaining wires to witness variables. Coefficients and
+/// witness values not below the prime fail synthesis with [`SynthesisError::Unsatisfiable`].
+#[derive(Debug, Clone, Copy)]
+pub struct R1csAdapter<'a, F, const FS: usize> {
+    r1cs: &'a R1csFile<FS>,
+    witness: Option<&'a [FieldElement<FS>]>,
+    field: PhantomData<F>,
+}
+
+impl<'a, F: PrimeField, const FS: usize> R1csAdapter<'a, F, FS> {
+    /// Wraps the circuit, checking that its prime is the modulus of `F`.
+    pub fn new(r1cs: &'a R1csFile<FS>) -> Result<Self, ValidationError> {
+        r1cs.header.check_modulus::<F>()?;
+
+        Ok(R1csAdapter {
+            r1cs,
+            witness: None,
+            field: PhantomData,
+        })
+    }
+
+    /// Assigns the variables from `witness`, indexed by wire, as read from a `.wtns` file.
+    pub fn with_witness(mut self, witness: &'a [FieldElement<FS>]) -> Self {
+        self.witness = Some(witness);
+        self
+    }
+
+    fn value(&self, wire: usize) -> Result<F, SynthesisError> {
+        self.witness
+            .and_then(|witness| witness.get(wire))
+            .ok_or(SynthesisError::AssignmentMissing)?
+            .to_field()
+            .map_err(|_| SynthesisError::Unsatisfiable)
+    }
+}
+
+impl<F: PrimeField, const FS: usize> ConstraintSynthesizer<F> for R1csAdapter<'_, F, FS> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let header = &self.r1cs.header;
+        let public = header
+            .public_wires()
+            .map_err(|_| SynthesisError::Unsatisfiable)?;
+
+        let mut variables = Vec::with_capacity(header.n_wires as usize);
+        variables.push(Variable::One);
+        for wire in 1..header.n_wires as usize {
+            let variable = if public.contains(&(wire as u32)) {
+                cs.new_input_variable(|| self.value(wire))?
+            } else {
+                cs.new_witness_variable(|| self.value(wire))?
+            };
+            variables.push(variable);
+        }
+
+        let combination = |lc: &LinearCombination<FS>| {
+            lc.iter()
+                .map(|(coeff, wire)| {
+                    let coeff = coeff
+                        .to_field()
+                        .map_err(|_| SynthesisError::Unsatisfiable)?;
+                    let variable = *variables
+                        .get(*wire as usize)
+                        .ok_or(SynthesisError::Unsatisfiable)?;
+                    Ok((coeff, variable))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(ArkCombination)
+        };
+        for c in &self.r1cs.constraints.0 {
+            cs.enforce_constraint(combination(&c.0)?, combination(&c.1)?, combination(&c.2)?)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_ff::Field;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn test_synthesize() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        // Solves the three constraints for w_2 = w_3 = 9 and w_4 = w_6 = 1.
+        let [w2, w3, w4, w6] = [9u64, 9, 1, 1].map(Fr::from);
+        let w5 = (Fr::from(68u64) * Fr::from(290u64).inverse().unwrap() - Fr::from(8u64) * w6)
+            * Fr::from(3u64).inverse().unwrap();
+        let w1 = -(Fr::from(3u64) * w5 + Fr::from(8u64) * w4) * Fr::from(4u64).inverse().unwrap();
+        let witness: Vec<_> = [Fr::from(1u64), w1, w2, w3, w4, w5, w6]
+            .iter()
+            .map(|&w| FieldElement::from_field(w).unwrap())
+            .collect();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        R1csAdapter::<Fr, 32>::new(&file)
+            .unwrap()
+            .with_witness(&witness)
+            .generate_constraints(cs.clone())
+            .unwrap();
+        assert_eq!(cs.num_instance_variables(), 4);
+        assert_eq!(cs.num_witness_variables(), 3);
+        assert_eq!(cs.num_constraints(), 3);
+        assert!(cs.is_satisfied().unwrap());
+
+        let mut wrong = witness.clone();
+        wrong[2] = FieldElement::from_u64(8);
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        R1csAdapter::<Fr, 32>::new(&file)
+            .unwrap()
+            .with_witness(&wrong)
+            .generate_constraints(cs.clone())
+            .unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        assert_eq!(
+            R1csAdapter::<Fr, 32>::new(&file)
+                .unwrap()
+                .generate_constraints(cs),
+            Err(SynthesisError::AssignmentMissing)
+        );
+    }
+
+    #[test]
+    fn test_prime_mismatch() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let mut file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        file.header.prime = FieldElement::from_u64(7);
+
+        assert!(matches!(
+            R1csAdapter::<Fr, 32>::new(&file),
+            Err(ValidationError::ModulusMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,159 @@
+//! Recomputing stale header counts from the sections.
+
+use crate::{R1csFile, ValidationError};
+
+/// The outcome of [`R1csFile::repair_header`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepairReport {
+    /// Header fields that were changed, in header order.
+    pub changes: Vec<HeaderChange>,
+    /// Inconsistencies left in place, as repairing them would need more than the header.
+    pub flagged: Vec<ValidationError>,
+}
+
+/// A header field changed by [`R1csFile::repair_header`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderChange {
+    pub field: &'static str,
+    pub old: u64,
+    pub new: u64,
+}
+
+impl RepairReport {
+    /// Whether the header was left as is, with nothing flagged.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.flagged.is_empty()
+    }
+
+    fn change(&mut self, field: &'static str, old: u64, new: u64) {
+        if old != new {
+            self.changes.push(HeaderChange { field, old, new });
+        }
+    }
+}
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Recomputes the header counts from the constraints and the wire map, for files written by
+    /// tools that edit the sections without updating the header.
+    ///
+    /// - `n_wires` becomes the larger of the highest referenced wire plus one and the wire map
+    ///   length. A map shorter than the referenced wires is flagged with
+    ///   [`ValidationError::WireMapLengthMismatch`].
+    /// - `n_labels` is raised to the highest label plus one. A larger count is kept, as circom
+    ///   counts the labels of the wires it optimized away.
+    /// - `n_constraints` becomes the number of constraints.
+    ///
+    /// The public and private signal counts are never changed, signals that don't fit in the new
+    /// `n_wires` are flagged with [`ValidationError::TooManySignals`]. Unless something is
+    /// flagged, the repaired file passes [`R1csFile::validate`] and can be written.
+    pub fn repair_header(&mut self) -> RepairReport {
+        let mut report = RepairReport::default();
+        let header = &mut self.header;
+
+        let referenced = self
+            .constraints
+            .iter()
+            .flat_map(|c| c.terms())
+            .map(|(_, term)| u64::from(term.wire) + 1)
+            .max()
+            .unwrap_or(0);
+        let mapped = self.map.len() as u64;
+        let n_wires = referenced.max(mapped) as u32;
+        report.change("n_wires", header.n_wires.into(), n_wires.into());
+        header.n_wires = n_wires;
+        if mapped < referenced {
+            report.flagged.push(ValidationError::WireMapLengthMismatch {
+                n_wires,
+                found: self.map.len(),
+            });
+        }
+
+        if let Err(e) = header.check_layout() {
+            report.flagged.push(e);
+        }
+
+        let labels = self.map.iter().map(|label| label + 1).max().unwrap_or(0);
+        let n_labels = header.n_labels.max(labels);
+        report.change("n_labels", header.n_labels, n_labels);
+        header.n_labels = n_labels;
+
+        let n_constraints = self.constraints.len() as u32;
+        report.change(
+            "n_constraints",
+            header.n_constraints.into(),
+            n_constraints.into(),
+        );
+        header.n_constraints = n_constraints;
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Constraint, FieldElement, LinearCombination};
+
+    #[test]
+    fn test_repair_header() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let original = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let mut file = original.clone();
+        assert!(file.repair_header().is_empty());
+        assert_eq!(file, original);
+
+        // A constraint on a new wire, appended without touching the header.
+        let one = FieldElement::from_u64(1);
+        let wire = original.header.n_wires;
+        file.constraints.0.push(Constraint::new(
+            LinearCombination::term(one, wire),
+            LinearCombination::term(one, 0),
+            LinearCombination::term(one, 1),
+        ));
+        file.map.0.push(original.header.n_labels + 4);
+        file.header.n_constraints -= 1;
+        let report = file.repair_header();
+        let change = |field, old: u64, new: u64| HeaderChange { field, old, new };
+        assert_eq!(
+            report.changes,
+            vec![
+                change("n_wires", wire.into(), u64::from(wire) + 1),
+                change(
+                    "n_labels",
+                    original.header.n_labels,
+                    original.header.n_labels + 5
+                ),
+                change(
+                    "n_constraints",
+                    u64::from(original.header.n_constraints) - 1,
+                    u64::from(original.header.n_constraints) + 1
+                ),
+            ]
+        );
+        assert!(report.flagged.is_empty());
+        assert_eq!(file.validate(), Ok(()));
+        let mut written = Vec::new();
+        file.write(&mut written).unwrap();
+        assert_eq!(R1csFile::<32>::read(written.as_slice()).unwrap(), file);
+
+        // Without map entries for the referenced wires, nor wires for the signals.
+        file.map.0.truncate(1);
+        file.header.n_pub_in = 100;
+        let report = file.repair_header();
+        assert!(report.changes.is_empty());
+        assert_eq!(
+            report.flagged,
+            vec![
+                ValidationError::WireMapLengthMismatch {
+                    n_wires: wire + 1,
+                    found: 1
+                },
+                ValidationError::TooManySignals {
+                    signals: 101 + u64::from(original.header.n_pub_out + original.header.n_prvt_in),
+                    n_wires: wire + 1
+                },
+            ]
+        );
+        assert_eq!(file.header.n_pub_in, 100);
+    }
+}
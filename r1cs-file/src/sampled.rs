@@ -0,0 +1,246 @@
+//! Spot checks of witnesses on a sample of the constraints, behind the `wtns` feature.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use wtns_file::WtnsFile;
+
+use crate::satisfy::{check_constraint, check_shape};
+use crate::{R1csFile, SatisfactionError, UnsatisfiedConstraint, WireUsageIndex};
+
+/// The outcome of [`check_witness_sampled`].
+///
+/// This is a partial check: constraints left out of the sample may well be unsatisfied, see
+/// [`SampledCheckReport::is_complete`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SampledCheckReport<const FS: usize> {
+    /// The checked constraints, in ascending order.
+    pub checked: Vec<u32>,
+    /// The number of checked constraints referencing a public wire.
+    pub public: usize,
+    /// The number of constraints of the circuit.
+    pub total: usize,
+    /// The checked constraints for which `a * b != c`, in order.
+    pub unsatisfied: Vec<UnsatisfiedConstraint<FS>>,
+}
+
+impl<const FS: usize> SampledCheckReport<FS> {
+    /// Whether every checked constraint is satisfied. This says nothing of the others.
+    pub fn is_satisfied(&self) -> bool {
+        self.unsatisfied.is_empty()
+    }
+
+    /// Whether the sample covered every constraint, making this a full check.
+    pub fn is_complete(&self) -> bool {
+        self.checked.len() == self.total
+    }
+}
+
+impl<const FS: usize> fmt::Display for SampledCheckReport<FS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.is_complete() {
+            "Full"
+        } else {
+            "Partial"
+        };
+        write!(
+            f,
+            "{} check of {} of {} constraints, {} referencing public wires: ",
+            kind,
+            self.checked.len(),
+            self.total,
+            self.public
+        )?;
+        if self.is_satisfied() {
+            return f.write_str("all satisfied");
+        }
+
+        write!(f, "{} not satisfied:", self.unsatisfied.len())?;
+        for c in &self.unsatisfied {
+            write!(f, " #{}", c.index)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks `wtns` against every constraint of `r1cs` referencing a public wire, and against `k`
+/// other constraints picked at random from `seed`.
+///
+/// The same seed always picks the same constraints. The witness is checked as by
+/// [`crate::check_witness`], every error but [`SatisfactionError::Unsatisfied`] is returned as
+/// is, unsatisfied constraints are listed in the report.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "r1cs_check_witness_sampled",
+        level = "debug",
+        skip_all,
+        fields(n_constraints = r1cs.constraints.0.len(), k, seed)
+    )
+)]
+pub fn check_witness_sampled<const FS: usize>(
+    r1cs: &R1csFile<FS>,
+    wtns: &WtnsFile<FS>,
+    k: usize,
+    seed: u64,
+) -> Result<SampledCheckReport<FS>, SatisfactionError<FS>> {
+    let modulus = check_shape(r1cs, wtns)?;
+    let constraints = &r1cs.constraints.0;
+    let total = constraints.len();
+
+    let starts = r1cs
+        .header
+        .check_layout()
+        .map_err(SatisfactionError::InvalidLayout)?;
+    let public = WireUsageIndex::build(r1cs).constraints_involving(starts[0]..starts[2]);
+    let mut checked: BTreeSet<u32> = public.iter().copied().collect();
+    let n_public = public.len();
+
+    // Floyd's algorithm: `k` distinct constraints out of those not referencing public wires.
+    let others = total - public.len();
+    let mut picked = BTreeSet::new();
+    let mut rng = SplitMix64(seed);
+    for j in others.saturating_sub(k)..others {
+        let r = rng.below(j as u64 + 1) as usize;
+        if !picked.insert(r) {
+            picked.insert(j);
+        }
+    }
+    // The `r`-th constraint not referencing a public wire.
+    let mut public = public.iter().peekable();
+    let mut skipped = 0;
+    for r in picked {
+        while let Some(&&p) = public.peek() {
+            if p as usize > r + skipped {
+                break;
+            }
+            public.next();
+            skipped += 1;
+        }
+        checked.insert((r + skipped) as u32);
+    }
+
+    let checked: Vec<u32> = checked.into_iter().collect();
+    let mut unsatisfied = Vec::new();
+    for &index in &checked {
+        let index = index as usize;
+        if let Some(c) = check_constraint(&modulus, &wtns.witness.0, index, &constraints[index])? {
+            unsatisfied.push(c);
+        }
+    }
+
+    Ok(SampledCheckReport {
+        public: n_public,
+        checked,
+        total,
+        unsatisfied,
+    })
+}
+
+/// The SplitMix64 generator, enough for picking constraints and stable across platforms.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A value below `n`, with a negligible bias for the `n` of a constraint count.
+    fn below(&mut self, n: u64) -> u64 {
+        ((u128::from(self.next()) * u128::from(n)) >> 64) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::satisfy::tests::chain_witness;
+    use crate::FieldElement;
+
+    /// `tests/test_circuit.r1cs` and its chain witness.
+    fn read() -> (R1csFile<32>, WtnsFile<32>) {
+        let data = std::fs::read("tests/test_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let prime = file.header.prime;
+
+        (file, WtnsFile::from_vec(chain_witness(&prime), prime))
+    }
+
+    #[test]
+    fn test_check_witness_sampled() {
+        let (file, wtns) = read();
+        let total = file.constraints.len();
+        let public_wires = file.header.public_wires().unwrap();
+        let touches_public = |i: u32| {
+            let c = &file.constraints[i as usize];
+            [&c.0, &c.1, &c.2]
+                .iter()
+                .any(|lc| lc.iter().any(|(_, wire)| public_wires.contains(wire)))
+        };
+
+        let report = check_witness_sampled(&file, &wtns, 20, 42).unwrap();
+        assert!(report.is_satisfied());
+        assert!(!report.is_complete());
+        assert_eq!(report.total, total);
+        assert!(report.public > 0);
+        assert_eq!(report.checked.len(), report.public + 20);
+        assert!(report.checked.windows(2).all(|w| w[0] < w[1]));
+        let public: Vec<_> = (0..total as u32).filter(|&i| touches_public(i)).collect();
+        assert_eq!(report.public, public.len());
+        assert!(public.iter().all(|i| report.checked.contains(i)));
+        assert!(report
+            .to_string()
+            .starts_with("Partial check of 21 of 1000 constraints"));
+
+        // Deterministic for a fixed seed.
+        assert_eq!(check_witness_sampled(&file, &wtns, 20, 42).unwrap(), report);
+        let other = check_witness_sampled(&file, &wtns, 20, 43).unwrap();
+        assert_ne!(other.checked, report.checked);
+
+        let full = check_witness_sampled(&file, &wtns, total, 0).unwrap();
+        assert!(full.is_complete());
+        assert_eq!(full.checked, (0..total as u32).collect::<Vec<_>>());
+        assert!(full.to_string().starts_with("Full check"));
+    }
+
+    #[test]
+    fn test_check_witness_sampled_corrupted() {
+        let (file, wtns) = read();
+        let report = check_witness_sampled(&file, &wtns, 20, 42).unwrap();
+        let sampled = *report.checked.last().unwrap();
+        let outside = (0..file.constraints.len() as u32)
+            .find(|i| !report.checked.contains(i))
+            .unwrap();
+
+        // Corrupting the constant term of a constraint in the sample is caught.
+        let corrupt = |i: u32| {
+            let mut file = file.clone();
+            let c = &mut file.constraints.0[i as usize];
+            c.2 .0.push((FieldElement::from_u64(1), 0));
+            file
+        };
+        let report = check_witness_sampled(&corrupt(sampled), &wtns, 20, 42).unwrap();
+        assert_eq!(
+            report
+                .unsatisfied
+                .iter()
+                .map(|c| c.index as u32)
+                .collect::<Vec<_>>(),
+            [sampled]
+        );
+        assert!(report
+            .to_string()
+            .ends_with(&format!("1 not satisfied: #{}", sampled)));
+
+        // One outside of it isn't.
+        let corrupted = corrupt(outside);
+        let report = check_witness_sampled(&corrupted, &wtns, 20, 42).unwrap();
+        assert!(report.is_satisfied());
+        assert!(crate::check_witness(&corrupted, &wtns).is_err());
+    }
+}
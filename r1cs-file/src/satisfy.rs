@@ -0,0 +1,311 @@
+//! Checking witnesses against circuits, behind the `wtns` feature.
+
+use std::fmt;
+
+use wtns_file::WtnsFile;
+
+use crate::{Constraint, FieldElement, LinearCombination, Modulus, R1csFile, ValidationError};
+
+/// A witness that doesn't satisfy a circuit, see [`check_witness`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SatisfactionError<const FS: usize> {
+    /// The witness and the circuit have different primes.
+    PrimeMismatch,
+    /// The prime isn't odd, so there is no arithmetic modulo it.
+    InvalidPrime(ValidationError),
+    /// The signals of the header don't fit in its wires.
+    InvalidLayout(ValidationError),
+    /// The witness has fewer values than the circuit has wires.
+    WitnessTooShort { witness_len: usize, n_wires: u32 },
+    /// The witness value at `index` is not below the prime.
+    NonCanonicalValue { index: usize },
+    /// A constraint references a wire past the end of the witness.
+    WireOutOfRange { constraint: usize, wire: u32 },
+    /// Constraints for which `a * b != c`, in order.
+    Unsatisfied(Vec<UnsatisfiedConstraint<FS>>),
+}
+
+/// A constraint not satisfied by a witness, with its linear combinations evaluated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsatisfiedConstraint<const FS: usize> {
+    pub index: usize,
+    pub a: FieldElement<FS>,
+    pub b: FieldElement<FS>,
+    pub c: FieldElement<FS>,
+}
+
+impl<const FS: usize> fmt::Display for SatisfactionError<FS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SatisfactionError::PrimeMismatch => {
+                f.write_str("The witness prime differs from the circuit prime")
+            }
+            SatisfactionError::InvalidPrime(e) => write!(f, "Invalid prime: {}", e),
+            SatisfactionError::InvalidLayout(e) => write!(f, "Invalid signal layout: {}", e),
+            SatisfactionError::WitnessTooShort {
+                witness_len,
+                n_wires,
+            } => write!(
+                f,
+                "The witness has {} values but the circuit has {} wires",
+                witness_len, n_wires
+            ),
+            SatisfactionError::NonCanonicalValue { index } => {
+                write!(f, "Witness value {} is not below the prime", index)
+            }
+            SatisfactionError::WireOutOfRange { constraint, wire } => write!(
+                f,
+                "Constraint {} references wire {} past the end of the witness",
+                constraint, wire
+            ),
+            SatisfactionError::Unsatisfied(constraints) => match constraints.first() {
+                Some(first) => write!(
+                    f,
+                    "{} constraints not satisfied, the first one is {}: a = {:#x}, b = {:#x}, c = {:#x}",
+                    constraints.len(),
+                    first.index,
+                    first.a,
+                    first.b,
+                    first.c
+                ),
+                None => write!(f, "{} constraints not satisfied", constraints.len()),
+            },
+        }
+    }
+}
+
+impl<const FS: usize> std::error::Error for SatisfactionError<FS> {}
+
+/// Checks that `wtns` satisfies every constraint of `r1cs`, stopping at the first one that isn't.
+///
+/// The primes must match, the witness must hold a value for every wire and every value must be
+/// below the prime. The linear combinations are evaluated modulo the prime, coefficients being
+/// expected below it, see [`R1csFile::check_canonical_coefficients`].
+pub fn check_witness<const FS: usize>(
+    r1cs: &R1csFile<FS>,
+    wtns: &WtnsFile<FS>,
+) -> Result<(), SatisfactionError<FS>> {
+    check(r1cs, wtns, false)
+}
+
+/// Checks that `wtns` satisfies every constraint of `r1cs` like [`check_witness`], listing
+/// every unsatisfied constraint.
+pub fn check_witness_all<const FS: usize>(
+    r1cs: &R1csFile<FS>,
+    wtns: &WtnsFile<FS>,
+) -> Result<(), SatisfactionError<FS>> {
+    check(r1cs, wtns, true)
+}
+
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "r1cs_check_witness",
+        level = "debug",
+        skip_all,
+        fields(
+            n_constraints = r1cs.constraints.0.len(),
+            witness_len = wtns.witness.0.len(),
+            all,
+            unsatisfied = tracing::field::Empty
+        )
+    )
+)]
+fn check<const FS: usize>(
+    r1cs: &R1csFile<FS>,
+    wtns: &WtnsFile<FS>,
+    all: bool,
+) -> Result<(), SatisfactionError<FS>> {
+    let modulus = check_shape(r1cs, wtns)?;
+    let witness = &wtns.witness.0;
+
+    let mut unsatisfied = Vec::new();
+    for (index, constraint) in r1cs.constraints.0.iter().enumerate() {
+        if let Some(c) = check_constraint(&modulus, witness, index, constraint)? {
+            unsatisfied.push(c);
+            if !all {
+                break;
+            }
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("unsatisfied", unsatisfied.len());
+
+    if unsatisfied.is_empty() {
+        Ok(())
+    } else {
+        Err(SatisfactionError::Unsatisfied(unsatisfied))
+    }
+}
+
+/// Checks the primes, the witness length and the witness values, returning the arithmetic modulo
+/// the prime.
+pub(crate) fn check_shape<const FS: usize>(
+    r1cs: &R1csFile<FS>,
+    wtns: &WtnsFile<FS>,
+) -> Result<Modulus<FS>, SatisfactionError<FS>> {
+    let prime = r1cs.header.prime;
+    if wtns.header.prime != prime {
+        return Err(SatisfactionError::PrimeMismatch);
+    }
+    let modulus = Modulus::new(prime).map_err(SatisfactionError::InvalidPrime)?;
+    let witness = &wtns.witness.0;
+    if witness.len() < r1cs.header.n_wires as usize {
+        return Err(SatisfactionError::WitnessTooShort {
+            witness_len: witness.len(),
+            n_wires: r1cs.header.n_wires,
+        });
+    }
+    if let Some(index) = witness.iter().position(|v| !modulus.is_canonical(v)) {
+        return Err(SatisfactionError::NonCanonicalValue { index });
+    }
+
+    Ok(modulus)
+}
+
+/// Checks constraint `index`, returning it if `a * b != c`.
+pub(crate) fn check_constraint<const FS: usize>(
+    modulus: &Modulus<FS>,
+    witness: &[FieldElement<FS>],
+    index: usize,
+    constraint: &Constraint<FS>,
+) -> Result<Option<UnsatisfiedConstraint<FS>>, SatisfactionError<FS>> {
+    let [a, b, c] = evaluate(modulus, witness, index, constraint)?;
+    if modulus.mul(&a, &b) == c {
+        Ok(None)
+    } else {
+        Ok(Some(UnsatisfiedConstraint { index, a, b, c }))
+    }
+}
+
+/// The values of the linear combinations of constraint `index`.
+pub(crate) fn evaluate<const FS: usize>(
+    modulus: &Modulus<FS>,
+    witness: &[FieldElement<FS>],
+    index: usize,
+    constraint: &Constraint<FS>,
+) -> Result<[FieldElement<FS>; 3], SatisfactionError<FS>> {
+    let combination = |lc: &LinearCombination<FS>| {
+        lc.iter()
+            .try_fold(FieldElement::from_u64(0), |sum, (coeff, wire)| {
+                let value =
+                    witness
+                        .get(*wire as usize)
+                        .ok_or(SatisfactionError::WireOutOfRange {
+                            constraint: index,
+                            wire: *wire,
+                        })?;
+                Ok(modulus.add(&sum, &modulus.mul(coeff, value)))
+            })
+    };
+    Ok([
+        combination(&constraint.0)?,
+        combination(&constraint.1)?,
+        combination(&constraint.2)?,
+    ])
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A witness for `tests/test_circuit.r1cs`, a chain of constraints
+    /// `-w_k * w_k = w_3 - w_next` ending at wire 1. With `w_2 = 1` and `w_3 = -1`, the wires of the
+    /// chain alternate between 0 and -1.
+    pub(crate) fn chain_witness(prime: &FieldElement<32>) -> Vec<FieldElement<32>> {
+        let zero = FieldElement::from_u64(0);
+        let one = FieldElement::from_u64(1);
+        let minus_one = Modulus::new(*prime).unwrap().neg(&one);
+
+        let mut witness = vec![one, minus_one, one, minus_one];
+        witness.extend((4..1003).map(|wire| if wire % 2 == 0 { zero } else { minus_one }));
+        witness
+    }
+
+    #[test]
+    fn test_check_witness() {
+        let data = std::fs::read("tests/test_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let prime = file.header.prime;
+
+        let witness = chain_witness(&prime);
+        let wtns = WtnsFile::from_vec(witness.clone(), prime);
+        assert_eq!(check_witness(&file, &wtns), Ok(()));
+        assert_eq!(check_witness_all(&file, &wtns), Ok(()));
+
+        let mut broken = witness.clone();
+        broken[500] = FieldElement::from_u64(7);
+        let wtns = WtnsFile::from_vec(broken, prime);
+        let modulus = Modulus::new(prime).unwrap();
+        let minus = |n: u64| modulus.neg(&FieldElement::from_u64(n));
+        // Wire 500 is set by constraint 496, from wire 499, and read by constraint 497.
+        assert_eq!(
+            check_witness(&file, &wtns),
+            Err(SatisfactionError::Unsatisfied(vec![
+                UnsatisfiedConstraint {
+                    index: 496,
+                    a: FieldElement::from_u64(1),
+                    b: minus(1),
+                    c: minus(8),
+                }
+            ]))
+        );
+        match check_witness_all(&file, &wtns) {
+            Err(SatisfactionError::Unsatisfied(constraints)) => {
+                assert_eq!(
+                    constraints.iter().map(|c| c.index).collect::<Vec<_>>(),
+                    [496, 497]
+                );
+                assert_eq!(constraints[1].a, minus(7));
+                assert_eq!(constraints[1].b, FieldElement::from_u64(7));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_check_witness_shape() {
+        let data = std::fs::read("tests/test_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let witness = chain_witness(&file.header.prime);
+
+        let wtns = WtnsFile::from_vec(witness.clone(), FieldElement::from_u64(7));
+        assert_eq!(
+            check_witness(&file, &wtns),
+            Err(SatisfactionError::PrimeMismatch)
+        );
+
+        let wtns = WtnsFile::from_vec(witness[..1000].to_vec(), file.header.prime);
+        assert_eq!(
+            check_witness(&file, &wtns),
+            Err(SatisfactionError::WitnessTooShort {
+                witness_len: 1000,
+                n_wires: 1003
+            })
+        );
+
+        let mut non_canonical = witness;
+        non_canonical[7] = file.header.prime;
+        let wtns = WtnsFile::from_vec(non_canonical, file.header.prime);
+        assert_eq!(
+            check_witness(&file, &wtns),
+            Err(SatisfactionError::NonCanonicalValue { index: 7 })
+        );
+
+        let even = FieldElement::from_u64(8);
+        let mut even_file = file.clone();
+        even_file.header.prime = even;
+        let wtns = WtnsFile::from_vec(vec![FieldElement::from_u64(1); 1003], even);
+        assert_eq!(
+            check_witness(&even_file, &wtns),
+            Err(SatisfactionError::InvalidPrime(ValidationError::EvenPrime))
+        );
+    }
+
+    #[test]
+    fn test_display_empty_unsatisfied() {
+        let error = SatisfactionError::<32>::Unsatisfied(vec![]);
+        assert_eq!(error.to_string(), "0 constraints not satisfied");
+    }
+}
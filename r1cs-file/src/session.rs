@@ -0,0 +1,513 @@
+//! A circuit together with its symbols and a witness, for interactive queries, behind the `wtns`
+//! feature.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::mem::size_of;
+
+use wtns_file::WtnsFile;
+
+use crate::display::{fmt_constraint, fmt_wire, to_u128};
+use crate::satisfy::evaluate;
+use crate::{
+    CircuitStats, Constraint, FieldElement, Modulus, R1csFile, ReverseWireMap, SymFile,
+    ValidationError, WireUsageIndex,
+};
+
+/// Options for [`CircuitSession::with_options`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionOptions {
+    /// Upper bound on the estimated size of the cached indexes, in bytes. An index that doesn't
+    /// fit is rebuilt, or replaced by a scan, on every query that needs it. `None` caches every
+    /// index.
+    pub max_cache_bytes: Option<usize>,
+}
+
+/// A query of a [`CircuitSession`] that can't be answered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SessionError {
+    /// The query needs a `.sym` file, see [`CircuitSession::with_symbols`].
+    NoSymbols,
+    /// The query needs a witness, see [`CircuitSession::with_witness`].
+    NoWitness,
+    /// The `.sym` file has no signal of this name.
+    UnknownSignal(String),
+    /// The signal of this name was optimized out and has no wire.
+    OptimizedOut(String),
+    /// The witness has no value for this wire.
+    NotInWitness { wire: u32 },
+    /// There is no constraint at this index.
+    ConstraintOutOfRange { index: usize, n_constraints: usize },
+    /// The prime isn't odd, so constraints can't be evaluated.
+    InvalidPrime(ValidationError),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::NoSymbols => f.write_str("The session has no symbols"),
+            SessionError::NoWitness => f.write_str("The session has no witness"),
+            SessionError::UnknownSignal(name) => write!(f, "Unknown signal {}", name),
+            SessionError::OptimizedOut(name) => write!(f, "Signal {} was optimized out", name),
+            SessionError::NotInWitness { wire } => {
+                write!(f, "The witness has no value for wire {}", wire)
+            }
+            SessionError::ConstraintOutOfRange {
+                index,
+                n_constraints,
+            } => write!(
+                f,
+                "Constraint {} is out of range, there are {} constraints",
+                index, n_constraints
+            ),
+            SessionError::InvalidPrime(e) => write!(f, "Invalid prime: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+/// Indexes built on demand by the queries of a [`CircuitSession`].
+#[derive(Debug, Default)]
+struct Caches {
+    reverse: Option<ReverseWireMap>,
+    usage: Option<WireUsageIndex>,
+    /// Signal name to wires, through the wire map, and its estimated size.
+    names: Option<(HashMap<String, Vec<u32>>, usize)>,
+    stats: Option<CircuitStats>,
+    bytes: usize,
+}
+
+/// A circuit, owned or borrowed, with its optional `.sym` file and witness.
+///
+/// The indexes the queries need are built on first use and kept, within the bound of
+/// [`SessionOptions::max_cache_bytes`]. They are dropped whenever the part of the session they
+/// were built from changes.
+#[derive(Debug)]
+pub struct CircuitSession<'a, const FS: usize> {
+    r1cs: Cow<'a, R1csFile<FS>>,
+    sym: Option<Cow<'a, SymFile>>,
+    witness: Option<Cow<'a, WtnsFile<FS>>>,
+    opts: SessionOptions,
+    caches: Caches,
+}
+
+impl<'a, const FS: usize> CircuitSession<'a, FS> {
+    pub fn new(r1cs: Cow<'a, R1csFile<FS>>) -> Self {
+        CircuitSession {
+            r1cs,
+            sym: None,
+            witness: None,
+            opts: SessionOptions::default(),
+            caches: Caches::default(),
+        }
+    }
+
+    pub fn with_symbols(mut self, sym: Cow<'a, SymFile>) -> Self {
+        self.set_symbols(Some(sym));
+        self
+    }
+
+    pub fn with_witness(mut self, witness: Cow<'a, WtnsFile<FS>>) -> Self {
+        self.witness = Some(witness);
+        self
+    }
+
+    /// Sets the options, dropping the cached indexes.
+    pub fn with_options(mut self, opts: SessionOptions) -> Self {
+        self.opts = opts;
+        self.caches = Caches::default();
+        self
+    }
+
+    pub fn r1cs(&self) -> &R1csFile<FS> {
+        &self.r1cs
+    }
+
+    /// The circuit for modification, copied first if it is borrowed. Every cached index is
+    /// dropped.
+    pub fn r1cs_mut(&mut self) -> &mut R1csFile<FS> {
+        self.caches = Caches::default();
+        self.r1cs.to_mut()
+    }
+
+    pub fn symbols(&self) -> Option<&SymFile> {
+        self.sym.as_deref()
+    }
+
+    /// Replaces the symbols, dropping the name index.
+    pub fn set_symbols(&mut self, sym: Option<Cow<'a, SymFile>>) {
+        if let Some((_, bytes)) = self.caches.names.take() {
+            self.caches.bytes -= bytes;
+        }
+        self.sym = sym;
+    }
+
+    pub fn witness(&self) -> Option<&WtnsFile<FS>> {
+        self.witness.as_deref()
+    }
+
+    /// Replaces the witness. No index depends on it.
+    pub fn set_witness(&mut self, witness: Option<Cow<'a, WtnsFile<FS>>>) {
+        self.witness = witness;
+    }
+
+    /// The estimated size of the cached indexes, in bytes.
+    pub fn cached_bytes(&self) -> usize {
+        self.caches.bytes
+    }
+
+    /// The witness value of the signal `name`, on its lowest wire if it is aliased.
+    pub fn value(&mut self, name: &str) -> Result<FieldElement<FS>, SessionError> {
+        if self.witness.is_none() {
+            return Err(SessionError::NoWitness);
+        }
+        let wire = self.wires_of(name)?[0];
+        let witness = self.witness.as_deref().expect("checked above");
+        let value = witness.witness.0.get(wire as usize);
+
+        value.copied().ok_or(SessionError::NotInWitness { wire })
+    }
+
+    /// Every wire of the signal `name`, in ascending order. Several wires are mapped to the label
+    /// of an aliased signal.
+    pub fn wires_of(&mut self, name: &str) -> Result<Vec<u32>, SessionError> {
+        let CircuitSession {
+            r1cs,
+            sym,
+            opts,
+            caches,
+            ..
+        } = self;
+        let sym = sym.as_deref().ok_or(SessionError::NoSymbols)?;
+        let max = opts.max_cache_bytes;
+        if caches.names.is_none() {
+            let entries = sym.entries();
+            let bytes = entries.iter().map(|entry| entry.name.len()).sum::<usize>()
+                + entries.len() * (size_of::<String>() + size_of::<Vec<u32>>())
+                + r1cs.map.0.len() * size_of::<u32>();
+            if caches.fits(max, bytes) {
+                caches.build_reverse(r1cs, max);
+                // A reverse map that doesn't fit the cache is built for this pass only, rather
+                // than scanning the wire map once per signal.
+                let uncached;
+                let reverse = match &caches.reverse {
+                    Some(reverse) => reverse,
+                    None => {
+                        uncached = r1cs.effective_map().reverse();
+                        &uncached
+                    }
+                };
+                let names = entries.iter().map(|entry| {
+                    let wires = reverse.all(entry.label).to_vec();
+                    (entry.name.clone(), wires)
+                });
+                caches.names = Some((names.collect(), bytes));
+                caches.bytes += bytes;
+            }
+        }
+
+        let entry = sym
+            .by_name(name)
+            .ok_or_else(|| SessionError::UnknownSignal(name.to_owned()))?;
+        let wires = match &caches.names {
+            Some((names, _)) => names[name].clone(),
+            None => label_wires(r1cs, caches.reverse.as_ref(), entry.label),
+        };
+        if wires.is_empty() {
+            return Err(SessionError::OptimizedOut(name.to_owned()));
+        }
+
+        Ok(wires)
+    }
+
+    /// The constraints referencing any wire of the signal `name`, in ascending order.
+    pub fn constraints_touching(&mut self, name: &str) -> Result<Vec<u32>, SessionError> {
+        let wires = self.wires_of(name)?;
+        if self.caches.usage.is_none() {
+            let file = &*self.r1cs;
+            let nnz: usize = file.constraints.0.iter().map(Constraint::num_terms).sum();
+            let bytes = (file.header.n_wires as usize + 1) * size_of::<usize>()
+                + nnz * size_of::<(u32, crate::Matrix)>();
+            if !self.caches.fits(self.opts.max_cache_bytes, bytes) {
+                return Ok(self.scan_constraints(&wires));
+            }
+            self.caches.usage = Some(WireUsageIndex::build(file));
+            self.caches.bytes += bytes;
+        }
+
+        let usage = self.caches.usage.as_ref().expect("built above");
+        Ok(usage.constraints_involving(wires))
+    }
+
+    /// Constraint `index` with signal names, followed by the values of its linear combinations
+    /// under the witness if there is one, e.g.
+    /// `(main.a) * (main.b) = (main.c)` and `a = 3, b = 4, c = 12: satisfied`.
+    pub fn explain(&self, index: usize) -> Result<String, SessionError> {
+        let file = &*self.r1cs;
+        let constraint =
+            file.constraints
+                .0
+                .get(index)
+                .ok_or(SessionError::ConstraintOutOfRange {
+                    index,
+                    n_constraints: file.constraints.len(),
+                })?;
+        let mut explained = Named {
+            file,
+            sym: self.sym.as_deref(),
+            constraint,
+        }
+        .to_string();
+
+        if let Some(witness) = &self.witness {
+            let modulus = Modulus::new(file.header.prime).map_err(SessionError::InvalidPrime)?;
+            let witness = &witness.witness.0;
+            let [a, b, c] = evaluate(&modulus, witness, index, constraint).map_err(|_| {
+                let mut wires = constraint.terms().map(|(_, term)| term.wire);
+                let wire = wires.find(|w| *w as usize >= witness.len());
+                SessionError::NotInWitness {
+                    wire: wire.unwrap_or_default(),
+                }
+            })?;
+            let satisfied = if modulus.mul(&a, &b) == c {
+                "satisfied"
+            } else {
+                "not satisfied"
+            };
+            explained.push_str(&format!(
+                "\na = {}, b = {}, c = {}: {}",
+                value(&a),
+                value(&b),
+                value(&c),
+                satisfied
+            ));
+        }
+
+        Ok(explained)
+    }
+
+    /// [`R1csFile::stats`], computed once.
+    pub fn stats(&mut self) -> &CircuitStats {
+        if self.caches.stats.is_none() {
+            self.caches.stats = Some(self.r1cs.stats());
+        }
+
+        self.caches.stats.as_ref().expect("computed above")
+    }
+
+    /// The constraints referencing any of `wires`, without the wire usage index.
+    fn scan_constraints(&self, wires: &[u32]) -> Vec<u32> {
+        let constraints = (0u32..).zip(&self.r1cs.constraints.0);
+        constraints
+            .filter(|(_, c)| c.terms().any(|(_, term)| wires.contains(&term.wire)))
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+/// `e` in decimal if it fits in a `u128`, in hexadecimal otherwise.
+fn value<const FS: usize>(e: &FieldElement<FS>) -> String {
+    match to_u128(e) {
+        Some(e) => e.to_string(),
+        None => format!("{:#x}", e),
+    }
+}
+
+impl Caches {
+    /// Whether an index of `bytes` fits within `max`.
+    fn fits(&self, max: Option<usize>, bytes: usize) -> bool {
+        match max {
+            Some(max) => self.bytes + bytes <= max,
+            None => true,
+        }
+    }
+
+    /// Builds the reverse wire map of `r1cs` unless it is cached or doesn't fit.
+    fn build_reverse<const FS: usize>(&mut self, r1cs: &R1csFile<FS>, max: Option<usize>) {
+        let bytes =
+            r1cs.map.0.len() * (size_of::<u64>() + size_of::<Vec<u32>>() + size_of::<u32>());
+        if self.reverse.is_none() && self.fits(max, bytes) {
+            self.reverse = Some(r1cs.effective_map().reverse());
+            self.bytes += bytes;
+        }
+    }
+}
+
+/// The wires of `r1cs` mapped to `label`, scanning the wire map without `reverse`.
+fn label_wires<const FS: usize>(
+    r1cs: &R1csFile<FS>,
+    reverse: Option<&ReverseWireMap>,
+    label: u64,
+) -> Vec<u32> {
+    match reverse {
+        Some(reverse) => reverse.all(label).to_vec(),
+        None => {
+            let map = r1cs.effective_map();
+            let wires = (0u32..).zip(map.iter()).filter(|(_, l)| **l == label);
+            wires.map(|(wire, _)| wire).collect()
+        }
+    }
+}
+
+/// A constraint with the wires named after their signals.
+struct Named<'s, const FS: usize> {
+    file: &'s R1csFile<FS>,
+    sym: Option<&'s SymFile>,
+    constraint: &'s Constraint<FS>,
+}
+
+impl<const FS: usize> fmt::Display for Named<'_, FS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = |wire: u32| {
+            let label = self.file.map.0.get(wire as usize)?;
+            Some(self.sym?.by_label(*label)?.name.as_str())
+        };
+        let wire = |wire: u32, f: &mut fmt::Formatter<'_>| match name(wire) {
+            Some(name) => f.write_str(name),
+            None => fmt_wire(wire, f),
+        };
+
+        fmt_constraint(self.constraint, Some(&self.file.header.prime), &wire, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read() -> (R1csFile<32>, SymFile, WtnsFile<32>) {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let r1cs = R1csFile::read(data.as_slice()).unwrap();
+        let data = std::fs::read("tests/simple_circuit.sym").unwrap();
+        let sym = SymFile::read(data.as_slice()).unwrap();
+        let data = std::fs::read("tests/simple_circuit.wtns").unwrap();
+        let wtns = WtnsFile::read(data.as_slice()).unwrap();
+
+        (r1cs, sym, wtns)
+    }
+
+    #[test]
+    fn test_session() {
+        let (r1cs, sym, wtns) = read();
+        let mut session = CircuitSession::new(Cow::Borrowed(&r1cs))
+            .with_symbols(Cow::Borrowed(&sym))
+            .with_witness(Cow::Borrowed(&wtns));
+
+        // Which signal is off? Start from the output.
+        assert_eq!(session.value("main.out"), Ok(FieldElement::from_u64(2)));
+        assert_eq!(session.value("main.b"), Ok(FieldElement::from_u64(4)));
+        assert_eq!(
+            session.value("main.tmp"),
+            Err(SessionError::OptimizedOut("main.tmp".to_owned()))
+        );
+        assert_eq!(
+            session.value("main.nope"),
+            Err(SessionError::UnknownSignal("main.nope".to_owned()))
+        );
+        let cached = session.cached_bytes();
+        assert!(cached > 0);
+
+        assert_eq!(session.constraints_touching("main.out"), Ok(vec![1]));
+        assert_eq!(
+            session.constraints_touching("main.mul.out"),
+            Ok(vec![0, 1, 2])
+        );
+        assert!(session.cached_bytes() > cached);
+        assert_eq!(
+            session.explain(1).unwrap(),
+            "(4*main.out + 8*main.c + 3*main.d) * (44*main.b + 6*main.mul.out) = (0)\n\
+             a = 0, b = 176, c = 0: satisfied"
+        );
+        assert!(matches!(
+            session.explain(3),
+            Err(SessionError::ConstraintOutOfRange {
+                index: 3,
+                n_constraints: 3
+            })
+        ));
+        assert_eq!(session.stats().n_constraints, 3);
+
+        // Break the constraint and look again, the borrowed circuit is copied.
+        let cached = session.cached_bytes();
+        let broken = &mut session.r1cs_mut().constraints.0[1];
+        broken.c_mut().0.push((FieldElement::from_u64(1), 0));
+        assert_eq!(session.cached_bytes(), 0);
+        assert!(session
+            .explain(1)
+            .unwrap()
+            .ends_with("c = 1: not satisfied"));
+        assert_eq!(r1cs.constraints[1].2.len(), 0);
+        // The indexes are rebuilt, the usage index with the new term.
+        assert_eq!(session.constraints_touching("main.out"), Ok(vec![1]));
+        assert!(session.cached_bytes() > cached);
+
+        session.set_witness(None);
+        assert_eq!(session.value("main.out"), Err(SessionError::NoWitness));
+        assert!(!session.explain(0).unwrap().contains('\n'));
+        session.set_symbols(None);
+        assert_eq!(
+            session.constraints_touching("main.out"),
+            Err(SessionError::NoSymbols)
+        );
+        assert!(session.explain(0).unwrap().starts_with("(3*w_5 + 8*w_6)"));
+    }
+
+    #[test]
+    fn test_session_circom() {
+        // `multiplier.r1cs` and `multiplier.sym` compiled by circom from `c <== a * b`, from the
+        // test vectors of ark-circom. The witness was computed for `a = 3, b = 11` by the
+        // WebAssembly witness calculator circom generated along with them.
+        let r1cs = R1csFile::<32>::read_file("tests/multiplier.r1cs").unwrap();
+        let data = std::fs::read("tests/multiplier.sym").unwrap();
+        let sym = SymFile::read(data.as_slice()).unwrap();
+        let data = std::fs::read("tests/multiplier.wtns").unwrap();
+        let wtns = WtnsFile::<32>::read(data.as_slice()).unwrap();
+        let mut session = CircuitSession::new(Cow::Owned(r1cs))
+            .with_symbols(Cow::Owned(sym))
+            .with_witness(Cow::Owned(wtns));
+
+        assert_eq!(session.value("main.c"), Ok(FieldElement::from_u64(33)));
+        assert_eq!(session.wires_of("main.a"), Ok(vec![2]));
+        assert_eq!(session.value("main.a"), Ok(FieldElement::from_u64(3)));
+        assert_eq!(session.value("main.b"), Ok(FieldElement::from_u64(11)));
+        assert_eq!(session.constraints_touching("main.b"), Ok(vec![0]));
+        let explained = session.explain(0).unwrap();
+        assert!(explained.starts_with("(-main.a) * (main.b) = (-main.c)\n"));
+        assert!(explained.ends_with(": satisfied"));
+        assert_eq!(session.stats().n_constraints, 1);
+
+        // A wrong output breaks the only constraint.
+        let mut wtns = session.witness().unwrap().clone();
+        wtns.witness.0[1] = FieldElement::from_u64(34);
+        session.set_witness(Some(Cow::Owned(wtns)));
+        assert!(session.explain(0).unwrap().ends_with("not satisfied"));
+    }
+
+    #[test]
+    fn test_session_memory_bound() {
+        let (r1cs, sym, wtns) = read();
+        let mut unbounded = CircuitSession::new(Cow::Borrowed(&r1cs))
+            .with_symbols(Cow::Borrowed(&sym))
+            .with_witness(Cow::Borrowed(&wtns));
+        let mut bounded = CircuitSession::new(Cow::Owned(r1cs.clone()))
+            .with_symbols(Cow::Owned(sym.clone()))
+            .with_witness(Cow::Owned(wtns.clone()))
+            .with_options(SessionOptions {
+                max_cache_bytes: Some(0),
+            });
+
+        for name in ["main.out", "main.a", "main.d", "main.mul.out"] {
+            assert_eq!(bounded.value(name), unbounded.value(name));
+            assert_eq!(bounded.wires_of(name), unbounded.wires_of(name));
+            assert_eq!(
+                bounded.constraints_touching(name),
+                unbounded.constraints_touching(name)
+            );
+        }
+        assert_eq!(bounded.cached_bytes(), 0);
+        assert!(unbounded.cached_bytes() > 0);
+    }
+}
@@ -0,0 +1,219 @@
+//! Export of the A, B and C matrices in sparse layouts.
+
+use std::collections::HashMap;
+
+use crate::{FieldElement, Modulus, R1csFile};
+
+/// Options for [`R1csFile::to_sparse_matrices_with`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SparseOptions {
+    pub duplicates: DuplicateEntries,
+}
+
+/// What to do with several terms of a linear combination on the same wire.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateEntries {
+    /// Keep every term as its own entry, and list the `(row, col)` positions in
+    /// [`SparseMatrix::duplicates`].
+    #[default]
+    Report,
+    /// Sum the coefficients modulo the header prime into a single entry, at the position of the
+    /// first term. Coefficients must be below the prime. Falls back to [`DuplicateEntries::Report`]
+    /// if the prime isn't odd.
+    Merge,
+}
+
+/// One of the matrices of a circuit, with a row per constraint and a column per wire, in
+/// compressed sparse row (CSR) layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseMatrix<const FS: usize> {
+    n_cols: u32,
+    row_ptr: Vec<usize>,
+    col_idx: Vec<u32>,
+    vals: Vec<FieldElement<FS>>,
+    duplicates: Vec<(u32, u32)>,
+}
+
+impl<const FS: usize> SparseMatrix<FS> {
+    fn new(n_cols: u32) -> Self {
+        SparseMatrix {
+            n_cols,
+            row_ptr: vec![0],
+            col_idx: Vec::new(),
+            vals: Vec::new(),
+            duplicates: Vec::new(),
+        }
+    }
+
+    pub fn n_rows(&self) -> usize {
+        self.row_ptr.len() - 1
+    }
+
+    pub fn n_cols(&self) -> u32 {
+        self.n_cols
+    }
+
+    /// Number of stored entries.
+    pub fn nnz(&self) -> usize {
+        self.vals.len()
+    }
+
+    /// The offsets of the rows into [`SparseMatrix::col_idx`] and [`SparseMatrix::vals`], one per
+    /// row followed by [`SparseMatrix::nnz`].
+    pub fn row_ptr(&self) -> &[usize] {
+        &self.row_ptr
+    }
+
+    pub fn col_idx(&self) -> &[u32] {
+        &self.col_idx
+    }
+
+    pub fn vals(&self) -> &[FieldElement<FS>] {
+        &self.vals
+    }
+
+    /// Positions holding several terms, with [`DuplicateEntries::Report`]. A position is listed
+    /// once per term after the first.
+    pub fn duplicates(&self) -> &[(u32, u32)] {
+        &self.duplicates
+    }
+
+    /// The entries in coordinate (COO) layout, as `(row, col, coeff)` triplets in row order.
+    pub fn triplets(&self) -> impl Iterator<Item = (u32, u32, &FieldElement<FS>)> + '_ {
+        self.row_ptr
+            .windows(2)
+            .enumerate()
+            .flat_map(move |(row, range)| {
+                (range[0]..range[1]).map(move |i| (row as u32, self.col_idx[i], &self.vals[i]))
+            })
+    }
+
+    /// Opens rows until `row` is the last one, whose end [`SparseMatrix::push`] moves.
+    fn start_row(&mut self, row: u32, columns: &mut HashMap<u32, usize>) {
+        while self.n_rows() <= row as usize {
+            self.row_ptr.push(self.vals.len());
+            columns.clear();
+        }
+    }
+
+    fn push(
+        &mut self,
+        col: u32,
+        coeff: &FieldElement<FS>,
+        modulus: Option<&Modulus<FS>>,
+        columns: &mut HashMap<u32, usize>,
+    ) {
+        match (columns.get(&col), modulus) {
+            (Some(&i), Some(modulus)) => {
+                self.vals[i] = modulus.add(&self.vals[i], coeff);
+                return;
+            }
+            (Some(_), None) => {
+                let row = self.n_rows() as u32 - 1;
+                self.duplicates.push((row, col));
+            }
+            (None, _) => {
+                columns.insert(col, self.vals.len());
+            }
+        }
+
+        self.col_idx.push(col);
+        self.vals.push(*coeff);
+        *self.row_ptr.last_mut().unwrap() = self.vals.len();
+    }
+}
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Exports the A, B and C matrices, see [`R1csFile::to_sparse_matrices_with`].
+    pub fn to_sparse_matrices(&self) -> [SparseMatrix<FS>; 3] {
+        self.to_sparse_matrices_with(&SparseOptions::default())
+    }
+
+    /// Exports the A, B and C matrices in a single pass over the terms, with a row per constraint
+    /// and `n_wires` columns. Entries are kept in the order of the terms within each row.
+    pub fn to_sparse_matrices_with(&self, options: &SparseOptions) -> [SparseMatrix<FS>; 3] {
+        let modulus = match options.duplicates {
+            DuplicateEntries::Report => None,
+            DuplicateEntries::Merge => Modulus::new(self.header.prime).ok(),
+        };
+
+        let n_wires = self.header.n_wires;
+        let mut matrices = [(); 3].map(|_| SparseMatrix::new(n_wires));
+        let mut columns = [(); 3].map(|_| HashMap::new());
+        for term in self.iter_terms() {
+            let m = term.matrix as usize;
+            let (matrix, columns) = (&mut matrices[m], &mut columns[m]);
+
+            matrix.start_row(term.constraint, columns);
+            matrix.push(term.wire, term.coeff, modulus.as_ref(), columns);
+        }
+
+        let n_rows = self.constraints.0.len();
+        for matrix in &mut matrices {
+            matrix.row_ptr.resize(n_rows + 1, matrix.vals.len());
+        }
+
+        matrices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Constraint, Matrix};
+
+    fn fe(n: u64) -> FieldElement<32> {
+        FieldElement::from_u64(n)
+    }
+
+    #[test]
+    fn test_sparse_matrices() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let stats = file.stats();
+
+        let matrices = file.to_sparse_matrices();
+        for (matrix, m) in matrices.iter().zip(Matrix::ALL) {
+            assert_eq!(matrix.n_rows() as u32, file.header.n_constraints);
+            assert_eq!(matrix.n_cols(), file.header.n_wires);
+            assert_eq!(matrix.nnz() as u64, stats.nnz(m));
+            assert_eq!(matrix.duplicates(), []);
+        }
+
+        let [a, b, c] = &matrices;
+        assert_eq!(a.row_ptr(), [0, 2, 5, 6]);
+        assert_eq!(a.col_idx(), [5, 6, 1, 4, 5, 6]);
+        assert_eq!(a.vals()[..2], [fe(3), fe(8)]);
+        assert_eq!(b.triplets().next(), Some((0, 0, &fe(2))));
+        assert_eq!(b.triplets().nth(5), Some((2, 0, &fe(6))));
+        assert_eq!(c.row_ptr(), [0, 2, 2, 3]);
+        assert_eq!(c.triplets().last(), Some((2, 6, &fe(600))));
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let mut file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        file.constraints.0.insert(
+            1,
+            Constraint::new(vec![(fe(1), 2), (fe(5), 1), (fe(3), 2)], vec![], vec![]),
+        );
+        file.header.n_constraints += 1;
+
+        let [a, b, _] = file.to_sparse_matrices();
+        assert_eq!(a.nnz(), 9);
+        assert_eq!(a.duplicates(), [(1, 2)]);
+        assert_eq!(b.row_ptr(), [0, 3, 3, 5, 8]);
+
+        let options = SparseOptions {
+            duplicates: DuplicateEntries::Merge,
+        };
+        let [a, _, _] = file.to_sparse_matrices_with(&options);
+        assert_eq!(a.nnz(), 8);
+        assert_eq!(a.duplicates(), []);
+        assert_eq!(
+            a.triplets().skip(2).take(2).collect::<Vec<_>>(),
+            [(1, 2, &fe(4)), (1, 1, &fe(5))]
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! Summary statistics of a circuit.
+
+use std::fmt;
+
+use crate::{Matrix, R1csFile};
+
+/// Number of constraints listed in [`CircuitStats::heaviest_constraints`].
+const HEAVIEST: usize = 5;
+
+/// The shape of a circuit, computed by [`R1csFile::stats`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CircuitStats {
+    pub n_constraints: u64,
+    pub n_wires: u32,
+    pub n_pub_out: u32,
+    pub n_pub_in: u32,
+    pub n_prvt_in: u32,
+    pub n_labels: u64,
+    /// Number of terms in each of the A, B and C matrices.
+    pub nnz: [u64; 3],
+    /// The largest number of terms in a single linear combination.
+    pub max_terms_per_lc: usize,
+    /// The average number of terms per linear combination, zero without constraints.
+    pub avg_terms_per_lc: f64,
+    /// Number of wires below `n_wires` that no term references.
+    pub unused_wires: u32,
+    /// Up to 5 constraints with the most terms, as `(index, terms)`, heaviest first.
+    pub heaviest_constraints: Vec<(usize, usize)>,
+    /// The smallest power of two not below the number of constraints, the size of the QAP
+    /// evaluation domain.
+    pub domain_size: u64,
+}
+
+impl CircuitStats {
+    /// Number of terms in `matrix`.
+    pub fn nnz(&self, matrix: Matrix) -> u64 {
+        self.nnz[matrix as usize]
+    }
+}
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Computes the circuit statistics in a single pass over the constraints.
+    pub fn stats(&self) -> CircuitStats {
+        let header = &self.header;
+        let mut nnz = [0u64; 3];
+        let mut max_terms_per_lc = 0;
+        let mut used = vec![false; header.n_wires as usize];
+        let mut heaviest: Vec<(usize, usize)> = Vec::with_capacity(HEAVIEST + 1);
+
+        for (index, c) in self.constraints.0.iter().enumerate() {
+            for matrix in Matrix::ALL {
+                let combination = c.combination(matrix);
+                nnz[matrix as usize] += combination.len() as u64;
+                max_terms_per_lc = max_terms_per_lc.max(combination.len());
+
+                for (_, wire) in combination.iter() {
+                    if let Some(used) = used.get_mut(*wire as usize) {
+                        *used = true;
+                    }
+                }
+            }
+
+            // Keep the heaviest constraints sorted, earlier ones first among equals.
+            let terms = c.num_terms();
+            let position = heaviest.partition_point(|(_, t)| *t >= terms);
+            if position < HEAVIEST {
+                heaviest.insert(position, (index, terms));
+                heaviest.truncate(HEAVIEST);
+            }
+        }
+
+        let n_constraints = self.constraints.0.len() as u64;
+        let total: u64 = nnz.iter().sum();
+        let avg_terms_per_lc = if n_constraints == 0 {
+            0.0
+        } else {
+            total as f64 / (3 * n_constraints) as f64
+        };
+
+        CircuitStats {
+            n_constraints,
+            n_wires: header.n_wires,
+            n_pub_out: header.n_pub_out,
+            n_pub_in: header.n_pub_in,
+            n_prvt_in: header.n_prvt_in,
+            n_labels: header.n_labels,
+            nnz,
+            max_terms_per_lc,
+            avg_terms_per_lc,
+            unused_wires: used.iter().filter(|used| !**used).count() as u32,
+            heaviest_constraints: heaviest,
+            domain_size: n_constraints.next_power_of_two(),
+        }
+    }
+}
+
+impl fmt::Display for CircuitStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# of Wires: {}", self.n_wires)?;
+        writeln!(f, "# of Constraints: {}", self.n_constraints)?;
+        writeln!(f, "# of Private Inputs: {}", self.n_prvt_in)?;
+        writeln!(f, "# of Public Inputs: {}", self.n_pub_in)?;
+        writeln!(f, "# of Outputs: {}", self.n_pub_out)?;
+        writeln!(f, "# of Labels: {}", self.n_labels)?;
+        writeln!(
+            f,
+            "Non-zero terms: A {}, B {}, C {}",
+            self.nnz[0], self.nnz[1], self.nnz[2]
+        )?;
+        writeln!(
+            f,
+            "Terms per linear combination: max {}, avg {:.2}",
+            self.max_terms_per_lc, self.avg_terms_per_lc
+        )?;
+        writeln!(f, "Unused wires: {}", self.unused_wires)?;
+        write!(f, "Heaviest constraints:")?;
+        for (index, terms) in &self.heaviest_constraints {
+            write!(f, " #{} ({} terms)", index, terms)?;
+        }
+        writeln!(f)?;
+        write!(f, "Domain size: {}", self.domain_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let mut file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        let stats = file.stats();
+        assert_eq!(stats.n_constraints, 3);
+        assert_eq!(stats.nnz, [6, 8, 3]);
+        assert_eq!(stats.nnz(Matrix::B), 8);
+        assert_eq!(stats.max_terms_per_lc, 3);
+        assert!((stats.avg_terms_per_lc - 17.0 / 9.0).abs() < 1e-9);
+        assert_eq!(stats.unused_wires, 0);
+        assert_eq!(stats.heaviest_constraints, [(0, 7), (1, 5), (2, 5)]);
+        assert_eq!(stats.domain_size, 4);
+
+        let report = stats.to_string();
+        assert!(report.contains("Non-zero terms: A 6, B 8, C 3"));
+        assert!(report.contains("avg 1.89"));
+
+        file.header.n_wires += 2;
+        assert_eq!(file.stats().unused_wires, 2);
+    }
+}
@@ -0,0 +1,142 @@
+//! circom `.sym` symbol files, naming the signals of a circuit.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::{BufRead, BufReader, Read};
+
+use crate::{R1csError, Result};
+
+/// A signal of the circuit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymEntry {
+    /// Label id, as found in the wire map.
+    pub label: u64,
+    /// Wire index, or `None` if the signal was optimized out (`-1` in the file).
+    pub wire: Option<u32>,
+    /// Index of the component the signal belongs to.
+    pub component: u64,
+    /// Full signal name, such as `main.out`.
+    pub name: String,
+}
+
+/// A parsed `.sym` file: one `label,wire,component,name` line per signal.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SymFile {
+    entries: Vec<SymEntry>,
+    by_label: HashMap<u64, usize>,
+    by_name: HashMap<String, usize>,
+    by_wire: HashMap<Option<u32>, Vec<usize>>,
+}
+
+impl SymFile {
+    /// Parses the file. Empty lines are skipped, and a malformed line fails with
+    /// [`R1csError::InvalidSymLine`].
+    pub fn read<R: Read>(r: R) -> Result<Self> {
+        let mut file = SymFile::default();
+        for (i, line) in BufReader::new(r).lines().enumerate() {
+            let line = line?;
+            let line = line.trim_end_matches('\r');
+            if line.is_empty() {
+                continue;
+            }
+
+            let entry = parse_line(line).ok_or(R1csError::InvalidSymLine { line: i + 1 })?;
+            file.push(entry);
+        }
+
+        Ok(file)
+    }
+
+    pub fn entries(&self) -> &[SymEntry] {
+        &self.entries
+    }
+
+    pub fn by_label(&self, label: u64) -> Option<&SymEntry> {
+        self.by_label.get(&label).map(|&i| &self.entries[i])
+    }
+
+    /// The signals assigned to `wire`, several when signals are aliased. A negative `wire`
+    /// returns the signals that were optimized out.
+    pub fn by_wire(&self, wire: i64) -> impl Iterator<Item = &SymEntry> + '_ {
+        // Indexes past `u32::MAX` can't name a wire, and match nothing.
+        let wire = if wire < 0 {
+            Some(None)
+        } else {
+            u32::try_from(wire).ok().map(Some)
+        };
+
+        wire.and_then(|wire| self.by_wire.get(&wire))
+            .into_iter()
+            .flatten()
+            .map(move |&i| &self.entries[i])
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<&SymEntry> {
+        self.by_name.get(name).map(|&i| &self.entries[i])
+    }
+
+    fn push(&mut self, entry: SymEntry) {
+        let i = self.entries.len();
+        self.by_label.entry(entry.label).or_insert(i);
+        self.by_name.entry(entry.name.clone()).or_insert(i);
+        self.by_wire.entry(entry.wire).or_default().push(i);
+        self.entries.push(entry);
+    }
+}
+
+fn parse_line(line: &str) -> Option<SymEntry> {
+    let mut fields = line.splitn(4, ',');
+    let label = fields.next()?.trim().parse().ok()?;
+    let wire = match fields.next()?.trim().parse::<i64>().ok()? {
+        -1 => None,
+        wire => Some(u32::try_from(wire).ok()?),
+    };
+    let component = fields.next()?.trim().parse().ok()?;
+    let name = fields.next()?.trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(SymEntry {
+        label,
+        wire,
+        component,
+        name: name.to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read() {
+        let data = std::fs::read("tests/simple_circuit.sym").unwrap();
+        let sym = SymFile::read(data.as_slice()).unwrap();
+
+        assert_eq!(sym.entries().len(), 7);
+        assert_eq!(sym.by_label(3).unwrap().name, "main.out");
+        assert_eq!(sym.by_name("main.mul.out").unwrap().wire, Some(6));
+        assert_eq!(sym.by_name("main.x"), None);
+        assert_eq!(sym.by_wire(2).map(|e| e.label).collect::<Vec<_>>(), [10]);
+        assert_eq!(
+            sym.by_wire(-1).map(|e| e.name.as_str()).collect::<Vec<_>>(),
+            ["main.tmp"]
+        );
+        assert_eq!(sym.by_wire(7).count(), 0);
+        assert_eq!(sym.by_wire(i64::MAX).count(), 0);
+
+        let aliased = SymFile::read(&b"1,1,0,main.a\n2,1,0,main.b\n\n"[..]).unwrap();
+        assert_eq!(aliased.by_wire(1).count(), 2);
+    }
+
+    #[test]
+    fn test_malformed() {
+        for data in ["1,1,0,main.a\n2,x,0,main.b\n", "1,1,0,main.a\r\n2,1,0\n"] {
+            assert!(matches!(
+                SymFile::read(data.as_bytes()),
+                Err(R1csError::InvalidSymLine { line: 2 })
+            ));
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Flat iteration over the terms of every constraint.
+
+use core::iter::FusedIterator;
+use core::ops::Range;
+
+use crate::{Constraint, FieldElement, Matrix, R1csFile};
+
+/// A term of a constraint, located in the matrices of the R1CS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatrixTerm<'a, const FS: usize> {
+    /// The row: index of the constraint.
+    pub constraint: u32,
+    pub matrix: Matrix,
+    /// The column: index of the wire.
+    pub wire: u32,
+    pub coeff: &'a FieldElement<FS>,
+}
+
+/// Iterator over the terms of [`R1csFile::iter_terms`] and [`R1csFile::iter_matrix`].
+#[derive(Debug, Clone)]
+pub struct MatrixTerms<'a, const FS: usize> {
+    constraints: &'a [Constraint<FS>],
+    /// Indices into [`Matrix::ALL`] of the matrices iterated over.
+    matrices: Range<usize>,
+    constraint: usize,
+    matrix: usize,
+    term: usize,
+    remaining: usize,
+}
+
+impl<'a, const FS: usize> MatrixTerms<'a, FS> {
+    fn new(constraints: &'a [Constraint<FS>], matrices: Range<usize>) -> Self {
+        let remaining = constraints
+            .iter()
+            .map(|c| {
+                Matrix::ALL[matrices.clone()]
+                    .iter()
+                    .map(|m| c.combination(*m).len())
+                    .sum::<usize>()
+            })
+            .sum();
+
+        MatrixTerms {
+            constraints,
+            matrix: matrices.start,
+            matrices,
+            constraint: 0,
+            term: 0,
+            remaining,
+        }
+    }
+}
+
+impl<'a, const FS: usize> Iterator for MatrixTerms<'a, FS> {
+    type Item = MatrixTerm<'a, FS>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(c) = self.constraints.get(self.constraint) {
+            let matrix = Matrix::ALL[self.matrix];
+            if let Some((coeff, wire)) = c.combination(matrix).get(self.term) {
+                self.term += 1;
+                self.remaining -= 1;
+                return Some(MatrixTerm {
+                    constraint: self.constraint as u32,
+                    matrix,
+                    wire: *wire,
+                    coeff,
+                });
+            }
+
+            self.term = 0;
+            self.matrix += 1;
+            if self.matrix == self.matrices.end {
+                self.matrix = self.matrices.start;
+                self.constraint += 1;
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<const FS: usize> ExactSizeIterator for MatrixTerms<'_, FS> {}
+
+impl<const FS: usize> FusedIterator for MatrixTerms<'_, FS> {}
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Iterates over the terms of every constraint, in constraint order and A, B, C within a
+    /// constraint.
+    ///
+    /// Creating the iterator counts the terms, so that its exact length is known.
+    pub fn iter_terms(&self) -> MatrixTerms<'_, FS> {
+        MatrixTerms::new(&self.constraints.0, 0..Matrix::ALL.len())
+    }
+
+    /// Iterates over the terms of `matrix`, row by row.
+    pub fn iter_matrix(&self, matrix: Matrix) -> MatrixTerms<'_, FS> {
+        let i = matrix as usize;
+        MatrixTerms::new(&self.constraints.0, i..i + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iter_terms() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        let terms = file.iter_terms();
+        assert_eq!(terms.len(), 17);
+        let expected = file.constraints.0.iter().enumerate().flat_map(|(i, c)| {
+            c.terms().map(move |(matrix, term)| MatrixTerm {
+                constraint: i as u32,
+                matrix,
+                wire: term.wire,
+                coeff: term.coeff,
+            })
+        });
+        assert!(terms.eq(expected));
+
+        let stats = file.stats();
+        for matrix in Matrix::ALL {
+            let mut terms = file.iter_matrix(matrix);
+            assert_eq!(terms.len() as u64, stats.nnz(matrix));
+            assert!(terms.all(|term| term.matrix == matrix));
+        }
+        let mut terms = file.iter_matrix(Matrix::C);
+        terms.next();
+        assert_eq!(terms.len(), 2);
+    }
+}
@@ -0,0 +1,384 @@
+//! A line-oriented text form of circuits, for hand-written fixtures and diffable snapshots.
+
+use std::convert::TryFrom;
+use std::io::{BufRead, BufWriter, Write};
+
+use crate::append::invalid_input;
+use crate::display::to_u128;
+use crate::{
+    Constraint, Constraints, Endianness, FieldElement, Header, LinearCombination, R1csError,
+    R1csFile, Result, WireMap, VERSION,
+};
+
+/// The integer keys, in header order.
+const KEYS: [&str; 6] = [
+    "wires",
+    "outputs",
+    "public_inputs",
+    "private_inputs",
+    "labels",
+    "constraints",
+];
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Writes the circuit in the text form read by [`R1csFile::read_text`].
+    ///
+    /// Coefficients that fit in a `u128` are written in decimal, others in hexadecimal. Fails with
+    /// an error of kind [`std::io::ErrorKind::InvalidInput`] if the file has custom gates,
+    /// metadata or custom sections, which the text form doesn't hold.
+    pub fn write_text<W: Write>(&self, w: W) -> Result<()> {
+        if self.custom_gates_list.is_some()
+            || self.custom_gates_application.is_some()
+            || self.metadata.is_some()
+            || !self.custom_sections.is_empty()
+        {
+            return Err(invalid_input(
+                "Custom gates, metadata and custom sections can't be written as text",
+            ));
+        }
+
+        let mut w = BufWriter::new(w);
+        let header = &self.header;
+        writeln!(w, "prime {:#x}", header.prime)?;
+        writeln!(w, "wires {}", header.n_wires)?;
+        writeln!(w, "outputs {}", header.n_pub_out)?;
+        writeln!(w, "public_inputs {}", header.n_pub_in)?;
+        writeln!(w, "private_inputs {}", header.n_prvt_in)?;
+        writeln!(w, "labels {}", header.n_labels)?;
+        writeln!(w, "constraints {}", header.n_constraints)?;
+
+        write!(w, "map")?;
+        for label in &self.map.0 {
+            write!(w, " {}", label)?;
+        }
+        writeln!(w)?;
+
+        for c in &self.constraints.0 {
+            write_combination(&mut w, &c.0)?;
+            write!(w, " * ")?;
+            write_combination(&mut w, &c.1)?;
+            write!(w, " = ")?;
+            write_combination(&mut w, &c.2)?;
+            writeln!(w)?;
+        }
+
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Reads a circuit in text form, as written by [`R1csFile::write_text`]:
+    ///
+    /// ```text
+    /// file        = { line "\n" }
+    /// line        = [ header | map | constraint ] [ "#" comment ]
+    /// header      = key value
+    /// key         = "prime" | "wires" | "outputs" | "public_inputs" | "private_inputs"
+    ///             | "labels" | "constraints"
+    /// map         = "map" { label }
+    /// constraint  = combination "*" combination "=" combination
+    /// combination = "[" { wire ":" coefficient } "]"
+    /// coefficient = decimal | "0x" hexadecimal
+    /// ```
+    ///
+    /// Tokens are separated by whitespace, and blank lines are skipped. Every key and the map appear
+    /// exactly once, before the first constraint. The prime is a coefficient, and the other values,
+    /// labels and wires are decimal integers. The values of the keys are stored in the header as is,
+    /// so they can disagree with the constraints and the map.
+    ///
+    /// For example, a circuit checking that `w_2 * w_3 = w_1`:
+    ///
+    /// ```text
+    /// prime 0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001
+    /// wires 4
+    /// outputs 1
+    /// public_inputs 0
+    /// private_inputs 2
+    /// labels 4
+    /// constraints 1
+    /// map 0 1 2 3
+    /// [2:1] * [3:1] = [1:1]
+    /// ```
+    ///
+    /// Fails with [`R1csError::InvalidText`] at the first line not following the grammar.
+    pub fn read_text<R: BufRead>(r: R) -> Result<Self> {
+        let mut prime = None;
+        let mut values = [None; 6];
+        let mut map = None;
+        let mut constraints = Vec::new();
+        let mut last_line = 0;
+
+        for (i, line) in r.lines().enumerate() {
+            let line = line?;
+            last_line = i + 1;
+            let invalid = |message: String| R1csError::InvalidText {
+                line: i + 1,
+                message,
+            };
+
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') {
+                constraints.push(parse_constraint(line).map_err(invalid)?);
+                continue;
+            }
+            if !constraints.is_empty() {
+                return Err(invalid("Header line after the constraints".to_owned()));
+            }
+
+            let mut words = line.split_whitespace();
+            let key = words.next().unwrap_or_default();
+            if key == "map" {
+                if map.is_some() {
+                    return Err(invalid("Duplicated map".to_owned()));
+                }
+                let labels = words
+                    .map(|label| parse_integer(label, "label"))
+                    .collect::<std::result::Result<_, _>>()
+                    .map_err(invalid)?;
+                map = Some(WireMap(labels));
+                continue;
+            }
+
+            let value = match (words.next(), words.next()) {
+                (Some(value), None) => value,
+                _ => return Err(invalid(format!("Expected a single value for {}", key))),
+            };
+            if key == "prime" {
+                if prime.is_some() {
+                    return Err(invalid("Duplicated key prime".to_owned()));
+                }
+                prime = Some(parse_coefficient(value).map_err(invalid)?);
+                continue;
+            }
+
+            let slot = KEYS
+                .iter()
+                .position(|k| *k == key)
+                .map(|i| &mut values[i])
+                .ok_or_else(|| invalid(format!("Unknown key {:?}", key)))?;
+            if slot.is_some() {
+                return Err(invalid(format!("Duplicated key {}", key)));
+            }
+            *slot = Some(parse_integer(value, key).map_err(invalid)?);
+        }
+
+        let missing = |key: &str| R1csError::InvalidText {
+            line: last_line + 1,
+            message: format!("Missing {}", key),
+        };
+        let value = |i: usize| values[i].ok_or_else(|| missing(KEYS[i]));
+        let value_u32 = |i: usize| -> Result<u32> {
+            u32::try_from(value(i)?).map_err(|_| R1csError::InvalidText {
+                line: last_line + 1,
+                message: format!("Value of {} doesn't fit in 32 bits", KEYS[i]),
+            })
+        };
+        let header = Header {
+            prime: prime.ok_or_else(|| missing("prime"))?,
+            n_wires: value_u32(0)?,
+            n_pub_out: value_u32(1)?,
+            n_pub_in: value_u32(2)?,
+            n_prvt_in: value_u32(3)?,
+            n_labels: value(4)?,
+            n_constraints: value_u32(5)?,
+        };
+
+        Ok(R1csFile {
+            header,
+            constraints: Constraints(constraints),
+            map: map.ok_or_else(|| missing("map"))?,
+            custom_gates_list: None,
+            custom_gates_application: None,
+            metadata: None,
+            custom_sections: Vec::new(),
+            version: VERSION,
+        })
+    }
+}
+
+fn write_combination<W: Write, const FS: usize>(
+    w: &mut W,
+    lc: &LinearCombination<FS>,
+) -> std::io::Result<()> {
+    write!(w, "[")?;
+    for (i, (coeff, wire)) in lc.iter().enumerate() {
+        if i > 0 {
+            write!(w, " ")?;
+        }
+        match to_u128(coeff) {
+            Some(value) => write!(w, "{}:{}", wire, value)?,
+            None => write!(w, "{}:{:#x}", wire, coeff)?,
+        }
+    }
+    write!(w, "]")
+}
+
+fn parse_constraint<const FS: usize>(line: &str) -> std::result::Result<Constraint<FS>, String> {
+    let mut rest = line;
+    let mut combinations = Vec::with_capacity(3);
+    for separator in ["*", "=", ""] {
+        rest = rest
+            .trim_start()
+            .strip_prefix('[')
+            .ok_or_else(|| "Expected '['".to_owned())?;
+        let end = rest.find(']').ok_or_else(|| "Expected ']'".to_owned())?;
+        combinations.push(parse_combination(&rest[..end])?);
+
+        rest = rest[end + 1..].trim_start();
+        if !separator.is_empty() {
+            rest = rest
+                .strip_prefix(separator)
+                .ok_or_else(|| format!("Expected '{}'", separator))?;
+        }
+    }
+    if !rest.is_empty() {
+        return Err(format!("Unexpected {:?} after the constraint", rest));
+    }
+
+    let c = combinations.pop().expect("three combinations");
+    let b = combinations.pop().expect("three combinations");
+    let a = combinations.pop().expect("three combinations");
+    Ok(Constraint(a, b, c))
+}
+
+fn parse_combination<const FS: usize>(
+    s: &str,
+) -> std::result::Result<LinearCombination<FS>, String> {
+    s.split_whitespace()
+        .map(|term| {
+            let (wire, coeff) = term
+                .split_once(':')
+                .ok_or_else(|| format!("Expected wire:coefficient, found {:?}", term))?;
+            let wire = parse_integer(wire, "wire")?;
+            let wire = u32::try_from(wire).map_err(|_| format!("Wire {} is too large", wire))?;
+            Ok((parse_coefficient(coeff)?, wire))
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map(LinearCombination::from)
+}
+
+fn parse_integer(s: &str, what: &str) -> std::result::Result<u64, String> {
+    s.parse()
+        .map_err(|_| format!("Invalid {} {:?}, expected a decimal integer", what, s))
+}
+
+/// Parses a decimal or `0x`-prefixed hexadecimal value.
+fn parse_coefficient<const FS: usize>(s: &str) -> std::result::Result<FieldElement<FS>, String> {
+    let too_large = || format!("Coefficient {} doesn't fit in {} bytes", s, FS);
+
+    if let Some(digits) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        if digits.is_empty() {
+            return Err(format!("Invalid coefficient {:?}", s));
+        }
+        // Trim leading zeros beyond the width, and pad to whole bytes.
+        let digits = digits.trim_start_matches('0');
+        if digits.len() > 2 * FS {
+            return Err(too_large());
+        }
+        let padded = format!("{:0>width$}", digits, width = 2 * FS);
+        return FieldElement::from_hex(&padded, Endianness::Big)
+            .map_err(|e| format!("Invalid coefficient {:?}: {}", s, e));
+    }
+
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!(
+            "Invalid coefficient {:?}, expected a decimal or 0x-prefixed hexadecimal value",
+            s
+        ));
+    }
+    let mut bytes = [0u8; FS];
+    for digit in s.bytes() {
+        let mut carry = u16::from(digit - b'0');
+        for byte in bytes.iter_mut() {
+            let value = u16::from(*byte) * 10 + carry;
+            *byte = value as u8;
+            carry = value >> 8;
+        }
+        if carry != 0 {
+            return Err(too_large());
+        }
+    }
+
+    Ok(FieldElement::from(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `p - 1` for the BN254 scalar prime.
+    fn minus_one() -> FieldElement<32> {
+        let mut bytes = *FieldElement::bn254_scalar_prime();
+        bytes[0] -= 1;
+        FieldElement::from(bytes)
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+
+        let mut text = Vec::new();
+        file.write_text(&mut text).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        assert!(text.starts_with(
+            "prime 0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001\nwires 7\n"
+        ));
+        assert!(
+            text.contains("\nmap 0 3 10 11 12 15 324\n[5:3 6:8] * [0:2 2:20 3:12] = [0:5 2:7]\n")
+        );
+        assert_eq!(R1csFile::<32>::read_text(text.as_bytes()).unwrap(), file);
+
+        let mut negative = file.clone();
+        negative.constraints.0[1].2 = LinearCombination::term(minus_one(), 2);
+        let mut text = Vec::new();
+        negative.write_text(&mut text).unwrap();
+        assert_eq!(
+            R1csFile::<32>::read_text(text.as_slice()).unwrap(),
+            negative
+        );
+    }
+
+    #[test]
+    fn test_read_text() {
+        let text = "# w_2 * w_3 = w_1\n\
+                    prime 0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001\n\
+                    wires 4\noutputs 1\npublic_inputs 0\nprivate_inputs 2\n\
+                    labels 4  # one per wire\nconstraints 1\nmap 0 1 2 3\n\n\
+                    [2:1] * [3:0x01] = [1:21888242871839275222246405745257275088548364400416034343698204186575808495616]\n";
+        let file = R1csFile::<32>::read_text(text.as_bytes()).unwrap();
+        assert_eq!(file.header.n_wires, 4);
+        assert_eq!(file.header.prime, FieldElement::bn254_scalar_prime());
+        assert_eq!(file.map.0, [0, 1, 2, 3]);
+        assert_eq!(
+            file.constraints.0[0].b().0[0],
+            (FieldElement::from_u64(1), 3)
+        );
+        assert_eq!(file.constraints.0[0].c().0[0].0, minus_one());
+
+        let error = |text: &str| match R1csFile::<32>::read_text(text.as_bytes()) {
+            Err(R1csError::InvalidText { line, message }) => (line, message),
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(
+            error("wires 4\nwires 5\n"),
+            (2, "Duplicated key wires".to_owned())
+        );
+        assert_eq!(
+            error("wires 4\n[1:2 3] * [] = []\n"),
+            (2, "Expected wire:coefficient, found \"3\"".to_owned())
+        );
+        assert_eq!(
+            error("wires 4\n[1:2] * []\n"),
+            (2, "Expected '='".to_owned())
+        );
+        assert_eq!(error("wires 4\n"), (2, "Missing prime".to_owned()));
+        assert_eq!(error("wire 4\n").1, "Unknown key \"wire\"");
+        assert_eq!(
+            error("[1:0x10000000000000000000000000000000000000000000000000000000000000000] * [] = []").1,
+            "Coefficient 0x10000000000000000000000000000000000000000000000000000000000000000 doesn't fit in 32 bytes"
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! Reverse index from wires to the constraints referencing them.
+
+use crate::{Matrix, R1csFile};
+
+/// For every wire, the constraints referencing it.
+///
+/// The positions of all wires are stored in one flat vector, sliced by per-wire offsets.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WireUsageIndex {
+    /// `positions[offsets[w]..offsets[w + 1]]` are the positions of wire `w`.
+    offsets: Vec<usize>,
+    positions: Vec<(u32, Matrix)>,
+}
+
+impl WireUsageIndex {
+    /// Indexes the terms of every constraint of `file`.
+    ///
+    /// Wires past `n_wires` referenced by a constraint are indexed as well.
+    pub fn build<const FS: usize>(file: &R1csFile<FS>) -> Self {
+        let mut counts = vec![0usize; file.header.n_wires as usize];
+        for term in file.iter_terms() {
+            let wire = term.wire as usize;
+            if wire >= counts.len() {
+                counts.resize(wire + 1, 0);
+            }
+            counts[wire] += 1;
+        }
+
+        let mut offsets = Vec::with_capacity(counts.len() + 1);
+        offsets.push(0);
+        for count in &counts {
+            offsets.push(offsets.last().unwrap() + count);
+        }
+
+        // Fill each wire's slice in constraint order, reusing `counts` as the next free slot.
+        let mut positions = vec![(0, Matrix::A); *offsets.last().unwrap()];
+        let n = counts.len();
+        counts.copy_from_slice(&offsets[..n]);
+        for term in file.iter_terms() {
+            let slot = &mut counts[term.wire as usize];
+            positions[*slot] = (term.constraint, term.matrix);
+            *slot += 1;
+        }
+
+        WireUsageIndex { offsets, positions }
+    }
+
+    /// Number of indexed wires.
+    pub fn len(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The `(constraint, matrix)` positions of the terms referencing `wire`, ordered by
+    /// constraint. A constraint appears once per term referencing the wire.
+    pub fn constraints_for(&self, wire: u32) -> &[(u32, Matrix)] {
+        let wire = wire as usize;
+        if wire >= self.len() {
+            return &[];
+        }
+
+        &self.positions[self.offsets[wire]..self.offsets[wire + 1]]
+    }
+
+    /// The constraints referencing any of `wires`, in ascending order and without repeats.
+    pub fn constraints_involving<I: IntoIterator<Item = u32>>(&self, wires: I) -> Vec<u32> {
+        let mut constraints: Vec<u32> = wires
+            .into_iter()
+            .flat_map(|wire| self.constraints_for(wire).iter().map(|(i, _)| *i))
+            .collect();
+        constraints.sort_unstable();
+        constraints.dedup();
+
+        constraints
+    }
+
+    /// Number of terms referencing `wire`.
+    pub fn degree(&self, wire: u32) -> usize {
+        self.constraints_for(wire).len()
+    }
+
+    /// Iterates over the wires and their degrees, most used first. Wires with the same degree are
+    /// ordered by index.
+    pub fn by_usage(&self) -> impl Iterator<Item = (u32, usize)> {
+        let mut wires: Vec<_> = (0..self.len() as u32)
+            .map(|wire| (wire, self.degree(wire)))
+            .collect();
+        wires.sort_by(|(a, da), (b, db)| db.cmp(da).then(a.cmp(b)));
+
+        wires.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wire_usage_index() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let index = WireUsageIndex::build(&file);
+        assert_eq!(index.len(), 7);
+
+        // Every term is found exactly once, at its own wire.
+        let mut total = 0;
+        for wire in 0..7 {
+            for (i, matrix) in index.constraints_for(wire) {
+                let c = &file.constraints.0[*i as usize];
+                assert!(c.combination(*matrix).iter().any(|(_, w)| *w == wire));
+            }
+            total += index.degree(wire);
+        }
+        assert_eq!(total, 17);
+        assert_eq!(index.constraints_for(7), []);
+        assert_eq!(index.constraints_involving([1]), [1]);
+        assert_eq!(index.constraints_involving([0, 2, 7]), [0, 2]);
+        assert_eq!(index.constraints_involving([2, 1, 0]), [0, 1, 2]);
+
+        let by_usage: Vec<_> = index.by_usage().collect();
+        assert_eq!(by_usage.len(), 7);
+        assert!(by_usage.windows(2).all(|w| w[0].1 >= w[1].1));
+        assert_eq!(by_usage.iter().map(|(_, d)| d).sum::<usize>(), 17);
+    }
+}
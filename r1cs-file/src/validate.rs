@@ -0,0 +1,426 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+
+#[cfg(feature = "std")]
+use crate::Constraint;
+use crate::{Header, Matrix, PrimeCheckError, R1csFile, WireMap};
+
+/// A structural inconsistency found by [`R1csFile::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationError {
+    /// A term references a wire not below `header.n_wires`.
+    WireOutOfRange {
+        constraint: usize,
+        matrix: Matrix,
+        term: usize,
+        wire: u32,
+    },
+    /// The header constraint count differs from the number of constraints.
+    ConstraintCountMismatch { header: u32, found: usize },
+    /// More constraints than the 32-bit header count holds.
+    TooManyConstraints { found: usize },
+    /// The wire map doesn't have an entry for every wire.
+    WireMapLengthMismatch { n_wires: u32, found: usize },
+    /// A wire maps to a label not below `header.n_labels`.
+    LabelOutOfRange { wire: usize, label: u64 },
+    /// Several wires map to the same label, found by [`WireMap::inverse`].
+    DuplicateLabel { label: u64, wires: Vec<u32> },
+    /// A wire permutation doesn't have an entry for every wire.
+    PermutationLengthMismatch { n_wires: u32, found: usize },
+    /// A wire permutation maps `wire` out of range or onto a wire already taken.
+    InvalidPermutation { wire: u32, target: u32 },
+    /// A wire permutation moves the constant one wire.
+    ConstantWireMoved { target: u32 },
+    /// The constant wire and the public and private signals don't fit in `n_wires`.
+    TooManySignals { signals: u64, n_wires: u32 },
+    /// Two files combined together are over different primes.
+    PrimeMismatch,
+    /// The file format version is neither 1 nor 2, see [`R1csFile::version`].
+    UnsupportedVersion { version: u32 },
+    /// The header prime is zero.
+    ZeroPrime,
+    /// The header prime is even.
+    EvenPrime,
+    /// The top byte of the header prime is zero, see [`crate::ParseWarning::PaddedPrime`].
+    PaddedPrime { field_size: u32, prime_bytes: u32 },
+    /// A coefficient is not below the header prime.
+    NonCanonicalCoefficient {
+        constraint: usize,
+        matrix: Matrix,
+        term: usize,
+    },
+    /// The header prime is not the modulus of the expected field, both shown in hexadecimal.
+    ModulusMismatch { prime: String, modulus: String },
+    /// A wire is not below `n_wires`, found by [`Header::wire_kind`].
+    WireNotInCircuit { wire: u32, n_wires: u32 },
+    /// The header prime failed [`Header::check_prime`].
+    NotPrime(PrimeCheckError),
+}
+
+/// Options for [`R1csFile::validate_with`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ValidateOptions {
+    /// Also checks that the header prime is prime, see [`Header::check_prime`].
+    pub check_primality: bool,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::WireOutOfRange {
+                constraint,
+                matrix,
+                term,
+                wire,
+            } => write!(
+                f,
+                "Constraint {}, matrix {}, term {}: wire {} is out of range",
+                constraint, matrix, term, wire
+            ),
+            ValidationError::ConstraintCountMismatch { header, found } => write!(
+                f,
+                "Header declares {} constraints but {} were found",
+                header, found
+            ),
+            ValidationError::TooManyConstraints { found } => write!(
+                f,
+                "{} constraints don't fit in the header constraint count",
+                found
+            ),
+            ValidationError::WireMapLengthMismatch { n_wires, found } => write!(
+                f,
+                "Header declares {} wires but the wire map has {} entries",
+                n_wires, found
+            ),
+            ValidationError::LabelOutOfRange { wire, label } => {
+                write!(f, "Wire {}: label {} is out of range", wire, label)
+            }
+            ValidationError::DuplicateLabel { label, wires } => {
+                write!(f, "Label {} is mapped to by wires {:?}", label, wires)
+            }
+            ValidationError::PermutationLengthMismatch { n_wires, found } => write!(
+                f,
+                "Header declares {} wires but the permutation has {} entries",
+                n_wires, found
+            ),
+            ValidationError::InvalidPermutation { wire, target } => write!(
+                f,
+                "Wire {} is mapped to {}, which is out of range or already taken",
+                wire, target
+            ),
+            ValidationError::ConstantWireMoved { target } => {
+                write!(f, "The constant wire 0 is mapped to {}", target)
+            }
+            ValidationError::TooManySignals { signals, n_wires } => write!(
+                f,
+                "{} signals including the constant wire don't fit in {} wires",
+                signals, n_wires
+            ),
+            ValidationError::PrimeMismatch => f.write_str("The primes differ"),
+            ValidationError::UnsupportedVersion { version } => {
+                write!(f, "Unsupported version {}", version)
+            }
+            ValidationError::ZeroPrime => f.write_str("The prime is zero"),
+            ValidationError::EvenPrime => f.write_str("The prime is even"),
+            ValidationError::PaddedPrime {
+                field_size,
+                prime_bytes,
+            } => write!(
+                f,
+                "The prime takes {} of the {} bytes of the field size",
+                prime_bytes, field_size
+            ),
+            ValidationError::NonCanonicalCoefficient {
+                constraint,
+                matrix,
+                term,
+            } => write!(
+                f,
+                "Constraint {}, matrix {}, term {}: coefficient is not reduced modulo the prime",
+                constraint, matrix, term
+            ),
+            ValidationError::ModulusMismatch { prime, modulus } => write!(
+                f,
+                "The prime {} is not the field modulus {}",
+                prime, modulus
+            ),
+            ValidationError::WireNotInCircuit { wire, n_wires } => write!(
+                f,
+                "Wire {} is not in the circuit of {} wires",
+                wire, n_wires
+            ),
+            ValidationError::NotPrime(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValidationError {}
+
+impl WireMap {
+    /// Checks the map length against `header.n_wires` and the labels against `header.n_labels`.
+    ///
+    /// An empty map stands for a file without a wire map section and is accepted.
+    pub fn validate<const FS: usize>(
+        &self,
+        header: &Header<FS>,
+    ) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        self.check(header, &mut errors);
+
+        into_result(errors)
+    }
+
+    fn check<const FS: usize>(&self, header: &Header<FS>, errors: &mut Vec<ValidationError>) {
+        if !self.0.is_empty() && header.n_wires as usize != self.0.len() {
+            errors.push(ValidationError::WireMapLengthMismatch {
+                n_wires: header.n_wires,
+                found: self.0.len(),
+            });
+        }
+
+        for (wire, label) in self.0.iter().enumerate() {
+            if *label >= header.n_labels {
+                errors.push(ValidationError::LabelOutOfRange {
+                    wire,
+                    label: *label,
+                });
+            }
+        }
+    }
+}
+
+/// The first term of `constraints` referencing a wire not below `n_wires`. `first` is the index of
+/// the first constraint, for the error.
+#[cfg(feature = "std")]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "r1cs_check_wires",
+        level = "trace",
+        skip_all,
+        fields(n_constraints = constraints.len())
+    )
+)]
+pub(crate) fn wire_out_of_range<const FS: usize>(
+    constraints: &[Constraint<FS>],
+    first: usize,
+    n_wires: u32,
+) -> Option<ValidationError> {
+    for (i, c) in constraints.iter().enumerate() {
+        for matrix in Matrix::ALL {
+            for (term, (_, wire)) in c.combination(matrix).iter().enumerate() {
+                if *wire >= n_wires {
+                    return Some(ValidationError::WireOutOfRange {
+                        constraint: first + i,
+                        matrix,
+                        term,
+                        wire: *wire,
+                    });
+                }
+            }
+        }
+    }
+
+    None
+}
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Checks that the sections are consistent with the header.
+    ///
+    /// Every violation is reported, not just the first one.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        self.validate_with(&ValidateOptions::default())
+    }
+
+    /// Like [`R1csFile::validate`], with `opts`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "r1cs_validate",
+            level = "debug",
+            skip_all,
+            fields(
+                n_constraints = self.constraints.0.len(),
+                check_primality = opts.check_primality,
+                findings = tracing::field::Empty
+            )
+        )
+    )]
+    pub fn validate_with(&self, opts: &ValidateOptions) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let header = &self.header;
+
+        if opts.check_primality {
+            if let Err(e) = header.check_prime() {
+                errors.push(ValidationError::NotPrime(e));
+            }
+        }
+
+        if header.n_constraints as usize != self.constraints.0.len() {
+            errors.push(ValidationError::ConstraintCountMismatch {
+                header: header.n_constraints,
+                found: self.constraints.0.len(),
+            });
+        }
+
+        for (constraint, c) in self.constraints.0.iter().enumerate() {
+            for matrix in Matrix::ALL {
+                for (term, (_, wire)) in c.combination(matrix).iter().enumerate() {
+                    if *wire >= header.n_wires {
+                        errors.push(ValidationError::WireOutOfRange {
+                            constraint,
+                            matrix,
+                            term,
+                            wire: *wire,
+                        });
+                    }
+                }
+            }
+        }
+
+        self.map.check(header, &mut errors);
+
+        into_result(errors)
+    }
+
+    /// Checks that the prime is odd and every constraint coefficient is below it.
+    ///
+    /// Coefficients are not checked against a zero prime, as none of them could be canonical.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "r1cs_check_canonical",
+            level = "debug",
+            skip_all,
+            fields(
+                n_constraints = self.constraints.0.len(),
+                findings = tracing::field::Empty
+            )
+        )
+    )]
+    pub fn check_canonical_coefficients(&self) -> Result<(), Vec<ValidationError>> {
+        let prime = &self.header.prime;
+        if prime.is_zero() {
+            return Err(vec![ValidationError::ZeroPrime]);
+        }
+
+        let mut errors = Vec::new();
+        if prime.byte(0).unwrap_or(0) & 1 == 0 {
+            errors.push(ValidationError::EvenPrime);
+        }
+
+        for (constraint, c) in self.constraints.0.iter().enumerate() {
+            for matrix in Matrix::ALL {
+                for (term, (coefficient, _)) in c.combination(matrix).iter().enumerate() {
+                    if coefficient.cmp_value(prime) != Ordering::Less {
+                        errors.push(ValidationError::NonCanonicalCoefficient {
+                            constraint,
+                            matrix,
+                            term,
+                        });
+                    }
+                }
+            }
+        }
+
+        into_result(errors)
+    }
+}
+
+fn into_result(errors: Vec<ValidationError>) -> Result<(), Vec<ValidationError>> {
+    #[cfg(feature = "tracing")]
+    {
+        tracing::Span::current().record("findings", errors.len());
+        for error in &errors {
+            tracing::debug!(%error, "validation finding");
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FieldElement;
+
+    #[test]
+    fn test_validate_prime() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let mut file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let opts = ValidateOptions {
+            check_primality: true,
+        };
+        assert_eq!(file.validate_with(&opts), Ok(()));
+
+        let mut prime = [0u8; 32];
+        prime.copy_from_slice(file.header.prime.as_bytes());
+        prime[7] ^= 0x80;
+        file.header.prime = FieldElement::from(prime);
+        assert_eq!(file.validate(), Ok(()));
+        assert!(matches!(
+            file.validate_with(&opts).unwrap_err()[..],
+            [ValidationError::NotPrime(PrimeCheckError::Composite { .. })]
+        ));
+    }
+
+    #[test]
+    fn test_validate() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let mut file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        assert_eq!(file.validate(), Ok(()));
+
+        file.constraints.0[1].1[0].1 = 7;
+        assert_eq!(
+            file.validate(),
+            Err(vec![ValidationError::WireOutOfRange {
+                constraint: 1,
+                matrix: Matrix::B,
+                term: 0,
+                wire: 7,
+            }])
+        );
+
+        file.header.n_constraints = 2;
+        file.map.0.push(1000);
+        assert_eq!(file.validate().unwrap_err().len(), 4);
+    }
+
+    #[test]
+    fn test_check_canonical_coefficients() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let mut file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        assert_eq!(file.check_canonical_coefficients(), Ok(()));
+
+        file.constraints.0[2].0[0].0 = FieldElement::from(*file.header.prime);
+        assert_eq!(
+            file.check_canonical_coefficients(),
+            Err(vec![ValidationError::NonCanonicalCoefficient {
+                constraint: 2,
+                matrix: Matrix::A,
+                term: 0,
+            }])
+        );
+
+        file.header.prime = FieldElement::from_u64(0);
+        assert_eq!(
+            file.check_canonical_coefficients(),
+            Err(vec![ValidationError::ZeroPrime])
+        );
+
+        file.header.prime = FieldElement::from_u64(1 << 40);
+        assert_eq!(
+            file.check_canonical_coefficients().unwrap_err()[0],
+            ValidationError::EvenPrime
+        );
+    }
+}
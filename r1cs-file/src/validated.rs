@@ -0,0 +1,271 @@
+//! Circuits known to have passed validation.
+
+use std::io::Write;
+use std::ops::{Deref, Range};
+
+use crate::{
+    MergeOptions, PermuteOptions, R1csError, R1csFile, Result, ValidateOptions, ValidationError,
+    WriteOptions,
+};
+
+/// A circuit that passed [`R1csFile::into_validated`].
+///
+/// It can only be read, through [`Deref`]. Modifying it requires [`ValidatedR1cs::into_inner`],
+/// which gives back an unvalidated [`R1csFile`]. The `_validated` methods skip the checks that
+/// validation already made.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatedR1cs<const FS: usize>(R1csFile<FS>);
+
+impl<const FS: usize> R1csFile<FS> {
+    /// Runs the full validation: [`R1csFile::validate_with`], the checks of
+    /// [`R1csFile::check_canonical_coefficients`] and the header signal layout, see
+    /// [`crate::Header::public_wires`].
+    ///
+    /// Every violation is reported, not just the first one.
+    pub fn into_validated(
+        self,
+        opts: &ValidateOptions,
+    ) -> Result<ValidatedR1cs<FS>, Vec<ValidationError>> {
+        let mut errors = self.validate_with(opts).err().unwrap_or_default();
+        if let Err(e) = self.check_canonical_coefficients() {
+            errors.extend(e);
+        }
+        if let Err(e) = self.header.check_layout() {
+            errors.push(e);
+        }
+
+        if errors.is_empty() {
+            Ok(ValidatedR1cs(self))
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl<const FS: usize> ValidatedR1cs<FS> {
+    /// The circuit, to be modified and validated again if needed.
+    pub fn into_inner(self) -> R1csFile<FS> {
+        self.0
+    }
+
+    /// The first wire of each signal region, see [`crate::Header::first_internal_wire`]. The
+    /// signals are known to fit in `n_wires`.
+    pub(crate) fn layout(&self) -> [u32; 4] {
+        self.0.header.region_starts().map(|start| start as u32)
+    }
+
+    /// [`crate::Header::public_wires`], which can't fail on a validated circuit.
+    pub fn public_wires(&self) -> Range<u32> {
+        let starts = self.layout();
+        starts[0]..starts[2]
+    }
+
+    /// [`R1csFile::write`] without checking the header against the sections.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "r1cs_write_validated", level = "debug", skip_all)
+    )]
+    pub fn write_validated<W: Write>(&self, w: W) -> Result<()> {
+        self.0.write_unchecked(w)
+    }
+
+    /// [`R1csFile::write_canonical`] without checking the header against the sections.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "r1cs_write_canonical_validated", level = "debug", skip_all)
+    )]
+    pub fn write_canonical_validated<W: Write>(&self, w: W) -> Result<()> {
+        self.0.write_sections(w, true, &WriteOptions::default())
+    }
+
+    /// [`R1csFile::permute_wires_with`] without checking the constraints and the wire map against
+    /// `n_wires`. Only `perm` and the new signal counts are checked.
+    ///
+    /// The renumbered circuit stays validated: validation doesn't depend on the wire order.
+    pub fn permute_wires_validated(&mut self, perm: &[u32], opts: &PermuteOptions) -> Result<()> {
+        let header = self
+            .0
+            .permuted_header(perm, opts)
+            .map_err(R1csError::Invalid)?;
+        self.0.apply_permutation(perm, header);
+
+        Ok(())
+    }
+
+    /// [`R1csFile::merge`] without checking the constraints of either circuit against its header.
+    pub fn merge_validated(
+        a: &ValidatedR1cs<FS>,
+        b: &ValidatedR1cs<FS>,
+        opts: MergeOptions,
+    ) -> Result<R1csFile<FS>> {
+        if *a.header.prime != *b.header.prime {
+            return Err(R1csError::Invalid(ValidationError::PrimeMismatch));
+        }
+
+        R1csFile::merge_consistent(a, b, opts)
+    }
+}
+
+impl<const FS: usize> Deref for ValidatedR1cs<FS> {
+    type Target = R1csFile<FS>;
+
+    fn deref(&self) -> &R1csFile<FS> {
+        &self.0
+    }
+}
+
+impl<const FS: usize> AsRef<R1csFile<FS>> for ValidatedR1cs<FS> {
+    fn as_ref(&self) -> &R1csFile<FS> {
+        &self.0
+    }
+}
+
+impl<const FS: usize> From<ValidatedR1cs<FS>> for R1csFile<FS> {
+    fn from(validated: ValidatedR1cs<FS>) -> Self {
+        validated.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FieldElement, Matrix};
+
+    fn read() -> R1csFile<32> {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        R1csFile::read(data.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_into_validated() {
+        let file = read();
+        let validated = file
+            .clone()
+            .into_validated(&ValidateOptions::default())
+            .unwrap();
+        assert_eq!(*validated, file);
+        assert_eq!(validated.header.n_wires, 7);
+
+        let mut written = Vec::new();
+        validated.write_validated(&mut written).unwrap();
+        let mut expected = Vec::new();
+        file.write(&mut expected).unwrap();
+        assert_eq!(written, expected);
+
+        assert_eq!(
+            validated.public_wires(),
+            file.header.public_wires().unwrap()
+        );
+        let mut written = Vec::new();
+        validated.write_canonical_validated(&mut written).unwrap();
+        let mut expected = Vec::new();
+        file.write_canonical(&mut expected).unwrap();
+        assert_eq!(written, expected);
+
+        let merged =
+            ValidatedR1cs::merge_validated(&validated, &validated, MergeOptions::default());
+        assert_eq!(
+            merged.unwrap(),
+            R1csFile::merge(&file, &file, MergeOptions::default()).unwrap()
+        );
+
+        // Modifying the circuit requires giving up the validated type.
+        let mut file = validated.into_inner();
+        file.header.n_pub_in = 10;
+        file.constraints.0[0].0[0].0 = FieldElement::from(*file.header.prime);
+        assert_eq!(
+            file.into_validated(&ValidateOptions::default()),
+            Err(vec![
+                ValidationError::NonCanonicalCoefficient {
+                    constraint: 0,
+                    matrix: Matrix::A,
+                    term: 0,
+                },
+                ValidationError::TooManySignals {
+                    signals: 15,
+                    n_wires: 7,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_permute_wires_validated() {
+        let file = read();
+        let mut validated = file
+            .clone()
+            .into_validated(&ValidateOptions::default())
+            .unwrap();
+
+        let perm = [0, 2, 3, 4, 5, 6, 1];
+        let opts = PermuteOptions {
+            n_pub_in: Some(3),
+            n_prvt_in: Some(2),
+            ..PermuteOptions::default()
+        };
+        validated.permute_wires_validated(&perm, &opts).unwrap();
+        let mut expected = file.clone();
+        expected.permute_wires_with(&perm, &opts).unwrap();
+        assert_eq!(*validated, expected);
+        assert!(expected.into_validated(&ValidateOptions::default()).is_ok());
+
+        let permuted = validated.clone();
+        assert!(matches!(
+            validated.permute_wires_validated(&[0, 1, 2], &opts),
+            Err(R1csError::Invalid(
+                ValidationError::PermutationLengthMismatch { .. }
+            ))
+        ));
+        assert_eq!(validated, permuted);
+    }
+
+    /// The `_validated` methods don't run the checks that validation already made.
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_validated_skips_checks() {
+        use crate::tests::recorded::Recorded;
+
+        const CHECKS: [&str; 4] = [
+            "r1cs_check_header",
+            "r1cs_validate",
+            "r1cs_check_wires",
+            "r1cs_check_canonical",
+        ];
+        let checked = |spans: Vec<String>| spans.iter().any(|s| CHECKS.contains(&s.as_str()));
+
+        let file = read();
+        let validated = file
+            .clone()
+            .into_validated(&ValidateOptions::default())
+            .unwrap();
+        let perm = [0, 2, 1, 3, 4, 5, 6];
+        let recorded = Recorded::default();
+        recorded.record(|| {
+            file.write(std::io::sink()).unwrap();
+            assert!(checked(recorded.take()));
+            validated.write_validated(std::io::sink()).unwrap();
+            assert!(!checked(recorded.take()));
+
+            file.write_canonical(std::io::sink()).unwrap();
+            assert!(checked(recorded.take()));
+            validated
+                .write_canonical_validated(std::io::sink())
+                .unwrap();
+            assert!(!checked(recorded.take()));
+
+            file.clone().permute_wires(&perm).unwrap();
+            assert!(checked(recorded.take()));
+            validated
+                .clone()
+                .permute_wires_validated(&perm, &PermuteOptions::default())
+                .unwrap();
+            assert!(!checked(recorded.take()));
+
+            R1csFile::merge(&file, &file, MergeOptions::default()).unwrap();
+            assert!(checked(recorded.take()));
+            ValidatedR1cs::merge_validated(&validated, &validated, MergeOptions::default())
+                .unwrap();
+            assert!(!checked(recorded.take()));
+        });
+    }
+}
@@ -0,0 +1,188 @@
+//! WebAssembly bindings, behind the `wasm` feature.
+//!
+//! Files are passed as byte arrays and parsed whatever their field size among those of
+//! [`R1csFileAny`]. Results are plain JS objects, with field elements as decimal strings as in
+//! the snarkjs exports, and errors are thrown as JS `Error`s carrying the error message.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+use wtns_file::{WtnsFile, WtnsFileDyn};
+
+use crate::{check_witness as check, R1csFile, R1csFileAny, SatisfactionError};
+
+/// Parses an r1cs file into an object with the header fields and the circuit statistics, plus
+/// the constraints in the format of `snarkjs r1cs export json` with `withConstraints`.
+#[wasm_bindgen]
+pub fn parse_r1cs(bytes: &[u8], with_constraints: bool) -> Result<JsValue, JsError> {
+    to_js(r1cs_value(bytes, with_constraints))
+}
+
+/// Parses a witness file into an object with its header fields and values.
+#[wasm_bindgen]
+pub fn parse_wtns(bytes: &[u8]) -> Result<JsValue, JsError> {
+    to_js(wtns_value(bytes))
+}
+
+/// Checks a witness against a circuit, returning `{ satisfied: true }`, or `satisfied: false`
+/// with the `reason` and, for an unsatisfied constraint, the first `constraint` that fails.
+#[wasm_bindgen]
+pub fn check_witness(r1cs_bytes: &[u8], wtns_bytes: &[u8]) -> Result<JsValue, JsError> {
+    to_js(check_value(r1cs_bytes, wtns_bytes))
+}
+
+fn to_js(value: Result<Value, String>) -> Result<JsValue, JsError> {
+    let value = value.map_err(|e| JsError::new(&e))?;
+
+    // Plain objects rather than `Map`s, which is what JSON objects are by default.
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+fn r1cs_value(bytes: &[u8], with_constraints: bool) -> Result<Value, String> {
+    let value = match R1csFileAny::read(bytes).map_err(|e| e.to_string())? {
+        R1csFileAny::Fs8(file) => r1cs_json(&file, with_constraints),
+        R1csFileAny::Fs32(file) => r1cs_json(&file, with_constraints),
+        R1csFileAny::Fs48(file) => r1cs_json(&file, with_constraints),
+        R1csFileAny::Fs64(file) => r1cs_json(&file, with_constraints),
+    };
+
+    Ok(value)
+}
+
+fn r1cs_json<const FS: usize>(file: &R1csFile<FS>, with_constraints: bool) -> Value {
+    let header = &file.header;
+    let stats = file.stats();
+    let mut value = json!({
+        "fieldSize": FS,
+        "prime": header.prime.to_dec_string(),
+        "nWires": header.n_wires,
+        "nPubOut": header.n_pub_out,
+        "nPubIn": header.n_pub_in,
+        "nPrvtIn": header.n_prvt_in,
+        "nLabels": header.n_labels,
+        "nConstraints": header.n_constraints,
+        "stats": {
+            "nnz": stats.nnz,
+            "maxTermsPerLc": stats.max_terms_per_lc,
+            "avgTermsPerLc": stats.avg_terms_per_lc,
+            "unusedWires": stats.unused_wires,
+            "domainSize": stats.domain_size,
+        },
+    });
+    if with_constraints {
+        value["constraints"] = file.to_json_value()["constraints"].take();
+    }
+
+    value
+}
+
+fn wtns_value(bytes: &[u8]) -> Result<Value, String> {
+    let file = WtnsFileDyn::read(bytes).map_err(|e| e.to_string())?;
+    let value = match file.field_size() {
+        8 => wtns_json(&file.try_into_const::<8>().map_err(|e| e.to_string())?),
+        32 => wtns_json(&file.try_into_const::<32>().map_err(|e| e.to_string())?),
+        48 => wtns_json(&file.try_into_const::<48>().map_err(|e| e.to_string())?),
+        64 => wtns_json(&file.try_into_const::<64>().map_err(|e| e.to_string())?),
+        found => return Err(format!("Unsupported field size {}", found)),
+    };
+
+    Ok(value)
+}
+
+fn wtns_json<const FS: usize>(file: &WtnsFile<FS>) -> Value {
+    let witness: Vec<_> = file.witness.iter().map(|v| v.to_dec_string()).collect();
+
+    json!({
+        "version": file.version,
+        "fieldSize": FS,
+        "prime": file.header.prime.to_dec_string(),
+        "witnessLen": file.header.witness_len,
+        "witness": witness,
+    })
+}
+
+fn check_value(r1cs_bytes: &[u8], wtns_bytes: &[u8]) -> Result<Value, String> {
+    match R1csFileAny::read(r1cs_bytes).map_err(|e| e.to_string())? {
+        R1csFileAny::Fs8(file) => check_json(&file, wtns_bytes),
+        R1csFileAny::Fs32(file) => check_json(&file, wtns_bytes),
+        R1csFileAny::Fs48(file) => check_json(&file, wtns_bytes),
+        R1csFileAny::Fs64(file) => check_json(&file, wtns_bytes),
+    }
+}
+
+fn check_json<const FS: usize>(r1cs: &R1csFile<FS>, wtns_bytes: &[u8]) -> Result<Value, String> {
+    let wtns = WtnsFile::<FS>::read(wtns_bytes).map_err(|e| e.to_string())?;
+
+    let value = match check(r1cs, &wtns) {
+        Ok(()) => json!({ "satisfied": true }),
+        Err(e) => {
+            let mut value = json!({ "satisfied": false, "reason": e.to_string() });
+            if let SatisfactionError::Unsatisfied(constraints) = &e {
+                let first = &constraints[0];
+                value["constraint"] = json!({
+                    "index": first.index,
+                    "a": first.a.to_dec_string(),
+                    "b": first.b.to_dec_string(),
+                    "c": first.c.to_dec_string(),
+                });
+            }
+            value
+        }
+    };
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::satisfy::tests::chain_witness;
+    use crate::FieldElement;
+
+    #[test]
+    fn test_wasm_values() {
+        let r1cs = std::fs::read("tests/test_circuit.r1cs").unwrap();
+        let value = r1cs_value(&r1cs, false).unwrap();
+        assert_eq!(value["fieldSize"], 32);
+        assert_eq!(value["nConstraints"], 1000);
+        assert_eq!(value["stats"]["domainSize"], 1024);
+        assert!(value.get("constraints").is_none());
+        let value = r1cs_value(&r1cs, true).unwrap();
+        assert_eq!(value["constraints"].as_array().unwrap().len(), 1000);
+        assert!(r1cs_value(&r1cs[..100], false).is_err());
+
+        let wtns = std::fs::read("../wtns-file/tests/witness.wtns").unwrap();
+        let value = wtns_value(&wtns).unwrap();
+        assert_eq!(value["witnessLen"], 5);
+        assert_eq!(value["witness"][1], "33");
+        assert_eq!(
+            value["witness"][4],
+            "21888242871839275222246405745257275088548364400416034343698204186575808495616"
+        );
+
+        // The chain witness of the satisfy tests, then with one wire broken.
+        let prime = R1csFile::<32>::read(r1cs.as_slice()).unwrap().header.prime;
+        let mut witness = chain_witness(&prime);
+        let mut data = Vec::new();
+        WtnsFile::from_vec(witness.clone(), prime)
+            .write(&mut data)
+            .unwrap();
+        assert_eq!(
+            check_value(&r1cs, &data).unwrap(),
+            json!({ "satisfied": true })
+        );
+
+        witness[500] = FieldElement::from_u64(7);
+        data.clear();
+        WtnsFile::from_vec(witness, prime).write(&mut data).unwrap();
+        let value = check_value(&r1cs, &data).unwrap();
+        assert_eq!(value["satisfied"], false);
+        assert_eq!(value["constraint"]["index"], 496);
+        assert!(check_value(&r1cs, &wtns).unwrap()["reason"]
+            .as_str()
+            .unwrap()
+            .contains("5 values"));
+    }
+}
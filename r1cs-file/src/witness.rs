@@ -0,0 +1,231 @@
+//! Witnesses laid out after the signals of a circuit header: named slices and templates.
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use wtns_file::{WtnsError, WtnsFile};
+
+use crate::{FieldElement, Header};
+
+/// A witness split by [`Header::split_witness`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WitnessView<'a, const FS: usize> {
+    witness: &'a [FieldElement<FS>],
+    /// See [`Header::region_starts`].
+    starts: [usize; 4],
+}
+
+/// A witness that doesn't fit the signal layout of a circuit, see [`Header::split_witness`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WitnessSplitError {
+    /// The witness and the circuit have different primes.
+    PrimeMismatch,
+    /// The witness has fewer values than the constant, outputs and inputs of the circuit.
+    WitnessTooShort { witness_len: usize, required: u64 },
+    /// The value of the constant wire 0 isn't 1.
+    FirstNotOne,
+}
+
+impl<const FS: usize> Header<FS> {
+    /// Splits `wtns` into the constant wire, the public outputs, the public inputs, the private
+    /// inputs and the internal wires of this circuit, in this order.
+    pub fn split_witness<'a>(
+        &self,
+        wtns: &'a WtnsFile<FS>,
+    ) -> Result<WitnessView<'a, FS>, WitnessSplitError> {
+        if wtns.header.prime != self.prime {
+            return Err(WitnessSplitError::PrimeMismatch);
+        }
+
+        let witness = wtns.witness.0.as_slice();
+        let starts = self.region_starts();
+        let required = starts[3];
+        if (witness.len() as u64) < required {
+            return Err(WitnessSplitError::WitnessTooShort {
+                witness_len: witness.len(),
+                required,
+            });
+        }
+        if witness[0] != FieldElement::from_u64(1) {
+            return Err(WitnessSplitError::FirstNotOne);
+        }
+
+        Ok(WitnessView {
+            witness,
+            // Below the witness length, so they fit.
+            starts: starts.map(|start| start as usize),
+        })
+    }
+
+    /// A witness for this circuit with the constant wire set to 1 and every other wire to 0.
+    pub fn witness_template(&self) -> WtnsFile<FS> {
+        let mut witness = vec![FieldElement::from_u64(0); self.n_wires as usize];
+        if let Some(one) = witness.first_mut() {
+            *one = FieldElement::from_u64(1);
+        }
+
+        WtnsFile::from_vec(witness, self.prime)
+    }
+
+    /// Sets the `i`-th public input of `wtns`, counted from 0.
+    pub fn set_public_input(
+        &self,
+        wtns: &mut WtnsFile<FS>,
+        i: u32,
+        value: FieldElement<FS>,
+    ) -> Result<(), WtnsError> {
+        set_signal(wtns, self.region_starts()[1], self.n_pub_in, i, value)
+    }
+
+    /// Sets the `i`-th private input of `wtns`, counted from 0.
+    pub fn set_private_input(
+        &self,
+        wtns: &mut WtnsFile<FS>,
+        i: u32,
+        value: FieldElement<FS>,
+    ) -> Result<(), WtnsError> {
+        set_signal(wtns, self.region_starts()[2], self.n_prvt_in, i, value)
+    }
+}
+
+fn set_signal<const FS: usize>(
+    wtns: &mut WtnsFile<FS>,
+    start: u64,
+    len: u32,
+    i: u32,
+    value: FieldElement<FS>,
+) -> Result<(), WtnsError> {
+    if i >= len {
+        return Err(WtnsError::IndexOutOfRange { index: i, len });
+    }
+
+    // Past the witness if it doesn't fit, as no witness is that long.
+    let index = u32::try_from(start + u64::from(i)).unwrap_or(u32::MAX);
+    wtns.set(index, value)
+}
+
+impl<'a, const FS: usize> WitnessView<'a, FS> {
+    /// The value of the constant wire 0, always 1.
+    pub fn one(&self) -> &'a FieldElement<FS> {
+        &self.witness[0]
+    }
+
+    pub fn public_outputs(&self) -> &'a [FieldElement<FS>] {
+        &self.witness[self.starts[0]..self.starts[1]]
+    }
+
+    pub fn public_inputs(&self) -> &'a [FieldElement<FS>] {
+        &self.witness[self.starts[1]..self.starts[2]]
+    }
+
+    pub fn private_inputs(&self) -> &'a [FieldElement<FS>] {
+        &self.witness[self.starts[2]..self.starts[3]]
+    }
+
+    /// The wires after the inputs.
+    pub fn internal(&self) -> &'a [FieldElement<FS>] {
+        &self.witness[self.starts[3]..]
+    }
+}
+
+impl fmt::Display for WitnessSplitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WitnessSplitError::PrimeMismatch => {
+                f.write_str("The witness prime differs from the circuit prime")
+            }
+            WitnessSplitError::WitnessTooShort {
+                witness_len,
+                required,
+            } => write!(
+                f,
+                "The witness has {} values but the circuit signals require {}",
+                witness_len, required
+            ),
+            WitnessSplitError::FirstNotOne => f.write_str("The first witness value isn't 1"),
+        }
+    }
+}
+
+impl std::error::Error for WitnessSplitError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::R1csFile;
+
+    #[test]
+    fn test_split_witness() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let prime = file.header.prime;
+
+        let witness: Vec<_> = (1..=9).map(FieldElement::from_u64).collect();
+        let wtns = WtnsFile::from_vec(witness.clone(), prime);
+        let view = file.header.split_witness(&wtns).unwrap();
+        assert_eq!(view.one(), &witness[0]);
+        assert_eq!(view.public_outputs(), &witness[1..2]);
+        assert_eq!(view.public_inputs(), &witness[2..4]);
+        assert_eq!(view.private_inputs(), &witness[4..7]);
+        assert_eq!(view.internal(), &witness[7..9]);
+
+        let wtns = WtnsFile::from_vec(witness[..7].to_vec(), prime);
+        let view = file.header.split_witness(&wtns).unwrap();
+        assert_eq!(view.private_inputs(), &witness[4..7]);
+        assert!(view.internal().is_empty());
+
+        let wtns = WtnsFile::from_vec(witness[..6].to_vec(), prime);
+        assert_eq!(
+            file.header.split_witness(&wtns),
+            Err(WitnessSplitError::WitnessTooShort {
+                witness_len: 6,
+                required: 7
+            })
+        );
+        let wtns = WtnsFile::from_vec(witness[1..].to_vec(), prime);
+        assert_eq!(
+            file.header.split_witness(&wtns),
+            Err(WitnessSplitError::FirstNotOne)
+        );
+        let wtns = WtnsFile::from_vec(witness, FieldElement::from_u64(7));
+        assert_eq!(
+            file.header.split_witness(&wtns),
+            Err(WitnessSplitError::PrimeMismatch)
+        );
+    }
+
+    #[test]
+    fn test_witness_template() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let header = &file.header;
+
+        let mut wtns = header.witness_template();
+        assert_eq!(wtns.header.witness_len, 7);
+        header
+            .set_public_input(&mut wtns, 1, FieldElement::from_u64(3))
+            .unwrap();
+        header
+            .set_private_input(&mut wtns, 0, FieldElement::from_u64(4))
+            .unwrap();
+        assert!(matches!(
+            header.set_private_input(&mut wtns, 3, FieldElement::from_u64(5)),
+            Err(WtnsError::IndexOutOfRange { index: 3, len: 3 })
+        ));
+        assert!(matches!(
+            wtns.set(7, FieldElement::from_u64(5)),
+            Err(WtnsError::IndexOutOfRange { index: 7, len: 7 })
+        ));
+
+        let view = header.split_witness(&wtns).unwrap();
+        let values = |values: &[FieldElement<32>]| {
+            values.iter().map(|v| v.as_bytes()[0]).collect::<Vec<_>>()
+        };
+        assert_eq!(values(view.public_outputs()), [0]);
+        assert_eq!(values(view.public_inputs()), [0, 3]);
+        assert_eq!(values(view.private_inputs()), [4, 0, 0]);
+        assert_eq!(wtns.header.witness_len as usize, wtns.witness.0.len());
+        let mut data = Vec::new();
+        wtns.write(&mut data).unwrap();
+    }
+}
@@ -0,0 +1,372 @@
+//! The header of snarkjs `.zkey` proving keys, to check them against a circuit.
+
+use std::fmt;
+use std::io::{self, Read, Seek};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+use iden3_binfile::{BinFileReader, Format, SectionEntry};
+
+use crate::{
+    CountingReader, Header, R1csError, Result, ValidatedR1cs, ValidationError,
+    MAX_SUPPORTED_FIELD_SIZE,
+};
+
+const ZKEY_FORMAT: Format = Format {
+    magic: *b"zkey",
+    versions: 1..=1,
+    max_sections: None,
+};
+/// Section holding the proving system.
+const SECTION_PROTOCOL: u32 = 1;
+/// Section holding the fields and sizes, followed by protocol-specific values.
+const SECTION_PROTOCOL_HEADER: u32 = 2;
+
+/// The values of a `.zkey` file describing the circuit it was generated for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZkeyHeader {
+    /// The proving system: 1 for Groth16, 2 for PLONK and 10 for FFLONK.
+    pub protocol: u32,
+    /// The prime of the scalar field, in little-endian order.
+    pub prime: Vec<u8>,
+    /// Number of wires of the circuit.
+    pub n_vars: u32,
+    /// Number of public outputs and inputs of the circuit.
+    pub n_public: u32,
+    pub domain_size: u32,
+}
+
+/// The ways a `.zkey` file doesn't match a circuit, see [`ZkeyHeader::matches`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MismatchReport {
+    pub mismatches: Vec<ZkeyMismatch>,
+}
+
+/// A value of a `.zkey` file disagreeing with the circuit header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ZkeyMismatch {
+    Prime,
+    Wires {
+        zkey: u32,
+        r1cs: u32,
+    },
+    PublicSignals {
+        zkey: u32,
+        r1cs: u32,
+    },
+    /// The evaluation domain is too small for the constraints and public signals.
+    DomainTooSmall {
+        domain_size: u32,
+        required: u64,
+    },
+    /// The circuit header declares more signals than wires, so no key can match it.
+    InvalidLayout(ValidationError),
+}
+
+impl ZkeyHeader {
+    /// Reads the protocol and protocol header sections, seeking past every other section body.
+    pub fn read<R: Read + Seek>(r: R) -> Result<Self> {
+        let mut file = BinFileReader::new(r, &ZKEY_FORMAT)?;
+        let sections = file.section_table()?;
+        let section = |id: u32| -> Result<SectionEntry> {
+            sections
+                .iter()
+                .find(|entry| entry.id == id)
+                .copied()
+                .ok_or_else(|| invalid_data(format!("Missing zkey section {}", id)))
+        };
+        let (protocol, protocol_header) = (
+            section(SECTION_PROTOCOL)?,
+            section(SECTION_PROTOCOL_HEADER)?,
+        );
+
+        let mut r = CountingReader::at(file.section_at(&protocol)?, protocol.offset);
+        let protocol = r
+            .read_u32::<LittleEndian>()
+            .map_err(|e| R1csError::from(e).eof_in(None, r.position()))?;
+
+        let mut r = CountingReader::at(file.section_at(&protocol_header)?, protocol_header.offset);
+        let read = |r: &mut CountingReader<_>| -> Result<Self> {
+            let _base_field = read_prime(r)?;
+            let prime = read_prime(r)?;
+
+            Ok(ZkeyHeader {
+                protocol,
+                prime,
+                n_vars: r.read_u32::<LittleEndian>()?,
+                n_public: r.read_u32::<LittleEndian>()?,
+                domain_size: r.read_u32::<LittleEndian>()?,
+            })
+        };
+
+        read(&mut r).map_err(|e| e.eof_in(None, r.position()))
+    }
+
+    /// Checks that the key was generated for a circuit with this header: the same prime, number
+    /// of wires and number of public signals, and a domain large enough for its constraints.
+    pub fn matches<const FS: usize>(
+        &self,
+        r1cs: &Header<FS>,
+    ) -> std::result::Result<(), MismatchReport> {
+        self.matches_layout(r1cs, r1cs.check_layout())
+    }
+
+    /// [`ZkeyHeader::matches`] without checking the signal layout of the circuit again.
+    pub fn matches_validated<const FS: usize>(
+        &self,
+        r1cs: &ValidatedR1cs<FS>,
+    ) -> std::result::Result<(), MismatchReport> {
+        self.matches_layout(&r1cs.header, Ok(r1cs.layout()))
+    }
+
+    /// [`ZkeyHeader::matches`] with the result of [`Header::check_layout`].
+    fn matches_layout<const FS: usize>(
+        &self,
+        r1cs: &Header<FS>,
+        layout: std::result::Result<[u32; 4], ValidationError>,
+    ) -> std::result::Result<(), MismatchReport> {
+        let mut mismatches = Vec::new();
+
+        let len = self.prime.len().max(FS);
+        let byte = |bytes: &[u8], i: usize| bytes.get(i).copied().unwrap_or(0);
+        if (0..len).any(|i| byte(&self.prime, i) != byte(r1cs.prime.as_bytes(), i)) {
+            mismatches.push(ZkeyMismatch::Prime);
+        }
+        if self.n_vars != r1cs.n_wires {
+            mismatches.push(ZkeyMismatch::Wires {
+                zkey: self.n_vars,
+                r1cs: r1cs.n_wires,
+            });
+        }
+        match layout {
+            Ok([outputs, _, private_inputs, _]) => {
+                let n_public = private_inputs - outputs;
+                if self.n_public != n_public {
+                    mismatches.push(ZkeyMismatch::PublicSignals {
+                        zkey: self.n_public,
+                        r1cs: n_public,
+                    });
+                }
+                // snarkjs adds a constraint per public signal and one for the constant wire.
+                let required = u64::from(r1cs.n_constraints) + u64::from(n_public) + 1;
+                if u64::from(self.domain_size) < required {
+                    mismatches.push(ZkeyMismatch::DomainTooSmall {
+                        domain_size: self.domain_size,
+                        required,
+                    });
+                }
+            }
+            Err(e) => mismatches.push(ZkeyMismatch::InvalidLayout(e)),
+        }
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(MismatchReport { mismatches })
+        }
+    }
+}
+
+impl fmt::Display for ZkeyMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZkeyMismatch::Prime => f.write_str("The zkey prime differs from the circuit prime"),
+            ZkeyMismatch::Wires { zkey, r1cs } => write!(
+                f,
+                "The zkey has {} wires but the circuit has {}",
+                zkey, r1cs
+            ),
+            ZkeyMismatch::PublicSignals { zkey, r1cs } => write!(
+                f,
+                "The zkey has {} public signals but the circuit has {}",
+                zkey, r1cs
+            ),
+            ZkeyMismatch::DomainTooSmall {
+                domain_size,
+                required,
+            } => write!(
+                f,
+                "The zkey domain of size {} is smaller than the {} required",
+                domain_size, required
+            ),
+            ZkeyMismatch::InvalidLayout(e) => write!(f, "Invalid circuit header: {}", e),
+        }
+    }
+}
+
+impl fmt::Display for MismatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, mismatch) in self.mismatches.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}", mismatch)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for MismatchReport {}
+
+/// Reads a field as its size in bytes followed by its prime.
+fn read_prime<R: Read>(r: &mut R) -> Result<Vec<u8>> {
+    let size = r.read_u32::<LittleEndian>()?;
+    if size as usize > MAX_SUPPORTED_FIELD_SIZE {
+        return Err(invalid_data(format!(
+            "Unsupported zkey field size {}",
+            size
+        )));
+    }
+
+    let mut prime = vec![0u8; size as usize];
+    r.read_exact(&mut prime)?;
+    Ok(prime)
+}
+
+fn invalid_data(msg: impl Into<String>) -> R1csError {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{primes, R1csFile, ValidateOptions};
+    use byteorder::WriteBytesExt;
+    use std::io::{Cursor, Write};
+
+    fn section(out: &mut Vec<u8>, id: u32, body: &[u8]) {
+        out.write_u32::<LittleEndian>(id).unwrap();
+        out.write_u64::<LittleEndian>(body.len() as u64).unwrap();
+        out.write_all(body).unwrap();
+    }
+
+    /// A Groth16 key laid out as snarkjs writes it, with the curve points zeroed.
+    fn zkey(prime: &[u8], n_vars: u32, n_public: u32, domain_size: u32) -> Vec<u8> {
+        let mut header = Vec::new();
+        // The base field isn't checked, any value will do.
+        for field in [&[0xff; 32][..], prime] {
+            header
+                .write_u32::<LittleEndian>(field.len() as u32)
+                .unwrap();
+            header.write_all(field).unwrap();
+        }
+        for value in [n_vars, n_public, domain_size] {
+            header.write_u32::<LittleEndian>(value).unwrap();
+        }
+        header.extend_from_slice(&[0; 640]);
+
+        let mut out = b"zkey".to_vec();
+        out.write_u32::<LittleEndian>(1).unwrap();
+        out.write_u32::<LittleEndian>(4).unwrap();
+        section(&mut out, 2, &header);
+        section(&mut out, 3, &[0; 100]);
+        section(&mut out, 1, &1u32.to_le_bytes());
+        section(&mut out, 4, &[0; 12]);
+        out
+    }
+
+    #[test]
+    fn test_read() {
+        let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+        let file = R1csFile::<32>::read(data.as_slice()).unwrap();
+        let prime = file.header.prime.as_bytes();
+
+        let header = ZkeyHeader::read(Cursor::new(zkey(prime, 7, 3, 8))).unwrap();
+        assert_eq!(
+            header,
+            ZkeyHeader {
+                protocol: 1,
+                prime: prime.to_vec(),
+                n_vars: 7,
+                n_public: 3,
+                domain_size: 8,
+            }
+        );
+        assert_eq!(header.matches(&file.header), Ok(()));
+
+        let stale = ZkeyHeader::read(Cursor::new(zkey(prime, 6, 2, 4))).unwrap();
+        assert_eq!(
+            stale.matches(&file.header).unwrap_err().mismatches,
+            [
+                ZkeyMismatch::Wires { zkey: 6, r1cs: 7 },
+                ZkeyMismatch::PublicSignals { zkey: 2, r1cs: 3 },
+                ZkeyMismatch::DomainTooSmall {
+                    domain_size: 4,
+                    required: 7
+                },
+            ]
+        );
+        let bls = ZkeyHeader::read(Cursor::new(zkey(
+            primes::BLS12_381_SCALAR.as_bytes(),
+            7,
+            3,
+            8,
+        )))
+        .unwrap();
+        assert_eq!(
+            bls.matches(&file.header).unwrap_err().mismatches,
+            [ZkeyMismatch::Prime]
+        );
+
+        // Signals past `n_wires` used to overflow the count of public signals.
+        let mut crafted = file.header.clone();
+        crafted.n_pub_out = u32::MAX;
+        crafted.n_pub_in = 2;
+        assert!(matches!(
+            header.matches(&crafted).unwrap_err().mismatches[..],
+            [ZkeyMismatch::InvalidLayout(
+                ValidationError::TooManySignals { .. }
+            )]
+        ));
+
+        assert!(matches!(
+            ZkeyHeader::read(Cursor::new(&data)),
+            Err(R1csError::InvalidMagic { found }) if found == *b"r1cs"
+        ));
+        let mut truncated = zkey(prime, 7, 3, 8);
+        truncated.truncate(100);
+        assert!(ZkeyHeader::read(Cursor::new(truncated)).is_err());
+    }
+
+    #[test]
+    fn test_read_snarkjs() {
+        // `multiplier.r1cs` compiled by circom, and a Groth16 key set up for it by snarkjs, from
+        // the test vectors of ark-circom.
+        let file = R1csFile::<32>::read_file("tests/multiplier.r1cs").unwrap();
+        let data = std::fs::read("tests/multiplier.zkey").unwrap();
+
+        let header = ZkeyHeader::read(Cursor::new(&data)).unwrap();
+        assert_eq!(
+            header,
+            ZkeyHeader {
+                protocol: 1,
+                prime: file.header.prime.as_bytes().to_vec(),
+                n_vars: 4,
+                n_public: 1,
+                domain_size: 4,
+            }
+        );
+        assert_eq!(header.matches(&file.header), Ok(()));
+        let validated = file.into_validated(&ValidateOptions::default()).unwrap();
+        assert_eq!(header.matches_validated(&validated), Ok(()));
+
+        let simple = R1csFile::<32>::read_file("tests/simple_circuit.r1cs").unwrap();
+        let validated = simple.clone().into_validated(&ValidateOptions::default());
+        assert_eq!(
+            header.matches_validated(&validated.unwrap()),
+            header.matches(&simple.header)
+        );
+        assert_eq!(
+            header.matches(&simple.header).unwrap_err().mismatches,
+            [
+                ZkeyMismatch::Wires { zkey: 4, r1cs: 7 },
+                ZkeyMismatch::PublicSignals { zkey: 1, r1cs: 3 },
+                ZkeyMismatch::DomainTooSmall {
+                    domain_size: 4,
+                    required: 7
+                },
+            ]
+        );
+    }
+}
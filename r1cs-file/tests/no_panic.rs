@@ -0,0 +1,139 @@
+//! Corrupted files either fail to parse or go through the analysis and export entry points
+//! without panicking.
+
+use std::io::{self, Cursor};
+
+use proptest::prelude::*;
+use r1cs_file::{
+    AnnotatedR1cs, DotOptions, DuplicateEntries, Modulus, ParseMode, R1csFile, R1csReader,
+    ReadOptions, SparseOptions, SymFile, ValidateOptions, WireUsageIndex,
+};
+
+/// The analyses and exports of `file` sized by its content, discarding the results.
+fn analyze<const FS: usize>(file: &R1csFile<FS>, sym: &SymFile) {
+    let _ = file.dump(io::sink(), None);
+    let _ = file.write_text(io::sink());
+    for c in file.constraints.iter() {
+        let _ = c.to_string();
+        let _ = c.display_with_prime(&file.header).to_string();
+    }
+    let merge = SparseOptions {
+        duplicates: DuplicateEntries::Merge,
+    };
+    let _ = file.to_sparse_matrices_with(&merge);
+    let _ = file.validate_with(&ValidateOptions {
+        check_primality: true,
+    });
+    let _ = file.check_canonical_coefficients();
+    let _ = file.header.public_wires();
+    let _ = file.content_id_index();
+    if let Ok(modulus) = Modulus::new(file.header.prime) {
+        let mut normalized = file.clone();
+        normalized.constraints.normalize_all(&modulus);
+    }
+    #[cfg(feature = "json")]
+    let _ = file.to_json_value();
+    #[cfg(feature = "matrix-market")]
+    for matrix in r1cs_file::Matrix::ALL {
+        let _ = file.write_matrix_market(io::sink(), matrix, r1cs_file::CoeffMode::Integer);
+    }
+
+    // Per-wire tables are sized by the header, like the witness of a circuit that large.
+    if file.validate().is_ok() && file.header.n_wires <= 1 << 16 {
+        analyze_wires(file, sym);
+    }
+}
+
+/// The analyses of a valid `file` building per-wire tables.
+fn analyze_wires<const FS: usize>(file: &R1csFile<FS>, sym: &SymFile) {
+    let _ = file.stats().to_string();
+    for neighborhood in [None, Some(0), Some(file.header.n_wires)] {
+        let opts = DotOptions {
+            neighborhood,
+            symbols: Some(sym),
+            ..DotOptions::default()
+        };
+        let _ = file.to_dot(io::sink(), opts);
+    }
+    let annotated = AnnotatedR1cs::new(file, sym);
+    for i in 0..file.constraints.len() {
+        let _ = annotated.annotate_constraint(i);
+    }
+    let usage = WireUsageIndex::build(file);
+    let _ = usage.constraints_involving(0..file.header.n_wires.saturating_add(1));
+
+    #[cfg(feature = "wtns")]
+    {
+        let wtns = file.header.witness_template();
+        let _ = r1cs_file::check_witness_all(file, &wtns);
+        let _ = r1cs_file::check_witness_sampled(file, &wtns, 2, 0);
+    }
+}
+
+/// Reads `data` every way there is, analyzing whatever parses.
+fn read_all(data: &[u8], sym: &SymFile) {
+    for mode in [ParseMode::Unchecked, ParseMode::Lenient] {
+        let opts = ReadOptions {
+            mode,
+            ..ReadOptions::default()
+        };
+        if let Ok((file, _)) = R1csFile::<32>::read_with(data, &opts) {
+            analyze(&file, sym);
+        }
+    }
+    if let Ok(file) = R1csFile::<32>::read_seek(Cursor::new(data)) {
+        analyze(&file, sym);
+    }
+    let _ = R1csFile::<32>::read_header(Cursor::new(data));
+    if let Ok(mut reader) = R1csReader::<_, 32>::new(data) {
+        for constraint in reader.constraints() {
+            let _ = constraint.map(|c| c.to_string());
+        }
+        let _ = reader.wire_map();
+    }
+}
+
+fn fixture() -> (Vec<u8>, SymFile) {
+    let data = std::fs::read("tests/simple_circuit.r1cs").unwrap();
+    let sym = std::fs::read("tests/simple_circuit.sym").unwrap();
+    (data, SymFile::read(sym.as_slice()).unwrap())
+}
+
+proptest! {
+    #[test]
+    fn test_corrupted_bytes(
+        edits in prop::collection::vec((any::<prop::sample::Index>(), any::<u8>()), 1..8),
+        len in any::<prop::sample::Index>(),
+    ) {
+        let (mut data, sym) = fixture();
+        for (index, byte) in edits {
+            let i = index.index(data.len());
+            data[i] = byte;
+        }
+        read_all(&data, &sym);
+        // And cut short.
+        read_all(&data[..len.index(data.len())], &sym);
+    }
+
+    #[test]
+    fn test_corrupted_words(
+        edits in prop::collection::vec((any::<prop::sample::Index>(), any::<u32>()), 1..4),
+    ) {
+        // Counts, sizes and wire indices are 32-bit words, small or huge values reach further.
+        let (mut data, sym) = fixture();
+        for (index, word) in edits {
+            let i = index.index(data.len() / 4) * 4;
+            data[i..i + 4].copy_from_slice(&word.to_le_bytes());
+        }
+        read_all(&data, &sym);
+    }
+
+    #[test]
+    fn test_arbitrary_bytes(data in prop::collection::vec(any::<u8>(), 0..512)) {
+        let (fixture, sym) = fixture();
+        // Past the preamble, or nothing gets parsed.
+        let mut bytes = fixture[..12].to_vec();
+        bytes.extend(data);
+        read_all(&bytes, &sym);
+    }
+}
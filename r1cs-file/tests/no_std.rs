@@ -0,0 +1,43 @@
+//! The `no_std` API, parsing and writing through byte slices and vectors. Run with
+//! `--no-default-features` to build the crate without `std`.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use r1cs_file::{ParseMode, R1csError, R1csFile, ReadOptions};
+
+const FIXTURE: &[u8] = include_bytes!("simple_circuit.r1cs");
+
+#[test]
+fn test_read_slice() {
+    let file = R1csFile::<32>::read(FIXTURE).unwrap();
+    assert_eq!(file.header.n_constraints as usize, file.constraints.len());
+    assert!(file.validate().is_ok());
+
+    let mut written = Vec::new();
+    file.write(&mut written).unwrap();
+    assert_eq!(R1csFile::<32>::read(written.as_slice()).unwrap(), file);
+
+    let opts = ReadOptions {
+        mode: ParseMode::Strict,
+        ..ReadOptions::default()
+    };
+    let (strict, warnings) = R1csFile::<32>::read_with(FIXTURE, &opts).unwrap();
+    assert_eq!(strict, file);
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_read_slice_errors() {
+    assert!(matches!(
+        R1csFile::<32>::read(&FIXTURE[..FIXTURE.len() - 1]),
+        Err(R1csError::UnexpectedEof { .. })
+    ));
+    assert!(matches!(
+        R1csFile::<32>::read(&b"wtns"[..]),
+        Err(R1csError::InvalidMagic { .. })
+    ));
+}
@@ -0,0 +1,92 @@
+//! Preparing rapidsnark inputs from the bundled fixtures.
+
+#![cfg(all(feature = "wtns", feature = "json"))]
+
+use std::fs;
+use std::path::PathBuf;
+
+use r1cs_file::{prepare_rapidsnark_inputs, FieldElement, RapidsnarkError, SatisfactionError};
+use wtns_file::WtnsFile;
+
+fn out_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("r1cs-file-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn test_prepare_rapidsnark_inputs() {
+    let out = out_dir("rapidsnark");
+    let inputs = prepare_rapidsnark_inputs::<32>(
+        "tests/simple_circuit.r1cs",
+        "tests/simple_circuit.wtns",
+        out.join("proof"),
+    )
+    .unwrap();
+
+    // Byte for byte what snarkjs writes for this witness.
+    let golden = fs::read("tests/simple_circuit.public.json").unwrap();
+    assert_eq!(fs::read(&inputs.public_json).unwrap(), golden);
+    assert!(inputs.public_json.ends_with("proof/public.json"));
+    assert_eq!(
+        inputs.proof_json,
+        inputs.public_json.with_file_name("proof.json")
+    );
+    assert!(!inputs.proof_json.exists());
+    assert!(inputs.r1cs.is_absolute());
+    assert!(inputs.wtns.ends_with("tests/simple_circuit.wtns"));
+
+    assert_eq!(inputs.header.n_wires, 7);
+    assert_eq!(inputs.public.len(), 3);
+    assert_eq!(inputs.public[0], FieldElement::from_u64(2));
+    assert_eq!(inputs.public[2], FieldElement::from_u64(4));
+
+    fs::remove_dir_all(out).unwrap();
+}
+
+#[test]
+fn test_prepare_rapidsnark_inputs_rejected() {
+    let out = out_dir("rapidsnark-rejected");
+    fs::create_dir_all(&out).unwrap();
+    let mut wtns = WtnsFile::<32>::read_file("tests/simple_circuit.wtns").unwrap();
+
+    // A witness value changed breaks a constraint, nothing is written.
+    let bad = out.join("bad.wtns");
+    wtns.witness.0[1] = FieldElement::from_u64(3);
+    wtns.write_file(&bad).unwrap();
+    let result = prepare_rapidsnark_inputs::<32>("tests/simple_circuit.r1cs", &bad, &out);
+    assert!(matches!(
+        result,
+        Err(RapidsnarkError::Witness(SatisfactionError::Unsatisfied(_)))
+    ));
+    assert!(!out.join("public.json").exists());
+
+    // rapidsnark wants one value per wire, no more.
+    let long = out.join("long.wtns");
+    wtns.witness.0.push(FieldElement::from_u64(0));
+    wtns.header.witness_len += 1;
+    wtns.write_file(&long).unwrap();
+    let result = prepare_rapidsnark_inputs::<32>("tests/simple_circuit.r1cs", &long, &out);
+    assert!(matches!(
+        result,
+        Err(RapidsnarkError::WitnessLength {
+            witness_len: 8,
+            n_wires: 7
+        })
+    ));
+
+    // A witness of another circuit.
+    let result = prepare_rapidsnark_inputs::<32>(
+        "tests/simple_circuit.r1cs",
+        "../wtns-file/tests/witness.wtns",
+        &out,
+    );
+    assert!(result.is_err());
+
+    let result = prepare_rapidsnark_inputs::<32>("tests/missing.r1cs", &bad, &out);
+    let e = result.unwrap_err();
+    assert!(matches!(e, RapidsnarkError::R1cs(_)));
+    assert!(e.to_string().contains("missing.r1cs"));
+
+    fs::remove_dir_all(out).unwrap();
+}
@@ -0,0 +1,30 @@
+//! Smoke tests of the WebAssembly bindings, run with
+//! `wasm-pack test --node r1cs-file -- --features wasm`.
+
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use r1cs_file::wasm::{check_witness, parse_r1cs, parse_wtns};
+use serde_json::Value;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn json(value: wasm_bindgen::JsValue) -> Value {
+    serde_wasm_bindgen::from_value(value).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn test_parse_fixtures() {
+    let r1cs = include_bytes!("simple_circuit.r1cs");
+    let value = json(parse_r1cs(r1cs, true).unwrap());
+    assert_eq!(value["nWires"], 7);
+    assert_eq!(value["constraints"].as_array().unwrap().len(), 3);
+
+    let wtns = include_bytes!("../../wtns-file/tests/witness.wtns");
+    let value = json(parse_wtns(wtns).unwrap());
+    assert_eq!(value["witness"][1], "33");
+
+    let value = json(check_witness(r1cs, wtns).unwrap());
+    assert_eq!(value["satisfied"], false);
+
+    assert!(parse_r1cs(&r1cs[..100], false).is_err());
+    assert!(parse_wtns(r1cs).is_err());
+}
@@ -0,0 +1,242 @@
+//! `r1cs-tool`, quick commands against r1cs and wtns files.
+//!
+//! The exit code is 0 on success, 1 when a file is read but fails the requested check, 2 on usage
+//! errors and 3 when a file can't be read. Errors are printed on stderr, one per line, as
+//! `error: <kind>: <message>` with kind `usage`, `read`, `invalid` or `unsatisfied`.
+
+use std::fs::File;
+use std::io::{self, BufReader, Write};
+use std::process::ExitCode;
+
+use r1cs_file::{check_witness_all, R1csFile, R1csFileAny, SatisfactionError};
+use wtns_file::{WtnsError, WtnsFile};
+
+const USAGE: &str = "Usage: r1cs-tool <command> <file>...
+
+Commands:
+  info <circuit.r1cs>                  Print the header and statistics of a circuit
+  validate <circuit.r1cs>              Check the sections against the header and the prime
+  export-json <circuit.r1cs>           Print the circuit in the JSON format of snarkjs
+  wtns-info <witness.wtns>             Print the header of a witness
+  check <circuit.r1cs> <witness.wtns>  Check that a witness satisfies a circuit";
+
+const COMMANDS: [&str; 5] = ["info", "validate", "export-json", "wtns-info", "check"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Usage,
+    Read,
+    Invalid,
+    Unsatisfied,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Usage => "usage",
+            Kind::Read => "read",
+            Kind::Invalid => "invalid",
+            Kind::Unsatisfied => "unsatisfied",
+        }
+    }
+
+    fn exit_code(self) -> u8 {
+        match self {
+            Kind::Invalid | Kind::Unsatisfied => 1,
+            Kind::Usage => 2,
+            Kind::Read => 3,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Failure {
+    kind: Kind,
+    messages: Vec<String>,
+}
+
+impl Failure {
+    fn new(kind: Kind, message: impl Into<String>) -> Self {
+        Failure {
+            kind,
+            messages: vec![message.into()],
+        }
+    }
+}
+
+impl From<io::Error> for Failure {
+    fn from(e: io::Error) -> Self {
+        Failure::new(Kind::Read, format!("writing the output: {}", e))
+    }
+}
+
+type Result<T = ()> = std::result::Result<T, Failure>;
+
+/// Runs `body` with the circuit of whatever field size.
+macro_rules! with_circuit {
+    ($any:expr, $file:ident => $body:expr) => {
+        match $any {
+            R1csFileAny::Fs8($file) => $body,
+            R1csFileAny::Fs32($file) => $body,
+            R1csFileAny::Fs48($file) => $body,
+            R1csFileAny::Fs64($file) => $body,
+            any => Err(Failure::new(
+                Kind::Read,
+                format!("unsupported field size {}", any.field_size()),
+            )),
+        }
+    };
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let stdout = io::stdout();
+    match run(&args, &mut stdout.lock()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => {
+            for message in &failure.messages {
+                eprintln!("error: {}: {}", failure.kind.name(), message);
+            }
+            if failure.kind == Kind::Usage {
+                eprintln!("\n{}", USAGE);
+            }
+            ExitCode::from(failure.kind.exit_code())
+        }
+    }
+}
+
+fn run<W: Write>(args: &[&str], out: &mut W) -> Result {
+    match args {
+        ["info", path] => with_circuit!(read_circuit(path)?, file => info(&file, out)),
+        ["validate", path] => with_circuit!(read_circuit(path)?, file => validate(&file, out)),
+        ["export-json", path] => {
+            with_circuit!(read_circuit(path)?, file => export_json(&file, path, out))
+        }
+        ["wtns-info", path] => wtns_info(path, out),
+        ["check", r1cs, wtns] => {
+            with_circuit!(read_circuit(r1cs)?, file => check(&file, wtns, out))
+        }
+        ["help"] | ["--help"] | ["-h"] => {
+            writeln!(out, "{}", USAGE)?;
+            Ok(())
+        }
+        [] => Err(Failure::new(Kind::Usage, "missing command")),
+        [command, ..] if COMMANDS.contains(command) => Err(Failure::new(
+            Kind::Usage,
+            format!("wrong number of arguments for {}", command),
+        )),
+        [command, ..] => Err(Failure::new(
+            Kind::Usage,
+            format!("unknown command {}", command),
+        )),
+    }
+}
+
+fn read_circuit(path: &str) -> Result<R1csFileAny> {
+    let read = || -> r1cs_file::Result<R1csFileAny> {
+        R1csFileAny::read(BufReader::new(File::open(path)?))
+    };
+
+    read().map_err(|e| Failure::new(Kind::Read, format!("{}: {}", path, e)))
+}
+
+fn read_witness<const FS: usize>(path: &str) -> Result<WtnsFile<FS>> {
+    WtnsFile::read_file(path).map_err(|e| Failure::new(Kind::Read, e.to_string()))
+}
+
+fn info<W: Write, const FS: usize>(file: &R1csFile<FS>, out: &mut W) -> Result {
+    let header = &file.header;
+    writeln!(out, "Field size: {}", FS)?;
+    writeln!(out, "Prime: {:#x}", header.prime)?;
+    match header.curve() {
+        Some(curve) => writeln!(out, "Curve: {:?}", curve)?,
+        None => writeln!(out, "Curve: unknown")?,
+    }
+    writeln!(out, "{}", file.stats())?;
+
+    Ok(())
+}
+
+fn validate<W: Write, const FS: usize>(file: &R1csFile<FS>, out: &mut W) -> Result {
+    let mut errors = Vec::new();
+    errors.extend(file.validate().err().into_iter().flatten());
+    errors.extend(
+        file.check_canonical_coefficients()
+            .err()
+            .into_iter()
+            .flatten(),
+    );
+
+    if !errors.is_empty() {
+        return Err(Failure {
+            kind: Kind::Invalid,
+            messages: errors.iter().map(|e| e.to_string()).collect(),
+        });
+    }
+
+    writeln!(out, "ok")?;
+    Ok(())
+}
+
+fn export_json<W: Write, const FS: usize>(file: &R1csFile<FS>, path: &str, out: &mut W) -> Result {
+    file.to_json_writer(&mut *out)
+        .map_err(|e| Failure::new(Kind::Read, format!("{}: {}", path, e)))?;
+    writeln!(out)?;
+
+    Ok(())
+}
+
+fn wtns_info<W: Write>(path: &str, out: &mut W) -> Result {
+    // The field size is only known from the header, try the most common one first.
+    match WtnsFile::<32>::read_file(path) {
+        Ok(wtns) => print_witness(&wtns, out),
+        Err(WtnsError::File { error, .. }) => match *error {
+            WtnsError::FieldSizeMismatch { found: 8, .. } => {
+                print_witness(&read_witness::<8>(path)?, out)
+            }
+            WtnsError::FieldSizeMismatch { found: 48, .. } => {
+                print_witness(&read_witness::<48>(path)?, out)
+            }
+            WtnsError::FieldSizeMismatch { found: 64, .. } => {
+                print_witness(&read_witness::<64>(path)?, out)
+            }
+            error => Err(Failure::new(Kind::Read, format!("{}: {}", path, error))),
+        },
+        Err(error) => Err(Failure::new(Kind::Read, error.to_string())),
+    }
+}
+
+fn print_witness<W: Write, const FS: usize>(wtns: &WtnsFile<FS>, out: &mut W) -> Result {
+    writeln!(out, "Version: {}", wtns.version)?;
+    writeln!(out, "Field size: {}", wtns.header.field_size)?;
+    writeln!(out, "Prime: {:#x}", wtns.header.prime)?;
+    writeln!(out, "Witness length: {}", wtns.header.witness_len)?;
+
+    Ok(())
+}
+
+fn check<W: Write, const FS: usize>(file: &R1csFile<FS>, path: &str, out: &mut W) -> Result {
+    let wtns = read_witness::<FS>(path)?;
+
+    match check_witness_all(file, &wtns) {
+        Ok(()) => {
+            writeln!(out, "ok")?;
+            Ok(())
+        }
+        Err(SatisfactionError::Unsatisfied(constraints)) => Err(Failure {
+            kind: Kind::Unsatisfied,
+            messages: constraints
+                .iter()
+                .map(|c| {
+                    format!(
+                        "constraint {}: a = {:#x}, b = {:#x}, c = {:#x}",
+                        c.index, c.a, c.b, c.c
+                    )
+                })
+                .collect(),
+        }),
+        Err(e) => Err(Failure::new(Kind::Invalid, e.to_string())),
+    }
+}
@@ -0,0 +1,127 @@
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use r1cs_file::{FieldElement, R1csFile};
+use wtns_file::WtnsFile;
+
+fn fixture(name: &str) -> String {
+    format!("{}/../r1cs-file/tests/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_r1cs-tool"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+/// Writes a witness for `tests/test_circuit.r1cs`, a chain of constraints
+/// `-w_k * w_k = w_3 - w_next`, with wire 500 set to `w500` if given.
+fn chain_witness(name: &str, w500: Option<u64>) -> PathBuf {
+    let file = R1csFile::<32>::read_file(fixture("test_circuit.r1cs")).unwrap();
+    let prime = file.header.prime;
+    let mut minus_one = *prime;
+    minus_one[0] -= 1;
+    let minus_one = FieldElement::from(minus_one);
+    let (zero, one) = (FieldElement::from_u64(0), FieldElement::from_u64(1));
+
+    let mut witness = vec![one, minus_one, one, minus_one];
+    witness.extend((4..1003).map(|wire| if wire % 2 == 0 { zero } else { minus_one }));
+    if let Some(value) = w500 {
+        witness[500] = FieldElement::from_u64(value);
+    }
+
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    WtnsFile::from_vec(witness, prime)
+        .write_file(&path)
+        .unwrap();
+    path
+}
+
+#[test]
+fn test_info() {
+    let output = run(&["info", &fixture("simple_circuit.r1cs")]);
+    assert!(output.status.success());
+    let out = stdout(&output);
+    assert!(out.starts_with("Field size: 32\nPrime: 0x30644e72"));
+    assert!(out.contains("Curve: Bn254\n"));
+    assert!(out.contains("# of Wires: 7\n"));
+    assert!(out.contains("# of Constraints: 3\n"));
+}
+
+#[test]
+fn test_validate() {
+    let output = run(&["validate", &fixture("simple_circuit.r1cs")]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "ok\n");
+}
+
+#[test]
+fn test_export_json() {
+    let output = run(&["export-json", &fixture("simple_circuit.r1cs")]);
+    assert!(output.status.success());
+    let expected = std::fs::read(fixture("simple_circuit.json")).unwrap();
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap(),
+        serde_json::from_slice::<serde_json::Value>(&expected).unwrap()
+    );
+}
+
+#[test]
+fn test_wtns_info_and_check() {
+    let valid = chain_witness("valid.wtns", None);
+    let valid = valid.to_str().unwrap();
+    let output = run(&["wtns-info", valid]);
+    assert!(output.status.success());
+    assert!(stdout(&output).ends_with("Witness length: 1003\n"));
+
+    let output = run(&["check", &fixture("test_circuit.r1cs"), valid]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "ok\n");
+
+    let broken = chain_witness("broken.wtns", Some(7));
+    let output = run(&[
+        "check",
+        &fixture("test_circuit.r1cs"),
+        broken.to_str().unwrap(),
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    let lines: Vec<_> = stderr(&output).lines().map(String::from).collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("error: unsatisfied: constraint 496: a = 0x"));
+    assert!(lines[1].starts_with("error: unsatisfied: constraint 497: a = 0x"));
+
+    // A witness of another circuit.
+    let output = run(&["check", &fixture("simple_circuit.r1cs"), valid]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_errors() {
+    let output = run(&[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("error: usage: missing command\n"));
+
+    let output = run(&["info"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("error: usage: wrong number of arguments for info\n"));
+
+    let output = run(&["frobnicate", "x"]);
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = run(&["info", &fixture("missing.r1cs")]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).starts_with("error: read: "));
+
+    let output = run(&["wtns-info", &fixture("simple_circuit.r1cs")]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("Invalid magic number"));
+}
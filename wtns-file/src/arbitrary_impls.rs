@@ -0,0 +1,69 @@
+//! arbitrary support, behind the `arbitrary` feature.
+//!
+//! A generated [`WtnsFile`] is consistent: the header matches the field size and the witness, and
+//! every value is below the prime, so it can be written with [`WtnsFile::write`] and read back
+//! unchanged. [`Header`] and [`Witness`] are generated on their own.
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::{FieldElement, Header, Witness, WtnsFile, FORMAT};
+
+impl<'a, const FS: usize> Arbitrary<'a> for Header<FS> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Header {
+            field_size: FS as u32,
+            prime: FieldElement::arbitrary_modulus(u)?,
+            witness_len: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a, const FS: usize> Arbitrary<'a> for Witness<FS> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.arbitrary().map(Witness)
+    }
+}
+
+impl<'a, const FS: usize> Arbitrary<'a> for WtnsFile<FS> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let version = u.int_in_range(FORMAT.versions.clone())?;
+        let prime = FieldElement::arbitrary_modulus(u)?;
+        let mut witness = Vec::new();
+        while !u.is_empty() && u.arbitrary()? {
+            witness.push(FieldElement::arbitrary_below(u, &prime)?);
+        }
+
+        Ok(WtnsFile {
+            version,
+            ..WtnsFile::from_vec(witness, prime)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Writes the file generated from `data` and reads it back.
+    fn round_trip<const FS: usize>(data: &[u8]) -> std::result::Result<(), TestCaseError> {
+        let file = WtnsFile::<FS>::arbitrary(&mut Unstructured::new(data)).unwrap();
+        let mut written = Vec::new();
+        file.write(&mut written).unwrap();
+        prop_assert_eq!(&WtnsFile::read(written.as_slice()).unwrap(), &file);
+        prop_assert_eq!(
+            &WtnsFile::read_seek(std::io::Cursor::new(&written)).unwrap(),
+            &file
+        );
+
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn test_write_read_identity(data in prop::collection::vec(any::<u8>(), 0..4096)) {
+            round_trip::<8>(&data)?;
+            round_trip::<32>(&data)?;
+        }
+    }
+}
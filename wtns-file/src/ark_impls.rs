@@ -0,0 +1,101 @@
+//! Conversions to and from arkworks prime fields, behind the `ark` feature.
+
+use ark_ff::PrimeField;
+
+use crate::{FieldElement, Header, Result, WtnsError, WtnsFile};
+
+impl<const FS: usize> Header<FS> {
+    /// Checks that the prime is the modulus of `F`.
+    pub fn check_modulus<F: PrimeField>(&self) -> Result<()> {
+        match FieldElement::<FS>::from_bigint(&F::MODULUS) {
+            Ok(modulus) if modulus == self.prime => Ok(()),
+            _ => {
+                let digits: String = F::MODULUS
+                    .as_ref()
+                    .iter()
+                    .rev()
+                    .map(|limb| format!("{:016x}", limb))
+                    .collect();
+                Err(WtnsError::ModulusMismatch {
+                    prime: format!("{:#x}", self.prime),
+                    modulus: format!("0x{}", digits.trim_start_matches('0')),
+                })
+            }
+        }
+    }
+}
+
+impl<const FS: usize> WtnsFile<FS> {
+    /// A witness of `values`, over the modulus of `F`.
+    ///
+    /// Fails with [`WtnsError::ModulusTooWide`] if the modulus doesn't fit in `FS` bytes.
+    pub fn from_field_elements<F: PrimeField>(values: &[F]) -> Result<Self> {
+        let too_wide = |_| WtnsError::ModulusTooWide {
+            field_size: FS as u32,
+        };
+        let prime = FieldElement::from_bigint(&F::MODULUS).map_err(too_wide)?;
+        // The values are below the modulus, so they fit whenever it does.
+        let witness = values
+            .iter()
+            .map(|v| FieldElement::from_field(*v).map_err(too_wide))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(WtnsFile::from_vec(witness, prime))
+    }
+
+    /// Converts the values into `F`, after checking the prime with [`Header::check_modulus`].
+    ///
+    /// Fails with [`WtnsError::NonCanonicalValue`] on the first value not below the prime.
+    pub fn to_field_vec<F: PrimeField>(&self) -> Result<Vec<F>> {
+        self.header.check_modulus::<F>()?;
+
+        self.witness
+            .0
+            .iter()
+            .enumerate()
+            .map(|(index, v)| {
+                v.to_field()
+                    .map_err(|_| WtnsError::NonCanonicalValue { index })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+
+    #[test]
+    fn test_field_vec() {
+        let data = std::fs::read("tests/witness.wtns").unwrap();
+        let file = WtnsFile::<32>::read(data.as_slice()).unwrap();
+        let values = [1u64, 33, 3, 11].map(Fr::from);
+
+        let parsed = file.to_field_vec::<Fr>().unwrap();
+        assert_eq!(parsed[..4], values);
+        assert_eq!(parsed[4], -Fr::from(1u64));
+
+        let built = WtnsFile::<32>::from_field_elements(&parsed).unwrap();
+        assert_eq!(built.header, file.header);
+        assert_eq!(built.witness, file.witness);
+        assert!(matches!(
+            WtnsFile::<8>::from_field_elements(&values),
+            Err(WtnsError::ModulusTooWide { field_size: 8 })
+        ));
+
+        let mut non_canonical = file.clone();
+        non_canonical.witness.0[3] = file.header.prime;
+        assert!(matches!(
+            non_canonical.to_field_vec::<Fr>(),
+            Err(WtnsError::NonCanonicalValue { index: 3 })
+        ));
+        let mut other_prime = file;
+        other_prime.header.prime = FieldElement::from_u64(97);
+        assert!(matches!(
+            other_prime.to_field_vec::<Fr>(),
+            Err(WtnsError::ModulusMismatch { modulus, .. })
+                if modulus == "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001"
+        ));
+    }
+}
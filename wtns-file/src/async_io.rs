@@ -0,0 +1,65 @@
+//! Reading and writing with tokio's async I/O traits.
+
+use std::io::Cursor;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::scrub::scrub;
+use crate::{Result, WtnsFile};
+
+impl<const FS: usize> WtnsFile<FS> {
+    /// Async version of [`WtnsFile::read`].
+    ///
+    /// The input is read into memory before being decoded, as the witness makes up nearly all of
+    /// the file anyway, so the sections may come in any order as with [`WtnsFile::read_seek`].
+    pub async fn read_async<R: AsyncRead + Unpin>(mut r: R) -> Result<Self> {
+        let mut data = Vec::new();
+        let read = r.read_to_end(&mut data).await;
+        let file = read
+            .map_err(Into::into)
+            .and_then(|_| WtnsFile::read_seek(Cursor::new(&data)));
+        scrub(&mut data);
+
+        file
+    }
+
+    /// Async version of [`WtnsFile::write`].
+    ///
+    /// The file is serialized in memory first.
+    pub async fn write_async<W: AsyncWrite + Unpin>(&self, mut w: W) -> Result<()> {
+        let mut data = Vec::new();
+        let written = match self.write(&mut data) {
+            Ok(()) => w.write_all(&data).await.map_err(Into::into),
+            Err(e) => Err(e),
+        };
+        scrub(&mut data);
+        written?;
+        w.flush().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FieldElement, WtnsError};
+
+    #[test]
+    fn test_async_round_trip() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let fe = || FieldElement::from([3u8; 32]);
+        let file = WtnsFile::<32>::from_vec(vec![fe(), fe()], fe());
+        let mut data = Vec::new();
+        runtime.block_on(file.write_async(&mut data)).unwrap();
+
+        let read = runtime.block_on(WtnsFile::<32>::read_async(data.as_slice()));
+        assert_eq!(read.unwrap(), file);
+
+        let truncated = runtime.block_on(WtnsFile::<32>::read_async(&data[..data.len() - 1]));
+        assert!(matches!(truncated, Err(WtnsError::UnexpectedEof { .. })));
+    }
+}
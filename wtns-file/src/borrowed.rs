@@ -0,0 +1,166 @@
+//! Zero-copy parsing of witness files already held in memory.
+
+use std::io::Cursor;
+
+use iden3_binfile::BinFileReader;
+
+use crate::{
+    locate_witness, FieldElement, Header, Result, SectionType, Witness, WtnsError, WtnsFile, FORMAT,
+};
+
+/// A witness file parsed from a byte buffer, with the values pointing into the buffer.
+///
+/// Only the witness borrows from the buffer; the header is small and decoded as in [`WtnsFile`].
+/// Sections are read in the same way as [`WtnsFile::read_seek`], in any order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WtnsFileRef<'a, const FS: usize> {
+    pub version: u32,
+    pub header: Header<FS>,
+    pub witness: WitnessRef<'a, FS>,
+}
+
+/// Witness values pointing into the parsed buffer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WitnessRef<'a, const FS: usize>(pub &'a [FieldElement<FS>]);
+
+impl<'a, const FS: usize> WtnsFileRef<'a, FS> {
+    /// Parses a whole file held in `data`.
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        let mut file = BinFileReader::new(Cursor::new(data), &FORMAT)?;
+        let version = file.version();
+        let (header, witness) = locate_witness::<_, FS>(&mut file)?;
+
+        let start = witness.offset as usize;
+        let body = data
+            .get(start..)
+            .and_then(|rest| rest.get(..witness.size as usize))
+            .ok_or(WtnsError::UnexpectedEof {
+                section: Some(SectionType::Witness),
+                offset: data.len() as u64,
+            })?;
+        // The size is a multiple of FS, checked by `locate_witness`.
+        let values = FieldElement::slice_from_bytes(body).unwrap();
+
+        Ok(WtnsFileRef {
+            version,
+            header,
+            witness: WitnessRef(values),
+        })
+    }
+
+    /// Copies the witness into an owned [`WtnsFile`].
+    pub fn to_owned(&self) -> WtnsFile<FS> {
+        WtnsFile {
+            version: self.version,
+            header: self.header.clone(),
+            witness: self.witness.to_owned(),
+        }
+    }
+}
+
+impl<'a, const FS: usize> WitnessRef<'a, FS> {
+    /// The values back to back, `FS` little-endian bytes each, as in the witness section.
+    pub fn as_flat_bytes(&self) -> &'a [u8] {
+        FieldElement::slice_as_bytes(self.0)
+    }
+
+    /// Copies the values into an owned [`Witness`].
+    pub fn to_owned(&self) -> Witness<FS> {
+        Witness(self.0.to_vec())
+    }
+}
+
+/// A read-only memory mapping of a witness file, to be parsed with [`WtnsFileRef::parse`].
+///
+/// [`WtnsFileRef`] borrows from the mapping, so the mapping has to outlive it.
+#[cfg(feature = "mmap")]
+pub struct MappedFile(memmap2::Mmap);
+
+#[cfg(feature = "mmap")]
+impl MappedFile {
+    /// Maps the file at `path`.
+    ///
+    /// The file must not be modified while mapped, otherwise parsed values may change under the
+    /// borrowed view.
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the mapping is read-only, and modifying the underlying file while it is mapped
+        // is documented as unsupported above.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+
+        Ok(MappedFile(mmap))
+    }
+
+    /// Parses the mapped file.
+    pub fn parse<const FS: usize>(&self) -> Result<WtnsFileRef<'_, FS>> {
+        WtnsFileRef::parse(&self.0)
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl std::ops::Deref for MappedFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<'a, const FS: usize> WtnsFileRef<'a, FS> {
+    /// Maps the file at `path`, see [`MappedFile::open`].
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> Result<MappedFile> {
+        MappedFile::open(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flat_bytes() {
+        let data = include_bytes!("../tests/witness.wtns");
+        let owned = WtnsFile::<32>::read(&data[..]).unwrap();
+        let section = &data[data.len() - 5 * 32..];
+        assert_eq!(owned.witness.as_flat_bytes(), section);
+        assert_eq!(
+            Witness::<32>::from_flat_bytes(section).unwrap(),
+            owned.witness
+        );
+        assert!(matches!(
+            Witness::<32>::from_flat_bytes(&section[1..]),
+            Err(WtnsError::UnalignedLength {
+                len: 159,
+                field_size: 32
+            })
+        ));
+
+        let borrowed = WtnsFileRef::<32>::parse(data).unwrap();
+        assert_eq!(borrowed.to_owned(), owned);
+        assert_eq!(borrowed.witness.as_flat_bytes().as_ptr(), section.as_ptr());
+
+        let swapped = include_bytes!("../tests/witness_swapped.wtns");
+        assert_eq!(WtnsFileRef::<32>::parse(swapped).unwrap().to_owned(), owned);
+        assert!(matches!(
+            WtnsFileRef::<32>::parse(&data[..data.len() - 1]),
+            Err(WtnsError::UnexpectedEof {
+                section: Some(SectionType::Witness),
+                ..
+            })
+        ));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mapped_file() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/witness.wtns");
+        let mapped = WtnsFileRef::<32>::open(path).unwrap();
+        let borrowed = mapped.parse::<32>().unwrap();
+
+        assert_eq!(
+            borrowed.to_owned(),
+            WtnsFile::<32>::read_file(path).unwrap()
+        );
+    }
+}
@@ -0,0 +1,156 @@
+//! Building files with a header consistent with their values.
+
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use crate::{FieldElement, Header, Result, Witness, WtnsError, WtnsFile, FORMAT};
+
+/// Version of the files built by [`WtnsFileBuilder`] unless set otherwise, the one snarkjs writes.
+const BUILDER_VERSION: u32 = 2;
+
+/// Builds a [`WtnsFile`], deriving the header witness length and field size from the values
+/// added to it and checking them against the prime.
+#[derive(Debug, Clone)]
+pub struct WtnsFileBuilder<const FS: usize> {
+    version: u32,
+    prime: Option<FieldElement<FS>>,
+    witness: Vec<FieldElement<FS>>,
+}
+
+impl<const FS: usize> Default for WtnsFileBuilder<FS> {
+    fn default() -> Self {
+        WtnsFileBuilder {
+            version: BUILDER_VERSION,
+            prime: None,
+            witness: Vec::new(),
+        }
+    }
+}
+
+impl<const FS: usize> WtnsFileBuilder<FS> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the file version, 2 by default. [`WtnsFileBuilder::build`] checks that it is
+    /// supported.
+    pub fn version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn prime(mut self, prime: FieldElement<FS>) -> Self {
+        self.prime = Some(prime);
+        self
+    }
+
+    /// Appends a value, returning its index.
+    pub fn push(&mut self, value: FieldElement<FS>) -> usize {
+        self.witness.push(value);
+        self.witness.len() - 1
+    }
+
+    pub fn extend<I: IntoIterator<Item = FieldElement<FS>>>(&mut self, values: I) {
+        self.witness.extend(values);
+    }
+
+    /// Builds the file.
+    ///
+    /// Fails with [`WtnsError::UnsupportedVersion`], with [`WtnsError::MissingPrime`] if the prime
+    /// is missing or zero, with [`WtnsError::WitnessTooLong`] if there are more values than a
+    /// header can declare and with [`WtnsError::NonCanonicalValue`] for the first value not below
+    /// the prime.
+    pub fn build(self) -> Result<WtnsFile<FS>> {
+        if !FORMAT.versions.contains(&self.version) {
+            return Err(WtnsError::UnsupportedVersion(self.version));
+        }
+        let prime = match self.prime {
+            Some(prime) if !prime.is_zero() => prime,
+            _ => return Err(WtnsError::MissingPrime),
+        };
+        let witness_len = witness_len(self.witness.len())?;
+        if let Some(index) = self.witness.iter().position(|value| *value >= prime) {
+            return Err(WtnsError::NonCanonicalValue { index });
+        }
+
+        Ok(WtnsFile {
+            version: self.version,
+            header: Header {
+                field_size: FS as u32,
+                prime,
+                witness_len,
+            },
+            witness: Witness(self.witness),
+        })
+    }
+}
+
+/// The header witness length of `len` values.
+fn witness_len(len: usize) -> Result<u32> {
+    u32::try_from(len).map_err(|_| WtnsError::WitnessTooLong { len: len as u64 })
+}
+
+impl<const FS: usize> WtnsFile<FS> {
+    /// A version 2 file holding `witness`, checking what [`WtnsFile::from_vec`] doesn't, see
+    /// [`WtnsFileBuilder::build`].
+    pub fn try_from_vec(witness: Vec<FieldElement<FS>>, prime: FieldElement<FS>) -> Result<Self> {
+        let mut builder = WtnsFileBuilder::new().prime(prime);
+        builder.witness = witness;
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fe(value: u64) -> FieldElement<32> {
+        FieldElement::from_u64(value)
+    }
+
+    #[test]
+    fn test_build() {
+        let mut builder = WtnsFileBuilder::new().prime(fe(101));
+        assert_eq!(builder.push(fe(1)), 0);
+        builder.extend([fe(2), fe(100)]);
+        let file = builder.build().unwrap();
+        assert_eq!(file.version, 2);
+        assert_eq!(file.header.field_size, 32);
+        assert_eq!(file.header.witness_len, 3);
+        assert_eq!(file.witness.0, [fe(1), fe(2), fe(100)]);
+
+        let mut data = Vec::new();
+        file.write(&mut data).unwrap();
+        assert_eq!(WtnsFile::<32>::read(data.as_slice()).unwrap(), file);
+
+        let v1 = WtnsFileBuilder::new().version(1).prime(fe(101)).build();
+        assert_eq!(v1.unwrap().version, 1);
+        assert_eq!(
+            WtnsFile::try_from_vec(vec![fe(1), fe(2), fe(100)], fe(101)).unwrap(),
+            file
+        );
+    }
+
+    #[test]
+    fn test_build_errors() {
+        assert!(matches!(
+            WtnsFileBuilder::new().version(3).prime(fe(101)).build(),
+            Err(WtnsError::UnsupportedVersion(3))
+        ));
+        assert!(matches!(
+            WtnsFileBuilder::<32>::new().build(),
+            Err(WtnsError::MissingPrime)
+        ));
+        assert!(matches!(
+            WtnsFile::try_from_vec(vec![fe(1), fe(101), fe(102)], fe(101)),
+            Err(WtnsError::NonCanonicalValue { index: 1 })
+        ));
+
+        assert_eq!(witness_len(u32::MAX as usize).unwrap(), u32::MAX);
+        #[cfg(target_pointer_width = "64")]
+        assert!(matches!(
+            witness_len(u32::MAX as usize + 1),
+            Err(WtnsError::WitnessTooLong { len }) if len == 1 << 32
+        ));
+    }
+}
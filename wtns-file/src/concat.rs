@@ -0,0 +1,149 @@
+//! Witnesses stitched together from parts covering consecutive wires.
+
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+
+use crate::{FieldElement, Header, Result, WtnsError, WtnsFile, WtnsReader, WtnsWriter};
+
+/// Options for [`WtnsFile::concat_with`] and [`concat_to_writer`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConcatOptions {
+    /// Drop the first value of every part after the first one, for parts that each start with
+    /// the constant one wire. The dropped values are not checked.
+    pub strip_leading: bool,
+}
+
+impl<const FS: usize> WtnsFile<FS> {
+    /// The values of all `parts` in order, keeping the leading value of every part.
+    ///
+    /// Fails if there are no parts, if the primes of the parts differ and if the combined length
+    /// doesn't fit in a `u32`.
+    pub fn concat(parts: &[WtnsFile<FS>]) -> Result<Self> {
+        Self::concat_with(parts, &ConcatOptions::default())
+    }
+
+    /// Like [`WtnsFile::concat`], with `opts`.
+    pub fn concat_with(parts: &[WtnsFile<FS>], opts: &ConcatOptions) -> Result<Self> {
+        let headers: Vec<_> = parts
+            .iter()
+            .map(|part| Header {
+                witness_len: part.witness.len() as u32,
+                ..part.header.clone()
+            })
+            .collect();
+        let (prime, len) = check_parts(&headers, opts)?;
+
+        let mut witness = Vec::with_capacity(len as usize);
+        for (i, part) in parts.iter().enumerate() {
+            witness.extend(part.witness.iter().skip(skipped(i, opts)));
+        }
+
+        Ok(WtnsFile::from_vec(witness, prime))
+    }
+}
+
+/// Like [`WtnsFile::concat_with`], streaming the values of the `parts` files to the file `w`
+/// instead of holding them in memory.
+///
+/// The headers of all parts are read first, so every part is open at the same time.
+pub fn concat_to_writer<R: Read, W: Write, const FS: usize>(
+    parts: impl IntoIterator<Item = R>,
+    w: W,
+    opts: &ConcatOptions,
+) -> Result<W> {
+    let mut parts = parts
+        .into_iter()
+        .map(WtnsReader::<_, FS>::new)
+        .collect::<Result<Vec<_>>>()?;
+    let headers: Vec<_> = parts.iter().map(|part| part.header().clone()).collect();
+    let (prime, len) = check_parts(&headers, opts)?;
+
+    let mut w = WtnsWriter::new(w, prime, Some(len))?;
+    for (i, part) in parts.iter_mut().enumerate() {
+        for value in part.values().skip(skipped(i, opts)) {
+            w.write_value(&value?)?;
+        }
+    }
+
+    w.finish()
+}
+
+/// The number of values dropped from the start of part `i`.
+fn skipped(i: usize, opts: &ConcatOptions) -> usize {
+    usize::from(opts.strip_leading && i > 0)
+}
+
+/// Checks that the parts share a prime, returning it with the combined length.
+fn check_parts<const FS: usize>(
+    headers: &[Header<FS>],
+    opts: &ConcatOptions,
+) -> Result<(FieldElement<FS>, u32)> {
+    let prime = headers.first().ok_or(WtnsError::NoParts)?.prime;
+
+    let mut len = 0u64;
+    for (i, header) in headers.iter().enumerate() {
+        if header.prime != prime {
+            return Err(WtnsError::PartPrimeMismatch { part: i });
+        }
+        len += (header.witness_len as u64).saturating_sub(skipped(i, opts) as u64);
+    }
+    let len = u32::try_from(len).map_err(|_| WtnsError::WitnessTooLong { len })?;
+
+    Ok((prime, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concat() {
+        let prime = FieldElement::<8>::from_u64(97);
+        let fe = FieldElement::from_u64;
+        let parts = [
+            WtnsFile::from_vec(vec![fe(1), fe(2), fe(3)], prime),
+            WtnsFile::from_vec(vec![fe(1), fe(4)], prime),
+            WtnsFile::from_vec(vec![], prime),
+        ];
+
+        let file = WtnsFile::concat(&parts).unwrap();
+        assert_eq!(file.witness.0, [fe(1), fe(2), fe(3), fe(1), fe(4)]);
+        assert_eq!(file.header.witness_len, 5);
+
+        let strip = ConcatOptions {
+            strip_leading: true,
+        };
+        let file = WtnsFile::concat_with(&parts, &strip).unwrap();
+        assert_eq!(file.witness.0, [fe(1), fe(2), fe(3), fe(4)]);
+
+        let files: Vec<_> = parts
+            .iter()
+            .map(|part| {
+                let mut data = Vec::new();
+                part.write(&mut data).unwrap();
+                data
+            })
+            .collect();
+        let streamed =
+            concat_to_writer::<_, _, 8>(files.iter().map(Vec::as_slice), Vec::new(), &strip)
+                .unwrap();
+        assert_eq!(WtnsFile::<8>::read(streamed.as_slice()).unwrap(), file);
+
+        assert!(matches!(
+            WtnsFile::<8>::concat(&[]),
+            Err(WtnsError::NoParts)
+        ));
+        let other = WtnsFile::from_vec(vec![fe(1)], fe(101));
+        assert!(matches!(
+            WtnsFile::concat(&[parts[0].clone(), other]),
+            Err(WtnsError::PartPrimeMismatch { part: 1 })
+        ));
+
+        let mut header = parts[0].header.clone();
+        header.witness_len = u32::MAX;
+        assert!(matches!(
+            check_parts(&[header.clone(), header], &ConcatOptions::default()),
+            Err(WtnsError::WitnessTooLong { len }) if len == 2 * u32::MAX as u64
+        ));
+    }
+}
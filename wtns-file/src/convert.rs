@@ -0,0 +1,82 @@
+//! Conversion of witnesses between field element sizes.
+
+use crate::{Header, Result, Witness, WtnsError, WtnsFile};
+
+impl<const FS: usize> WtnsFile<FS> {
+    /// Converts the prime and every value to `FS2` bytes, zero-extending them if `FS2` is larger
+    /// and checking that the dropped bytes are zero if it is smaller.
+    ///
+    /// Fails with [`WtnsError::ModulusTooWide`] if the prime doesn't fit in `FS2` bytes and with
+    /// [`WtnsError::ValueTooWide`] for the first value that doesn't.
+    pub fn try_convert<const FS2: usize>(&self) -> Result<WtnsFile<FS2>> {
+        let prime = self
+            .header
+            .prime
+            .try_truncate()
+            .ok_or(WtnsError::ModulusTooWide {
+                field_size: FS2 as u32,
+            })?;
+        let witness = self
+            .witness
+            .iter()
+            .enumerate()
+            .map(|(index, value)| {
+                value.try_truncate().ok_or(WtnsError::ValueTooWide {
+                    index,
+                    field_size: FS2 as u32,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(WtnsFile {
+            version: self.version,
+            header: Header {
+                field_size: FS2 as u32,
+                prime,
+                witness_len: self.header.witness_len,
+            },
+            witness: Witness(witness),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FieldElement;
+
+    #[test]
+    fn test_try_convert() {
+        let data = std::fs::read("tests/witness.wtns").unwrap();
+        let file = WtnsFile::<32>::read(data.as_slice()).unwrap();
+
+        let wide = file.try_convert::<48>().unwrap();
+        assert_eq!(wide.header.field_size, 48);
+        assert_eq!(wide.witness[4], file.witness[4].widen());
+        let mut written = Vec::new();
+        wide.write(&mut written).unwrap();
+        assert_eq!(WtnsFile::<48>::read(written.as_slice()).unwrap(), wide);
+        assert_eq!(wide.try_convert::<32>().unwrap(), file);
+
+        assert!(matches!(
+            file.try_convert::<8>(),
+            Err(WtnsError::ModulusTooWide { field_size: 8 })
+        ));
+        let mut small = WtnsFile::<8>::from_vec(
+            vec![FieldElement::from_u64(1), FieldElement::from_u64(2)],
+            FieldElement::from_u64(0xfffffffb),
+        );
+        assert_eq!(
+            small.try_convert::<4>().unwrap().try_convert().unwrap(),
+            small
+        );
+        small.witness.0[1] = FieldElement::from_u64(1 << 32);
+        assert!(matches!(
+            small.try_convert::<4>(),
+            Err(WtnsError::ValueTooWide {
+                index: 1,
+                field_size: 4
+            })
+        ));
+    }
+}
@@ -0,0 +1,135 @@
+//! CSV export of the witness values, behind the `csv` feature.
+
+use std::io::{BufWriter, Write};
+use std::ops::Range;
+
+use crate::{Result, WtnsFile};
+
+/// How [`WtnsFile::write_csv`] formats the values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CsvValueFormat {
+    #[default]
+    Decimal,
+    /// `0x` followed by all `2 * FS` digits, most significant first.
+    Hex,
+}
+
+/// Resolves witness indices to signal names, for the `name` column of [`WtnsFile::write_csv`].
+///
+/// r1cs-file implements it for circuits paired with their `.sym` file, resolving each index
+/// through the wire map.
+pub trait SignalNames {
+    /// The name of the signal at witness `index`, `None` to leave the cell empty.
+    fn signal_name(&self, index: usize) -> Option<&str>;
+}
+
+/// Options for [`WtnsFile::write_csv`].
+#[derive(Clone, Default)]
+pub struct CsvOptions<'a> {
+    pub format: CsvValueFormat,
+    /// The indices to write, clamped to the witness length. All of them if `None`.
+    pub range: Option<Range<usize>>,
+    /// Adds a `name` column resolved with these names.
+    pub names: Option<&'a dyn SignalNames>,
+}
+
+impl<const FS: usize> WtnsFile<FS> {
+    /// Writes the witness as CSV, a header row followed by an `index,value` row per value, with
+    /// a third `name` column if [`CsvOptions::names`] is set. Names are quoted if they contain a
+    /// comma, a quote or a line break.
+    ///
+    /// Rows are written one at a time, without building the whole document in memory.
+    pub fn write_csv<W: Write>(&self, w: W, opts: CsvOptions<'_>) -> Result<()> {
+        let mut w = BufWriter::new(w);
+        let values = &self.witness.0;
+        let range = opts.range.unwrap_or(0..values.len());
+        let start = range.start.min(values.len());
+        let end = range.end.clamp(start, values.len());
+
+        w.write_all(b"index,value")?;
+        if opts.names.is_some() {
+            w.write_all(b",name")?;
+        }
+        w.write_all(b"\n")?;
+
+        for (index, value) in (start..end).zip(&values[start..end]) {
+            match opts.format {
+                CsvValueFormat::Decimal => write!(w, "{},{}", index, value.to_dec_string())?,
+                CsvValueFormat::Hex => write!(w, "{},{:#x}", index, value)?,
+            }
+            if let Some(names) = opts.names {
+                w.write_all(b",")?;
+                write_field(&mut w, names.signal_name(index).unwrap_or(""))?;
+            }
+            w.write_all(b"\n")?;
+        }
+        w.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Writes `field`, quoted as in RFC 4180 if needed.
+fn write_field<W: Write>(w: &mut W, field: &str) -> Result<()> {
+    if field.contains([',', '"', '\n', '\r']) {
+        write!(w, "\"{}\"", field.replace('"', "\"\""))?;
+    } else {
+        w.write_all(field.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Names(Vec<&'static str>);
+
+    impl SignalNames for Names {
+        fn signal_name(&self, index: usize) -> Option<&str> {
+            self.0.get(index).copied()
+        }
+    }
+
+    fn csv(file: &WtnsFile<32>, opts: CsvOptions<'_>) -> String {
+        let mut data = Vec::new();
+        file.write_csv(&mut data, opts).unwrap();
+        String::from_utf8(data).unwrap()
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let data = std::fs::read("tests/witness.wtns").unwrap();
+        let file = WtnsFile::<32>::read(data.as_slice()).unwrap();
+        let names = Names(vec!["one", "main.out", "main.in[0,1]", "say \"hi\""]);
+
+        let opts = CsvOptions {
+            names: Some(&names),
+            ..CsvOptions::default()
+        };
+        let golden = std::fs::read_to_string("tests/witness.csv").unwrap();
+        assert_eq!(csv(&file, opts), golden);
+
+        let hex = CsvOptions {
+            format: CsvValueFormat::Hex,
+            range: Some(1..3),
+            names: None,
+        };
+        assert_eq!(
+            csv(&file, hex),
+            format!("index,value\n1,0x{:064x}\n2,0x{:064x}\n", 33, 3)
+        );
+
+        let past_end = CsvOptions {
+            range: Some(4..100),
+            ..CsvOptions::default()
+        };
+        assert_eq!(csv(&file, past_end).lines().count(), 2);
+        let empty = CsvOptions {
+            range: Some(10..100),
+            ..CsvOptions::default()
+        };
+        assert_eq!(csv(&file, empty), "index,value\n");
+    }
+}
@@ -0,0 +1,206 @@
+//! Value by value comparison of two witnesses.
+
+use std::fmt;
+use std::io::Read;
+
+use crate::{FieldElement, Header, Result, WtnsFile, WtnsReader};
+
+/// Options for [`wtns_diff`] and [`wtns_diff_streams`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// The number of differing values kept as examples.
+    pub max_examples: usize,
+    /// Don't report different lengths, only comparing the values both witnesses have. For
+    /// generators that pad the witness.
+    pub ignore_trailing: bool,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            max_examples: 10,
+            ignore_trailing: false,
+        }
+    }
+}
+
+/// Differences found by [`wtns_diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WtnsDiff<const FS: usize> {
+    /// The primes of both witnesses, if they differ.
+    pub primes: Option<(FieldElement<FS>, FieldElement<FS>)>,
+    /// The lengths of both witnesses, if they differ and trailing values are not ignored.
+    pub lengths: Option<(usize, usize)>,
+    /// The number of indices below both lengths whose values differ.
+    pub count: u64,
+    /// The first differing values, as `(index, value in a, value in b)`.
+    pub examples: Vec<(usize, FieldElement<FS>, FieldElement<FS>)>,
+}
+
+impl<const FS: usize> WtnsDiff<FS> {
+    /// Compares the primes and lengths, before any value.
+    fn new(a: &Header<FS>, b: &Header<FS>, opts: &DiffOptions) -> Self {
+        let (la, lb) = (a.witness_len as usize, b.witness_len as usize);
+
+        WtnsDiff {
+            primes: Some((a.prime, b.prime)).filter(|_| a.prime != b.prime),
+            lengths: Some((la, lb)).filter(|_| la != lb && !opts.ignore_trailing),
+            count: 0,
+            examples: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, index: usize, a: FieldElement<FS>, b: FieldElement<FS>, opts: &DiffOptions) {
+        if a == b {
+            return;
+        }
+
+        self.count += 1;
+        if self.examples.len() < opts.max_examples {
+            self.examples.push((index, a, b));
+        }
+    }
+
+    /// Whether the witnesses are identical, up to trailing values if ignored.
+    pub fn is_empty(&self) -> bool {
+        self.primes.is_none() && self.lengths.is_none() && self.count == 0
+    }
+}
+
+/// Compares the primes, the lengths and the values of `a` and `b`.
+///
+/// Values are compared at every index below both lengths, even if the primes differ. The lengths
+/// are taken from the values, not from the headers.
+pub fn wtns_diff<const FS: usize>(
+    a: &WtnsFile<FS>,
+    b: &WtnsFile<FS>,
+    opts: &DiffOptions,
+) -> WtnsDiff<FS> {
+    let header = |file: &WtnsFile<FS>| Header {
+        witness_len: file.witness.len() as u32,
+        ..file.header.clone()
+    };
+    let mut diff = WtnsDiff::new(&header(a), &header(b), opts);
+    for (index, (va, vb)) in a.witness.iter().zip(&b.witness).enumerate() {
+        diff.push(index, *va, *vb, opts);
+    }
+
+    diff
+}
+
+/// Like [`wtns_diff`], reading both witnesses value by value instead of holding them in memory.
+///
+/// The lengths are taken from the headers.
+pub fn wtns_diff_streams<A: Read, B: Read, const FS: usize>(
+    a: A,
+    b: B,
+    opts: &DiffOptions,
+) -> Result<WtnsDiff<FS>> {
+    let mut a = WtnsReader::<_, FS>::new(a)?;
+    let mut b = WtnsReader::<_, FS>::new(b)?;
+
+    let mut diff = WtnsDiff::new(a.header(), b.header(), opts);
+    for (index, (va, vb)) in a.values().zip(b.values()).enumerate() {
+        diff.push(index, va?, vb?, opts);
+    }
+
+    Ok(diff)
+}
+
+impl<const FS: usize> fmt::Display for WtnsDiff<FS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("No differences");
+        }
+
+        if let Some((a, b)) = &self.primes {
+            writeln!(f, "Primes differ: {:#x} -> {:#x}", a, b)?;
+        }
+        if let Some((a, b)) = self.lengths {
+            writeln!(f, "Lengths differ: {} -> {}", a, b)?;
+        }
+
+        if self.count > 0 {
+            writeln!(f, "{} values differ:", self.count)?;
+            let value = |value: &FieldElement<FS>| match value.to_u64() {
+                Some(n) => format!("{:#x} ({})", value, n),
+                None => format!("{:#x}", value),
+            };
+            for (index, a, b) in &self.examples {
+                writeln!(f, "  #{}: {} -> {}", index, value(a), value(b))?;
+            }
+            if self.count > self.examples.len() as u64 {
+                writeln!(f, "  ...")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let fe = FieldElement::<4>::from;
+        let prime = fe([0xfb, 0xff, 0xff, 0xff]);
+        let a = WtnsFile::from_vec(
+            vec![fe([1, 0, 0, 0]), fe([2, 0, 0, 0]), fe([3, 0, 0, 0])],
+            prime,
+        );
+        let opts = DiffOptions::default();
+
+        let diff = wtns_diff(&a, &a, &opts);
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "No differences");
+
+        let mut b = a.clone();
+        b.witness.0[0] = fe([9, 0, 0, 0]);
+        b.witness.0[2] = fe([0xff; 4]);
+        b.witness.0.push(fe([4, 0, 0, 0]));
+        let diff = wtns_diff(&a, &b, &opts);
+        assert_eq!(diff.primes, None);
+        assert_eq!(diff.lengths, Some((3, 4)));
+        assert_eq!(diff.count, 2);
+        assert_eq!(
+            diff.to_string(),
+            "Lengths differ: 3 -> 4\n\
+             2 values differ:\n  \
+             #0: 0x00000001 (1) -> 0x00000009 (9)\n  \
+             #2: 0x00000003 (3) -> 0xffffffff (4294967295)\n"
+        );
+
+        let padded = DiffOptions {
+            max_examples: 1,
+            ignore_trailing: true,
+        };
+        let diff = wtns_diff(&a, &b, &padded);
+        assert_eq!(diff.lengths, None);
+        assert_eq!((diff.count, diff.examples.len()), (2, 1));
+        assert!(diff.to_string().ends_with("(9)\n  ...\n"));
+
+        b.header.prime = fe([7, 0, 0, 0]);
+        let diff = wtns_diff(&a, &b, &opts);
+        assert_eq!(diff.primes, Some((prime, b.header.prime)));
+        assert!(diff
+            .to_string()
+            .starts_with("Primes differ: 0xfffffffb -> 0x00000007\n"));
+    }
+
+    #[test]
+    fn test_diff_streams() {
+        let data = std::fs::read("tests/witness.wtns").unwrap();
+        let a = WtnsFile::<32>::read(data.as_slice()).unwrap();
+        let mut b = a.clone();
+        b.witness.0[3] = FieldElement::from_u64(12);
+        let mut other = Vec::new();
+        b.write(&mut other).unwrap();
+
+        let opts = DiffOptions::default();
+        let streamed = wtns_diff_streams::<_, _, 32>(data.as_slice(), other.as_slice(), &opts);
+        assert_eq!(streamed.unwrap(), wtns_diff(&a, &b, &opts));
+        assert!(wtns_diff_streams::<_, _, 32>(data.as_slice(), &other[..100], &opts).is_err());
+    }
+}
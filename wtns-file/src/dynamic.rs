@@ -0,0 +1,255 @@
+//! Witness files whose field size is only known at run time.
+
+use std::io::{Read, Write};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use iden3_binfile::{BinFileReader, BinFileWriter};
+
+use crate::{
+    read_stream, scrub, CountingReader, FieldElement, Header, Result, SectionType, Witness,
+    WtnsError, WtnsFile, FORMAT, MAGIC, MAX_PREALLOCATION,
+};
+
+/// A witness file read without knowing its field size, the values being kept as raw bytes.
+///
+/// Prefer [`WtnsFile`] when the field size is known at compile time. Sections of unknown types
+/// are skipped when reading and are not written back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WtnsFileDyn {
+    version: u32,
+    field_size: u32,
+    prime: Vec<u8>,
+    /// The values, `field_size` bytes each.
+    values: Vec<u8>,
+}
+
+/// The header of a [`WtnsFileDyn`] being read.
+struct HeaderDyn {
+    field_size: u32,
+    prime: Vec<u8>,
+    witness_len: u32,
+}
+
+impl WtnsFileDyn {
+    /// Reads a file from a stream, taking the field size from the header section, which must come
+    /// before the witness section.
+    pub fn read<R: Read>(r: R) -> Result<Self> {
+        let mut file = BinFileReader::new(r, &FORMAT)?;
+
+        let version = file.version();
+        // The closures make the section reader lifetime generic, which function items can't.
+        #[allow(clippy::redundant_closure)]
+        let (header, values) = read_stream(
+            &mut file,
+            |r, size| read_header(r, size),
+            |r, size, header| read_values(r, size, header),
+        )?;
+
+        Ok(WtnsFileDyn {
+            version,
+            field_size: header.field_size,
+            prime: header.prime,
+            values,
+        })
+    }
+
+    /// Writes the header and witness sections, the same bytes as the file read if it had no
+    /// other sections.
+    pub fn write<W: Write>(&self, w: W) -> Result<()> {
+        let mut w = BinFileWriter::new(w, MAGIC, self.version, 2)?;
+        let header_size = 4 + self.prime.len() as u64 + 4;
+        w.write_section(SectionType::Header as u32, header_size, |w| {
+            w.write_u32::<LittleEndian>(self.field_size)?;
+            w.write_all(&self.prime)?;
+            w.write_u32::<LittleEndian>(self.len() as u32)
+        })?;
+        w.write_section(SectionType::Witness as u32, self.values.len() as u64, |w| {
+            w.write_all(&self.values)
+        })?;
+        w.finish()?;
+
+        Ok(())
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// The size of the prime and of every value, in bytes.
+    pub fn field_size(&self) -> u32 {
+        self.field_size
+    }
+
+    /// The little-endian bytes of the prime.
+    pub fn prime(&self) -> &[u8] {
+        &self.prime
+    }
+
+    /// The number of values.
+    pub fn len(&self) -> usize {
+        self.values.len() / self.field_size as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The little-endian bytes of the value at index `i`.
+    ///
+    /// Panics if `i` is not below [`WtnsFileDyn::len`].
+    pub fn value(&self, i: usize) -> &[u8] {
+        let size = self.field_size as usize;
+        &self.values[i * size..(i + 1) * size]
+    }
+
+    /// Converts into a [`WtnsFile`], failing if the field size isn't `FS`.
+    pub fn try_into_const<const FS: usize>(&self) -> Result<WtnsFile<FS>> {
+        if self.field_size != FS as u32 {
+            return Err(WtnsError::HeaderFieldSize {
+                expected: FS as u32,
+                found: self.field_size,
+            });
+        }
+
+        let element = |bytes: &[u8]| {
+            let mut buf = [0u8; FS];
+            // The chunks are FS bytes long.
+            buf.copy_from_slice(bytes);
+            let element = FieldElement::from(buf);
+            #[cfg(feature = "zeroize")]
+            zeroize::Zeroize::zeroize(&mut buf);
+
+            element
+        };
+        let witness: Vec<_> = self.values.chunks_exact(FS).map(element).collect();
+
+        Ok(WtnsFile {
+            version: self.version,
+            header: Header {
+                field_size: self.field_size,
+                prime: element(&self.prime),
+                witness_len: witness.len() as u32,
+            },
+            witness: Witness(witness),
+        })
+    }
+}
+
+fn read_header<R: Read>(r: &mut CountingReader<R>, sec_size: u64) -> Result<HeaderDyn> {
+    let offset = r.position();
+    let field_size = r.read_u32::<LittleEndian>()?;
+    if field_size == 0 {
+        return Err(WtnsError::UnsupportedFieldSize {
+            found: field_size,
+            offset,
+        });
+    }
+    if sec_size != 4 + field_size as u64 + 4 {
+        return Err(WtnsError::InvalidSectionSize {
+            section: SectionType::Header,
+            size: sec_size,
+            offset: offset - 8,
+        });
+    }
+
+    let prime = read_bytes(r, field_size as u64)?;
+    let witness_len = r.read_u32::<LittleEndian>()?;
+
+    Ok(HeaderDyn {
+        field_size,
+        prime,
+        witness_len,
+    })
+}
+
+fn read_values<R: Read>(
+    r: &mut CountingReader<R>,
+    sec_size: u64,
+    header: &HeaderDyn,
+) -> Result<Vec<u8>> {
+    if sec_size != header.witness_len as u64 * header.field_size as u64 {
+        return Err(WtnsError::InvalidSectionSize {
+            section: SectionType::Witness,
+            size: sec_size,
+            offset: r.position() - 8,
+        });
+    }
+
+    read_bytes(r, sec_size)
+}
+
+/// Reads `len` bytes, growing the buffer as they arrive rather than trusting `len` up front.
+fn read_bytes<R: Read>(r: &mut CountingReader<R>, len: u64) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    while (bytes.len() as u64) < len {
+        let start = bytes.len();
+        let chunk = (len - start as u64).min(MAX_PREALLOCATION as u64) as usize;
+        scrub::reserve(&mut bytes, chunk);
+        bytes.resize(start + chunk, 0);
+        if let Err(e) = r.read_exact(&mut bytes[start..]) {
+            scrub::scrub(&mut bytes);
+            return Err(e.into());
+        }
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for WtnsFileDyn {
+    /// Zeroizes the values, including the spare capacity, and empties them. The prime is kept.
+    fn zeroize(&mut self) {
+        self.values.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dyn_round_trip() {
+        let data = std::fs::read("tests/witness.wtns").unwrap();
+        let file = WtnsFile::<32>::read(data.as_slice()).unwrap();
+
+        let dyn_file = WtnsFileDyn::read(data.as_slice()).unwrap();
+        assert_eq!(dyn_file.field_size(), 32);
+        assert_eq!(dyn_file.len(), 5);
+        assert_eq!(dyn_file.prime(), file.header.prime.as_bytes());
+        assert_eq!(dyn_file.value(4), file.witness.0[4].as_bytes());
+        assert_eq!(dyn_file.try_into_const::<32>().unwrap(), file);
+        assert!(matches!(
+            dyn_file.try_into_const::<48>(),
+            Err(WtnsError::HeaderFieldSize {
+                expected: 48,
+                found: 32
+            })
+        ));
+        let mut written = Vec::new();
+        dyn_file.write(&mut written).unwrap();
+        assert_eq!(written, data);
+
+        let wide = WtnsFile::<48>::from_vec(vec![FieldElement::from([9; 48]); 3], [1; 48].into());
+        let mut data = Vec::new();
+        wide.write(&mut data).unwrap();
+        let dyn_file = WtnsFileDyn::read(data.as_slice()).unwrap();
+        assert_eq!((dyn_file.field_size(), dyn_file.len()), (48, 3));
+        assert_eq!(dyn_file.try_into_const::<48>().unwrap(), wide);
+
+        assert!(matches!(
+            WtnsFileDyn::read(&data[..data.len() - 1]),
+            Err(WtnsError::UnexpectedEof {
+                section: Some(SectionType::Witness),
+                ..
+            })
+        ));
+        data[24..28].copy_from_slice(&0u32.to_le_bytes());
+        assert!(matches!(
+            WtnsFileDyn::read(data.as_slice()),
+            Err(WtnsError::UnsupportedFieldSize {
+                found: 0,
+                offset: 24
+            })
+        ));
+    }
+}
@@ -0,0 +1,371 @@
+use alloc::string::String;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+
+use iden3_binfile::{io, BinFileError};
+
+use crate::{SectionType, MAX_SECTIONS};
+
+pub type Result<T, E = WtnsError> = core::result::Result<T, E>;
+
+/// Errors produced while reading or writing wtns files.
+///
+/// Errors detected in the input carry the byte offset at which they were detected. Offsets are
+/// counted from the start of the file, or from the start of the section when a section is read on
+/// its own.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum WtnsError {
+    InvalidMagic {
+        found: [u8; 4],
+    },
+    UnsupportedVersion(u32),
+    TooManySections(u32),
+    UnexpectedSection {
+        expected: SectionType,
+        found: u32,
+        offset: u64,
+    },
+    InvalidSectionSize {
+        section: SectionType,
+        size: u64,
+        offset: u64,
+    },
+    FieldSizeMismatch {
+        expected: u32,
+        found: u32,
+        offset: u64,
+    },
+    /// A header field size that can't be read without knowing it up front.
+    UnsupportedFieldSize {
+        found: u32,
+        offset: u64,
+    },
+    /// The file has no section of this type.
+    MissingSection(SectionType),
+    /// A second header or witness section, `offset` is the start of its body.
+    DuplicateSection {
+        section: SectionType,
+        offset: u64,
+    },
+    /// The input ended early. `section` is `None` outside of section bodies.
+    UnexpectedEof {
+        section: Option<SectionType>,
+        offset: u64,
+    },
+    /// The input ended before the value at `index` of the `witness_len` declared by the header.
+    TruncatedWitness {
+        index: u32,
+        witness_len: u32,
+        offset: u64,
+    },
+    /// The header field size of a file to be written, or converted, differs from `FS`.
+    HeaderFieldSize {
+        expected: u32,
+        found: u32,
+    },
+    /// The header witness length of a file to be written differs from the number of values.
+    WitnessLengthMismatch {
+        header: u32,
+        found: usize,
+    },
+    /// A witness value is not below the prime.
+    NonCanonicalValue {
+        index: usize,
+    },
+    /// An index assigned twice, by the entries at positions `first` and `second`.
+    DuplicateAssignment {
+        index: u32,
+        first: usize,
+        second: usize,
+    },
+    /// An index past the end of the witness, or of a range of its signals.
+    IndexOutOfRange {
+        index: u32,
+        len: u32,
+    },
+    /// The header prime is not the modulus of the expected field, both shown in hexadecimal.
+    ModulusMismatch {
+        prime: String,
+        modulus: String,
+    },
+    /// The modulus of a field doesn't fit in the field size of the file.
+    ModulusTooWide {
+        field_size: u32,
+    },
+    /// A limit set in [`crate::WtnsReadOptions`] was exceeded.
+    LimitExceeded {
+        limit: Limit,
+        value: u64,
+        max: u64,
+        offset: u64,
+    },
+    /// Witness parts to concatenate, but none of them.
+    NoParts,
+    /// The prime of the part at index `part` differs from the prime of the first part.
+    PartPrimeMismatch {
+        part: usize,
+    },
+    /// A witness of `len` values, more than a header can declare.
+    WitnessTooLong {
+        len: u64,
+    },
+    /// The value at `index` doesn't fit in `field_size` bytes.
+    ValueTooWide {
+        index: usize,
+        field_size: u32,
+    },
+    /// A flat buffer of `len` bytes that isn't made of whole values of `field_size` bytes.
+    UnalignedLength {
+        len: usize,
+        field_size: u32,
+    },
+    /// A file to be built without a prime, or with a zero prime.
+    MissingPrime,
+    Io(io::Error),
+    /// An error reading or writing the file at `path`.
+    #[cfg(feature = "std")]
+    File {
+        path: PathBuf,
+        error: Box<WtnsError>,
+    },
+}
+
+/// Resource limits configurable in [`crate::WtnsReadOptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Limit {
+    WitnessLen,
+    SectionSize,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Limit::WitnessLen => "max_witness_len",
+            Limit::SectionSize => "max_section_size",
+        };
+
+        f.write_str(name)
+    }
+}
+
+impl WtnsError {
+    /// Turns an unexpected end of input into [`WtnsError::UnexpectedEof`] at `offset`.
+    pub(crate) fn eof_in(self, section: Option<SectionType>, offset: u64) -> Self {
+        match self {
+            WtnsError::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                WtnsError::UnexpectedEof { section, offset }
+            }
+            e => e,
+        }
+    }
+
+    /// The [`io::ErrorKind`] used when converting into an [`io::Error`].
+    fn io_kind(&self) -> io::ErrorKind {
+        match self {
+            WtnsError::Io(e) => e.kind(),
+            WtnsError::UnexpectedEof { .. } | WtnsError::TruncatedWitness { .. } => {
+                io::ErrorKind::UnexpectedEof
+            }
+            WtnsError::HeaderFieldSize { .. }
+            | WtnsError::WitnessLengthMismatch { .. }
+            | WtnsError::DuplicateAssignment { .. }
+            | WtnsError::ModulusTooWide { .. }
+            | WtnsError::ValueTooWide { .. }
+            | WtnsError::NoParts
+            | WtnsError::PartPrimeMismatch { .. }
+            | WtnsError::WitnessTooLong { .. }
+            | WtnsError::UnalignedLength { .. }
+            | WtnsError::MissingPrime
+            | WtnsError::IndexOutOfRange { .. } => io::ErrorKind::InvalidInput,
+            #[cfg(feature = "std")]
+            WtnsError::File { error, .. } => error.io_kind(),
+            _ => io::ErrorKind::InvalidData,
+        }
+    }
+}
+
+impl fmt::Display for WtnsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WtnsError::InvalidMagic { found } => {
+                write!(f, "Invalid magic number {:?} at offset 0", found)
+            }
+            WtnsError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported version {} at offset 4", version)
+            }
+            WtnsError::TooManySections(n) => write!(
+                f,
+                "Number of sections {} at offset 8 is not supported (at most {})",
+                n, MAX_SECTIONS
+            ),
+            WtnsError::UnexpectedSection {
+                expected,
+                found,
+                offset,
+            } => write!(
+                f,
+                "Invalid section type {} at offset {}: expected {}",
+                found, offset, expected
+            ),
+            WtnsError::InvalidSectionSize {
+                section,
+                size,
+                offset,
+            } => write!(
+                f,
+                "Invalid {} section size {} at offset {}",
+                section, size, offset
+            ),
+            WtnsError::FieldSizeMismatch {
+                expected,
+                found,
+                offset,
+            } => write!(
+                f,
+                "Wrong field size at offset {}: expected {}, found {}",
+                offset, expected, found
+            ),
+            WtnsError::UnsupportedFieldSize { found, offset } => {
+                write!(f, "Unsupported field size {} at offset {}", found, offset)
+            }
+            WtnsError::MissingSection(section) => write!(f, "Missing {} section", section),
+            WtnsError::DuplicateSection { section, offset } => {
+                write!(f, "Duplicated {} section at offset {}", section, offset)
+            }
+            WtnsError::UnexpectedEof {
+                section: Some(section),
+                offset,
+            } => write!(
+                f,
+                "Unexpected end of file in {} section at offset {}",
+                section, offset
+            ),
+            WtnsError::UnexpectedEof {
+                section: None,
+                offset,
+            } => write!(f, "Unexpected end of file at offset {}", offset),
+            WtnsError::TruncatedWitness {
+                index,
+                witness_len,
+                offset,
+            } => write!(
+                f,
+                "Unexpected end of file at offset {}: witness value {} of {}",
+                offset, index, witness_len
+            ),
+            WtnsError::HeaderFieldSize { expected, found } => write!(
+                f,
+                "Header field size {} differs from the element size {}",
+                found, expected
+            ),
+            WtnsError::WitnessLengthMismatch { header, found } => write!(
+                f,
+                "Header declares {} witness values but {} were found",
+                header, found
+            ),
+            WtnsError::NonCanonicalValue { index } => {
+                write!(f, "Witness value {} is not below the prime", index)
+            }
+            WtnsError::DuplicateAssignment {
+                index,
+                first,
+                second,
+            } => write!(
+                f,
+                "Index {} is assigned by both entries {} and {}",
+                index, first, second
+            ),
+            WtnsError::IndexOutOfRange { index, len } => {
+                write!(f, "Index {} is out of range for length {}", index, len)
+            }
+            WtnsError::ModulusMismatch { prime, modulus } => write!(
+                f,
+                "The prime {} is not the field modulus {}",
+                prime, modulus
+            ),
+            WtnsError::ModulusTooWide { field_size } => {
+                write!(f, "The field modulus doesn't fit in {} bytes", field_size)
+            }
+            WtnsError::LimitExceeded {
+                limit,
+                value,
+                max,
+                offset,
+            } => write!(
+                f,
+                "Limit {} exceeded at offset {}: {} is larger than {}",
+                limit, offset, value, max
+            ),
+            WtnsError::NoParts => f.write_str("No witness parts to concatenate"),
+            WtnsError::PartPrimeMismatch { part } => write!(
+                f,
+                "The prime of witness part {} differs from the prime of the first part",
+                part
+            ),
+            WtnsError::WitnessTooLong { len } => write!(
+                f,
+                "A witness of {} values is too long for a u32 length",
+                len
+            ),
+            WtnsError::ValueTooWide { index, field_size } => write!(
+                f,
+                "Witness value {} doesn't fit in {} bytes",
+                index, field_size
+            ),
+            WtnsError::UnalignedLength { len, field_size } => write!(
+                f,
+                "{} bytes are not a whole number of {}-byte values",
+                len, field_size
+            ),
+            WtnsError::MissingPrime => f.write_str("The prime is missing or zero"),
+            WtnsError::Io(e) => write!(f, "I/O error: {}", e),
+            #[cfg(feature = "std")]
+            WtnsError::File { path, error } => write!(f, "{}: {}", path.display(), error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WtnsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WtnsError::Io(e) => Some(e),
+            WtnsError::File { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for WtnsError {
+    fn from(e: io::Error) -> Self {
+        WtnsError::Io(e)
+    }
+}
+
+impl From<BinFileError> for WtnsError {
+    fn from(e: BinFileError) -> Self {
+        match e {
+            BinFileError::InvalidMagic { found } => WtnsError::InvalidMagic { found },
+            BinFileError::UnsupportedVersion(version) => WtnsError::UnsupportedVersion(version),
+            BinFileError::TooManySections(n) => WtnsError::TooManySections(n),
+            BinFileError::UnexpectedEof { offset } => WtnsError::UnexpectedEof {
+                section: None,
+                offset,
+            },
+            BinFileError::Io(e) => WtnsError::Io(e),
+            e => WtnsError::Io(e.into()),
+        }
+    }
+}
+
+impl From<WtnsError> for io::Error {
+    fn from(e: WtnsError) -> Self {
+        match e {
+            WtnsError::Io(e) => e,
+            e => io::Error::new(e.io_kind(), e),
+        }
+    }
+}
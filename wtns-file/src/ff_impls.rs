@@ -0,0 +1,92 @@
+//! Conversions to and from `ff` prime fields, behind the `ff` feature.
+
+use ff::PrimeField;
+
+use crate::{Endianness, FieldElement, Header, Result, WtnsError, WtnsFile};
+
+impl<const FS: usize> Header<FS> {
+    /// Checks that the prime is the modulus of the `ff` field `F`.
+    pub fn check_ff_prime<F: PrimeField>(&self) -> Result<()> {
+        if self.prime.is_ff_modulus::<F>() {
+            return Ok(());
+        }
+
+        Err(WtnsError::ModulusMismatch {
+            prime: format!("{:#x}", self.prime),
+            modulus: F::MODULUS.to_owned(),
+        })
+    }
+}
+
+impl<const FS: usize> WtnsFile<FS> {
+    /// A witness of `values`, over the modulus of the `ff` field `F`.
+    ///
+    /// Fails with [`WtnsError::ModulusTooWide`] if the modulus doesn't fit in `FS` bytes.
+    pub fn from_ff_elements<F: PrimeField>(values: &[F]) -> Result<Self> {
+        let too_wide = || WtnsError::ModulusTooWide {
+            field_size: FS as u32,
+        };
+        let prime = FieldElement::from_hex(F::MODULUS, Endianness::Big).map_err(|_| too_wide())?;
+        // The values are below the modulus, so they fit whenever it does.
+        let witness = values
+            .iter()
+            .map(|v| FieldElement::from_ff(*v).ok_or_else(too_wide))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(WtnsFile::from_vec(witness, prime))
+    }
+
+    /// Converts the values into `F`, after checking the prime with [`Header::check_ff_prime`].
+    ///
+    /// Fails with [`WtnsError::NonCanonicalValue`] on the first value not below the prime.
+    pub fn to_ff_vec<F: PrimeField>(&self) -> Result<Vec<F>> {
+        self.header.check_ff_prime::<F>()?;
+
+        self.witness
+            .0
+            .iter()
+            .enumerate()
+            .map(|(index, v)| v.to_ff().ok_or(WtnsError::NonCanonicalValue { index }))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bls12_381::Scalar;
+
+    #[test]
+    fn test_ff_conversions() {
+        let values = vec![Scalar::one(), -Scalar::one(), Scalar::from(42)];
+        let witness = values
+            .iter()
+            .map(|v| FieldElement::<32>::from_ff(*v).unwrap())
+            .collect();
+        let modulus = FieldElement::from_hex(Scalar::MODULUS, Endianness::Big).unwrap();
+        let file = WtnsFile::from_vec(witness, modulus);
+
+        file.header.check_ff_prime::<Scalar>().unwrap();
+        let parsed: Vec<Scalar> = file.witness.0.iter().map(|v| v.to_ff().unwrap()).collect();
+        assert_eq!(parsed, values);
+        assert_eq!(file.to_ff_vec::<Scalar>().unwrap(), values);
+        assert_eq!(WtnsFile::from_ff_elements(&values).unwrap(), file);
+        assert!(matches!(
+            WtnsFile::<8>::from_ff_elements(&values),
+            Err(WtnsError::ModulusTooWide { field_size: 8 })
+        ));
+        assert_eq!(modulus.to_ff::<Scalar>(), None);
+        assert_eq!(FieldElement::<8>::from_ff(-Scalar::one()), None);
+
+        let mut bn254 = file.header;
+        bn254.prime = FieldElement::from_hex(
+            "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001",
+            Endianness::Big,
+        )
+        .unwrap();
+        assert!(matches!(
+            bn254.check_ff_prime::<Scalar>(),
+            Err(WtnsError::ModulusMismatch { modulus, .. }) if modulus == Scalar::MODULUS
+        ));
+    }
+}
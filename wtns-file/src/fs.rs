@@ -0,0 +1,97 @@
+//! Reading and writing files by path.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::{Result, WtnsError, WtnsFile};
+
+impl<const FS: usize> WtnsFile<FS> {
+    /// Reads the file at `path` through a buffered reader, with [`WtnsFile::read_seek`].
+    ///
+    /// Errors are wrapped in [`WtnsError::File`] with the path.
+    pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let read = || -> Result<Self> {
+            let file = File::open(path)?;
+            WtnsFile::read_seek(BufReader::new(file))
+        };
+
+        read().map_err(|e| WtnsError::file(path, e))
+    }
+
+    /// Writes the file to `path` through a buffered writer.
+    ///
+    /// The file is written to a temporary file in the same directory first, then renamed over
+    /// `path`, so `path` never holds a partially written file. Errors are wrapped in
+    /// [`WtnsError::File`] with the path.
+    pub fn write_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        write_atomic(path, |w| self.write(w)).map_err(|e| WtnsError::file(path, e))
+    }
+}
+
+impl WtnsError {
+    fn file(path: &Path, error: WtnsError) -> Self {
+        WtnsError::File {
+            path: path.to_owned(),
+            error: Box::new(error),
+        }
+    }
+}
+
+/// Writes `path` by way of a temporary file renamed once complete.
+fn write_atomic(path: &Path, write: impl FnOnce(&mut BufWriter<File>) -> Result<()>) -> Result<()> {
+    let tmp = temp_path(path);
+    let result = (|| -> Result<()> {
+        let mut w = BufWriter::new(File::create(&tmp)?);
+        write(&mut w)?;
+        w.flush()?;
+        w.get_ref().sync_all()?;
+        std::fs::rename(&tmp, path)?;
+
+        Ok(())
+    })();
+
+    if result.is_err() {
+        // The temporary file may not exist, and the original error matters more.
+        let _ = std::fs::remove_file(&tmp);
+    }
+
+    result
+}
+
+/// A hidden file next to `path`, unique to this process.
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FieldElement;
+
+    #[test]
+    fn test_file_round_trip() {
+        let dir = std::env::temp_dir().join(format!("wtns-file-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("witness.wtns");
+
+        let fe = || FieldElement::from([7u8; 32]);
+        let file = WtnsFile::<32>::from_vec(vec![fe(), fe(), fe()], fe());
+        file.write_file(&path).unwrap();
+        assert_eq!(WtnsFile::<32>::read_file(&path).unwrap(), file);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        let e = WtnsFile::<32>::read_file(dir.join("missing.wtns")).unwrap_err();
+        assert!(matches!(&e, WtnsError::File { error, .. } if matches!(**error, WtnsError::Io(_))));
+        assert!(e.to_string().contains("missing.wtns"));
+
+        // A failed write leaves no temporary file behind.
+        assert!(file.write_file(dir.join("no-such-dir/out.wtns")).is_err());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,97 @@
+//! The JSON format of `snarkjs wtns export json`, behind the `json` feature.
+
+use std::io::{self, BufWriter, Read, Write};
+
+use crate::{FieldElement, ParseFieldElementError, Result, WtnsError, WtnsFile};
+
+impl<const FS: usize> WtnsFile<FS> {
+    /// Writes the witness as a JSON array of decimal strings, formatted like
+    /// `snarkjs wtns export json`: one value per line, indented by a single space.
+    ///
+    /// Values are written one at a time, without building the whole document in memory.
+    pub fn to_json_writer<W: Write>(&self, w: W) -> Result<()> {
+        let mut w = BufWriter::new(w);
+
+        w.write_all(b"[")?;
+        for (i, value) in self.witness.0.iter().enumerate() {
+            let separator = if i == 0 { "\n" } else { ",\n" };
+            write!(w, "{} \"{}\"", separator, value.to_dec_string())?;
+        }
+        if !self.witness.0.is_empty() {
+            w.write_all(b"\n")?;
+        }
+        w.write_all(b"]")?;
+        w.flush()?;
+
+        Ok(())
+    }
+
+    /// Parses a JSON array of decimal strings, as written by `snarkjs wtns export json`.
+    ///
+    /// Every value must be below `prime`, or this fails with [`WtnsError::NonCanonicalValue`].
+    /// Malformed JSON fails with an [`io::ErrorKind::InvalidData`] error.
+    pub fn from_json_reader<R: Read>(r: R, prime: FieldElement<FS>) -> Result<Self> {
+        let values: Vec<String> = serde_json::from_reader(r).map_err(io::Error::from)?;
+
+        let witness = values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| match FieldElement::from_dec_str(value) {
+                Ok(value) if value < prime => Ok(value),
+                Ok(_) | Err(ParseFieldElementError::WrongSize { .. }) => {
+                    Err(WtnsError::NonCanonicalValue { index })
+                }
+                Err(e) => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("value {}: {}", index, e),
+                )
+                .into()),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(WtnsFile::from_vec(witness, prime))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bn254() -> FieldElement<32> {
+        FieldElement::from_dec_str(
+            "21888242871839275222246405745257275088548364400416034343698204186575808495617",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let fixture = std::fs::read_to_string("tests/witness.json").unwrap();
+        let file = WtnsFile::<32>::from_json_reader(fixture.as_bytes(), bn254()).unwrap();
+        assert_eq!(file.header.witness_len, 5);
+        assert_eq!(file.witness.0[1], FieldElement::from_u64(33));
+
+        let mut json = Vec::new();
+        file.to_json_writer(&mut json).unwrap();
+        assert_eq!(String::from_utf8(json).unwrap(), fixture.trim_end());
+
+        let mut empty = Vec::new();
+        WtnsFile::from_vec(vec![], bn254())
+            .to_json_writer(&mut empty)
+            .unwrap();
+        assert_eq!(empty, b"[]");
+    }
+
+    #[test]
+    fn test_json_errors() {
+        let prime = FieldElement::<32>::from_u64(7);
+        assert!(matches!(
+            WtnsFile::from_json_reader(&br#"["1", "7"]"#[..], prime),
+            Err(WtnsError::NonCanonicalValue { index: 1 })
+        ));
+
+        let e = WtnsFile::from_json_reader(&br#"["1", "x"]"#[..], prime).unwrap_err();
+        assert_eq!(io::Error::from(e).kind(), io::ErrorKind::InvalidData);
+        assert!(WtnsFile::from_json_reader(&b"[1, 2]"[..], prime).is_err());
+    }
+}
@@ -1,13 +1,138 @@
 //! Implementation of binary .wtns file parser/serializer.
 //! According to https://github.com/iden3/snarkjs/blob/master/src/wtns_utils.js
+//!
+//! The crate is `no_std` with `alloc` when the default `std` feature is disabled. Files are then
+//! read from and written to the minimal byte sources and sinks of [`iden3_binfile::io`], such as
+//! byte slices and vectors, and everything seeking or touching the file system is left out.
 
-use std::io::{Error, ErrorKind, Read, Result, Write};
+#![cfg_attr(not(feature = "std"), no_std)]
 
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+extern crate alloc;
+
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::Seek;
+
+use byteorder::LittleEndian;
+use iden3_binfile::io::{self, Read, ReadBytesExt, Write, WriteBytesExt};
+#[cfg(feature = "std")]
+use iden3_binfile::SectionEntry;
+use iden3_binfile::{BinFileReader, BinFileWriter, Format, SectionReader};
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls;
+#[cfg(feature = "ark")]
+mod ark_impls;
+#[cfg(feature = "async")]
+mod async_io;
+#[cfg(feature = "std")]
+mod borrowed;
+mod builder;
+#[cfg(feature = "std")]
+mod concat;
+mod convert;
+#[cfg(feature = "csv")]
+mod csv;
+#[cfg(feature = "std")]
+mod diff;
+#[cfg(feature = "std")]
+mod dynamic;
+mod error;
+#[cfg(feature = "ff")]
+mod ff_impls;
+#[cfg(feature = "std")]
+mod fs;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "std")]
+mod patch;
+#[cfg(feature = "std")]
+mod random_access;
+#[cfg(feature = "std")]
+mod reader;
+mod scrub;
+#[cfg(feature = "serde")]
+mod serde_impls;
+#[cfg(feature = "std")]
+mod sparse;
+mod validate;
+#[cfg(feature = "std")]
+mod writer;
+#[cfg(feature = "zeroize")]
+mod zeroize_impls;
+
+#[cfg(feature = "mmap")]
+pub use borrowed::MappedFile;
+#[cfg(feature = "std")]
+pub use borrowed::{WitnessRef, WtnsFileRef};
+pub use builder::WtnsFileBuilder;
+#[cfg(feature = "std")]
+pub use concat::{concat_to_writer, ConcatOptions};
+#[cfg(feature = "csv")]
+pub use csv::{CsvOptions, CsvValueFormat, SignalNames};
+#[cfg(feature = "std")]
+pub use diff::{wtns_diff, wtns_diff_streams, DiffOptions, WtnsDiff};
+#[cfg(feature = "std")]
+pub use dynamic::WtnsFileDyn;
+pub use error::{Limit, Result, WtnsError};
+#[cfg(feature = "std")]
+pub use patch::patch_value;
+#[cfg(feature = "std")]
+pub use random_access::WtnsRandomAccess;
+#[cfg(feature = "std")]
+pub use reader::WtnsReader;
+#[cfg(feature = "std")]
+pub use sparse::SparseWitnessOptions;
+pub use validate::{ValidateOptions, WtnsValidationError};
+#[cfg(feature = "std")]
+pub use writer::WtnsWriter;
+pub use zkp_fields::{Endianness, FieldElement, ParseFieldElementError};
 
 const MAGIC: &[u8; 4] = b"wtns";
+/// Version of the files built by [`WtnsFile::from_vec`] and [`WtnsWriter`].
+const VERSION: u32 = 1;
+/// Upper bound on the number of sections, including unknown ones, accepted when reading.
+const MAX_SECTIONS: u32 = 64;
+/// Upper bound on the number of values preallocated from lengths declared in the file.
+const MAX_PREALLOCATION: usize = 1 << 16;
+const FORMAT: Format = Format {
+    magic: *MAGIC,
+    versions: 0..=2,
+    max_sections: Some(MAX_SECTIONS),
+};
 
-#[derive(Debug, PartialEq)]
+/// Options for [`WtnsFile::read_with`] and [`WtnsFile::read_seek_with`], to bound the resources
+/// spent on untrusted files.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WtnsReadOptions {
+    /// Maximum witness length declared by the header.
+    pub max_witness_len: Option<u32>,
+    /// Maximum declared size of the header and witness sections, in bytes.
+    pub max_section_size: Option<u64>,
+}
+
+impl WtnsReadOptions {
+    fn check_limit(&self, limit: Limit, value: u64, offset: u64) -> Result<()> {
+        let max = match limit {
+            Limit::WitnessLen => self.max_witness_len.map(u64::from),
+            Limit::SectionSize => self.max_section_size,
+        };
+
+        match max {
+            Some(max) if value > max => Err(WtnsError::LimitExceeded {
+                limit,
+                value,
+                max,
+                offset,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "serde_impls::WtnsFileData<FS>"))]
 pub struct WtnsFile<const FS: usize> {
     pub version: u32,
     pub header: Header<FS>,
@@ -15,9 +140,12 @@ pub struct WtnsFile<const FS: usize> {
 }
 
 impl<const FS: usize> WtnsFile<FS> {
+    /// A version 1 file holding `witness`, unchecked: the length is truncated to `u32` and the
+    /// values aren't compared to the prime. See [`WtnsFile::try_from_vec`] and
+    /// [`WtnsFileBuilder`] for the checked ways.
     pub fn from_vec(witness: Vec<FieldElement<FS>>, prime: FieldElement<FS>) -> Self {
         WtnsFile {
-            version: 1,
+            version: VERSION,
             header: Header {
                 field_size: FS as u32,
                 prime,
@@ -27,29 +155,66 @@ impl<const FS: usize> WtnsFile<FS> {
         }
     }
 
-    pub fn read<R: Read>(mut r: R) -> Result<Self> {
-        let mut magic = [0u8; 4];
-        r.read_exact(&mut magic)?;
+    /// The value of `wire`, failing with [`WtnsError::IndexOutOfRange`] against the header
+    /// witness length if the wire is past it.
+    pub fn value(&self, wire: u32) -> Result<&FieldElement<FS>> {
+        let value = if wire < self.header.witness_len {
+            self.witness.get(wire as usize)
+        } else {
+            None
+        };
 
-        if magic != *MAGIC {
-            return Err(Error::new(ErrorKind::InvalidData, "Invalid magic number"));
-        }
+        value.ok_or(WtnsError::IndexOutOfRange {
+            index: wire,
+            len: self.header.witness_len,
+        })
+    }
 
-        let version = r.read_u32::<LittleEndian>()?;
-        if version > 2 {
-            return Err(Error::new(ErrorKind::InvalidData, "Unsupported version"));
+    /// Sets the value of `wire`, which must be below the witness length.
+    pub fn set(&mut self, wire: u32, value: FieldElement<FS>) -> Result<()> {
+        let len = self.witness.0.len();
+        match self.witness.0.get_mut(wire as usize) {
+            Some(slot) => {
+                *slot = value;
+                Ok(())
+            }
+            None => Err(WtnsError::IndexOutOfRange {
+                index: wire,
+                len: len as u32,
+            }),
         }
+    }
 
-        let num_sections = r.read_u32::<LittleEndian>()?;
-        if num_sections > 2 {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                "Number of sections >2 is not supported",
-            ));
-        }
+    /// Reads a file from a stream.
+    ///
+    /// Sections of unknown types are skipped. The header section must come before the witness
+    /// section, use [`WtnsFile::read_seek`] to read them in any order.
+    pub fn read<R: Read>(r: R) -> Result<Self> {
+        Self::read_with(r, &WtnsReadOptions::default())
+    }
+
+    /// Like [`WtnsFile::read`], with `opts`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "wtns_read",
+            level = "debug",
+            skip_all,
+            fields(field_size = FS, witness_len = tracing::field::Empty)
+        )
+    )]
+    pub fn read_with<R: Read>(r: R, opts: &WtnsReadOptions) -> Result<Self> {
+        let mut file = BinFileReader::new(r, &FORMAT)?;
+
+        let version = file.version();
+        let (header, witness) = read_stream(
+            &mut file,
+            |r, size| read_header_with(r, size, opts),
+            |r, size, header| read_witness_with(r, size, header, opts),
+        )?;
 
-        let header = Header::read(&mut r)?;
-        let witness = Witness::read(&mut r, &header)?;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("witness_len", header.witness_len);
 
         Ok(WtnsFile {
             version,
@@ -58,18 +223,101 @@ impl<const FS: usize> WtnsFile<FS> {
         })
     }
 
-    pub fn write<W: Write>(&self, mut w: W) -> Result<()> {
-        w.write_all(MAGIC)?;
-        w.write_u32::<LittleEndian>(self.version)?;
-        w.write_u32::<LittleEndian>(2)?;
-        self.header.write(&mut w)?;
-        self.witness.write(&mut w)?;
+    /// Reads a file from a seekable source, with the header and witness sections in any order.
+    ///
+    /// The section table is walked first, seeking past section bodies, so sections of unknown
+    /// types are never read.
+    #[cfg(feature = "std")]
+    pub fn read_seek<R: Read + Seek>(r: R) -> Result<Self> {
+        Self::read_seek_with(r, &WtnsReadOptions::default())
+    }
+
+    /// Like [`WtnsFile::read_seek`], with `opts`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "wtns_read_seek",
+            level = "debug",
+            skip_all,
+            fields(field_size = FS, witness_len = tracing::field::Empty)
+        )
+    )]
+    #[cfg(feature = "std")]
+    pub fn read_seek_with<R: Read + Seek>(r: R, opts: &WtnsReadOptions) -> Result<Self> {
+        let mut file = BinFileReader::new(r, &FORMAT)?;
+
+        let version = file.version();
+        let (header, witness) = find_sections(&mut file)?;
+
+        let section = file.section_at(&header)?;
+        let header = read_section(section, SectionType::Header, |r| {
+            read_header_with(r, header.size, opts)
+        })?;
+        let section = file.section_at(&witness)?;
+        let witness = read_section(section, SectionType::Witness, |r| {
+            read_witness_with(r, witness.size, &header, opts)
+        })?;
+
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("witness_len", header.witness_len);
+
+        Ok(WtnsFile {
+            version,
+            header,
+            witness,
+        })
+    }
+
+    /// Writes the file, after checking that the header field size is `FS` and that the header
+    /// witness length is the number of values.
+    ///
+    /// Nothing is written if either check fails. Use [`WtnsFile::write_unchecked`] to write the
+    /// file as is.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "wtns_write",
+            level = "debug",
+            skip_all,
+            fields(field_size = FS, witness_len = self.witness.0.len())
+        )
+    )]
+    pub fn write<W: Write>(&self, w: W) -> Result<()> {
+        let header = &self.header;
+        if header.field_size != FS as u32 {
+            return Err(WtnsError::HeaderFieldSize {
+                expected: FS as u32,
+                found: header.field_size,
+            });
+        }
+        if header.witness_len as usize != self.witness.0.len() {
+            return Err(WtnsError::WitnessLengthMismatch {
+                header: header.witness_len,
+                found: self.witness.0.len(),
+            });
+        }
+
+        self.write_unchecked(w)
+    }
+
+    /// Writes the file as is, even if the header disagrees with the witness, for producing
+    /// malformed test vectors.
+    pub fn write_unchecked<W: Write>(&self, w: W) -> Result<()> {
+        let mut w = BinFileWriter::new(w, MAGIC, self.version, 2)?;
+        w.write_section(SectionType::Header as u32, Header::<FS>::size(), |w| {
+            self.header.write_body(w)
+        })?;
+        w.write_section(SectionType::Witness as u32, self.witness.size(), |w| {
+            self.witness.write_body(w)
+        })?;
+        w.finish()?;
 
         Ok(())
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Header<const FS: usize> {
     pub field_size: u32,
     pub prime: FieldElement<FS>,
@@ -77,28 +325,39 @@ pub struct Header<const FS: usize> {
 }
 
 impl<const FS: usize> Header<FS> {
-    pub fn read<R: Read>(mut r: R) -> Result<Self> {
-        let sec_type = SectionType::read(&mut r)?;
-        if sec_type != SectionType::Header {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                "Invalid section type: expected header",
-            ));
-        }
+    pub fn read<R: Read>(r: R) -> Result<Self> {
+        Self::read_counted(&mut CountingReader::new(r))
+    }
 
-        let sec_size = r.read_u64::<LittleEndian>()?;
-        if sec_size != 4 + FS as u64 + 4 {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                "Invalid header section size",
-            ));
+    fn read_counted<R: Read>(r: &mut CountingReader<R>) -> Result<Self> {
+        let read = |r: &mut CountingReader<R>| -> Result<Self> {
+            let sec_size = read_section_header(&mut *r, SectionType::Header)?;
+            Self::read_body(r, sec_size)
+        };
+
+        read(r).map_err(|e| e.eof_in(Some(SectionType::Header), r.position()))
+    }
+
+    /// Reads the body of a header section of `sec_size` bytes.
+    fn read_body<R: Read>(r: &mut CountingReader<R>, sec_size: u64) -> Result<Self> {
+        if sec_size != Self::size() {
+            return Err(WtnsError::InvalidSectionSize {
+                section: SectionType::Header,
+                size: sec_size,
+                offset: r.position() - 8,
+            });
         }
 
+        let offset = r.position();
         let field_size = r.read_u32::<LittleEndian>()?;
-        let prime = FieldElement::read(&mut r)?;
+        let prime = read_element(&mut *r)?;
 
         if field_size != FS as u32 {
-            return Err(Error::new(ErrorKind::InvalidData, "Wrong field size"));
+            return Err(WtnsError::FieldSizeMismatch {
+                expected: FS as u32,
+                found: field_size,
+                offset,
+            });
         }
 
         let witness_len = r.read_u32::<LittleEndian>()?;
@@ -112,56 +371,155 @@ impl<const FS: usize> Header<FS> {
 
     pub fn write<W: Write>(&self, mut w: W) -> Result<()> {
         SectionType::Header.write(&mut w)?;
+        w.write_u64::<LittleEndian>(Self::size())?;
 
-        let sec_size = 4 + FS as u64 + 4;
-        w.write_u64::<LittleEndian>(sec_size)?;
+        self.write_body(w)
+    }
 
+    fn write_body<W: Write>(&self, mut w: W) -> Result<()> {
         w.write_u32::<LittleEndian>(FS as u32)?;
-        self.prime.write(&mut w)?;
+        w.write_all(self.prime.as_bytes())?;
         w.write_u32::<LittleEndian>(self.witness_len)?;
 
         Ok(())
     }
+
+    /// Size of the section body.
+    fn size() -> u64 {
+        4 + FS as u64 + 4
+    }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Witness<const FS: usize>(pub Vec<FieldElement<FS>>);
 
 impl<const FS: usize> Witness<FS> {
-    pub fn read<R: Read>(mut r: R, header: &Header<FS>) -> Result<Self> {
-        let sec_type = SectionType::read(&mut r)?;
-        if sec_type != SectionType::Witness {
-            return Err(Error::new(ErrorKind::InvalidData, "Invalid section type: expected witness"));
-        }
-        let sec_size = r.read_u64::<LittleEndian>()?;
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The value at `index`, `None` past the end.
+    pub fn get(&self, index: usize) -> Option<&FieldElement<FS>> {
+        self.0.get(index)
+    }
+
+    pub fn iter(&self) -> core::slice::Iter<'_, FieldElement<FS>> {
+        self.0.iter()
+    }
+
+    /// The values in batches of `n`, the last one possibly shorter.
+    ///
+    /// Panics if `n` is zero.
+    pub fn chunks(&self, n: usize) -> core::slice::Chunks<'_, FieldElement<FS>> {
+        self.0.chunks(n)
+    }
 
+    pub fn read<R: Read>(r: R, header: &Header<FS>) -> Result<Self> {
+        Self::read_counted(&mut CountingReader::new(r), header)
+    }
+
+    /// The values back to back, `FS` little-endian bytes each, as in the witness section.
+    ///
+    /// The bytes are borrowed from the values, nothing is copied.
+    pub fn as_flat_bytes(&self) -> &[u8] {
+        FieldElement::slice_as_bytes(&self.0)
+    }
+
+    /// The values of `bytes`, `FS` little-endian bytes each, failing if the length isn't a
+    /// multiple of `FS`.
+    pub fn from_flat_bytes(bytes: &[u8]) -> Result<Self> {
+        let values = FieldElement::slice_from_bytes(bytes).ok_or(WtnsError::UnalignedLength {
+            len: bytes.len(),
+            field_size: FS as u32,
+        })?;
+
+        Ok(Witness(values.to_vec()))
+    }
+
+    fn read_counted<R: Read>(r: &mut CountingReader<R>, header: &Header<FS>) -> Result<Self> {
+        let read = |r: &mut CountingReader<R>| -> Result<Self> {
+            let sec_size = read_section_header(&mut *r, SectionType::Witness)?;
+            Self::read_body(r, sec_size, header)
+        };
+
+        read(r).map_err(|e| e.eof_in(Some(SectionType::Witness), r.position()))
+    }
+
+    /// Reads the body of a witness section of `sec_size` bytes.
+    fn read_body<R: Read>(
+        r: &mut CountingReader<R>,
+        sec_size: u64,
+        header: &Header<FS>,
+    ) -> Result<Self> {
         if sec_size != header.witness_len as u64 * FS as u64 {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                "Invalid witness section size",
-            ));
+            return Err(WtnsError::InvalidSectionSize {
+                section: SectionType::Witness,
+                size: sec_size,
+                offset: r.position() - 8,
+            });
         }
 
-        let mut witness = Vec::with_capacity(header.witness_len as usize);
+        // The declared length may be forged, the vector grows as values are actually read.
+        let mut witness = Vec::with_capacity((header.witness_len as usize).min(MAX_PREALLOCATION));
         for _ in 0..header.witness_len {
-            witness.push(FieldElement::read(&mut r)?);
+            let value = match read_element(&mut *r) {
+                Ok(value) => value,
+                Err(e) => {
+                    scrub::scrub(&mut witness);
+                    return Err(e.into());
+                }
+            };
+            scrub::reserve(&mut witness, 1);
+            witness.push(value);
         }
 
         Ok(Witness(witness))
     }
 
-    fn write<W: Write>(&self, mut w: W) -> Result<()> {
-        SectionType::Witness.write(&mut w)?;
-
-        let sec_size = (self.0.len() * FS) as u64;
-        w.write_u64::<LittleEndian>(sec_size)?;
-
+    fn write_body<W: Write>(&self, mut w: W) -> Result<()> {
         for e in &self.0 {
-            e.write(&mut w)?;
+            w.write_all(e.as_bytes())?;
         }
 
         Ok(())
     }
+
+    /// Size of the section body.
+    fn size(&self) -> u64 {
+        (self.0.len() * FS) as u64
+    }
+}
+
+impl<const FS: usize> core::ops::Index<usize> for Witness<FS> {
+    type Output = FieldElement<FS>;
+
+    fn index(&self, index: usize) -> &FieldElement<FS> {
+        &self.0[index]
+    }
+}
+
+impl<const FS: usize> IntoIterator for Witness<FS> {
+    type Item = FieldElement<FS>;
+    type IntoIter = alloc::vec::IntoIter<FieldElement<FS>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, const FS: usize> IntoIterator for &'a Witness<FS> {
+    type Item = &'a FieldElement<FS>;
+    type IntoIter = core::slice::Iter<'a, FieldElement<FS>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
@@ -173,16 +531,12 @@ pub enum SectionType {
 }
 
 impl SectionType {
-    fn read<R: Read>(mut r: R) -> Result<Self> {
-        let num = r.read_u32::<LittleEndian>()?;
-
-        let ty = match num {
-            1 => SectionType::Header,
-            2 => SectionType::Witness,
-            _ => SectionType::Unknown,
-        };
-
-        Ok(ty)
+    fn from_id(id: u32) -> Option<Self> {
+        match id {
+            1 => Some(SectionType::Header),
+            2 => Some(SectionType::Witness),
+            _ => None,
+        }
     }
 
     fn write<W: Write>(&self, mut w: W) -> Result<()> {
@@ -192,37 +546,228 @@ impl SectionType {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct FieldElement<const FS: usize>([u8; FS]);
+impl core::fmt::Display for SectionType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let name = match self {
+            SectionType::Header => "header",
+            SectionType::Witness => "witness",
+            SectionType::Unknown => "unknown",
+        };
+
+        f.write_str(name)
+    }
+}
+
+/// Reads the header section and then the witness section of `file`, skipping sections of unknown
+/// types.
+fn read_stream<R: Read, H, T>(
+    file: &mut BinFileReader<R>,
+    mut read_header: impl FnMut(&mut CountingReader<SectionReader<'_, R>>, u64) -> Result<H>,
+    mut read_witness: impl FnMut(&mut CountingReader<SectionReader<'_, R>>, u64, &H) -> Result<T>,
+) -> Result<(H, T)> {
+    let mut header = None;
+    let mut witness = None;
+    while let Some((id, size, section)) = file.next_section()? {
+        let offset = section.position();
+        match SectionType::from_id(id) {
+            Some(SectionType::Header) => {
+                ensure_unique(&header, SectionType::Header, offset)?;
+                header = Some(read_section(section, SectionType::Header, |r| {
+                    read_header(r, size)
+                })?);
+            }
+            Some(SectionType::Witness) => {
+                ensure_unique(&witness, SectionType::Witness, offset)?;
+                let header = header.as_ref().ok_or(WtnsError::UnexpectedSection {
+                    expected: SectionType::Header,
+                    found: id,
+                    offset: offset - 12,
+                })?;
+                witness = Some(read_section(section, SectionType::Witness, |r| {
+                    read_witness(r, size, header)
+                })?);
+            }
+            _ => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(id, size, "skipping unknown section");
+            }
+        }
+    }
+
+    let header = header.ok_or(WtnsError::MissingSection(SectionType::Header))?;
+    let witness = witness.ok_or(WtnsError::MissingSection(SectionType::Witness))?;
+
+    Ok((header, witness))
+}
 
-impl<const FS: usize> FieldElement<FS> {
-    pub fn as_bytes(&self) -> &[u8] {
-        &self.0[..]
+/// Walks the section table of `file` for the header and witness sections.
+#[cfg(feature = "std")]
+fn find_sections<R: Read + Seek>(
+    file: &mut BinFileReader<R>,
+) -> Result<(SectionEntry, SectionEntry)> {
+    let mut header = None;
+    let mut witness = None;
+    for entry in file.section_table()? {
+        let (slot, section) = match SectionType::from_id(entry.id) {
+            Some(SectionType::Header) => (&mut header, SectionType::Header),
+            Some(SectionType::Witness) => (&mut witness, SectionType::Witness),
+            _ => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(id = entry.id, size = entry.size, "skipping unknown section");
+                continue;
+            }
+        };
+        ensure_unique(slot, section, entry.offset)?;
+        *slot = Some(entry);
     }
 
-    fn read<R: Read>(mut r: R) -> Result<Self> {
-        let mut buf = [0; FS];
-        r.read_exact(&mut buf)?;
+    let header = header.ok_or(WtnsError::MissingSection(SectionType::Header))?;
+    let witness = witness.ok_or(WtnsError::MissingSection(SectionType::Witness))?;
+
+    Ok((header, witness))
+}
+
+/// Reads a header section body of `sec_size` bytes, checking the limits of `opts`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "wtns_header",
+        level = "trace",
+        skip_all,
+        fields(size = sec_size, witness_len = tracing::field::Empty)
+    )
+)]
+fn read_header_with<R: Read, const FS: usize>(
+    r: &mut CountingReader<R>,
+    sec_size: u64,
+    opts: &WtnsReadOptions,
+) -> Result<Header<FS>> {
+    opts.check_limit(Limit::SectionSize, sec_size, r.position() - 8)?;
+    let header = Header::read_body(r, sec_size)?;
+    opts.check_limit(
+        Limit::WitnessLen,
+        u64::from(header.witness_len),
+        r.position() - 4,
+    )?;
+
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("witness_len", header.witness_len);
 
-        Ok(FieldElement(buf))
+    Ok(header)
+}
+
+/// Reads a witness section body of `sec_size` bytes, checking the limits of `opts`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(name = "wtns_witness", level = "trace", skip_all, fields(size = sec_size))
+)]
+fn read_witness_with<R: Read, const FS: usize>(
+    r: &mut CountingReader<R>,
+    sec_size: u64,
+    header: &Header<FS>,
+    opts: &WtnsReadOptions,
+) -> Result<Witness<FS>> {
+    opts.check_limit(Limit::SectionSize, sec_size, r.position() - 8)?;
+
+    Witness::read_body(r, sec_size, header)
+}
+
+/// Reads the header section of `file` and locates the witness section, checking its size against
+/// the header.
+#[cfg(feature = "std")]
+fn locate_witness<R: Read + Seek, const FS: usize>(
+    file: &mut BinFileReader<R>,
+) -> Result<(Header<FS>, SectionEntry)> {
+    let (header, witness) = find_sections(file)?;
+
+    let section = file.section_at(&header)?;
+    let header = read_section(section, SectionType::Header, |r| {
+        Header::<FS>::read_body(r, header.size)
+    })?;
+    if witness.size != header.witness_len as u64 * FS as u64 {
+        return Err(WtnsError::InvalidSectionSize {
+            section: SectionType::Witness,
+            size: witness.size,
+            offset: witness.offset - 8,
+        });
     }
 
-    fn write<W: Write>(&self, mut w: W) -> Result<()> {
-        w.write_all(&self.0[..])
+    Ok((header, witness))
+}
+
+/// Reads the body of a section of type `section` with `read_body`.
+fn read_section<R: Read, T>(
+    body: SectionReader<'_, R>,
+    section: SectionType,
+    read_body: impl FnOnce(&mut CountingReader<SectionReader<'_, R>>) -> Result<T>,
+) -> Result<T> {
+    let offset = body.position();
+    let mut r = CountingReader::at(body, offset);
+    read_body(&mut r).map_err(|e| e.eof_in(Some(section), r.position()))
+}
+
+fn ensure_unique<T>(slot: &Option<T>, section: SectionType, offset: u64) -> Result<()> {
+    if slot.is_some() {
+        return Err(WtnsError::DuplicateSection { section, offset });
     }
+
+    Ok(())
 }
 
-impl<const FS: usize> From<[u8; FS]> for FieldElement<FS> {
-    fn from(array: [u8; FS]) -> Self {
-        FieldElement(array)
+/// Reads a section header, checking its type, and returns the section size.
+fn read_section_header<R: Read>(r: &mut CountingReader<R>, expected: SectionType) -> Result<u64> {
+    let offset = r.position();
+    let found = r.read_u32::<LittleEndian>()?;
+    if found != expected as u32 {
+        return Err(WtnsError::UnexpectedSection {
+            expected,
+            found,
+            offset,
+        });
     }
+
+    Ok(r.read_u64::<LittleEndian>()?)
 }
 
-impl<const FS: usize> std::ops::Deref for FieldElement<FS> {
-    type Target = [u8; FS];
+/// Reads the `FS` little-endian bytes of an element, like [`FieldElement::read`] but through
+/// [`iden3_binfile::io`].
+fn read_element<R: Read, const FS: usize>(mut r: R) -> io::Result<FieldElement<FS>> {
+    let mut bytes = [0u8; FS];
+    let read = r.read_exact(&mut bytes).map(|()| FieldElement::from(bytes));
+    // Don't leave a copy of the value, or of the part read before an error, on the stack.
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(&mut bytes);
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+    read
+}
+
+/// Reader wrapper keeping track of the position for error reporting.
+struct CountingReader<R> {
+    inner: R,
+    position: u64,
+}
+
+impl<R> CountingReader<R> {
+    fn new(inner: R) -> Self {
+        CountingReader { inner, position: 0 }
+    }
+
+    /// A reader whose first byte is at `position` in the file.
+    fn at(inner: R, position: u64) -> Self {
+        CountingReader { inner, position }
+    }
+
+    fn position(&self) -> u64 {
+        self.position
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.position += n as u64;
+
+        Ok(n)
     }
 }
 
@@ -234,7 +779,10 @@ mod tests {
     const FS: usize = 32;
 
     fn fe() -> FieldElement<FS> {
-        FieldElement::from([1,0,1,0,1,0,1,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,1])
+        FieldElement::from([
+            1, 0, 1, 0, 1, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 1,
+        ])
     }
 
     #[test]
@@ -247,4 +795,298 @@ mod tests {
 
         assert_eq!(file, new_file);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_set() {
+        let mut file = WtnsFile::<FS>::from_vec(vec![fe(), fe(), fe()], fe());
+        file.set(2, FieldElement::from_u64(5)).unwrap();
+        assert_eq!(file.witness.0[2], FieldElement::from_u64(5));
+        assert!(matches!(
+            file.set(3, fe()),
+            Err(WtnsError::IndexOutOfRange { index: 3, len: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_witness_accessors() {
+        let values: Vec<_> = (1..=5).map(FieldElement::<FS>::from_u64).collect();
+        let mut file = WtnsFile::from_vec(values.clone(), fe());
+        let witness = &file.witness;
+
+        assert_eq!((witness.len(), witness.is_empty()), (5, false));
+        assert_eq!(witness[4], values[4]);
+        assert_eq!(witness.get(4), Some(&values[4]));
+        assert_eq!(witness.get(5), None);
+        assert!(witness.iter().eq(&values));
+        assert!(witness.into_iter().eq(&values));
+        let chunks: Vec<_> = witness.chunks(2).map(|c| c.len()).collect();
+        assert_eq!(chunks, [2, 2, 1]);
+
+        assert_eq!(file.value(4).unwrap(), &values[4]);
+        file.header.witness_len = 4;
+        assert!(matches!(
+            file.value(4),
+            Err(WtnsError::IndexOutOfRange { index: 4, len: 4 })
+        ));
+        assert_eq!(file.witness.into_iter().collect::<Vec<_>>(), values);
+    }
+
+    #[test]
+    fn test_field_element_ord() {
+        let mut a = [0u8; FS];
+        let mut b = [0u8; FS];
+        a[0] = 2;
+        b[FS - 1] = 1;
+        assert!(FieldElement::from(a) < FieldElement::from(b));
+        assert!(fe() > FieldElement::from(a));
+
+        let witness = Witness(vec![fe(), FieldElement::from(a)]);
+        assert_eq!(witness.clone(), witness);
+    }
+
+    #[test]
+    fn test_errors() {
+        let file = WtnsFile::<FS>::from_vec(vec![fe(), fe(), fe()], fe());
+        let mut data = Vec::new();
+        file.write(&mut data).unwrap();
+
+        let mut bad_magic = data.clone();
+        bad_magic[..4].copy_from_slice(b"r1cs");
+        assert!(matches!(
+            WtnsFile::<FS>::read(bad_magic.as_slice()),
+            Err(WtnsError::InvalidMagic { found }) if &found == b"r1cs"
+        ));
+
+        assert!(matches!(
+            WtnsFile::<FS>::read(&data[..data.len() - 1]),
+            Err(WtnsError::UnexpectedEof {
+                section: Some(SectionType::Witness),
+                offset,
+            }) if offset == data.len() as u64 - 1
+        ));
+
+        // Field size lives right after the preamble and the header section header.
+        let mut wrong_field_size = data.clone();
+        wrong_field_size[24..28].copy_from_slice(&48u32.to_le_bytes());
+        assert!(matches!(
+            WtnsFile::<FS>::read(wrong_field_size.as_slice()),
+            Err(WtnsError::FieldSizeMismatch {
+                expected: 32,
+                found: 48,
+                offset: 24,
+            })
+        ));
+
+        let mut one_section = data.clone();
+        one_section[8..12].copy_from_slice(&1u32.to_le_bytes());
+        assert!(matches!(
+            WtnsFile::<FS>::read(one_section.as_slice()),
+            Err(WtnsError::MissingSection(SectionType::Witness))
+        ));
+
+        let err: std::io::Error = WtnsFile::<FS>::read(&data[..2]).unwrap_err().into();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_section_order() {
+        let plain = std::fs::read("tests/witness.wtns").unwrap();
+        let extra = std::fs::read("tests/witness_extra_section.wtns").unwrap();
+        let swapped = std::fs::read("tests/witness_swapped.wtns").unwrap();
+
+        let file = WtnsFile::<FS>::read(plain.as_slice()).unwrap();
+        assert_eq!(file.witness.0.len(), 5);
+        assert_eq!(WtnsFile::read(extra.as_slice()).unwrap(), file);
+        assert_eq!(WtnsFile::read_seek(Cursor::new(&extra)).unwrap(), file);
+        assert_eq!(WtnsFile::read_seek(Cursor::new(&swapped)).unwrap(), file);
+        assert!(matches!(
+            WtnsFile::<FS>::read(swapped.as_slice()),
+            Err(WtnsError::UnexpectedSection {
+                expected: SectionType::Header,
+                found: 2,
+                offset: 12,
+            })
+        ));
+
+        // The header section appended again.
+        let mut duplicate = plain.clone();
+        duplicate[8..12].copy_from_slice(&3u32.to_le_bytes());
+        duplicate.extend_from_slice(&plain[12..64]);
+        for read in [
+            WtnsFile::<FS>::read(duplicate.as_slice()),
+            WtnsFile::read_seek(Cursor::new(&duplicate)),
+        ] {
+            assert!(matches!(
+                read,
+                Err(WtnsError::DuplicateSection {
+                    section: SectionType::Header,
+                    offset,
+                }) if offset == plain.len() as u64 + 12
+            ));
+        }
+    }
+
+    #[test]
+    fn test_read_limits() {
+        let mut data = std::fs::read("tests/witness.wtns").unwrap();
+        // Forge a header declaring u32::MAX values, backed by a witness section of that size.
+        data[60..64].copy_from_slice(&u32::MAX.to_le_bytes());
+        data[68..76].copy_from_slice(&(u32::MAX as u64 * 32).to_le_bytes());
+
+        assert!(matches!(
+            WtnsFile::<32>::read(data.as_slice()),
+            Err(WtnsError::UnexpectedEof {
+                section: Some(SectionType::Witness),
+                ..
+            })
+        ));
+
+        let opts = WtnsReadOptions {
+            max_witness_len: Some(1 << 20),
+            max_section_size: None,
+        };
+        assert!(matches!(
+            WtnsFile::<32>::read_with(data.as_slice(), &opts),
+            Err(WtnsError::LimitExceeded {
+                limit: Limit::WitnessLen,
+                value,
+                max: 0x100000,
+                offset: 60,
+            }) if value == u32::MAX as u64
+        ));
+        let opts = WtnsReadOptions {
+            max_witness_len: None,
+            max_section_size: Some(1 << 20),
+        };
+        let err = WtnsFile::<32>::read_seek_with(Cursor::new(&data), &opts).unwrap_err();
+        assert!(matches!(
+            err,
+            WtnsError::LimitExceeded {
+                limit: Limit::SectionSize,
+                offset: 68,
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Limit max_section_size exceeded at offset 68: 137438953440 is larger than 1048576"
+        );
+
+        let plain = std::fs::read("tests/witness.wtns").unwrap();
+        let opts = WtnsReadOptions {
+            max_witness_len: Some(5),
+            max_section_size: Some(160),
+        };
+        let file = WtnsFile::<32>::read_with(plain.as_slice(), &opts).unwrap();
+        assert_eq!(
+            file,
+            WtnsFile::read_seek_with(Cursor::new(&plain), &opts).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_write_checks_header() {
+        let file = WtnsFile::<FS>::from_vec(vec![fe(), fe(), fe()], fe());
+
+        let mut wrong_len = file.clone();
+        wrong_len.header.witness_len = 2;
+        let mut data = Vec::new();
+        assert!(matches!(
+            wrong_len.write(&mut data),
+            Err(WtnsError::WitnessLengthMismatch {
+                header: 2,
+                found: 3
+            })
+        ));
+        assert!(data.is_empty());
+        wrong_len.write_unchecked(&mut data).unwrap();
+        assert!(matches!(
+            WtnsFile::<FS>::read(data.as_slice()),
+            Err(WtnsError::InvalidSectionSize { .. })
+        ));
+
+        let mut wrong_field_size = file;
+        wrong_field_size.header.field_size = 48;
+        let e = wrong_field_size.write(Vec::new()).unwrap_err();
+        assert!(matches!(
+            e,
+            WtnsError::HeaderFieldSize {
+                expected: 32,
+                found: 48
+            }
+        ));
+        assert_eq!(
+            std::io::Error::from(e).kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing_spans() {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing_subscriber::layer::{Context, SubscriberExt};
+        use tracing_subscriber::Layer;
+
+        /// Names of the spans and messages of the events, in order.
+        #[derive(Clone, Default)]
+        struct Recorded(Arc<Mutex<Vec<String>>>);
+
+        impl Visit for Recorded {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "message" {
+                    self.0.lock().unwrap().push(format!("{:?}", value));
+                }
+            }
+        }
+
+        impl<S: tracing::Subscriber> Layer<S> for Recorded {
+            fn on_new_span(
+                &self,
+                attrs: &tracing::span::Attributes<'_>,
+                _id: &tracing::span::Id,
+                _ctx: Context<'_, S>,
+            ) {
+                let name = attrs.metadata().name().to_string();
+                self.0.lock().unwrap().push(name);
+            }
+
+            fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+                event.record(&mut self.clone());
+            }
+        }
+
+        let recorded = Recorded::default();
+        let subscriber = tracing_subscriber::registry().with(recorded.clone());
+        let take = || std::mem::take(&mut *recorded.0.lock().unwrap());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let file = WtnsFile::<FS>::from_vec(vec![fe(), fe()], fe());
+            let mut data = Vec::new();
+            file.write(&mut data).unwrap();
+            WtnsFile::<FS>::read(Cursor::new(&data)).unwrap();
+            assert_eq!(
+                take(),
+                ["wtns_write", "wtns_read", "wtns_header", "wtns_witness"]
+            );
+
+            let extra = std::fs::read("tests/witness_extra_section.wtns").unwrap();
+            WtnsFile::<FS>::read(extra.as_slice()).unwrap();
+            assert!(take().contains(&"skipping unknown section".to_string()));
+
+            let mut reader = WtnsReader::<_, FS>::new(data.as_slice()).unwrap();
+            assert_eq!(reader.values().count(), 2);
+            assert_eq!(take(), ["wtns_stream"]);
+
+            // The prime is not above the values.
+            assert!(file.validate().is_err());
+            let events = take();
+            assert_eq!(events[0], "wtns_validate");
+            assert!(events.len() > 1);
+            assert!(events[1..]
+                .iter()
+                .all(|event| event == "validation finding"));
+        });
+    }
+}
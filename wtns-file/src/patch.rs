@@ -0,0 +1,83 @@
+//! Overwriting single values of a witness file in place.
+
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use iden3_binfile::BinFileReader;
+
+use crate::{locate_witness, FieldElement, Result, WtnsError, FORMAT};
+
+/// Overwrites the value at `index` in the witness file `file`, writing only its `FS` bytes.
+///
+/// The preamble and the header section are checked first, including the field size, the
+/// witness section size and `index` against the witness length, so nothing is written to a file
+/// that isn't a witness of field size `FS`.
+pub fn patch_value<F: Read + Write + Seek, const FS: usize>(
+    file: F,
+    index: u32,
+    value: &FieldElement<FS>,
+) -> Result<()> {
+    let mut file = BinFileReader::new(file, &FORMAT)?;
+    let (header, witness) = locate_witness::<_, FS>(&mut file)?;
+    if index >= header.witness_len {
+        return Err(WtnsError::IndexOutOfRange {
+            index,
+            len: header.witness_len,
+        });
+    }
+
+    let mut file = file.into_inner();
+    file.seek(SeekFrom::Start(witness.offset + index as u64 * FS as u64))?;
+    value.write(&mut file)?;
+    file.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SectionType, WtnsFile};
+    use std::fs::OpenOptions;
+
+    #[test]
+    fn test_patch_value() {
+        let dir = std::env::temp_dir().join(format!("wtns-file-patch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("witness.wtns");
+        std::fs::copy("tests/witness_extra_section.wtns", &path).unwrap();
+        let mut expected = WtnsFile::<32>::read_file(&path).unwrap();
+
+        let open = || {
+            OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&path)
+                .unwrap()
+        };
+        let value = FieldElement::from_u64(42);
+        patch_value(open(), 2, &value).unwrap();
+        expected.set(2, value).unwrap();
+        assert_eq!(WtnsFile::<32>::read_file(&path).unwrap(), expected);
+
+        let original = std::fs::read(&path).unwrap();
+        assert!(matches!(
+            patch_value(open(), 5, &value),
+            Err(WtnsError::IndexOutOfRange { index: 5, len: 5 })
+        ));
+        assert!(matches!(
+            patch_value(open(), 0, &FieldElement::<48>::from([0; 48])),
+            Err(WtnsError::InvalidSectionSize {
+                section: SectionType::Header,
+                ..
+            })
+        ));
+        assert_eq!(std::fs::read(&path).unwrap(), original);
+        std::fs::write(&path, b"r1cs not a witness").unwrap();
+        assert!(matches!(
+            patch_value(open(), 0, &value),
+            Err(WtnsError::InvalidMagic { .. })
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,143 @@
+//! Reading single witness values of a file without loading the witness.
+
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+
+use iden3_binfile::BinFileReader;
+
+use crate::{locate_witness, FieldElement, Header, Result, WtnsError, FORMAT};
+
+/// Random access to the values of a witness file, seeking to each value read.
+///
+/// Only the header is read when opening, the witness section is located through the section
+/// table, so the sections may come in any order.
+pub struct WtnsRandomAccess<R, const FS: usize> {
+    file: R,
+    header: Header<FS>,
+    /// Offset of the witness section body.
+    offset: u64,
+}
+
+impl<R: Read + Seek, const FS: usize> WtnsRandomAccess<R, FS> {
+    /// Reads the header and locates the witness section, checking its size against the header.
+    pub fn open(r: R) -> Result<Self> {
+        let mut file = BinFileReader::new(r, &FORMAT)?;
+        let (header, witness) = locate_witness(&mut file)?;
+
+        Ok(WtnsRandomAccess {
+            file: file.into_inner(),
+            header,
+            offset: witness.offset,
+        })
+    }
+
+    pub fn header(&self) -> &Header<FS> {
+        &self.header
+    }
+
+    /// The number of values, from the header.
+    pub fn len(&self) -> usize {
+        self.header.witness_len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.header.witness_len == 0
+    }
+
+    /// Reads the value at `index`, which must be below the witness length.
+    pub fn get(&mut self, index: u32) -> Result<FieldElement<FS>> {
+        self.check_index(index)?;
+        self.seek_to(index)?;
+
+        self.read_value(index)
+    }
+
+    /// Reads the values at the indices of `range`, which must not end past the witness length.
+    pub fn get_range(&mut self, range: Range<u32>) -> Result<Vec<FieldElement<FS>>> {
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.check_index(range.end - 1)?;
+        self.seek_to(range.start)?;
+
+        range.map(|index| self.read_value(index)).collect()
+    }
+
+    pub fn into_inner(self) -> R {
+        self.file
+    }
+
+    fn check_index(&self, index: u32) -> Result<()> {
+        if index >= self.header.witness_len {
+            return Err(WtnsError::IndexOutOfRange {
+                index,
+                len: self.header.witness_len,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn seek_to(&mut self, index: u32) -> Result<()> {
+        let offset = self.offset + index as u64 * FS as u64;
+        self.file.seek(SeekFrom::Start(offset))?;
+
+        Ok(())
+    }
+
+    /// Reads the value at `index`, the file being positioned at it.
+    fn read_value(&mut self, index: u32) -> Result<FieldElement<FS>> {
+        FieldElement::read(&mut self.file).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => WtnsError::TruncatedWitness {
+                index,
+                witness_len: self.header.witness_len,
+                offset: self.offset + index as u64 * FS as u64,
+            },
+            _ => WtnsError::Io(e),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WtnsFile;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_random_access() {
+        let full = WtnsFile::<32>::read_file("tests/witness.wtns").unwrap();
+
+        for path in ["tests/witness.wtns", "tests/witness_swapped.wtns"] {
+            let file = std::fs::File::open(path).unwrap();
+            let mut witness = WtnsRandomAccess::<_, 32>::open(file).unwrap();
+            assert_eq!(witness.header(), &full.header);
+            for index in [4, 0, 2] {
+                assert_eq!(witness.get(index).unwrap(), full.witness[index as usize]);
+            }
+            assert_eq!(witness.get_range(1..4).unwrap(), full.witness.0[1..4]);
+            assert_eq!(witness.get_range(3..3).unwrap(), []);
+            assert!(matches!(
+                witness.get(5),
+                Err(WtnsError::IndexOutOfRange { index: 5, len: 5 })
+            ));
+            assert!(matches!(
+                witness.get_range(2..6),
+                Err(WtnsError::IndexOutOfRange { index: 5, len: 5 })
+            ));
+        }
+
+        let data = std::fs::read("tests/witness.wtns").unwrap();
+        let mut truncated =
+            WtnsRandomAccess::<_, 32>::open(Cursor::new(&data[..data.len() - 1])).unwrap();
+        assert_eq!(truncated.get(3).unwrap(), full.witness[3]);
+        assert!(matches!(
+            truncated.get(4),
+            Err(WtnsError::TruncatedWitness {
+                index: 4,
+                witness_len: 5,
+                ..
+            })
+        ));
+    }
+}
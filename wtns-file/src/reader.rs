@@ -0,0 +1,162 @@
+use std::io::{self, BufReader, Read};
+
+use iden3_binfile::BinFileReader;
+
+use crate::{
+    ensure_unique, read_section, FieldElement, Header, Result, SectionType, WtnsError, FORMAT,
+};
+
+/// Streaming reader yielding witness values one at a time.
+///
+/// The header is read eagerly, then the values are decoded straight off a buffered reader,
+/// without holding the witness in memory. Sections of unknown types before the witness are
+/// skipped. The header must come before the witness, and the sections after the witness are not
+/// read.
+pub struct WtnsReader<R, const FS: usize> {
+    file: BinFileReader<BufReader<R>>,
+    header: Header<FS>,
+    /// Index of the next value.
+    next: u32,
+    failed: bool,
+}
+
+impl<R: Read, const FS: usize> WtnsReader<R, FS> {
+    /// Reads sections up to the header of the witness section.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "wtns_stream",
+            level = "debug",
+            skip_all,
+            fields(field_size = FS, witness_len = tracing::field::Empty)
+        )
+    )]
+    pub fn new(r: R) -> Result<Self> {
+        let mut file = BinFileReader::new(BufReader::new(r), &FORMAT)?;
+
+        let mut header = None;
+        loop {
+            let (id, size, section) = match file.next_section()? {
+                Some(section) => section,
+                None if header.is_none() => {
+                    return Err(WtnsError::MissingSection(SectionType::Header))
+                }
+                None => return Err(WtnsError::MissingSection(SectionType::Witness)),
+            };
+
+            let offset = section.position();
+            match SectionType::from_id(id) {
+                Some(SectionType::Header) => {
+                    ensure_unique(&header, SectionType::Header, offset)?;
+                    header = Some(read_section(section, SectionType::Header, |r| {
+                        Header::read_body(r, size)
+                    })?);
+                }
+                Some(SectionType::Witness) => {
+                    let header = header.ok_or(WtnsError::UnexpectedSection {
+                        expected: SectionType::Header,
+                        found: id,
+                        offset: offset - 12,
+                    })?;
+                    if size != header.witness_len as u64 * FS as u64 {
+                        return Err(WtnsError::InvalidSectionSize {
+                            section: SectionType::Witness,
+                            size,
+                            offset: offset - 8,
+                        });
+                    }
+
+                    #[cfg(feature = "tracing")]
+                    tracing::Span::current().record("witness_len", header.witness_len);
+
+                    return Ok(WtnsReader {
+                        file,
+                        header,
+                        next: 0,
+                        failed: false,
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn header(&self) -> &Header<FS> {
+        &self.header
+    }
+
+    /// The number of values, from the header.
+    pub fn len(&self) -> usize {
+        self.header.witness_len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.header.witness_len == 0
+    }
+
+    /// Iterates over the remaining values, in order.
+    ///
+    /// Iteration stops after the first error.
+    pub fn values(&mut self) -> impl Iterator<Item = Result<FieldElement<FS>>> + '_ {
+        std::iter::from_fn(move || {
+            if self.failed || self.next >= self.header.witness_len {
+                return None;
+            }
+
+            let next = self.next_value();
+            self.failed = next.is_err();
+            Some(next)
+        })
+    }
+
+    fn next_value(&mut self) -> Result<FieldElement<FS>> {
+        let offset = self.file.position();
+        let value = FieldElement::read(&mut self.file).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => WtnsError::TruncatedWitness {
+                index: self.next,
+                witness_len: self.header.witness_len,
+                offset,
+            },
+            _ => WtnsError::Io(e),
+        })?;
+        self.next += 1;
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WtnsFile;
+
+    #[test]
+    fn test_stream_values() {
+        let data = std::fs::read("tests/witness.wtns").unwrap();
+        let file = WtnsFile::<32>::read(data.as_slice()).unwrap();
+
+        let mut reader = WtnsReader::<_, 32>::new(data.as_slice()).unwrap();
+        assert_eq!(reader.header(), &file.header);
+        assert_eq!(reader.len(), file.witness.0.len());
+        let values = reader.values().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(values, file.witness.0);
+        assert!(reader.values().next().is_none());
+
+        let extra = std::fs::read("tests/witness_extra_section.wtns").unwrap();
+        let mut reader = WtnsReader::<_, 32>::new(extra.as_slice()).unwrap();
+        assert!(reader.values().map(Result::unwrap).eq(file.witness.0));
+
+        // The last value is cut in half.
+        let mut reader = WtnsReader::<_, 32>::new(&data[..data.len() - 16]).unwrap();
+        let values: Vec<_> = reader.values().collect();
+        assert_eq!(values.len(), 5);
+        assert!(matches!(
+            values[4],
+            Err(WtnsError::TruncatedWitness {
+                index: 4,
+                witness_len: 5,
+                offset,
+            }) if offset == data.len() as u64 - 32
+        ));
+    }
+}
@@ -0,0 +1,35 @@
+//! Handling of buffers holding witness values, zeroized once given up with the `zeroize`
+//! feature. Without it, these are plain vector operations.
+
+use alloc::vec::Vec;
+
+/// Zeroizes and empties `buf`.
+#[cfg(feature = "zeroize")]
+pub(crate) fn scrub<T: zeroize::Zeroize>(buf: &mut Vec<T>) {
+    zeroize::Zeroize::zeroize(buf);
+}
+
+#[cfg(not(feature = "zeroize"))]
+pub(crate) fn scrub<T>(buf: &mut Vec<T>) {
+    buf.clear();
+}
+
+/// Reserves room for `additional` more elements. When `buf` has to grow, the elements are moved
+/// to a new buffer at least twice as large and the old one is zeroized, instead of being left in
+/// freed memory by a reallocation.
+#[cfg(feature = "zeroize")]
+pub(crate) fn reserve<T: Copy + zeroize::Zeroize>(buf: &mut Vec<T>, additional: usize) {
+    if buf.capacity() - buf.len() >= additional {
+        return;
+    }
+
+    let mut grown = Vec::with_capacity((buf.len() + additional).max(2 * buf.capacity()));
+    grown.extend_from_slice(buf);
+    scrub(buf);
+    *buf = grown;
+}
+
+#[cfg(not(feature = "zeroize"))]
+pub(crate) fn reserve<T>(buf: &mut Vec<T>, additional: usize) {
+    buf.reserve(additional);
+}
@@ -0,0 +1,86 @@
+//! serde support, behind the `serde` feature.
+//!
+//! Field elements are encoded by zkp-fields: lowercase hexadecimal strings in human-readable
+//! formats and `FS` little-endian bytes in binary ones. A deserialized [`WtnsFile`] is checked
+//! like a file to be written, see [`WtnsFile::write`].
+
+use std::convert::TryFrom;
+
+use serde::Deserialize;
+
+use crate::{Header, Witness, WtnsError, WtnsFile};
+
+/// The fields of a [`WtnsFile`], before the header is checked against the witness.
+#[derive(Deserialize)]
+pub(crate) struct WtnsFileData<const FS: usize> {
+    version: u32,
+    header: Header<FS>,
+    witness: Witness<FS>,
+}
+
+impl<const FS: usize> TryFrom<WtnsFileData<FS>> for WtnsFile<FS> {
+    type Error = WtnsError;
+
+    fn try_from(data: WtnsFileData<FS>) -> Result<Self, Self::Error> {
+        let header = &data.header;
+        if header.field_size != FS as u32 {
+            return Err(WtnsError::HeaderFieldSize {
+                expected: FS as u32,
+                found: header.field_size,
+            });
+        }
+        if header.witness_len as usize != data.witness.0.len() {
+            return Err(WtnsError::WitnessLengthMismatch {
+                header: header.witness_len,
+                found: data.witness.0.len(),
+            });
+        }
+
+        Ok(WtnsFile {
+            version: data.version,
+            header: data.header,
+            witness: data.witness,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FieldElement, WtnsFile};
+
+    #[test]
+    fn test_serde_round_trip() {
+        let file = WtnsFile::<8>::from_vec(
+            vec![FieldElement::from_u64(1), FieldElement::from_u64(0xab)],
+            FieldElement::from_u64(0xfffffffb),
+        );
+
+        let json = serde_json::to_string(&file).unwrap();
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"version":1,"header":{"field_size":8,"prime":"00000000fffffffb","witness_len":2},"#,
+                r#""witness":["0000000000000001","00000000000000ab"]}"#
+            )
+        );
+        assert_eq!(serde_json::from_str::<WtnsFile<8>>(&json).unwrap(), file);
+
+        let binary = bincode::serialize(&file).unwrap();
+        let mut expected = vec![1, 0, 0, 0, 8, 0, 0, 0];
+        expected.extend_from_slice(&[8, 0, 0, 0, 0, 0, 0, 0, 0xfb, 0xff, 0xff, 0xff, 0, 0, 0, 0]);
+        expected.extend_from_slice(&[2, 0, 0, 0]);
+        expected.extend_from_slice(&[2, 0, 0, 0, 0, 0, 0, 0]);
+        expected.extend_from_slice(&[8, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0]);
+        expected.extend_from_slice(&[8, 0, 0, 0, 0, 0, 0, 0, 0xab, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(binary, expected);
+        assert_eq!(bincode::deserialize::<WtnsFile<8>>(&binary).unwrap(), file);
+
+        let wrong_len = json.replace(r#""witness_len":2"#, r#""witness_len":3"#);
+        assert!(serde_json::from_str::<WtnsFile<8>>(&wrong_len).is_err());
+        let wrong_size = json.replace(r#""field_size":8"#, r#""field_size":32"#);
+        assert!(serde_json::from_str::<WtnsFile<8>>(&wrong_size).is_err());
+        let short_value = json.replace("00000000000000ab", "00ab");
+        assert!(serde_json::from_str::<WtnsFile<8>>(&short_value).is_err());
+        assert!(bincode::deserialize::<WtnsFile<32>>(&binary).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+//! Witnesses built from a few assigned values.
+
+use std::collections::hash_map::{Entry, HashMap};
+
+use crate::{FieldElement, Result, WtnsError, WtnsFile};
+
+/// Options for [`WtnsFile::from_sparse_with`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SparseWitnessOptions {
+    /// Leave the value at index 0 to zero instead of one, for witnesses not computed by circom.
+    pub zero_first: bool,
+}
+
+impl<const FS: usize> WtnsFile<FS> {
+    /// A witness of `len` values with index 0 set to one, the assigned `entries` and zeros
+    /// elsewhere.
+    ///
+    /// Fails on an index not below `len`, on an index assigned twice and on a value not below
+    /// `prime`.
+    pub fn from_sparse(
+        len: u32,
+        prime: FieldElement<FS>,
+        entries: impl IntoIterator<Item = (u32, FieldElement<FS>)>,
+    ) -> Result<Self> {
+        Self::from_sparse_with(len, prime, entries, &SparseWitnessOptions::default())
+    }
+
+    /// Like [`WtnsFile::from_sparse`], with `opts`.
+    pub fn from_sparse_with(
+        len: u32,
+        prime: FieldElement<FS>,
+        entries: impl IntoIterator<Item = (u32, FieldElement<FS>)>,
+        opts: &SparseWitnessOptions,
+    ) -> Result<Self> {
+        let mut witness = vec![FieldElement::from_u64(0); len as usize];
+        if let (Some(first), false) = (witness.first_mut(), opts.zero_first) {
+            *first = FieldElement::from_u64(1);
+        }
+
+        // Position in `entries` of each assigned index.
+        let mut assigned = HashMap::new();
+        for (position, (index, value)) in entries.into_iter().enumerate() {
+            let slot = witness
+                .get_mut(index as usize)
+                .ok_or(WtnsError::IndexOutOfRange { index, len })?;
+            if value >= prime {
+                return Err(WtnsError::NonCanonicalValue {
+                    index: index as usize,
+                });
+            }
+            match assigned.entry(index) {
+                Entry::Occupied(first) => {
+                    return Err(WtnsError::DuplicateAssignment {
+                        index,
+                        first: *first.get(),
+                        second: position,
+                    })
+                }
+                Entry::Vacant(entry) => entry.insert(position),
+            };
+
+            *slot = value;
+        }
+
+        Ok(WtnsFile::from_vec(witness, prime))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_sparse() {
+        let prime = FieldElement::<32>::from_u64(97);
+        let fe = FieldElement::from_u64;
+
+        let file = WtnsFile::from_sparse(5, prime, vec![(3, fe(7)), (1, fe(2))]).unwrap();
+        assert_eq!(file.witness.0, [fe(1), fe(2), fe(0), fe(7), fe(0)]);
+        assert_eq!(file.header.witness_len, 5);
+
+        let opts = SparseWitnessOptions { zero_first: true };
+        let file = WtnsFile::from_sparse_with(2, prime, vec![(1, fe(2))], &opts).unwrap();
+        assert_eq!(file.witness.0, [fe(0), fe(2)]);
+
+        assert!(matches!(
+            WtnsFile::from_sparse(5, prime, vec![(5, fe(1))]),
+            Err(WtnsError::IndexOutOfRange { index: 5, len: 5 })
+        ));
+        assert!(matches!(
+            WtnsFile::from_sparse(5, prime, vec![(2, fe(1)), (4, fe(1)), (2, fe(3))]),
+            Err(WtnsError::DuplicateAssignment {
+                index: 2,
+                first: 0,
+                second: 2
+            })
+        ));
+        assert!(matches!(
+            WtnsFile::from_sparse(5, prime, vec![(4, prime)]),
+            Err(WtnsError::NonCanonicalValue { index: 4 })
+        ));
+    }
+}
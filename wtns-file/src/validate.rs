@@ -0,0 +1,169 @@
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+
+use crate::{FieldElement, WtnsFile};
+
+/// An inconsistency found by [`WtnsFile::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WtnsValidationError {
+    /// The header field size differs from `FS`.
+    HeaderFieldSize { expected: u32, found: u32 },
+    /// The header witness length differs from the number of values.
+    WitnessLengthMismatch { header: u32, found: usize },
+    /// The header prime is zero, so no value can be below it.
+    ZeroPrime,
+    /// The value at `index` is not below the header prime.
+    NonCanonicalValue { index: usize },
+    /// The value at index 0, the constant wire, isn't 1, with
+    /// [`ValidateOptions::require_first_one`].
+    FirstNotOne,
+}
+
+/// Options for [`WtnsFile::validate_with`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidateOptions {
+    /// Require the value at index 0 to be 1, as in every witness computed by circom. Hand-built
+    /// witnesses may not follow it.
+    pub require_first_one: bool,
+}
+
+impl<const FS: usize> WtnsFile<FS> {
+    /// Checks the header against the values and that every value is below the prime, with the
+    /// default options.
+    pub fn validate(&self) -> Result<(), Vec<WtnsValidationError>> {
+        self.validate_with(&ValidateOptions::default())
+    }
+
+    /// Like [`WtnsFile::validate`], with `opts`.
+    ///
+    /// Every violation is reported, not just the first one.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "wtns_validate",
+            level = "debug",
+            skip_all,
+            fields(
+                witness_len = self.witness.0.len(),
+                findings = tracing::field::Empty
+            )
+        )
+    )]
+    pub fn validate_with(&self, opts: &ValidateOptions) -> Result<(), Vec<WtnsValidationError>> {
+        let mut errors = Vec::new();
+        let header = &self.header;
+        let witness = &self.witness.0;
+
+        if header.field_size != FS as u32 {
+            errors.push(WtnsValidationError::HeaderFieldSize {
+                expected: FS as u32,
+                found: header.field_size,
+            });
+        }
+        if header.witness_len as usize != witness.len() {
+            errors.push(WtnsValidationError::WitnessLengthMismatch {
+                header: header.witness_len,
+                found: witness.len(),
+            });
+        }
+
+        if header.prime.is_zero() {
+            errors.push(WtnsValidationError::ZeroPrime);
+        } else {
+            for (index, value) in witness.iter().enumerate() {
+                if value.cmp_value(&header.prime) != Ordering::Less {
+                    errors.push(WtnsValidationError::NonCanonicalValue { index });
+                }
+            }
+        }
+
+        if opts.require_first_one && witness.first() != Some(&FieldElement::from_u64(1)) {
+            errors.push(WtnsValidationError::FirstNotOne);
+        }
+
+        #[cfg(feature = "tracing")]
+        {
+            tracing::Span::current().record("findings", errors.len());
+            for error in &errors {
+                tracing::debug!(%error, "validation finding");
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl fmt::Display for WtnsValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WtnsValidationError::HeaderFieldSize { expected, found } => write!(
+                f,
+                "Header field size {} differs from the element size {}",
+                found, expected
+            ),
+            WtnsValidationError::WitnessLengthMismatch { header, found } => write!(
+                f,
+                "Header declares {} witness values but {} were found",
+                header, found
+            ),
+            WtnsValidationError::ZeroPrime => f.write_str("The prime is zero"),
+            WtnsValidationError::NonCanonicalValue { index } => {
+                write!(f, "Witness value {} is not below the prime", index)
+            }
+            WtnsValidationError::FirstNotOne => f.write_str("The first witness value isn't 1"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WtnsValidationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let data = std::fs::read("tests/witness.wtns").unwrap();
+        let mut file = WtnsFile::<32>::read(data.as_slice()).unwrap();
+        let strict = ValidateOptions {
+            require_first_one: true,
+        };
+        assert_eq!(file.validate(), Ok(()));
+        assert_eq!(file.validate_with(&strict), Ok(()));
+
+        file.witness.0[0] = FieldElement::from_u64(2);
+        file.witness.0[3] = file.header.prime;
+        file.witness.0.push(FieldElement::from([0xff; 32]));
+        file.header.field_size = 48;
+        assert_eq!(
+            file.validate_with(&strict).unwrap_err(),
+            [
+                WtnsValidationError::HeaderFieldSize {
+                    expected: 32,
+                    found: 48
+                },
+                WtnsValidationError::WitnessLengthMismatch {
+                    header: 5,
+                    found: 6
+                },
+                WtnsValidationError::NonCanonicalValue { index: 3 },
+                WtnsValidationError::NonCanonicalValue { index: 5 },
+                WtnsValidationError::FirstNotOne,
+            ]
+        );
+        assert_eq!(file.validate().unwrap_err().len(), 4);
+
+        file.header.prime = FieldElement::from_u64(0);
+        assert!(file
+            .validate()
+            .unwrap_err()
+            .contains(&WtnsValidationError::ZeroPrime));
+    }
+}
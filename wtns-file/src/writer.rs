@@ -0,0 +1,163 @@
+use std::io::{self, Seek, SeekFrom, Write};
+
+use byteorder::{LittleEndian, WriteBytesExt};
+use iden3_binfile::{BinFileWriter, PREAMBLE_SIZE, SECTION_HEADER_SIZE};
+
+use crate::{FieldElement, Header, Result, SectionType, WtnsError, MAGIC, VERSION};
+
+/// Streaming writer taking witness values one at a time.
+///
+/// The values are written as they come, producing the same bytes as [`crate::WtnsFile::write`].
+/// When the number of values is given up front, any writer will do and [`WtnsWriter::finish`]
+/// checks that it was met. Otherwise the header witness length and the witness section size are
+/// written by [`WtnsWriter::finish_seek`], seeking back once all the values are known.
+pub struct WtnsWriter<W, const FS: usize> {
+    file: BinFileWriter<W>,
+    expected_len: Option<u32>,
+    written: u32,
+}
+
+impl<W: Write, const FS: usize> WtnsWriter<W, FS> {
+    /// Writes the header, with a witness length of 0 if `expected_len` is `None`.
+    pub fn new(w: W, prime: FieldElement<FS>, expected_len: Option<u32>) -> Result<Self> {
+        let mut file = BinFileWriter::new(w, MAGIC, VERSION, 2)?;
+        let header = Header {
+            field_size: FS as u32,
+            prime,
+            witness_len: expected_len.unwrap_or(0),
+        };
+        file.write_section(SectionType::Header as u32, Header::<FS>::size(), |w| {
+            header.write_body(w)
+        })?;
+
+        let id = SectionType::Witness as u32;
+        match expected_len {
+            Some(len) => file.begin_sized_section(id, len as u64 * FS as u64)?,
+            None => file.begin_section(id)?,
+        }
+
+        Ok(WtnsWriter {
+            file,
+            expected_len,
+            written: 0,
+        })
+    }
+
+    /// Writes the next value, failing once the expected number of values has been written.
+    pub fn write_value(&mut self, value: &FieldElement<FS>) -> Result<()> {
+        let len = self.expected_len.unwrap_or(u32::MAX);
+        if self.written >= len {
+            return Err(WtnsError::IndexOutOfRange {
+                index: self.written,
+                len,
+            });
+        }
+
+        value.write(&mut self.file)?;
+        self.written += 1;
+
+        Ok(())
+    }
+
+    /// The number of values written so far.
+    pub fn written(&self) -> u32 {
+        self.written
+    }
+
+    /// Checks that the expected number of values was written and returns the writer.
+    ///
+    /// Fails if no length was given to [`WtnsWriter::new`], use [`WtnsWriter::finish_seek`] then.
+    pub fn finish(mut self) -> Result<W> {
+        let expected_len = self.expected_len.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The witness length must be given up front to finish without seeking",
+            )
+        })?;
+        self.check_len(expected_len)?;
+        self.file.end_sized_section()?;
+
+        Ok(self.file.finish()?)
+    }
+
+    fn check_len(&self, expected_len: u32) -> Result<()> {
+        if self.written != expected_len {
+            return Err(WtnsError::WitnessLengthMismatch {
+                header: expected_len,
+                found: self.written as usize,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: Write + Seek, const FS: usize> WtnsWriter<W, FS> {
+    /// Like [`WtnsWriter::finish`], but if no length was given to [`WtnsWriter::new`], seeks back
+    /// to write the number of values written in the header and the witness section size.
+    pub fn finish_seek(mut self) -> Result<W> {
+        match self.expected_len {
+            Some(expected_len) => {
+                self.check_len(expected_len)?;
+                self.file.end_sized_section()?;
+            }
+            None => {
+                self.file.end_section()?;
+
+                // The witness length closes the header section body.
+                let end = self.file.stream_position()?;
+                let witness_len = PREAMBLE_SIZE + SECTION_HEADER_SIZE + Header::<FS>::size() - 4;
+                self.file.seek(SeekFrom::Start(witness_len))?;
+                self.file.write_u32::<LittleEndian>(self.written)?;
+                self.file.seek(SeekFrom::Start(end))?;
+            }
+        }
+
+        Ok(self.file.finish()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WtnsFile;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_stream_values() {
+        let data = std::fs::read("tests/witness.wtns").unwrap();
+        let file = WtnsFile::<32>::read(data.as_slice()).unwrap();
+        let file = WtnsFile::from_vec(file.witness.0, file.header.prime);
+        let mut expected = Vec::new();
+        file.write(&mut expected).unwrap();
+
+        let len = Some(file.header.witness_len);
+        let mut w = WtnsWriter::new(Vec::new(), file.header.prime, len).unwrap();
+        for value in &file.witness.0 {
+            w.write_value(value).unwrap();
+        }
+        assert!(matches!(
+            w.write_value(&file.header.prime),
+            Err(WtnsError::IndexOutOfRange { index: 5, len: 5 })
+        ));
+        assert_eq!(w.finish().unwrap(), expected);
+
+        let mut w = WtnsWriter::new(Cursor::new(Vec::new()), file.header.prime, None).unwrap();
+        for value in &file.witness.0 {
+            w.write_value(value).unwrap();
+        }
+        assert_eq!(w.finish_seek().unwrap().into_inner(), expected);
+
+        let mut w = WtnsWriter::new(Vec::new(), file.header.prime, len).unwrap();
+        w.write_value(&file.witness.0[0]).unwrap();
+        assert!(matches!(
+            w.finish(),
+            Err(WtnsError::WitnessLengthMismatch {
+                header: 5,
+                found: 1
+            })
+        ));
+        let w = WtnsWriter::<_, 32>::new(Vec::new(), file.header.prime, None).unwrap();
+        assert!(w.finish().is_err());
+    }
+}
@@ -0,0 +1,52 @@
+//! zeroize support, behind the `zeroize` feature.
+//!
+//! Zeroizing a witness empties it, the memory of its values being overwritten with zeros. The
+//! types are not zeroized on drop, as that would prevent moving the values out of them: wrap them
+//! in [`zeroize::Zeroizing`] for that.
+//!
+//! With the feature, the buffers used while reading and writing files are zeroized too, on
+//! success and on error. What is handed to a [`std::io::Write`], and the buffers of the
+//! readers, writers and JSON encodings involved, are out of reach and not zeroized.
+
+use zeroize::Zeroize;
+
+use crate::{Witness, WtnsFile};
+
+impl<const FS: usize> Zeroize for Witness<FS> {
+    /// Zeroizes the values, including the spare capacity, and empties the witness.
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<const FS: usize> Zeroize for WtnsFile<FS> {
+    /// Zeroizes the witness and sets the header witness length to zero, to match it. The prime is
+    /// kept.
+    fn zeroize(&mut self) {
+        self.witness.zeroize();
+        self.header.witness_len = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FieldElement;
+
+    #[test]
+    fn test_zeroize() {
+        let mut file = WtnsFile::<32>::read_file("tests/witness.wtns").unwrap();
+        let prime = file.header.prime;
+        let values = file.witness.0.as_ptr();
+        let capacity = file.witness.0.capacity();
+
+        file.zeroize();
+        assert!(file.witness.is_empty());
+        assert_eq!(file.header.witness_len, 0);
+        assert_eq!(file.header.prime, prime);
+        assert_eq!(file.witness.0.capacity(), capacity);
+        // SAFETY: the buffer is still allocated, and its elements were initialized by the read.
+        let spare = unsafe { std::slice::from_raw_parts(values, capacity) };
+        assert!(spare.iter().all(FieldElement::is_zero));
+    }
+}
@@ -0,0 +1,36 @@
+//! The `no_std` API, parsing and writing through byte slices and vectors. Run with
+//! `--no-default-features` to build the crate without `std`.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use wtns_file::{FieldElement, WtnsError, WtnsFile};
+
+const FIXTURE: &[u8] = include_bytes!("witness.wtns");
+
+#[test]
+fn test_read_slice() {
+    let file = WtnsFile::<32>::read(FIXTURE).unwrap();
+    assert_eq!(file.header.witness_len as usize, file.witness.len());
+    assert_eq!(file.witness[0], FieldElement::from_u64(1));
+    assert!(file.validate().is_ok());
+
+    let mut written = Vec::new();
+    file.write(&mut written).unwrap();
+    assert_eq!(WtnsFile::<32>::read(written.as_slice()).unwrap(), file);
+}
+
+#[test]
+fn test_read_slice_errors() {
+    assert!(matches!(
+        WtnsFile::<32>::read(&FIXTURE[..FIXTURE.len() - 1]),
+        Err(WtnsError::UnexpectedEof { .. })
+    ));
+    assert!(matches!(
+        WtnsFile::<32>::read(&b"r1cs"[..]),
+        Err(WtnsError::InvalidMagic { .. })
+    ));
+}
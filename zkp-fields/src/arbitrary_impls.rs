@@ -0,0 +1,86 @@
+//! arbitrary support, behind the `arbitrary` feature.
+//!
+//! [`Arbitrary`] produces any `FS` bytes. Fuzz targets generating whole files want elements below
+//! the file's prime instead, see [`FieldElement::arbitrary_below`].
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::FieldElement;
+
+impl<'a, const FS: usize> Arbitrary<'a> for FieldElement<FS> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let () = Self::SUPPORTED_SIZE;
+
+        let mut bytes = [0; FS];
+        u.fill_buffer(&mut bytes)?;
+
+        Ok(FieldElement(bytes))
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (FS, Some(FS))
+    }
+}
+
+impl<const FS: usize> FieldElement<FS> {
+    /// An arbitrary odd modulus above 2, to generate elements with
+    /// [`FieldElement::arbitrary_below`]. It isn't necessarily prime.
+    pub fn arbitrary_modulus(u: &mut Unstructured<'_>) -> Result<Self> {
+        let mut modulus = Self::arbitrary(u)?;
+        modulus.0[0] |= 1;
+        if modulus.0[1..].iter().all(|&b| b == 0) && modulus.0[0] == 1 {
+            modulus.0[0] = 3;
+        }
+
+        Ok(modulus)
+    }
+
+    /// An arbitrary element below `modulus`, which must not be zero.
+    pub fn arbitrary_below(u: &mut Unstructured<'_>, modulus: &Self) -> Result<Self> {
+        let top = modulus
+            .0
+            .iter()
+            .rposition(|&b| b != 0)
+            .expect("the modulus is not zero");
+
+        // Pick the most significant byte up to the modulus's, and clear it if the element ends up
+        // too large, which can only happen when both are equal.
+        let mut bytes = [0; FS];
+        u.fill_buffer(&mut bytes[..top])?;
+        bytes[top] = u.int_in_range(0..=modulus.0[top])?;
+        let mut value = FieldElement(bytes);
+        if value >= *modulus {
+            value.0[top] = 0;
+        }
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arbitrary_below() {
+        let data: Vec<u8> = (0..=255).cycle().take(4096).collect();
+        let mut u = Unstructured::new(&data);
+        for _ in 0..64 {
+            let modulus = FieldElement::<8>::arbitrary_modulus(&mut u).unwrap();
+            assert!(modulus > FieldElement::from_u64(2));
+            let value = FieldElement::arbitrary_below(&mut u, &modulus).unwrap();
+            assert!(value < modulus);
+        }
+
+        let small = FieldElement::<8>::from_u64(3);
+        let mut exhausted = Unstructured::new(&[]);
+        assert_eq!(
+            FieldElement::arbitrary_below(&mut exhausted, &small).unwrap(),
+            FieldElement::from_u64(0)
+        );
+        assert_eq!(
+            FieldElement::<8>::arbitrary_modulus(&mut exhausted).unwrap(),
+            small
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Conversions to and from arkworks prime fields, behind the `ark` feature.
+
+use core::fmt;
+
+use ark_ff::{BigInteger, PrimeField};
+
+use crate::FieldElement;
+
+/// A value that can't be converted between a [`FieldElement`] and an arkworks field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldConversionError {
+    /// The value is not below the modulus of the field it's converted into.
+    NotInField,
+    /// A field value doesn't fit in `field_size` bytes.
+    ValueTooWide { field_size: usize },
+}
+
+impl fmt::Display for FieldConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldConversionError::NotInField => {
+                f.write_str("The value is not below the field modulus")
+            }
+            FieldConversionError::ValueTooWide { field_size } => {
+                write!(f, "The value doesn't fit in {} bytes", field_size)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FieldConversionError {}
+
+impl<const FS: usize> FieldElement<FS> {
+    /// Converts the element into `F`.
+    ///
+    /// Fails with [`FieldConversionError::NotInField`] if the value is not below the modulus of
+    /// `F`.
+    pub fn to_field<F: PrimeField>(&self) -> Result<F, FieldConversionError> {
+        let mut bigint = F::BigInt::default();
+        let limbs = bigint.as_mut();
+        for (i, chunk) in self.0.chunks(8).enumerate() {
+            let mut bytes = [0u8; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            let limb = u64::from_le_bytes(bytes);
+
+            match limbs.get_mut(i) {
+                Some(l) => *l = limb,
+                None if limb == 0 => {}
+                None => return Err(FieldConversionError::NotInField),
+            }
+        }
+
+        F::from_bigint(bigint).ok_or(FieldConversionError::NotInField)
+    }
+
+    /// Converts a field value into an element.
+    ///
+    /// Fails with [`FieldConversionError::ValueTooWide`] if the value doesn't fit in `FS` bytes.
+    pub fn from_field<F: PrimeField>(f: F) -> Result<Self, FieldConversionError> {
+        Self::from_bigint(&f.into_bigint())
+    }
+
+    /// Converts an arkworks big integer, such as the `MODULUS` of a field, into an element.
+    ///
+    /// Fails with [`FieldConversionError::ValueTooWide`] if the value doesn't fit in `FS` bytes.
+    /// Nothing is allocated, so this is fine to call on every value of a large witness.
+    pub fn from_bigint<B: BigInteger>(b: &B) -> Result<Self, FieldConversionError> {
+        let () = Self::SUPPORTED_SIZE;
+
+        let mut bytes = [0u8; FS];
+        for (i, limb) in b.as_ref().iter().enumerate() {
+            let le = limb.to_le_bytes();
+            let start = (i * 8).min(FS);
+            let len = (FS - start).min(8);
+            if le[len..].iter().any(|byte| *byte != 0) {
+                return Err(FieldConversionError::ValueTooWide { field_size: FS });
+            }
+
+            bytes[start..start + len].copy_from_slice(&le[..len]);
+        }
+
+        Ok(FieldElement(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primes::BN254_SCALAR;
+    use ark_bn254::Fr;
+
+    #[test]
+    fn test_field_conversions() {
+        assert_eq!(
+            FieldElement::<32>::from_u64(6).to_field::<Fr>(),
+            Ok(Fr::from(6u64))
+        );
+
+        let minus_one = -Fr::from(1u64);
+        let fe = FieldElement::<32>::from_field(minus_one).unwrap();
+        assert_eq!(fe.to_field::<Fr>(), Ok(minus_one));
+        assert_eq!(
+            FieldElement::<8>::from_field(minus_one),
+            Err(FieldConversionError::ValueTooWide { field_size: 8 })
+        );
+        assert_eq!(
+            FieldElement::<32>::from_bigint(&Fr::MODULUS),
+            Ok(BN254_SCALAR)
+        );
+        assert_eq!(
+            FieldElement::<4>::from_bigint(&Fr::MODULUS),
+            Err(FieldConversionError::ValueTooWide { field_size: 4 })
+        );
+        assert_eq!(
+            BN254_SCALAR.to_field::<Fr>(),
+            Err(FieldConversionError::NotInField)
+        );
+        assert_eq!(
+            FieldElement::<64>::from([0xff; 64]).to_field::<Fr>(),
+            Err(FieldConversionError::NotInField)
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Conversions between [`FieldElement`] and [`BigUint`], behind the `bigint` feature.
+
+use alloc::string::{String, ToString};
+
+use num_bigint::BigUint;
+
+use crate::{FieldElement, ParseFieldElementError};
+
+impl<const FS: usize> FieldElement<FS> {
+    /// Converts `value`, failing if it doesn't fit in `FS` bytes.
+    pub fn from_biguint(value: &BigUint) -> Result<Self, ParseFieldElementError> {
+        let () = Self::SUPPORTED_SIZE;
+
+        let le = value.to_bytes_le();
+        // Zero is encoded as a single zero byte.
+        let len = if value.bits() == 0 { 0 } else { le.len() };
+        if len > FS {
+            return Err(ParseFieldElementError::WrongSize {
+                expected: FS,
+                found: len,
+            });
+        }
+
+        let mut bytes = [0u8; FS];
+        bytes[..len].copy_from_slice(&le[..len]);
+        Ok(FieldElement(bytes))
+    }
+
+    pub fn to_biguint(&self) -> BigUint {
+        BigUint::from_bytes_le(&self.0)
+    }
+
+    /// Parses a decimal string, failing if the value doesn't fit in `FS` bytes.
+    pub fn from_dec_str(s: &str) -> Result<Self, ParseFieldElementError> {
+        if s.is_empty() {
+            return Err(ParseFieldElementError::Empty);
+        }
+        if let Some((index, ch)) = s.char_indices().find(|(_, ch)| !ch.is_ascii_digit()) {
+            return Err(ParseFieldElementError::InvalidChar { ch, index });
+        }
+
+        let value = BigUint::parse_bytes(s.as_bytes(), 10).expect("checked to be decimal digits");
+        Self::from_biguint(&value)
+    }
+
+    pub fn to_dec_string(&self) -> String {
+        self.to_biguint().to_string()
+    }
+}
+
+impl<const FS: usize> From<FieldElement<FS>> for BigUint {
+    fn from(e: FieldElement<FS>) -> Self {
+        e.to_biguint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_biguint() {
+        let zero = FieldElement::<8>::from_u64(0);
+        assert_eq!(zero.to_biguint(), BigUint::from(0u32));
+        assert_eq!(FieldElement::from_biguint(&BigUint::from(0u32)), Ok(zero));
+        assert_eq!(zero.to_dec_string(), "0");
+
+        let max = FieldElement::<8>::from_u64(u64::MAX);
+        assert_eq!(max.to_biguint(), BigUint::from(u64::MAX));
+        assert_eq!(
+            FieldElement::from_biguint(&BigUint::from(u64::MAX)),
+            Ok(max)
+        );
+        assert_eq!(
+            FieldElement::<8>::from_biguint(&(BigUint::from(1u32) << 64usize)),
+            Err(ParseFieldElementError::WrongSize {
+                expected: 8,
+                found: 9
+            })
+        );
+    }
+
+    #[test]
+    fn test_dec_str() {
+        let prime = FieldElement::<32>::bn254_scalar_prime();
+        let dec = "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+        assert_eq!(prime.to_dec_string(), dec);
+        assert_eq!(FieldElement::from_dec_str(dec), Ok(prime));
+        assert_eq!(
+            FieldElement::<32>::from_dec_str("0007"),
+            Ok(FieldElement::from_u64(7))
+        );
+
+        // 2^256 doesn't fit.
+        let too_large = (BigUint::from(1u32) << 256usize).to_string();
+        assert!(matches!(
+            FieldElement::<32>::from_dec_str(&too_large),
+            Err(ParseFieldElementError::WrongSize { found: 33, .. })
+        ));
+        assert_eq!(
+            FieldElement::<32>::from_dec_str(""),
+            Err(ParseFieldElementError::Empty)
+        );
+        assert_eq!(
+            FieldElement::<32>::from_dec_str("12a"),
+            Err(ParseFieldElementError::InvalidChar { ch: 'a', index: 2 })
+        );
+    }
+}
@@ -0,0 +1,28 @@
+//! bytemuck support, behind the `bytemuck` feature.
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::FieldElement;
+
+// SAFETY: an element is a transparent wrapper over `[u8; FS]`, for which all zeros is valid.
+unsafe impl<const FS: usize> Zeroable for FieldElement<FS> {}
+
+// SAFETY: an element is a transparent wrapper over `[u8; FS]`, which is `Copy`, has no padding
+// and accepts any byte pattern.
+unsafe impl<const FS: usize> Pod for FieldElement<FS> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::FieldElement;
+
+    #[test]
+    fn test_cast() {
+        let values = [
+            FieldElement::<4>::from([1, 0, 0, 0]),
+            FieldElement::from([2, 3, 0, 0]),
+        ];
+        let bytes: &[u8] = bytemuck::cast_slice(&values);
+        assert_eq!(bytes, [1, 0, 0, 0, 2, 3, 0, 0]);
+        assert_eq!(bytemuck::cast_slice::<u8, FieldElement<4>>(bytes), values);
+    }
+}
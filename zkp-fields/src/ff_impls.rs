@@ -0,0 +1,82 @@
+//! Conversions to and from `ff` prime fields, behind the `ff` feature.
+//!
+//! The `ff` crate leaves the byte order of `PrimeField::Repr` to the field; these conversions
+//! assume it's little-endian, as it is for the BLS12-381, BN254 and Pasta scalar fields.
+
+use ff::PrimeField;
+
+use crate::{Endianness, FieldElement};
+
+impl<const FS: usize> FieldElement<FS> {
+    /// Converts the element into `F`, or returns `None` if the value is not below the modulus of
+    /// `F`.
+    pub fn to_ff<F: PrimeField>(&self) -> Option<F> {
+        let mut repr = F::Repr::default();
+        copy_le(&self.0, repr.as_mut())?;
+
+        F::from_repr(repr).into()
+    }
+
+    /// Converts a field value into an element, or returns `None` if it doesn't fit in `FS` bytes.
+    pub fn from_ff<F: PrimeField>(f: F) -> Option<Self> {
+        let () = Self::SUPPORTED_SIZE;
+
+        let mut bytes = [0u8; FS];
+        copy_le(f.to_repr().as_ref(), &mut bytes)?;
+        Some(FieldElement(bytes))
+    }
+
+    /// Whether the element is the modulus of the `ff` field `F`.
+    pub fn is_ff_modulus<F: PrimeField>(&self) -> bool {
+        FieldElement::<FS>::from_hex(F::MODULUS, Endianness::Big).is_ok_and(|m| m == *self)
+    }
+}
+
+/// Copies little-endian `src` into `dst`, zero-extending it. Returns `None` if a nonzero byte of
+/// `src` doesn't fit.
+fn copy_le(src: &[u8], dst: &mut [u8]) -> Option<()> {
+    let len = src.len().min(dst.len());
+    if src[len..].iter().any(|b| *b != 0) {
+        return None;
+    }
+
+    dst[..len].copy_from_slice(&src[..len]);
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primes::{BLS12_381_SCALAR, BN254_SCALAR};
+    use bls12_381::Scalar;
+
+    #[test]
+    fn test_ff_conversions() {
+        assert_eq!(
+            FieldElement::<32>::from_u64(6).to_ff::<Scalar>(),
+            Some(Scalar::from(6))
+        );
+
+        let minus_one = -Scalar::one();
+        let fe = FieldElement::<32>::from_ff(minus_one).unwrap();
+        assert_eq!(fe.to_ff::<Scalar>(), Some(minus_one));
+        assert_eq!(FieldElement::<8>::from_ff(minus_one), None);
+        assert_eq!(
+            FieldElement::<8>::from_ff(Scalar::from(7)),
+            Some(FieldElement::from_u64(7))
+        );
+
+        assert_eq!(BLS12_381_SCALAR.to_ff::<Scalar>(), None);
+        assert_eq!(FieldElement::<64>::from([0xff; 64]).to_ff::<Scalar>(), None);
+        assert_eq!(
+            FieldElement::<48>::from_u64(1)
+                .widen::<64>()
+                .to_ff::<Scalar>(),
+            Some(Scalar::one())
+        );
+
+        assert!(BLS12_381_SCALAR.is_ff_modulus::<Scalar>());
+        assert!(BLS12_381_SCALAR.widen::<48>().is_ff_modulus::<Scalar>());
+        assert!(!BN254_SCALAR.is_ff_modulus::<Scalar>());
+    }
+}
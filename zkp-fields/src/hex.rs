@@ -0,0 +1,224 @@
+//! Hexadecimal and byte slice conversions for [`FieldElement`].
+
+use alloc::format;
+use alloc::string::String;
+use core::convert::TryFrom;
+use core::fmt;
+
+use crate::FieldElement;
+
+/// Byte order of the digits of a hexadecimal string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    /// Least significant byte first, the order of the bytes in the file.
+    Little,
+    /// Most significant byte first, the usual way of writing numbers.
+    Big,
+}
+
+/// Errors converting strings or byte slices into a [`FieldElement`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseFieldElementError {
+    /// The input string is empty.
+    Empty,
+    /// The number of hexadecimal digits is odd.
+    OddLength(usize),
+    /// A character is not a digit. `index` is its byte index in the input.
+    InvalidChar { ch: char, index: usize },
+    /// The input holds more bytes than fit in the element, or a byte slice has the wrong length.
+    WrongSize { expected: usize, found: usize },
+}
+
+impl fmt::Display for ParseFieldElementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseFieldElementError::Empty => f.write_str("Empty string"),
+            ParseFieldElementError::OddLength(len) => {
+                write!(f, "Odd number of hexadecimal digits: {}", len)
+            }
+            ParseFieldElementError::InvalidChar { ch, index } => {
+                write!(f, "Invalid digit {:?} at index {}", ch, index)
+            }
+            ParseFieldElementError::WrongSize { expected, found } => {
+                write!(
+                    f,
+                    "Wrong size: expected {} bytes, found {}",
+                    expected, found
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseFieldElementError {}
+
+impl<const FS: usize> FieldElement<FS> {
+    /// Parses a hexadecimal string, with or without `0x` prefix, in the byte order `endianness`.
+    ///
+    /// Strings shorter than `2 * FS` digits are zero-extended on the most significant side, so
+    /// `from_hex("0x05", Endianness::Big)` is 5.
+    pub fn from_hex(s: &str, endianness: Endianness) -> Result<Self, ParseFieldElementError> {
+        let () = Self::SUPPORTED_SIZE;
+
+        let (prefix, digits) = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(digits) => (2, digits),
+            None => (0, s),
+        };
+        if digits.len() % 2 != 0 {
+            return Err(ParseFieldElementError::OddLength(digits.len()));
+        }
+        let n = digits.len() / 2;
+        if n > FS {
+            return Err(ParseFieldElementError::WrongSize {
+                expected: FS,
+                found: n,
+            });
+        }
+
+        let mut values = digits.char_indices().map(|(index, ch)| {
+            ch.to_digit(16)
+                .map(|digit| digit as u8)
+                .ok_or(ParseFieldElementError::InvalidChar {
+                    ch,
+                    index: prefix + index,
+                })
+        });
+        let mut bytes = [0u8; FS];
+        for i in 0..n {
+            let high = values.next().expect("even number of digits")?;
+            let low = values.next().expect("even number of digits")?;
+            let position = match endianness {
+                Endianness::Little => i,
+                Endianness::Big => n - 1 - i,
+            };
+            bytes[position] = high << 4 | low;
+        }
+
+        Ok(FieldElement(bytes))
+    }
+
+    /// Formats the `FS` bytes as lowercase hexadecimal, without prefix, in the byte order
+    /// `endianness`.
+    pub fn to_hex(&self, endianness: Endianness) -> String {
+        match endianness {
+            Endianness::Little => self.0.iter().map(|b| format!("{:02x}", b)).collect(),
+            Endianness::Big => format!("{:x}", self),
+        }
+    }
+}
+
+impl<const FS: usize> TryFrom<&[u8]> for FieldElement<FS> {
+    type Error = ParseFieldElementError;
+
+    /// Reads `FS` little-endian bytes.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let () = Self::SUPPORTED_SIZE;
+
+        <[u8; FS]>::try_from(bytes).map(FieldElement).map_err(|_| {
+            ParseFieldElementError::WrongSize {
+                expected: FS,
+                found: bytes.len(),
+            }
+        })
+    }
+}
+
+/// Formats the value with all `2 * FS` digits, most significant first. The alternate flag adds
+/// a `0x` prefix.
+impl<const FS: usize> fmt::LowerHex for FieldElement<FS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        for byte in self.0.iter().rev() {
+            write!(f, "{:02x}", byte)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Formats the value with all `2 * FS` digits, most significant first. The alternate flag adds
+/// a `0x` prefix.
+impl<const FS: usize> fmt::UpperHex for FieldElement<FS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        for byte in self.0.iter().rev() {
+            write!(f, "{:02X}", byte)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex() {
+        let fe = FieldElement::<8>::from_u64(0x0102_0304_0506_07ab);
+        assert_eq!(fe.to_hex(Endianness::Big), "01020304050607ab");
+        assert_eq!(fe.to_hex(Endianness::Little), "ab07060504030201");
+        assert_eq!(format!("{:x}", fe), "01020304050607ab");
+        assert_eq!(format!("{:#X}", fe), "0x01020304050607AB");
+
+        for (s, endianness) in [
+            ("01020304050607ab", Endianness::Big),
+            ("0x01020304050607AB", Endianness::Big),
+            ("0Xab07060504030201", Endianness::Little),
+        ] {
+            assert_eq!(FieldElement::from_hex(s, endianness), Ok(fe));
+        }
+        assert_eq!(
+            FieldElement::<8>::from_hex("0x0105", Endianness::Big),
+            Ok(FieldElement::from_u64(0x105))
+        );
+        assert_eq!(
+            FieldElement::<8>::from_hex("0501", Endianness::Little),
+            Ok(FieldElement::from_u64(0x105))
+        );
+
+        assert_eq!(
+            FieldElement::<8>::from_hex("0x123", Endianness::Big),
+            Err(ParseFieldElementError::OddLength(3))
+        );
+        assert_eq!(
+            FieldElement::<8>::from_hex("0x12g4", Endianness::Big),
+            Err(ParseFieldElementError::InvalidChar { ch: 'g', index: 4 })
+        );
+        assert_eq!(
+            FieldElement::<8>::from_hex("000102030405060708", Endianness::Big),
+            Err(ParseFieldElementError::WrongSize {
+                expected: 8,
+                found: 9
+            })
+        );
+        assert_eq!(
+            FieldElement::<8>::from_hex("0x12g4", Endianness::Big)
+                .unwrap_err()
+                .to_string(),
+            "Invalid digit 'g' at index 4"
+        );
+    }
+
+    #[test]
+    fn test_try_from_slice() {
+        let bytes = [1u8, 2, 3, 4, 5, 6, 7, 8, 9];
+        assert_eq!(
+            FieldElement::<8>::try_from(&bytes[..8]),
+            Ok(FieldElement::from(*b"\x01\x02\x03\x04\x05\x06\x07\x08"))
+        );
+        assert_eq!(
+            FieldElement::<8>::try_from(&bytes[..]),
+            Err(ParseFieldElementError::WrongSize {
+                expected: 8,
+                found: 9
+            })
+        );
+    }
+}
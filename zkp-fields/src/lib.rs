@@ -0,0 +1,494 @@
+//! Fixed-size prime field elements shared by the r1cs-file and wtns-file crates.
+//!
+//! The crate is `no_std` with `alloc` when the default `std` feature is disabled, which only
+//! removes reading and writing through [`std::io`].
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use core::convert::{TryFrom, TryInto};
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls;
+#[cfg(feature = "ark")]
+mod ark;
+#[cfg(feature = "bigint")]
+mod bigint;
+#[cfg(feature = "bytemuck")]
+mod bytemuck_impls;
+#[cfg(feature = "ff")]
+mod ff_impls;
+mod hex;
+pub mod primes;
+#[cfg(feature = "serde")]
+mod serde_impls;
+#[cfg(feature = "zeroize")]
+mod zeroize_impls;
+
+#[cfg(feature = "ark")]
+pub use ark::FieldConversionError;
+pub use hex::{Endianness, ParseFieldElementError};
+pub use primes::Curve;
+
+/// Smallest field element size in bytes this crate supports.
+pub const MIN_SUPPORTED_FIELD_SIZE: usize = 4;
+/// Largest field element size in bytes this crate supports.
+pub const MAX_SUPPORTED_FIELD_SIZE: usize = 128;
+
+/// A field element stored as `FS` little-endian bytes.
+///
+/// `FS` must be a multiple of 4 between [`MIN_SUPPORTED_FIELD_SIZE`] and
+/// [`MAX_SUPPORTED_FIELD_SIZE`]; other sizes are rejected at compile time by the constructors and
+/// the parser.
+///
+/// The element is laid out exactly as its bytes, so slices of elements can be viewed as flat byte
+/// buffers, see [`FieldElement::slice_as_bytes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct FieldElement<const FS: usize>([u8; FS]);
+
+impl<const FS: usize> FieldElement<FS> {
+    const SUPPORTED_SIZE: () = assert!(
+        FS >= MIN_SUPPORTED_FIELD_SIZE && FS <= MAX_SUPPORTED_FIELD_SIZE && FS.is_multiple_of(4),
+        "unsupported field size: FS must be a multiple of 4 in 4..=128"
+    );
+
+    /// Builds an element from little-endian 64-bit limbs.
+    ///
+    /// The number of limbs must be exactly `FS / 8`, which is checked at compile time.
+    pub const fn from_limbs_le<const N: usize>(limbs: [u64; N]) -> Self {
+        let () = Self::SUPPORTED_SIZE;
+        let () = LimbCount::<FS, N>::MATCHES_FIELD_SIZE;
+
+        let mut bytes = [0u8; FS];
+        let mut i = 0;
+        while i < N {
+            let limb = limbs[i].to_le_bytes();
+            let mut j = 0;
+            while j < 8 {
+                bytes[i * 8 + j] = limb[j];
+                j += 1;
+            }
+            i += 1;
+        }
+
+        FieldElement(bytes)
+    }
+
+    /// Builds an element holding the integer `value`.
+    pub const fn from_u64(value: u64) -> Self {
+        let () = Self::SUPPORTED_SIZE;
+        let () = LimbCount::<FS, 1>::FITS_FIELD_SIZE;
+
+        let mut bytes = [0u8; FS];
+        let value = value.to_le_bytes();
+        let mut i = 0;
+        while i < 8 {
+            bytes[i] = value[i];
+            i += 1;
+        }
+
+        FieldElement(bytes)
+    }
+
+    /// Builds an element holding the integer `value`.
+    pub const fn from_u128(value: u128) -> Self {
+        let () = Self::SUPPORTED_SIZE;
+        let () = LimbCount::<FS, 2>::FITS_FIELD_SIZE;
+
+        let mut bytes = [0u8; FS];
+        let value = value.to_le_bytes();
+        let mut i = 0;
+        while i < 16 {
+            bytes[i] = value[i];
+            i += 1;
+        }
+
+        FieldElement(bytes)
+    }
+
+    /// Builds an element from its big-endian byte representation.
+    pub fn from_be_bytes(mut bytes: [u8; FS]) -> Self {
+        let () = Self::SUPPORTED_SIZE;
+
+        bytes.reverse();
+        FieldElement(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0[..]
+    }
+
+    /// The bytes of `values` back to back, without copying.
+    pub fn slice_as_bytes(values: &[Self]) -> &[u8] {
+        // SAFETY: an element is a transparent wrapper over `[u8; FS]`, which has no padding and an
+        // alignment of 1, so `values` is made of `values.len() * FS` initialized bytes.
+        unsafe { core::slice::from_raw_parts(values.as_ptr().cast::<u8>(), values.len() * FS) }
+    }
+
+    /// Views `bytes` as elements of `FS` bytes each, without copying, or returns `None` if the
+    /// length is not a multiple of `FS`.
+    pub fn slice_from_bytes(bytes: &[u8]) -> Option<&[Self]> {
+        let () = Self::SUPPORTED_SIZE;
+
+        if !bytes.len().is_multiple_of(FS) {
+            return None;
+        }
+
+        // SAFETY: an element is a transparent wrapper over `[u8; FS]`, with an alignment of 1 and
+        // any byte pattern valid, and `bytes` holds exactly `bytes.len() / FS` of them.
+        Some(unsafe {
+            core::slice::from_raw_parts(bytes.as_ptr().cast::<Self>(), bytes.len() / FS)
+        })
+    }
+
+    pub fn to_be_bytes(&self) -> [u8; FS] {
+        let mut bytes = self.0;
+        bytes.reverse();
+        bytes
+    }
+
+    /// Returns the value as a `u64`, or `None` if it doesn't fit.
+    pub fn to_u64(&self) -> Option<u64> {
+        let mut bytes = [0u8; 8];
+        let len = FS.min(8);
+        bytes[..len].copy_from_slice(&self.0[..len]);
+
+        if self.0[len..].iter().all(|b| *b == 0) {
+            Some(u64::from_le_bytes(bytes))
+        } else {
+            None
+        }
+    }
+
+    /// Returns all little-endian 64-bit limbs.
+    ///
+    /// The number of limbs must be exactly `FS / 8`, which is checked at compile time.
+    pub fn as_u64_limbs<const N: usize>(&self) -> [u64; N] {
+        let () = LimbCount::<FS, N>::MATCHES_FIELD_SIZE;
+
+        let mut limbs = [0u64; N];
+        for (limb, bytes) in limbs.iter_mut().zip(self.0.chunks_exact(8)) {
+            *limb = u64::from_le_bytes(bytes.try_into().expect("chunks are 8 bytes long"));
+        }
+
+        limbs
+    }
+
+    /// Zero-extends the element to `N` bytes.
+    ///
+    /// `N` must not be below `FS`, which is checked at compile time.
+    pub const fn widen<const N: usize>(&self) -> FieldElement<N> {
+        let () = FieldElement::<N>::SUPPORTED_SIZE;
+        let () = Width::<FS, N>::NOT_NARROWER;
+
+        let mut bytes = [0u8; N];
+        let mut i = 0;
+        while i < FS {
+            bytes[i] = self.0[i];
+            i += 1;
+        }
+
+        FieldElement(bytes)
+    }
+
+    /// Truncates the element to `N` bytes, or returns `None` if any dropped byte is nonzero.
+    ///
+    /// Truncating to a larger size zero-extends, same as [`FieldElement::widen`].
+    pub fn try_truncate<const N: usize>(&self) -> Option<FieldElement<N>> {
+        let () = FieldElement::<N>::SUPPORTED_SIZE;
+
+        let len = FS.min(N);
+        if self.0[len..].iter().any(|b| *b != 0) {
+            return None;
+        }
+
+        let mut bytes = [0u8; N];
+        bytes[..len].copy_from_slice(&self.0[..len]);
+        Some(FieldElement(bytes))
+    }
+
+    /// Returns the byte at index `i`, or `None` if `i >= FS`.
+    pub fn byte(&self, i: usize) -> Option<u8> {
+        self.0.get(i).copied()
+    }
+
+    /// Returns `N` bytes starting at `offset`, or `None` if the range does not fit into `FS`.
+    pub fn chunk<const N: usize>(&self, offset: usize) -> Option<&[u8; N]> {
+        let end = offset.checked_add(N)?;
+        let slice = self.0.get(offset..end)?;
+
+        // The slice is exactly N bytes long, so the conversion can't fail.
+        <&[u8; N]>::try_from(slice).ok()
+    }
+
+    /// Returns the `i`-th little-endian 64-bit limb, or `None` if it does not fit into `FS`.
+    pub fn limb(&self, i: usize) -> Option<u64> {
+        let offset = i.checked_mul(8)?;
+        self.chunk::<8>(offset)
+            .map(|bytes| u64::from_le_bytes(*bytes))
+    }
+
+    /// Compares the elements as little-endian unsigned integers, same as [`Ord`].
+    pub fn cmp_value(&self, other: &Self) -> core::cmp::Ordering {
+        self.cmp(other)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.iter().all(|b| *b == 0)
+    }
+
+    /// Reads the `FS` little-endian bytes of an element.
+    #[cfg(feature = "std")]
+    pub fn read<R: Read>(mut r: R) -> io::Result<Self> {
+        let () = Self::SUPPORTED_SIZE;
+
+        let mut buf = [0; FS];
+        let read = r.read_exact(&mut buf).map(|()| FieldElement(buf));
+        // Don't leave a copy of the value, or of the part read before an error, on the stack.
+        #[cfg(feature = "zeroize")]
+        zeroize::Zeroize::zeroize(&mut buf);
+
+        read
+    }
+
+    #[cfg(feature = "std")]
+    pub fn write<W: Write>(&self, mut w: W) -> io::Result<()> {
+        w.write_all(&self.0[..])?;
+
+        Ok(())
+    }
+}
+
+/// Compile-time checks of limb counts against the field size.
+struct LimbCount<const FS: usize, const N: usize>;
+
+impl<const FS: usize, const N: usize> LimbCount<FS, N> {
+    const MATCHES_FIELD_SIZE: () =
+        assert!(N * 8 == FS, "the number of limbs must be exactly FS / 8");
+
+    const FITS_FIELD_SIZE: () = assert!(N * 8 <= FS, "the limbs don't fit into FS bytes");
+}
+
+/// Compile-time check of a width conversion from `FS` to `N` bytes.
+struct Width<const FS: usize, const N: usize>;
+
+impl<const FS: usize, const N: usize> Width<FS, N> {
+    const NOT_NARROWER: () = assert!(N >= FS, "can't widen to a smaller field size");
+}
+
+impl<const FS: usize> From<[u8; FS]> for FieldElement<FS> {
+    fn from(array: [u8; FS]) -> Self {
+        let () = Self::SUPPORTED_SIZE;
+
+        FieldElement(array)
+    }
+}
+
+impl<const FS: usize> core::ops::Deref for FieldElement<FS> {
+    type Target = [u8; FS];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Elements are ordered as little-endian unsigned integers, the last byte being the most
+/// significant one, not by lexicographic byte order.
+impl<const FS: usize> Ord for FieldElement<FS> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl<const FS: usize> PartialOrd for FieldElement<FS> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supported_field_size_bounds() {
+        let min = FieldElement::<MIN_SUPPORTED_FIELD_SIZE>::from([0; MIN_SUPPORTED_FIELD_SIZE]);
+        let max = FieldElement::<MAX_SUPPORTED_FIELD_SIZE>::from([0xff; MAX_SUPPORTED_FIELD_SIZE]);
+
+        assert_eq!(min.as_bytes(), [0; 4]);
+        assert_eq!(max.limb(15), Some(u64::MAX));
+    }
+
+    #[test]
+    fn test_slice_bytes() {
+        let values = [
+            FieldElement::<4>::from([1, 0, 0, 0]),
+            FieldElement::from([2, 3, 0, 0]),
+        ];
+        let bytes = FieldElement::slice_as_bytes(&values);
+        assert_eq!(bytes, [1, 0, 0, 0, 2, 3, 0, 0]);
+        assert_eq!(
+            FieldElement::<4>::slice_from_bytes(bytes),
+            Some(&values[..])
+        );
+        assert_eq!(FieldElement::<4>::slice_from_bytes(&bytes[1..]), None);
+        assert_eq!(FieldElement::<4>::slice_from_bytes(&[]), Some(&[][..]));
+    }
+
+    #[test]
+    fn test_field_element_accessors() {
+        let mut bytes = [0u8; 32];
+        for (i, b) in bytes.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        let fe = FieldElement::from(bytes);
+
+        assert_eq!(fe.byte(0), Some(0));
+        assert_eq!(fe.byte(31), Some(31));
+        assert_eq!(fe.byte(32), None);
+
+        assert_eq!(fe.chunk::<4>(28), Some(&[28, 29, 30, 31]));
+        assert_eq!(fe.chunk::<4>(29), None);
+        assert_eq!(fe.chunk::<1>(usize::MAX), None);
+
+        assert_eq!(fe.limb(0), Some(0x0706050403020100));
+        assert_eq!(fe.limb(3), Some(0x1f1e1d1c1b1a1918));
+        assert_eq!(fe.limb(4), None);
+        assert_eq!(fe.limb(usize::MAX), None);
+    }
+
+    #[test]
+    fn test_cmp_value() {
+        use std::cmp::Ordering;
+
+        let mut bytes = [0u8; 32];
+        for (i, b) in bytes.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        let fe = FieldElement::from(bytes);
+
+        // Plain byte order would rank 256 below 1.
+        let one = FieldElement::<32>::from_u64(1);
+        let x256 = FieldElement::<32>::from_u64(256);
+        assert_eq!(fe.cmp_value(&fe), Ordering::Equal);
+        assert_eq!(x256.cmp_value(&one), Ordering::Greater);
+        assert_eq!(x256.cmp_value(&fe), Ordering::Less);
+        assert!(FieldElement::<32>::from_u64(0).is_zero());
+        assert!(!fe.is_zero());
+    }
+
+    #[test]
+    fn test_field_element_ord() {
+        let values: [u128; 7] = [
+            0,
+            1,
+            0xff,
+            0x100,
+            0x1_0000_0000,
+            u64::MAX as u128,
+            u128::MAX,
+        ];
+        let fe = |v: u128| FieldElement::<16>::from(v.to_le_bytes());
+        for a in values {
+            for b in values {
+                assert_eq!(fe(a).cmp(&fe(b)), a.cmp(&b), "{} vs {}", a, b);
+            }
+        }
+
+        // Differing only in the most significant byte.
+        let mut high = [0u8; 32];
+        high[31] = 1;
+        let mut low = [0xffu8; 32];
+        low[31] = 0;
+        assert!(FieldElement::from(high) > FieldElement::from(low));
+
+        let mut sorted = vec![fe(0x100), fe(2), fe(1 << 120), fe(0x1ff)];
+        sorted.sort();
+        assert_eq!(sorted, [fe(2), fe(0x100), fe(0x1ff), fe(1 << 120)]);
+
+        let coefficients: std::collections::HashSet<_> = sorted.iter().copied().collect();
+        assert!(coefficients.contains(&fe(0x1ff)));
+    }
+
+    #[test]
+    fn test_const_constructors() {
+        const FE: FieldElement<16> = FieldElement::from_limbs_le([0x0706050403020100, 1]);
+        const SMALL: FieldElement<16> = FieldElement::from_u64(0x1122);
+
+        assert_eq!(FE.limb(0), Some(0x0706050403020100));
+        assert_eq!(FE.limb(1), Some(1));
+        assert_eq!(
+            SMALL.as_bytes(),
+            [0x22, 0x11, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+
+        assert_eq!(
+            primes::BN254_SCALAR.as_bytes(),
+            hex_literal::hex!("010000f093f5e1439170b97948e833285d588181b64550b829a031e1724e6430")
+        );
+        assert_eq!(
+            primes::GOLDILOCKS.as_bytes(),
+            0xffffffff00000001u64.to_le_bytes()
+        );
+    }
+
+    #[test]
+    fn test_integer_conversions() {
+        const WIDE: FieldElement<16> = FieldElement::from_u128(u128::MAX);
+
+        assert_eq!(WIDE.as_u64_limbs(), [u64::MAX, u64::MAX]);
+        assert_eq!(WIDE.to_u64(), None);
+        assert_eq!(
+            FieldElement::<16>::from_u128(u128::from(u64::MAX)).to_u64(),
+            Some(u64::MAX)
+        );
+        assert_eq!(
+            FieldElement::<4>::from([1, 2, 3, 4]).to_u64(),
+            Some(0x04030201)
+        );
+
+        let prime = FieldElement::<32>::bn254_scalar_prime();
+        assert_eq!(FieldElement::from_be_bytes(prime.to_be_bytes()), prime);
+        assert_eq!(prime.to_be_bytes()[0], 0x30);
+        assert_eq!(prime.as_u64_limbs::<4>()[3], 0x30644e72e131a029);
+    }
+
+    #[test]
+    fn test_width_conversions() {
+        let prime = FieldElement::<32>::bn254_scalar_prime();
+        let wide = prime.widen::<48>();
+        assert_eq!(&wide[..32], &prime[..]);
+        assert!(wide[32..].iter().all(|b| *b == 0));
+        assert_eq!(wide.try_truncate::<32>(), Some(prime));
+        assert_eq!(prime.try_truncate::<48>(), Some(wide));
+
+        // The lowest dropped byte or the highest one being set both fail.
+        let mut bytes = [0u8; 48];
+        bytes[32] = 1;
+        assert_eq!(FieldElement::from(bytes).try_truncate::<32>(), None);
+        bytes[32] = 0;
+        bytes[47] = 0x80;
+        assert_eq!(FieldElement::from(bytes).try_truncate::<32>(), None);
+
+        // The largest value that still fits.
+        bytes = [0u8; 48];
+        bytes[..32].fill(0xff);
+        assert_eq!(
+            FieldElement::from(bytes).try_truncate::<32>(),
+            Some(FieldElement::from([0xff; 32]))
+        );
+
+        assert_eq!(
+            FieldElement::<8>::from_u64(1 << 32).try_truncate::<4>(),
+            None
+        );
+        assert_eq!(
+            FieldElement::<8>::from_u64(u64::from(u32::MAX)).try_truncate::<4>(),
+            Some(FieldElement::from([0xff; 4]))
+        );
+    }
+}
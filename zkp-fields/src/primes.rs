@@ -0,0 +1,133 @@
+//! Moduli of commonly used prime fields, matching the primes circom can compile for.
+
+use crate::FieldElement;
+
+/// Scalar field of BN254 (circom's `bn128`).
+pub const BN254_SCALAR: FieldElement<32> = FieldElement::from_limbs_le([
+    0x43e1f593f0000001,
+    0x2833e84879b97091,
+    0xb85045b68181585d,
+    0x30644e72e131a029,
+]);
+
+/// Scalar field of BLS12-381.
+pub const BLS12_381_SCALAR: FieldElement<32> = FieldElement::from_limbs_le([
+    0xffffffff00000001,
+    0x53bda402fffe5bfe,
+    0x3339d80809a1d805,
+    0x73eda753299d7d48,
+]);
+
+/// Scalar field of BLS12-377.
+pub const BLS12_377_SCALAR: FieldElement<32> = FieldElement::from_limbs_le([
+    0x0a11800000000001,
+    0x59aa76fed0000001,
+    0x60b44d1e5c37b001,
+    0x12ab655e9a2ca556,
+]);
+
+/// Base field of the Pallas curve.
+pub const PALLAS_BASE: FieldElement<32> = FieldElement::from_limbs_le([
+    0x992d30ed00000001,
+    0x224698fc094cf91b,
+    0x0000000000000000,
+    0x4000000000000000,
+]);
+
+/// Base field of the Vesta curve.
+pub const VESTA_BASE: FieldElement<32> = FieldElement::from_limbs_le([
+    0x8c46eb2100000001,
+    0x224698fc0994a8dd,
+    0x0000000000000000,
+    0x4000000000000000,
+]);
+
+/// The Goldilocks field, `2^64 - 2^32 + 1`.
+pub const GOLDILOCKS: FieldElement<8> = FieldElement::from_limbs_le([0xffffffff00000001]);
+
+/// Well-known fields, identified by their modulus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Curve {
+    Bn254,
+    Bls12_381Scalar,
+    Bls12_377Scalar,
+    Pallas,
+    Vesta,
+    Goldilocks,
+}
+
+impl Curve {
+    pub const ALL: [Curve; 6] = [
+        Curve::Bn254,
+        Curve::Bls12_381Scalar,
+        Curve::Bls12_377Scalar,
+        Curve::Pallas,
+        Curve::Vesta,
+        Curve::Goldilocks,
+    ];
+
+    /// Little-endian bytes of the field modulus, as stored in the header.
+    pub fn prime_bytes(self) -> &'static [u8] {
+        match self {
+            Curve::Bn254 => &BN254_SCALAR.0,
+            Curve::Bls12_381Scalar => &BLS12_381_SCALAR.0,
+            Curve::Bls12_377Scalar => &BLS12_377_SCALAR.0,
+            Curve::Pallas => &PALLAS_BASE.0,
+            Curve::Vesta => &VESTA_BASE.0,
+            Curve::Goldilocks => &GOLDILOCKS.0,
+        }
+    }
+
+    /// Finds the field with the given modulus bytes.
+    pub fn from_prime_bytes(prime: &[u8]) -> Option<Curve> {
+        Curve::ALL
+            .iter()
+            .copied()
+            .find(|curve| curve.prime_bytes() == prime)
+    }
+}
+
+impl FieldElement<32> {
+    pub const fn bn254_scalar_prime() -> Self {
+        BN254_SCALAR
+    }
+
+    pub const fn bls12_381_scalar_prime() -> Self {
+        BLS12_381_SCALAR
+    }
+
+    pub const fn bls12_377_scalar_prime() -> Self {
+        BLS12_377_SCALAR
+    }
+
+    pub const fn pallas_prime() -> Self {
+        PALLAS_BASE
+    }
+
+    pub const fn vesta_prime() -> Self {
+        VESTA_BASE
+    }
+}
+
+impl FieldElement<8> {
+    pub const fn goldilocks_prime() -> Self {
+        GOLDILOCKS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curve_from_prime_bytes() {
+        for curve in Curve::ALL {
+            assert_eq!(Curve::from_prime_bytes(curve.prime_bytes()), Some(curve));
+        }
+        assert_eq!(
+            Curve::from_prime_bytes(FieldElement::<32>::from_u64(7).as_bytes()),
+            None
+        );
+    }
+}
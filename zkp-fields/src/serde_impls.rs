@@ -0,0 +1,99 @@
+//! serde support, behind the `serde` feature.
+//!
+//! Field elements are encoded as lowercase hexadecimal strings, most significant digit first and
+//! without prefix, in human-readable formats, and as their `FS` little-endian bytes in binary
+//! ones.
+
+use core::convert::TryFrom;
+use core::fmt;
+
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+use crate::{Endianness, FieldElement};
+
+impl<const FS: usize> Serialize for FieldElement<FS> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.to_hex(Endianness::Big))
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+impl<'de, const FS: usize> Deserialize<'de> for FieldElement<FS> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(FieldElementVisitor)
+        } else {
+            deserializer.deserialize_bytes(FieldElementVisitor)
+        }
+    }
+}
+
+struct FieldElementVisitor<const FS: usize>;
+
+impl<'de, const FS: usize> Visitor<'de> for FieldElementVisitor<FS> {
+    type Value = FieldElement<FS>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bytes or {} hexadecimal digits", FS, 2 * FS)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        if v.len() != 2 * FS {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+
+        FieldElement::from_hex(v, Endianness::Big)
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        FieldElement::try_from(v).map_err(|_| E::invalid_length(v.len(), &self))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = [0u8; FS];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+        }
+        if seq.next_element::<u8>()?.is_some() {
+            return Err(de::Error::invalid_length(FS + 1, &self));
+        }
+
+        Ok(FieldElement(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FieldElement;
+
+    #[test]
+    fn test_field_element_encoding() {
+        let fe = FieldElement::<8>::from_u64(0x0102_0304_0506_07ab);
+        assert_eq!(serde_json::to_string(&fe).unwrap(), r#""01020304050607ab""#);
+        assert_eq!(
+            bincode::serialize(&fe).unwrap(),
+            [8, 0, 0, 0, 0, 0, 0, 0, 0xab, 7, 6, 5, 4, 3, 2, 1]
+        );
+
+        let json = serde_json::from_str::<FieldElement<8>>(r#""01020304050607AB""#);
+        assert_eq!(json.unwrap(), fe);
+        let binary = bincode::serialize(&fe).unwrap();
+        assert_eq!(
+            bincode::deserialize::<FieldElement<8>>(&binary).unwrap(),
+            fe
+        );
+
+        assert!(serde_json::from_str::<FieldElement<8>>(r#""0102""#).is_err());
+        assert!(serde_json::from_str::<FieldElement<8>>(r#""01020304050607xy""#).is_err());
+        assert!(serde_json::from_str::<FieldElement<16>>(r#""01020304050607ab""#).is_err());
+        assert!(bincode::deserialize::<FieldElement<16>>(&binary).is_err());
+    }
+}
@@ -0,0 +1,26 @@
+//! zeroize support, behind the `zeroize` feature.
+//!
+//! Elements are `Copy`, so they can't be zeroized on drop: wrap their containers in
+//! [`zeroize::Zeroizing`] instead.
+
+use zeroize::Zeroize;
+
+use crate::FieldElement;
+
+impl<const FS: usize> Zeroize for FieldElement<FS> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zeroize() {
+        let mut fe = FieldElement::<32>::from([0xab; 32]);
+        fe.zeroize();
+        assert!(fe.is_zero());
+    }
+}
@@ -0,0 +1,7 @@
+use zkp_fields::FieldElement;
+
+const ONE: FieldElement<132> = FieldElement::from_u64(1);
+
+fn main() {
+    let _ = ONE;
+}
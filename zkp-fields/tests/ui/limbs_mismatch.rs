@@ -0,0 +1,7 @@
+use zkp_fields::FieldElement;
+
+const P: FieldElement<32> = FieldElement::from_limbs_le([1, 2, 3]);
+
+fn main() {
+    let _ = P;
+}
@@ -0,0 +1,7 @@
+use zkp_fields::FieldElement;
+
+const NARROW: FieldElement<32> = FieldElement::<48>::from_u64(1).widen();
+
+fn main() {
+    let _ = NARROW;
+}